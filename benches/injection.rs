@@ -0,0 +1,139 @@
+//! 各注入路径的延迟与吞吐量基准
+//!
+//! 打开一个测试窗口并置于前台，全局注入的事件都会进入该窗口，每次迭代后清空消息队列。
+//! 对比 SendInput 单键与批量、虚拟键与扫描码、Unicode 与按键模拟、
+//! WM_CHAR 投递与同步发送、剪贴板粘贴等路径：
+//!
+//! ```bash
+//! cargo bench --bench injection --features window_target,clipboard
+//! ```
+//!
+//! 运行期间会抢占焦点，请不要操作键盘。
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use sophia_keyboard_sender::{
+    key_click, press_combination, press_keys_simultaneously, send_char, send_char_to_window,
+    set_clipboard_text, set_injection_mode, set_text_injection, set_window_send_mode, type_string,
+    type_string_to_window, CaptureWindow, InjectionMode, Key, Modifier, TextInjection,
+    WindowSendMode,
+};
+use std::time::Duration;
+
+/// 吞吐量测试使用的文本
+const TEXT: &str = "The quick brown fox jumps over the lazy dog 0123456789 !?";
+
+/// 打开接收注入事件的测试窗口，每次迭代后用 `clear` 清空消息，避免队列写满
+fn open_window() -> CaptureWindow {
+    CaptureWindow::open("sophia injection bench").expect("failed to create bench window")
+}
+
+fn bench_per_key(c: &mut Criterion) {
+    let window = open_window();
+    let mut group = c.benchmark_group("per_key");
+
+    for (name, mode) in [
+        ("virtual_key", InjectionMode::VirtualKey),
+        ("scan_code", InjectionMode::ScanCode),
+    ] {
+        set_injection_mode(mode);
+        group.bench_function(format!("send_input/{}", name), |b| {
+            b.iter(|| {
+                key_click(Key::A, Some(Duration::ZERO)).unwrap();
+                window.clear();
+            })
+        });
+    }
+    set_injection_mode(InjectionMode::VirtualKey);
+
+    let keys = [Key::A, Key::B, Key::C, Key::D];
+    group.bench_function("send_input/4_keys_sequential", |b| {
+        b.iter(|| {
+            for key in keys {
+                key_click(key, Some(Duration::ZERO)).unwrap();
+            }
+            window.clear();
+        })
+    });
+    group.bench_function("send_input/4_keys_batched", |b| {
+        b.iter(|| {
+            press_keys_simultaneously(&keys, Some(Duration::ZERO)).unwrap();
+            window.clear();
+        })
+    });
+    group.bench_function("send_input/combination", |b| {
+        b.iter(|| {
+            press_combination(&[Modifier::Shift], Key::A, Some(Duration::ZERO)).unwrap();
+            window.clear();
+        })
+    });
+
+    for (name, mode) in [
+        ("unicode", TextInjection::Unicode),
+        ("keystrokes", TextInjection::KeyStrokes),
+    ] {
+        set_text_injection(mode);
+        group.bench_function(format!("send_char/{}", name), |b| {
+            b.iter(|| {
+                send_char('A').unwrap();
+                window.clear();
+            })
+        });
+    }
+    set_text_injection(TextInjection::Unicode);
+
+    for (name, mode) in [
+        ("post", WindowSendMode::Post),
+        ("synchronous", WindowSendMode::synchronous()),
+    ] {
+        set_window_send_mode(mode);
+        group.bench_function(format!("wm_char/{}", name), |b| {
+            b.iter(|| {
+                send_char_to_window(window.handle(), 'A').unwrap();
+                window.clear();
+            })
+        });
+    }
+    set_window_send_mode(WindowSendMode::Post);
+
+    group.finish();
+}
+
+fn bench_throughput(c: &mut Criterion) {
+    let window = open_window();
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Elements(TEXT.chars().count() as u64));
+
+    for (name, mode) in [
+        ("unicode", TextInjection::Unicode),
+        ("keystrokes", TextInjection::KeyStrokes),
+    ] {
+        set_text_injection(mode);
+        group.bench_function(format!("type_string/{}", name), |b| {
+            b.iter(|| {
+                type_string(TEXT).unwrap();
+                window.clear();
+            })
+        });
+    }
+    set_text_injection(TextInjection::Unicode);
+
+    group.bench_function("type_string_to_window/post", |b| {
+        b.iter(|| {
+            type_string_to_window(window.handle(), TEXT).unwrap();
+            window.clear();
+        })
+    });
+
+    // 粘贴只注入一次 Ctrl+V，但需要先写入剪贴板
+    group.bench_function("clipboard_paste", |b| {
+        b.iter(|| {
+            set_clipboard_text(TEXT).unwrap();
+            press_combination(&[Modifier::Control], Key::V, Some(Duration::ZERO)).unwrap();
+            window.clear();
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_per_key, bench_throughput);
+criterion_main!(benches);
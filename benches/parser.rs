@@ -0,0 +1,58 @@
+//! 命令与时长解析吞吐量基准
+//!
+//! 对比预编译的 `CommandParser`/`DurationParser` 与逐次调用的旧路径：
+//!
+//! ```bash
+//! cargo bench --bench parser
+//! ```
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sophia_keyboard_sender::{parse_sleep_duration, CommandParser, DurationParser};
+
+const COMMANDS: &[&str] = &[
+    "key:a",
+    "key:enter,duration:100ms",
+    "text:hello world,hwnd:0x1A2B",
+    "action:key_click,key:backspace,count:5,gap:20ms",
+    "shortcut:ctrl+shift+esc",
+];
+
+const DURATIONS: &[&str] = &["100ms", "2s", "1.5s", "1m30s", "1h 2m 3s", "250"];
+
+fn bench_command_params(c: &mut Criterion) {
+    let parser = CommandParser::new();
+    c.bench_function("command_params/fresh_regex", |b| {
+        b.iter(|| {
+            for command in COMMANDS {
+                black_box(CommandParser::new().parse(black_box(command)));
+            }
+        })
+    });
+    c.bench_function("command_params/precompiled", |b| {
+        b.iter(|| {
+            for command in COMMANDS {
+                black_box(parser.parse(black_box(command)));
+            }
+        })
+    });
+}
+
+fn bench_durations(c: &mut Criterion) {
+    let parser = DurationParser::new();
+    c.bench_function("durations/sleep_utils", |b| {
+        b.iter(|| {
+            for input in DURATIONS {
+                black_box(parse_sleep_duration(black_box(input)).unwrap());
+            }
+        })
+    });
+    c.bench_function("durations/precompiled", |b| {
+        b.iter(|| {
+            for input in DURATIONS {
+                black_box(parser.parse(black_box(input)).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_command_params, bench_durations);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+//! 后台监视示例：常驻运行，记事本切到前台时自动输入时间戳
+//!
+//! ```bash
+//! cargo run --example foreground_watch --features window_target
+//! ```
+//!
+//! 按 Ctrl+C 退出。
+use sophia_keyboard_sender::{
+    on_foreground_changed, process_name, type_string, window_process_id, window_title,
+};
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (tx, rx) = mpsc::channel();
+    // 回调在监视线程中执行，只做转发，输入放在主线程
+    let _watcher = on_foreground_changed(move |hwnd| {
+        let _ = tx.send(hwnd);
+    })?;
+
+    for hwnd in rx {
+        let process = process_name(window_process_id(hwnd)).unwrap_or_default();
+        println!("foreground: {} [{}]", window_title(hwnd), process);
+
+        if process.eq_ignore_ascii_case("notepad.exe") {
+            let secs = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_secs();
+            type_string(&format!("[focused at {}]\n", secs))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! 表单填写示例：依次输入各字段，字段之间用 Tab 切换
+//!
+//! 运行后请在 3 秒内点击表单的第一个输入框：
+//!
+//! ```bash
+//! cargo run --example form_filler
+//! ```
+use sophia_keyboard_sender::{enter_text, key_click, EntryStrategy, Key};
+use std::time::Duration;
+
+const FIELDS: &[(&str, &str)] = &[
+    ("name", "Zhang San"),
+    ("email", "zhangsan@example.com"),
+    ("phone", "13800000000"),
+    (
+        "comment",
+        "Long values are pasted or typed in chunks automatically, \
+         so this field will not drop characters in slow web forms.",
+    ),
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::thread::sleep(Duration::from_secs(3));
+
+    for (index, (field, value)) in FIELDS.iter().enumerate() {
+        if index > 0 {
+            key_click(Key::Tab, None)?;
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        // 根据长度自动选择逐字输入、分块输入或粘贴
+        let strategy = enter_text(None, value)?;
+        let how = match strategy {
+            EntryStrategy::Typed => "typed".to_string(),
+            EntryStrategy::Pasted => "pasted".to_string(),
+            EntryStrategy::ChunkedTyping { chunks } => format!("typed in {} chunks", chunks),
+        };
+        println!("{}: {}", field, how);
+    }
+    Ok(())
+}
@@ -0,0 +1,14 @@
+//! 最小构建体积基准
+//!
+//! 仅使用 `minimal` 特性提供的全局输入功能，供 `tests/minimal_size.rs` 检查发布构建体积：
+//!
+//! ```bash
+//! cargo build --release --example minimal_size --no-default-features --features minimal
+//! ```
+use sophia_keyboard_sender::{key_click, type_string, Key};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    type_string("hello")?;
+    key_click(Key::Enter, None)?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! 窗口目标示例：启动记事本、输入文本并退出
+//!
+//! ```bash
+//! cargo run --example notepad --features window_target
+//! ```
+use sophia_keyboard_sender::{
+    launch_and_target, quit_app_gracefully, set_window_focus, window_title, FocusedTypist, Key,
+    QuitOutcome,
+};
+use std::time::Duration;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let hwnd = launch_and_target("notepad.exe", &[])?;
+    println!("launched: {} (0x{:X})", window_title(hwnd), hwnd);
+
+    // 只在记事本位于前台时输入，切走后自动暂停
+    set_window_focus(hwnd, true)?;
+    FocusedTypist::new(hwnd)
+        .focus_timeout(Duration::from_secs(10))
+        .queue_text("Hello from sophia_keyboard_sender")
+        .queue_key(Key::Enter)
+        .queue_text("This line was typed only while Notepad had focus.")
+        .run()?;
+
+    match quit_app_gracefully(hwnd, Duration::from_secs(3))? {
+        QuitOutcome::ConfirmationDialog(dialog) => {
+            println!("save dialog is open: {}", window_title(dialog))
+        }
+        outcome => println!("{:?}", outcome),
+    }
+    Ok(())
+}
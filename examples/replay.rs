@@ -0,0 +1,49 @@
+//! 回放示例：按时间轴回放两次命令，并对比两次的时序差异
+//!
+//! 运行后请在 3 秒内切换到一个文本编辑器：
+//!
+//! ```bash
+//! cargo run --example replay
+//! ```
+use sophia_keyboard_sender::{
+    compare_journals, replay, ReplayOptions, ReplayReport, RetryPolicy, TimedCommand,
+};
+use std::time::Duration;
+
+fn main() {
+    std::thread::sleep(Duration::from_secs(3));
+
+    let commands = [
+        TimedCommand::new(Duration::ZERO, "text:first line"),
+        TimedCommand::new(Duration::from_millis(300), "key:enter"),
+        TimedCommand::new(Duration::from_millis(600), "text:second line"),
+        TimedCommand::new(Duration::from_millis(900), "key:enter"),
+    ];
+    let options = ReplayOptions {
+        retry: RetryPolicy::polling(Duration::from_millis(200), Duration::from_millis(50)),
+        max_lateness: Some(Duration::from_millis(250)),
+    };
+
+    let first = replay(&commands, &options);
+    print_report("first run", &first);
+    let second = replay(&commands, &options);
+    print_report("second run", &second);
+
+    let diff = compare_journals(&first, &second);
+    if diff.is_identical() {
+        println!("both runs match");
+    } else {
+        println!("differences:\n{}", diff);
+    }
+}
+
+fn print_report(name: &str, report: &ReplayReport) {
+    println!(
+        "{}: {:?} total, max lateness {:?}, {} retried, {} dropped",
+        name,
+        report.total_duration,
+        report.max_lateness(),
+        report.retried(),
+        report.dropped()
+    );
+}
@@ -0,0 +1,57 @@
+//! 脚本执行示例：逐行执行命令文件
+//!
+//! 脚本每行一条文本命令，空行和 `#` 开头的行会被跳过，支持 `label:`、`goto:`、`call:` 和 `return`：
+//!
+//! ```bash
+//! cargo run --example script_runner -- path/to/script.txt
+//! ```
+//!
+//! 不指定文件时执行内置的示例脚本。
+use sophia_keyboard_sender::{Script, ScriptOptions};
+use std::time::Duration;
+
+const DEMO_SCRIPT: &str = "\
+# 内置示例：运行后 3 秒内切换到文本编辑器
+call:greet
+text:Selecting everything...
+shortcut:ctrl+a
+goto:end
+
+label:greet
+text:Hello from script_runner
+key:enter
+return
+
+label:end
+";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let script = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            std::thread::sleep(Duration::from_secs(3));
+            DEMO_SCRIPT.to_string()
+        }
+    };
+    // 先校验全部命令和标签，出错时不会执行任何一行
+    let script = Script::parse(&script)?;
+    let options = ScriptOptions::default()
+        .delay(Duration::from_millis(100))
+        .stop_on_error(true);
+    let report = script.run_with(&options)?;
+
+    for result in &report.results {
+        match &result.result {
+            Ok(()) => println!("line {}: ok ({:?})", result.line, result.duration),
+            Err(e) => println!("line {}: {}", result.line, e),
+        }
+    }
+    println!(
+        "{} ok, {} failed, {} skipped in {:?}",
+        report.succeeded(),
+        report.failed(),
+        report.skipped,
+        report.total_duration
+    );
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! 命令行发送工具
+//!
+//! ```bash
+//! sophia-send "key:enter" "text:hello"          # 依次执行文本命令
+//! sophia-send --dump-mappings                   # 输出每个标准按键的虚拟键码和扫描码
+//! sophia-send --dump-mappings snapshot.txt      # 与快照比较，有差异时以状态码 1 退出
+//! sophia-send --self-test                       # 自检各注入方式，有不可用的方式时以状态码 1 退出
+//! ```
+//!
+//! 自检需要 `window_target` 特性。
+use sophia_keyboard_sender::{
+    diff_key_mappings, dump_key_mappings, format_key_mappings, parse_key_mappings, send,
+};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: sophia-send <command>...
+       sophia-send --dump-mappings [snapshot]
+       sophia-send --self-test";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        None | Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Some("--dump-mappings") => dump_mappings(args.get(1)),
+        Some("--self-test") => self_test(),
+        Some(_) => run_commands(&args),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("sophia-send: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// 输出当前映射；指定快照时只输出差异
+fn dump_mappings(snapshot: Option<&String>) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let current = dump_key_mappings();
+    let Some(path) = snapshot else {
+        print!("{}", format_key_mappings(&current));
+        return Ok(ExitCode::SUCCESS);
+    };
+    let expected = parse_key_mappings(&std::fs::read_to_string(path)?)?;
+    let changes = diff_key_mappings(&expected, &current);
+    for change in &changes {
+        println!("{}", change);
+    }
+    Ok(if changes.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// 运行自检并输出报告
+#[cfg(feature = "window_target")]
+fn self_test() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let report = sophia_keyboard_sender::self_test();
+    println!("{}", report);
+    Ok(if report.all_passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+#[cfg(not(feature = "window_target"))]
+fn self_test() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    Err("--self-test requires the window_target feature".into())
+}
+
+/// 依次执行命令，遇到第一条失败的命令时停止
+fn run_commands(commands: &[String]) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    for command in commands {
+        send(command)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,337 @@
+// src/convenience/csv_form.rs
+//! CSV 数据驱动的批量表单填写
+use super::form::{fill_form_with, FormField, FormOptions};
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::Key;
+use std::path::Path;
+use std::time::Duration;
+
+/// 模板字段引用的列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnRef {
+    /// 按表头名称引用（需要 CSV 带表头）
+    Name(String),
+    /// 按列序号引用（从 0 开始）
+    Index(usize),
+}
+
+/// 模板中的一个字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateField {
+    pub column: ColumnRef,
+    pub tabs_after: usize,
+}
+
+/// 表单模板：字段顺序、提交键和提交后等待时间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormTemplate {
+    pub fields: Vec<TemplateField>,
+    /// CSV 第一行是否为表头
+    pub has_header: bool,
+    /// 每行填写完成后按下的提交键
+    pub submit_key: Option<Key>,
+    /// 提交后等待表单重置的时间
+    pub post_submit_wait: Duration,
+    /// 从第几条数据行开始（从 0 开始，不含表头），用于失败后续传
+    pub start_row: usize,
+    /// 字段输入与 Tab 的延迟选项
+    pub options: FormOptions,
+}
+
+impl Default for FormTemplate {
+    fn default() -> Self {
+        Self {
+            fields: Vec::new(),
+            has_header: true,
+            submit_key: Some(Key::Enter),
+            post_submit_wait: Duration::from_millis(500),
+            start_row: 0,
+            options: FormOptions::default(),
+        }
+    }
+}
+
+impl FormTemplate {
+    /// 创建空模板（默认带表头，提交键为 Enter）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加按表头名称引用的字段，输入后按一次 Tab
+    pub fn field(self, column: &str) -> Self {
+        self.field_with_tabs(ColumnRef::Name(column.to_string()), 1)
+    }
+
+    /// 追加字段并指定输入后按 Tab 的次数
+    pub fn field_with_tabs(mut self, column: ColumnRef, tabs_after: usize) -> Self {
+        self.fields.push(TemplateField { column, tabs_after });
+        self
+    }
+
+    /// 设置 CSV 是否带表头
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// 设置提交键，`None` 表示不提交
+    pub fn submit_key(mut self, key: Option<Key>) -> Self {
+        self.submit_key = key;
+        self
+    }
+
+    /// 设置提交后的等待时间
+    pub fn post_submit_wait(mut self, wait: Duration) -> Self {
+        self.post_submit_wait = wait;
+        self
+    }
+
+    /// 设置起始数据行，用于从上次失败处继续
+    pub fn start_row(mut self, row: usize) -> Self {
+        self.start_row = row;
+        self
+    }
+
+    /// 设置字段输入选项
+    pub fn options(mut self, options: FormOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// 填写进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormProgress {
+    /// 当前数据行（从 0 开始，不含表头）
+    pub row: usize,
+    /// 数据行总数
+    pub total: usize,
+}
+
+/// 批量填写结果
+#[derive(Debug)]
+pub struct CsvFillReport {
+    /// 数据行总数
+    pub total_rows: usize,
+    /// 本次成功填写的行数
+    pub filled: usize,
+    /// 失败的行号及错误；续传时将 `start_row` 设为该行号
+    pub failure: Option<(usize, KeyboardSenderError)>,
+}
+
+impl CsvFillReport {
+    /// 是否全部成功
+    pub fn is_success(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// 续传时应使用的起始行
+    pub fn resume_row(&self) -> Option<usize> {
+        self.failure.as_ref().map(|(row, _)| *row)
+    }
+}
+
+/// 读取 CSV 文件并逐行填写表单
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "convenience")]
+/// # {
+/// use sophia_keyboard_sender::convenience::{fill_forms_from_csv, FormTemplate};
+///
+/// let template = FormTemplate::new().field("name").field("email").field("phone");
+/// let report = fill_forms_from_csv("people.csv", &template, |p| {
+///     println!("{}/{}", p.row + 1, p.total);
+/// })
+/// .unwrap();
+///
+/// if let Some(row) = report.resume_row() {
+///     // 修正问题后从失败的行继续
+///     let _ = fill_forms_from_csv("people.csv", &template.clone().start_row(row), |_| {});
+/// }
+/// # }
+/// ```
+pub fn fill_forms_from_csv<P, F>(
+    path: P,
+    template: &FormTemplate,
+    on_progress: F,
+) -> Result<CsvFillReport>
+where
+    P: AsRef<Path>,
+    F: FnMut(&FormProgress),
+{
+    let content = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+        KeyboardSenderError::ParseError(format!("{}: {}", path.as_ref().display(), e))
+    })?;
+    fill_forms_from_csv_str(&content, template, on_progress)
+}
+
+/// 从 CSV 文本逐行填写表单
+///
+/// 模板与表头不匹配等定义错误会在填写任何一行之前返回；
+/// 某一行填写失败时停止并在报告中记录该行，以便续传。
+pub fn fill_forms_from_csv_str<F>(
+    content: &str,
+    template: &FormTemplate,
+    mut on_progress: F,
+) -> Result<CsvFillReport>
+where
+    F: FnMut(&FormProgress),
+{
+    let mut records = parse_csv(content)?;
+    let header = if template.has_header && !records.is_empty() {
+        Some(records.remove(0))
+    } else {
+        None
+    };
+
+    let columns = resolve_columns(template, header.as_deref())?;
+    let total = records.len();
+    let mut filled = 0;
+
+    for (row, record) in records.iter().enumerate().skip(template.start_row) {
+        on_progress(&FormProgress { row, total });
+
+        let result = fields_for_record(&columns, template, record, row)
+            .and_then(|fields| fill_form_with(&fields, &template.options))
+            .and_then(|_| match template.submit_key {
+                Some(key) => crate::core::key_click(key, None),
+                None => Ok(()),
+            });
+
+        if let Err(e) = result {
+            return Ok(CsvFillReport {
+                total_rows: total,
+                filled,
+                failure: Some((row, e)),
+            });
+        }
+
+        filled += 1;
+        if !template.post_submit_wait.is_zero() {
+            crate::core::clock().sleep(template.post_submit_wait);
+        }
+    }
+
+    Ok(CsvFillReport {
+        total_rows: total,
+        filled,
+        failure: None,
+    })
+}
+
+/// 将模板字段解析为列序号
+fn resolve_columns(template: &FormTemplate, header: Option<&[String]>) -> Result<Vec<usize>> {
+    template
+        .fields
+        .iter()
+        .map(|field| match &field.column {
+            ColumnRef::Index(i) => Ok(*i),
+            ColumnRef::Name(name) => header
+                .and_then(|h| h.iter().position(|c| c.trim() == name))
+                .ok_or_else(|| {
+                    KeyboardSenderError::ParseError(format!("CSV column not found: {}", name))
+                }),
+        })
+        .collect()
+}
+
+fn fields_for_record(
+    columns: &[usize],
+    template: &FormTemplate,
+    record: &[String],
+    row: usize,
+) -> Result<Vec<FormField>> {
+    columns
+        .iter()
+        .zip(&template.fields)
+        .map(|(&col, field)| {
+            let value = record.get(col).ok_or_else(|| {
+                KeyboardSenderError::ParseError(format!("row {} has no column {}", row, col))
+            })?;
+            Ok(FormField::new(value).tabs_after(field.tabs_after))
+        })
+        .collect()
+}
+
+/// 解析 CSV 文本（支持引号、转义引号和引号内换行），忽略空行
+pub fn parse_csv(content: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if !(record.len() == 1 && record[0].is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(KeyboardSenderError::ParseError(
+            "Unterminated quoted field in CSV".to_string(),
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv("name,note\r\nalice,\"hi, \"\"there\"\"\"\n\nbob,\"a\nb\"").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["name".to_string(), "note".to_string()],
+                vec!["alice".to_string(), "hi, \"there\"".to_string()],
+                vec!["bob".to_string(), "a\nb".to_string()],
+            ]
+        );
+        assert!(parse_csv("\"open").is_err());
+    }
+
+    #[test]
+    fn test_resolve_columns() {
+        let header = vec!["name".to_string(), "email".to_string()];
+        let template = FormTemplate::new()
+            .field("email")
+            .field_with_tabs(ColumnRef::Index(0), 2);
+        assert_eq!(
+            resolve_columns(&template, Some(&header)).unwrap(),
+            vec![1, 0]
+        );
+
+        let missing = FormTemplate::new().field("phone");
+        assert!(resolve_columns(&missing, Some(&header)).is_err());
+    }
+}
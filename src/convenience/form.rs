@@ -0,0 +1,144 @@
+// src/convenience/form.rs
+//! 按 Tab 顺序填写表单
+use crate::error::Result;
+use crate::types::Key;
+use std::time::Duration;
+
+/// 表单字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormField {
+    /// 要输入的值，空字符串表示跳过该字段
+    pub value: String,
+    /// 输入后按 Tab 的次数（用于跳过中间的控件）
+    pub tabs_after: usize,
+}
+
+impl FormField {
+    /// 创建字段，输入后按一次 Tab
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            tabs_after: 1,
+        }
+    }
+
+    /// 设置输入后按 Tab 的次数
+    pub fn tabs_after(mut self, tabs: usize) -> Self {
+        self.tabs_after = tabs;
+        self
+    }
+}
+
+/// 表单填写选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormOptions {
+    /// 输入字段值之后的等待时间
+    pub after_value_delay: Duration,
+    /// 每次按 Tab 之后的等待时间
+    pub after_tab_delay: Duration,
+    /// 输入前先全选（Ctrl+A）以覆盖字段中已有内容
+    pub clear_before_type: bool,
+    /// 所有字段填写完成后按下的提交键
+    pub submit_key: Option<Key>,
+}
+
+impl Default for FormOptions {
+    fn default() -> Self {
+        Self {
+            after_value_delay: Duration::from_millis(20),
+            after_tab_delay: Duration::from_millis(50),
+            clear_before_type: false,
+            submit_key: None,
+        }
+    }
+}
+
+/// 按 Tab 顺序填写表单（使用默认选项）
+///
+/// # 示例
+/// ```
+/// # #[cfg(feature = "convenience")]
+/// # {
+/// use sophia_keyboard_sender::convenience::{fill_form, FormField};
+///
+/// fill_form(&[
+///     FormField::new("alice"),
+///     FormField::new("alice@example.com").tabs_after(2), // 跳过一个复选框
+///     FormField::new("secret"),
+/// ])
+/// .unwrap();
+/// # }
+/// ```
+pub fn fill_form(fields: &[FormField]) -> Result<()> {
+    fill_form_with(fields, &FormOptions::default())
+}
+
+/// 按 Tab 顺序填写表单
+pub fn fill_form_with(fields: &[FormField], options: &FormOptions) -> Result<()> {
+    for field in fields {
+        if !field.value.is_empty() {
+            if options.clear_before_type {
+                crate::core::press_combination(&[crate::types::Modifier::Control], Key::A, None)?;
+            }
+            crate::core::type_string(&field.value)?;
+            sleep_if_nonzero(options.after_value_delay);
+        }
+
+        for _ in 0..field.tabs_after {
+            crate::core::key_click(Key::Tab, None)?;
+            sleep_if_nonzero(options.after_tab_delay);
+        }
+    }
+
+    if let Some(key) = options.submit_key {
+        crate::core::key_click(key, None)?;
+    }
+    Ok(())
+}
+
+/// 依次输入每个值，值之间按一次 Tab（最后一个值之后不按）
+///
+/// # 示例
+/// ```
+/// # #[cfg(feature = "convenience")]
+/// # {
+/// use sophia_keyboard_sender::convenience::fill_by_tabs;
+///
+/// fill_by_tabs(&["alice", "alice@example.com", "secret"]).unwrap();
+/// # }
+/// ```
+pub fn fill_by_tabs(values: &[&str]) -> Result<()> {
+    fill_form(&tab_fields(values))
+}
+
+fn tab_fields(values: &[&str]) -> Vec<FormField> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let tabs = if i + 1 < values.len() { 1 } else { 0 };
+            FormField::new(value).tabs_after(tabs)
+        })
+        .collect()
+}
+
+fn sleep_if_nonzero(delay: Duration) {
+    if !delay.is_zero() {
+        crate::core::clock().sleep(delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tab_fields() {
+        let fields = tab_fields(&["a", "b", "c"]);
+        assert_eq!(
+            fields.iter().map(|f| f.tabs_after).collect::<Vec<_>>(),
+            vec![1, 1, 0]
+        );
+        assert_eq!(fields[1].value, "b");
+    }
+}
@@ -0,0 +1,12 @@
+// src/convenience/mod.rs
+//! 便捷函数（需要启用 `convenience` 特性）
+//!
+//! 构建在核心 API 之上的常用操作封装，例如方向键导航、表单填写和 CSV 批量填表。
+
+pub mod csv_form;
+pub mod form;
+pub mod navigation;
+
+pub use csv_form::*;
+pub use form::*;
+pub use navigation::*;
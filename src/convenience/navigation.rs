@@ -0,0 +1,133 @@
+// src/convenience/navigation.rs
+//! 方向键导航：带次数和修饰键的方向移动，以及表格类应用的单元格移动
+use crate::error::Result;
+use crate::types::{Key, Modifier};
+use std::time::Duration;
+
+/// 连续导航按键之间的默认间隔
+pub const DEFAULT_NAVIGATION_GAP: Duration = Duration::from_millis(10);
+
+/// 方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// 对应的方向键
+    pub fn key(self) -> Key {
+        match self {
+            Direction::Up => Key::ArrowUp,
+            Direction::Down => Key::ArrowDown,
+            Direction::Left => Key::ArrowLeft,
+            Direction::Right => Key::ArrowRight,
+        }
+    }
+
+    /// 相反方向
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+/// 按住修饰键向指定方向移动 `count` 次
+///
+/// # 示例
+/// ```
+/// # #[cfg(feature = "convenience")]
+/// # {
+/// use sophia_keyboard_sender::convenience::{navigate, Direction};
+/// use sophia_keyboard_sender::Modifier;
+///
+/// // 向下选中 5 行
+/// navigate(Direction::Down, 5, &[Modifier::Shift]).unwrap();
+/// # }
+/// ```
+pub fn navigate(direction: Direction, count: usize, modifiers: &[Modifier]) -> Result<()> {
+    navigate_with_gap(direction, count, modifiers, DEFAULT_NAVIGATION_GAP)
+}
+
+/// 与 [`navigate`] 相同，但可指定每次移动之间的间隔
+pub fn navigate_with_gap(
+    direction: Direction,
+    count: usize,
+    modifiers: &[Modifier],
+    gap: Duration,
+) -> Result<()> {
+    if modifiers.is_empty() {
+        return crate::core::key_click_n(direction.key(), count, Some(gap));
+    }
+
+    for i in 0..count {
+        if i > 0 {
+            crate::core::clock().sleep(gap);
+        }
+        crate::core::press_combination(modifiers, direction.key(), None)?;
+    }
+    Ok(())
+}
+
+/// 将偏移量拆分为水平和垂直两段移动
+fn cell_moves(dx: i32, dy: i32) -> [(Direction, usize); 2] {
+    let horizontal = if dx >= 0 {
+        Direction::Right
+    } else {
+        Direction::Left
+    };
+    let vertical = if dy >= 0 {
+        Direction::Down
+    } else {
+        Direction::Up
+    };
+    [
+        (horizontal, dx.unsigned_abs() as usize),
+        (vertical, dy.unsigned_abs() as usize),
+    ]
+}
+
+/// 在表格类应用中移动活动单元格：`dx` 为列偏移（正数向右），`dy` 为行偏移（正数向下）
+pub fn move_cell(dx: i32, dy: i32) -> Result<()> {
+    for (direction, count) in cell_moves(dx, dy) {
+        navigate(direction, count, &[])?;
+    }
+    Ok(())
+}
+
+/// 从当前单元格扩展选区（按住 Shift 移动）
+pub fn select_cells(dx: i32, dy: i32) -> Result<()> {
+    for (direction, count) in cell_moves(dx, dy) {
+        navigate(direction, count, &[Modifier::Shift])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_moves() {
+        assert_eq!(
+            cell_moves(3, -2),
+            [(Direction::Right, 3), (Direction::Up, 2)]
+        );
+        assert_eq!(
+            cell_moves(-1, 0),
+            [(Direction::Left, 1), (Direction::Down, 0)]
+        );
+    }
+
+    #[test]
+    fn test_direction_keys() {
+        assert_eq!(Direction::Up.key(), Key::ArrowUp);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+    }
+}
@@ -0,0 +1,372 @@
+// src/core/accessibility.rs
+//! 辅助功能模拟：粘滞键与慢速键
+//!
+//! 通过低级键盘钩子（`WH_KEYBOARD_LL`）拦截物理按键，按策略吞掉并重新注入：
+//!
+//! - 粘滞键：单独按下并松开修饰键后将其锁存，下一个普通按键会带上锁存的修饰键；
+//!   再次单独按下同一修饰键取消锁存。修饰键本身只在与普通按键组合时才发送。
+//! - 慢速键：按键按住超过阈值才生效，短于阈值的误触被丢弃。
+//!
+//! 钩子运行在独立的后台线程上（带消息循环），注入的事件（包括本模块重新发出的事件）
+//! 直接放行，不会被再次处理。
+use crate::error::Result;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    UI::WindowsAndMessaging::{
+        CallNextHookEx, KillTimer, SetTimer, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED, MSG,
+        WM_KEYDOWN, WM_SYSKEYDOWN, WM_TIMER,
+    },
+};
+
+use super::hook_thread::{install_keyboard_hook, HookThread};
+
+/// 慢速键检查间隔
+const SLOW_KEYS_TICK: Duration = Duration::from_millis(10);
+
+/// 辅助功能选项
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AccessibilityOptions {
+    /// 启用粘滞键模拟
+    pub sticky_keys: bool,
+    /// 启用慢速键，按住超过该时长的按键才生效
+    pub slow_keys: Option<Duration>,
+}
+
+impl AccessibilityOptions {
+    /// 不启用任何功能
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启用粘滞键模拟
+    pub fn sticky_keys(mut self) -> Self {
+        self.sticky_keys = true;
+        self
+    }
+
+    /// 启用慢速键
+    pub fn slow_keys(mut self, threshold: Duration) -> Self {
+        self.slow_keys = Some(threshold);
+        self
+    }
+}
+
+/// 辅助功能钩子，丢弃时移除钩子并结束钩子线程
+#[derive(Debug)]
+pub struct AccessibilityHook {
+    _hook: HookThread,
+}
+
+impl AccessibilityHook {
+    /// 停止模拟
+    pub fn stop(self) {}
+}
+
+/// 启用粘滞键模拟，返回的钩子被丢弃时停止
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::enable_sticky_keys_emulation;
+///
+/// // 依次按 Ctrl、C 即可复制
+/// let hook = enable_sticky_keys_emulation().unwrap();
+/// std::thread::sleep(std::time::Duration::from_secs(60));
+/// drop(hook);
+/// # }
+/// ```
+pub fn enable_sticky_keys_emulation() -> Result<AccessibilityHook> {
+    install_accessibility_hook(AccessibilityOptions::new().sticky_keys())
+}
+
+/// 启用慢速键，按住超过 `threshold` 的按键才生效
+pub fn enable_slow_keys(threshold: Duration) -> Result<AccessibilityHook> {
+    install_accessibility_hook(AccessibilityOptions::new().slow_keys(threshold))
+}
+
+/// 按选项安装辅助功能钩子
+pub fn install_accessibility_hook(options: AccessibilityOptions) -> Result<AccessibilityHook> {
+    let hook = HookThread::spawn("accessibility-hook", move || install(options), on_message)?;
+    Ok(AccessibilityHook { _hook: hook })
+}
+
+thread_local! {
+    // 每个钩子线程只安装一个钩子，过滤状态保存在该线程本地
+    static FILTER: RefCell<Option<KeyFilter>> = const { RefCell::new(None) };
+}
+
+/// 在钩子线程上安装钩子，启用慢速键时同时启动检查计时器
+fn install(options: AccessibilityOptions) -> Result<impl FnOnce()> {
+    let slow_keys = options.slow_keys.is_some();
+    FILTER.with(|f| *f.borrow_mut() = Some(KeyFilter::new(options)));
+
+    let unhook = install_keyboard_hook(Some(keyboard_proc))?;
+    let timer = if slow_keys {
+        unsafe { SetTimer(HWND(0), 0, SLOW_KEYS_TICK.as_millis() as u32, None) }
+    } else {
+        0
+    };
+    Ok(move || {
+        if timer != 0 {
+            unsafe {
+                let _ = KillTimer(HWND(0), timer);
+            }
+        }
+        unhook();
+    })
+}
+
+/// 钩子线程的消息处理：慢速键计时器到期时注入已生效的按键
+fn on_message(msg: &MSG) -> bool {
+    if msg.message != WM_TIMER {
+        return false;
+    }
+    let events = FILTER.with(|f| {
+        f.borrow_mut()
+            .as_mut()
+            .map(|filter| filter.on_tick(Instant::now()))
+            .unwrap_or_default()
+    });
+    inject(&events);
+    true
+}
+
+unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code < 0 {
+        return CallNextHookEx(HHOOK(0), code, wparam, lparam);
+    }
+    let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+    if info.flags.0 & LLKHF_INJECTED.0 != 0 {
+        return CallNextHookEx(HHOOK(0), code, wparam, lparam);
+    }
+
+    let vk = info.vkCode as u16;
+    let down = wparam.0 as u32 == WM_KEYDOWN || wparam.0 as u32 == WM_SYSKEYDOWN;
+    let events = FILTER.with(|f| {
+        f.borrow_mut()
+            .as_mut()
+            .map(|filter| filter.on_key(vk, down, Instant::now()))
+    });
+    match events {
+        // 输出与输入相同时直接放行，保留原始扫描码和标志
+        Some(events) if events != [(vk, down)] => {
+            inject(&events);
+            LRESULT(1)
+        }
+        _ => CallNextHookEx(HHOOK(0), code, wparam, lparam),
+    }
+}
+
+/// 注入事件（虚拟键码，是否按下）
+fn inject(events: &[(u16, bool)]) {
+    for &(vk, down) in events {
+        let _ = super::global::reinject_vk(vk, !down);
+    }
+}
+
+/// 修饰键（含左右区分的虚拟键码）
+fn is_modifier_vk(vk: u16) -> bool {
+    matches!(vk, 0x10..=0x12 | 0xA0..=0xA5 | 0x5B | 0x5C)
+}
+
+/// 按键过滤状态机：输入物理按键事件，输出要发出的事件（虚拟键码，是否按下）
+#[derive(Debug)]
+struct KeyFilter {
+    options: AccessibilityOptions,
+    /// 慢速键：已按下、尚未达到阈值的按键
+    pending: HashMap<u16, Instant>,
+    /// 慢速键：已生效的按键
+    accepted: HashSet<u16>,
+    /// 粘滞键：物理按住的修饰键 -> 期间是否与普通按键组合过
+    held_modifiers: Vec<(u16, bool)>,
+    /// 粘滞键：锁存的修饰键
+    latched: Vec<u16>,
+    /// 粘滞键：带修饰键发出的普通按键 -> 发出的修饰键
+    combos: HashMap<u16, Vec<u16>>,
+}
+
+impl KeyFilter {
+    fn new(options: AccessibilityOptions) -> Self {
+        Self {
+            options,
+            pending: HashMap::new(),
+            accepted: HashSet::new(),
+            held_modifiers: Vec::new(),
+            latched: Vec::new(),
+            combos: HashMap::new(),
+        }
+    }
+
+    fn on_key(&mut self, vk: u16, down: bool, now: Instant) -> Vec<(u16, bool)> {
+        if self.options.slow_keys.is_none() {
+            return self.sticky(vk, down);
+        }
+        if down {
+            if self.accepted.contains(&vk) {
+                return self.sticky(vk, true);
+            }
+            self.pending.entry(vk).or_insert(now);
+            // 自动重复的按下事件也可能先于定时器到达阈值
+            self.on_tick(now)
+        } else if self.accepted.remove(&vk) {
+            self.sticky(vk, false)
+        } else {
+            // 按住时间不足，丢弃
+            self.pending.remove(&vk);
+            Vec::new()
+        }
+    }
+
+    fn on_tick(&mut self, now: Instant) -> Vec<(u16, bool)> {
+        let Some(threshold) = self.options.slow_keys else {
+            return Vec::new();
+        };
+        let mut ready: Vec<(u16, Instant)> = self
+            .pending
+            .iter()
+            .filter(|(_, &since)| now.duration_since(since) >= threshold)
+            .map(|(&vk, &since)| (vk, since))
+            .collect();
+        ready.sort_by_key(|&(_, since)| since);
+
+        let mut events = Vec::new();
+        for (vk, _) in ready {
+            self.pending.remove(&vk);
+            self.accepted.insert(vk);
+            events.extend(self.sticky(vk, true));
+        }
+        events
+    }
+
+    fn sticky(&mut self, vk: u16, down: bool) -> Vec<(u16, bool)> {
+        if !self.options.sticky_keys {
+            return vec![(vk, down)];
+        }
+
+        if is_modifier_vk(vk) {
+            if down {
+                if !self.held_modifiers.iter().any(|&(held, _)| held == vk) {
+                    self.held_modifiers.push((vk, false));
+                }
+            } else if let Some(index) = self.held_modifiers.iter().position(|&(m, _)| m == vk) {
+                let (_, combined) = self.held_modifiers.remove(index);
+                if !combined {
+                    // 单独按下并松开：切换锁存
+                    match self.latched.iter().position(|&m| m == vk) {
+                        Some(index) => {
+                            self.latched.remove(index);
+                        }
+                        None => self.latched.push(vk),
+                    }
+                }
+            }
+            return Vec::new();
+        }
+
+        if down {
+            if self.combos.contains_key(&vk) {
+                return vec![(vk, true)];
+            }
+            let mut modifiers: Vec<u16> = self.held_modifiers.iter().map(|&(m, _)| m).collect();
+            for &m in &self.latched {
+                if !modifiers.contains(&m) {
+                    modifiers.push(m);
+                }
+            }
+            if modifiers.is_empty() {
+                return vec![(vk, true)];
+            }
+            for held in &mut self.held_modifiers {
+                held.1 = true;
+            }
+            self.latched.clear();
+            let mut events: Vec<(u16, bool)> = modifiers.iter().map(|&m| (m, true)).collect();
+            events.push((vk, true));
+            self.combos.insert(vk, modifiers);
+            events
+        } else {
+            let mut events = vec![(vk, false)];
+            if let Some(modifiers) = self.combos.remove(&vk) {
+                events.extend(modifiers.iter().rev().map(|&m| (m, false)));
+            }
+            events
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LCTRL: u16 = 0xA2;
+    const LSHIFT: u16 = 0xA0;
+    const C: u16 = 0x43;
+
+    #[test]
+    fn test_sticky_latch() {
+        let now = Instant::now();
+        let mut filter = KeyFilter::new(AccessibilityOptions::new().sticky_keys());
+        assert!(filter.on_key(LCTRL, true, now).is_empty());
+        assert!(filter.on_key(LCTRL, false, now).is_empty());
+        assert_eq!(filter.on_key(C, true, now), vec![(LCTRL, true), (C, true)]);
+        assert_eq!(
+            filter.on_key(C, false, now),
+            vec![(C, false), (LCTRL, false)]
+        );
+        // 锁存只作用于下一个按键
+        assert_eq!(filter.on_key(C, true, now), vec![(C, true)]);
+        assert_eq!(filter.on_key(C, false, now), vec![(C, false)]);
+
+        // 再次单独按下取消锁存
+        for _ in 0..2 {
+            filter.on_key(LSHIFT, true, now);
+            filter.on_key(LSHIFT, false, now);
+        }
+        assert_eq!(filter.on_key(C, true, now), vec![(C, true)]);
+    }
+
+    #[test]
+    fn test_sticky_held_chord() {
+        let now = Instant::now();
+        let mut filter = KeyFilter::new(AccessibilityOptions::new().sticky_keys());
+        filter.on_key(LCTRL, true, now);
+        assert_eq!(filter.on_key(C, true, now), vec![(LCTRL, true), (C, true)]);
+        filter.on_key(C, false, now);
+        // 组合过的修饰键松开时不锁存
+        filter.on_key(LCTRL, false, now);
+        assert_eq!(filter.on_key(C, true, now), vec![(C, true)]);
+    }
+
+    #[test]
+    fn test_slow_keys() {
+        let start = Instant::now();
+        let threshold = Duration::from_millis(300);
+        let mut filter = KeyFilter::new(AccessibilityOptions::new().slow_keys(threshold));
+
+        // 短按被丢弃
+        assert!(filter.on_key(C, true, start).is_empty());
+        assert!(filter
+            .on_key(C, false, start + Duration::from_millis(100))
+            .is_empty());
+
+        // 按住超过阈值后生效
+        assert!(filter.on_key(C, true, start).is_empty());
+        assert!(filter
+            .on_tick(start + Duration::from_millis(200))
+            .is_empty());
+        assert_eq!(filter.on_tick(start + threshold), vec![(C, true)]);
+        assert_eq!(
+            filter.on_key(C, true, start + Duration::from_millis(400)),
+            vec![(C, true)]
+        );
+        assert_eq!(
+            filter.on_key(C, false, start + Duration::from_millis(500)),
+            vec![(C, false)]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// src/core/atomic_op.rs
+//! 逻辑操作的串行化
+//!
+//! 本 crate 注入的每个逻辑操作（一次按键点击、组合键、字符串输入等）在执行期间
+//! 持有一个进程级的可重入锁，因此多个线程同时发送时，不同操作的事件不会相互交错；
+//! 但操作之间的先后顺序不作保证。
+use std::cell::Cell;
+use std::sync::{Mutex, MutexGuard};
+
+static OPERATION_LOCK: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    /// 当前线程持有锁的嵌套深度
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// 逻辑操作守卫，持有期间其他线程的操作会等待
+pub struct OperationGuard {
+    _lock: Option<MutexGuard<'static, ()>>,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// 开始一个逻辑操作；同一线程内可以嵌套调用
+pub fn begin_operation() -> OperationGuard {
+    let outermost = DEPTH.with(|depth| {
+        let current = depth.get();
+        depth.set(current + 1);
+        current == 0
+    });
+
+    let lock = outermost.then(|| {
+        // 其他线程在持锁时 panic 不影响串行化语义，忽略中毒状态
+        OPERATION_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    });
+    OperationGuard { _lock: lock }
+}
+
+/// 将一组发送作为一个不可交错的逻辑操作执行
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{atomic_operation, key_click, type_string, Key};
+///
+/// // 其他线程的输入不会插入到这两步之间
+/// atomic_operation(|| {
+///     type_string("user")?;
+///     key_click(Key::Tab, None)
+/// })
+/// .unwrap();
+/// ```
+pub fn atomic_operation<T, F: FnOnce() -> T>(f: F) -> T {
+    let _guard = begin_operation();
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    #[test]
+    fn test_operations_are_not_interleaved() {
+        let log = Arc::new(StdMutex::new(Vec::new()));
+        let threads: Vec<_> = (0..8)
+            .map(|id| {
+                let log = log.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        atomic_operation(|| {
+                            for step in 0..5 {
+                                // 嵌套操作不应死锁
+                                atomic_operation(|| log.lock().unwrap().push((id, step)));
+                                std::thread::yield_now();
+                            }
+                        });
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 8 * 50 * 5);
+        for chunk in log.chunks(5) {
+            let id = chunk[0].0;
+            let steps: Vec<_> = chunk
+                .iter()
+                .map(|&(i, s)| {
+                    assert_eq!(i, id);
+                    s
+                })
+                .collect();
+            assert_eq!(steps, vec![0, 1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn test_lock_released_after_panic() {
+        let result = std::thread::spawn(|| atomic_operation(|| panic!("boom"))).join();
+        assert!(result.is_err());
+        assert_eq!(atomic_operation(|| 1 + 1), 2);
+    }
+}
@@ -0,0 +1,271 @@
+// src/core/capture.rs
+//! 发送并读取响应
+//!
+//! [`send_and_capture`] 向请求/响应式的窗口（REPL、聊天框等）输入一行文本并按回车，
+//! 然后在超时时间内读取窗口新增的文本：
+//! - [`CaptureMethod::WindowText`]：通过 `WM_GETTEXT` 读取窗口文本并与发送前比较，
+//!   适用于编辑框、富文本框等控件（`hwnd` 应为该控件本身）
+//! - [`CaptureMethod::Clipboard`]：全选（Ctrl+A）后复制（Ctrl+C），适用于不响应 `WM_GETTEXT`
+//!   的控制台和终端；会激活目标窗口，剪贴板原有文本在结束后恢复（需要 `clipboard` 特性）
+//!
+//! `WindowText` 方式在文本首次变化后继续读取，直到在 `settle` 时间内不再变化，避免只读到部分输出。
+//! `Clipboard` 方式不轮询：反复全选复制会在控制台中反复激活窗口，没有选中内容时 Ctrl+C 还会中断
+//! 正在运行的程序。它在提交后等待 `settle`，只全选复制一次，再按 Esc 取消选择，
+//! 返回最后一次出现的输入之后的文本。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, WindowHandle};
+use std::time::Duration;
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_GETTEXT, WM_GETTEXTLENGTH,
+    },
+};
+
+/// 读取响应的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureMethod {
+    /// 通过 `WM_GETTEXT` 读取窗口文本（默认）
+    #[default]
+    WindowText,
+    /// 输出稳定后全选并复制一次到剪贴板
+    #[cfg(feature = "clipboard")]
+    Clipboard,
+}
+
+/// 读取响应的选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureSpec {
+    /// 读取方式
+    pub method: CaptureMethod,
+    /// 等待响应的超时时间（默认 5 秒）
+    pub timeout: Duration,
+    /// 文本保持不变多久视为输出结束（默认 300ms）；`Clipboard` 方式下为提交后复制前的等待时间
+    pub settle: Duration,
+    /// 两次读取之间的间隔（默认 50ms，`Clipboard` 方式不轮询）
+    pub poll_interval: Duration,
+    /// 输入后是否按回车提交（默认启用）
+    pub submit: bool,
+}
+
+impl Default for CaptureSpec {
+    fn default() -> Self {
+        Self {
+            method: CaptureMethod::default(),
+            timeout: Duration::from_secs(5),
+            settle: Duration::from_millis(300),
+            poll_interval: Duration::from_millis(50),
+            submit: true,
+        }
+    }
+}
+
+impl CaptureSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置读取方式
+    pub fn method(mut self, method: CaptureMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// 设置等待响应的超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置输出稳定时间
+    pub fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    /// 设置读取间隔
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 输入后是否按回车
+    pub fn submit(mut self, submit: bool) -> Self {
+        self.submit = submit;
+        self
+    }
+}
+
+/// 向窗口输入文本（默认再按回车），返回窗口新增的文本
+///
+/// 超时前文本没有变化时返回 `Timeout` 错误；文本已变化但未稳定时返回超时前读到的内容。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{send_and_capture, CaptureSpec};
+/// use std::time::Duration;
+///
+/// let output = send_and_capture(0x1A2B, "1 + 1", &CaptureSpec::new().timeout(Duration::from_secs(2))).unwrap();
+/// println!("{}", output);
+/// # }
+/// ```
+pub fn send_and_capture(hwnd: WindowHandle, text: &str, spec: &CaptureSpec) -> Result<String> {
+    #[cfg(feature = "clipboard")]
+    if spec.method == CaptureMethod::Clipboard {
+        return super::with_clipboard_preserved(|| capture(hwnd, text, spec));
+    }
+    capture(hwnd, text, spec)
+}
+
+fn capture(hwnd: WindowHandle, text: &str, spec: &CaptureSpec) -> Result<String> {
+    #[cfg(feature = "clipboard")]
+    if spec.method == CaptureMethod::Clipboard {
+        return capture_by_clipboard(hwnd, text, spec);
+    }
+
+    let before = window_text(hwnd, spec.timeout)?;
+    super::type_string_to_window(hwnd, text)?;
+    if spec.submit {
+        super::send_key_click_to_window(hwnd, Key::Enter, None)?;
+    }
+
+    let clock = super::clock();
+    let start = clock.now();
+    let mut last = before.clone();
+    let mut changed_at = None;
+    loop {
+        clock.sleep(spec.poll_interval);
+        let current = window_text(hwnd, spec.timeout)?;
+        let now = clock.now();
+        if current != last {
+            last = current;
+            changed_at = Some(now);
+        } else if changed_at.is_some_and(|at| now.duration_since(at) >= spec.settle) {
+            break;
+        }
+        if now.duration_since(start) >= spec.timeout {
+            if changed_at.is_none() {
+                return Err(KeyboardSenderError::Timeout(format!(
+                    "window {:#x} did not respond within {:?}",
+                    hwnd, spec.timeout
+                )));
+            }
+            break;
+        }
+    }
+    Ok(new_text(&before, &last))
+}
+
+/// 剪贴板方式：提交后等待 `settle`，全选复制一次
+#[cfg(feature = "clipboard")]
+fn capture_by_clipboard(hwnd: WindowHandle, text: &str, spec: &CaptureSpec) -> Result<String> {
+    super::type_string_to_window(hwnd, text)?;
+    if spec.submit {
+        super::send_key_click_to_window(hwnd, Key::Enter, None)?;
+    }
+    super::clock().sleep(spec.settle);
+
+    super::set_window_focus(hwnd, true)?;
+    super::press_combination(&[crate::types::Modifier::Control], Key::A, None)?;
+    let copied = super::copy_selection(spec.timeout);
+    // 取消全选，避免之后的输入替换全部内容
+    let _ = super::key_click(Key::Escape, None);
+    Ok(after_input(&copied?, text))
+}
+
+/// 最后一次出现的输入之后的文本（去掉紧随其后的换行），输入没有出现时返回全部文本
+#[cfg(feature = "clipboard")]
+fn after_input(output: &str, input: &str) -> String {
+    match output.rfind(input).filter(|_| !input.is_empty()) {
+        Some(index) => output[index + input.len()..]
+            .trim_start_matches(['\r', '\n'])
+            .to_string(),
+        None => output.to_string(),
+    }
+}
+
+/// 通过 `WM_GETTEXT` 读取窗口文本
+pub fn window_text(hwnd: WindowHandle, timeout: Duration) -> Result<String> {
+    let mut len = 0usize;
+    send_message(hwnd, WM_GETTEXTLENGTH, 0, 0, timeout, &mut len)?;
+    let mut buffer = vec![0u16; len + 1];
+    let mut copied = 0usize;
+    send_message(
+        hwnd,
+        WM_GETTEXT,
+        buffer.len(),
+        buffer.as_mut_ptr() as isize,
+        timeout,
+        &mut copied,
+    )?;
+    Ok(String::from_utf16_lossy(&buffer[..copied.min(len)]))
+}
+
+fn send_message(
+    hwnd: WindowHandle,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+    timeout: Duration,
+    result: &mut usize,
+) -> Result<()> {
+    let ok = unsafe {
+        SendMessageTimeoutW(
+            HWND(hwnd),
+            message,
+            WPARAM(wparam),
+            LPARAM(lparam),
+            SMTO_ABORTIFHUNG,
+            timeout.as_millis().min(u128::from(u32::MAX)) as u32,
+            Some(result),
+        )
+    };
+    if ok.0 == 0 {
+        return Err(KeyboardSenderError::Timeout(format!(
+            "window {:#x} did not return its text within {:?}",
+            hwnd, timeout
+        )));
+    }
+    Ok(())
+}
+
+/// 去掉与发送前相同的前缀，得到新增的文本
+fn new_text(before: &str, after: &str) -> String {
+    let common = before
+        .char_indices()
+        .zip(after.chars())
+        .find(|((_, a), b)| a != b)
+        .map(|((index, _), _)| index)
+        .unwrap_or_else(|| before.len().min(after.len()));
+    after[common..].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_text() {
+        assert_eq!(
+            new_text(">>> ", ">>> 1 + 1\r\n2\r\n>>> "),
+            "1 + 1\r\n2\r\n>>> "
+        );
+        assert_eq!(new_text("", "hello"), "hello");
+        assert_eq!(new_text("abc", "abc"), "");
+        assert_eq!(new_text("héllo", "hélp"), "p");
+    }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn test_after_input() {
+        assert_eq!(
+            after_input("PS> 1 + 1\r\n2\r\nPS> 2 + 2\r\n4\r\nPS> ", "2 + 2"),
+            "4\r\nPS> "
+        );
+        assert_eq!(after_input("no echo", "dir"), "no echo");
+        assert_eq!(after_input("abc", ""), "abc");
+    }
+}
@@ -0,0 +1,216 @@
+// src/core/capture_window.rs
+//! 捕获窗口
+//!
+//! 自检、按键事件集成测试和注入基准都需要一个接收注入事件的窗口：创建后置于前台，
+//! 窗口过程记录收到的按键和字符消息及其到达时刻。[`CaptureWindow`] 是它们共用的实现。
+//!
+//! 窗口过程在创建窗口的线程上运行，记录只能在该线程上通过 [`CaptureWindow::take_events`] 取出。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use windows::core::{w, HSTRING};
+use windows::Win32::{
+    Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+    UI::Input::KeyboardAndMouse::SetFocus,
+    UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, PeekMessageW,
+        RegisterClassW, SetForegroundWindow, TranslateMessage, MSG, PM_REMOVE, WINDOW_EX_STYLE,
+        WM_CHAR, WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP, WNDCLASSW, WS_OVERLAPPED,
+        WS_VISIBLE,
+    },
+};
+
+const CLASS_NAME: windows::core::PCWSTR = w!("SophiaCaptureWindow");
+
+/// 捕获到的消息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapturedKind {
+    /// `WM_KEYDOWN` / `WM_SYSKEYDOWN`
+    KeyDown,
+    /// `WM_KEYUP` / `WM_SYSKEYUP`
+    KeyUp,
+    /// `WM_CHAR`（增补平面字符为两条代理项消息）
+    Char,
+}
+
+/// 捕获窗口收到的一条键盘消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapturedEvent {
+    pub kind: CapturedKind,
+    /// 按键消息为虚拟键码，字符消息为 UTF-16 单元
+    pub code: u16,
+    /// 扫描码（`lParam` 的 16-23 位）
+    pub scan: u16,
+    /// 扩展键标志
+    pub extended: bool,
+    /// 窗口过程收到消息的时刻
+    pub at: Instant,
+}
+
+impl CapturedEvent {
+    /// 是否为按键消息
+    pub fn is_key(&self) -> bool {
+        matches!(self.kind, CapturedKind::KeyDown | CapturedKind::KeyUp)
+    }
+}
+
+thread_local! {
+    static CAPTURED: RefCell<Vec<CapturedEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+unsafe extern "system" fn capture_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let kind = match msg {
+        WM_KEYDOWN | WM_SYSKEYDOWN => CapturedKind::KeyDown,
+        WM_KEYUP | WM_SYSKEYUP => CapturedKind::KeyUp,
+        WM_CHAR => CapturedKind::Char,
+        _ => return DefWindowProcW(hwnd, msg, wparam, lparam),
+    };
+    let event = CapturedEvent {
+        kind,
+        code: wparam.0 as u16,
+        scan: ((lparam.0 >> 16) & 0xFF) as u16,
+        extended: (lparam.0 >> 24) & 1 != 0,
+        at: Instant::now(),
+    };
+    CAPTURED.with(|captured| captured.borrow_mut().push(event));
+    // 系统按键也不交给默认处理，避免 Alt 激活菜单等副作用
+    LRESULT(0)
+}
+
+/// 记录键盘消息的顶层窗口，销毁时关闭
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{key_click, CaptureWindow, Key};
+/// use std::time::Duration;
+///
+/// let window = CaptureWindow::open("capture").unwrap();
+/// key_click(Key::A, None).unwrap();
+/// std::thread::sleep(Duration::from_millis(30));
+/// println!("{:?}", window.take_events());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CaptureWindow(HWND);
+
+impl CaptureWindow {
+    /// 创建窗口并置于前台，等待系统完成激活后清空已收到的消息
+    pub fn open(title: &str) -> Result<Self> {
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(capture_proc),
+            hInstance: HINSTANCE(0),
+            lpszClassName: CLASS_NAME,
+            ..Default::default()
+        };
+        // 重复注册会失败，窗口类仍然可用
+        unsafe { RegisterClassW(&class) };
+        let hwnd = unsafe {
+            CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                CLASS_NAME,
+                &HSTRING::from(title),
+                WS_OVERLAPPED | WS_VISIBLE,
+                100,
+                100,
+                320,
+                120,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        if hwnd.0 == 0 {
+            return Err(KeyboardSenderError::WindowsError);
+        }
+        let window = Self(hwnd);
+        window.focus();
+        std::thread::sleep(Duration::from_millis(100));
+        window.clear();
+        Ok(window)
+    }
+
+    /// 窗口句柄
+    pub fn handle(&self) -> WindowHandle {
+        self.0 .0
+    }
+
+    /// 把窗口置于前台并设置键盘焦点，返回是否成为前台窗口
+    pub fn focus(&self) -> bool {
+        unsafe {
+            let foreground = SetForegroundWindow(self.0).as_bool();
+            SetFocus(self.0);
+            foreground
+        }
+    }
+
+    /// 处理队列中的消息（按键经 `TranslateMessage` 转换出字符消息后分发给窗口过程）
+    pub fn pump(&self) {
+        let mut msg = MSG::default();
+        unsafe {
+            while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+    }
+
+    /// 处理队列中的消息并取出全部记录
+    pub fn take_events(&self) -> Vec<CapturedEvent> {
+        self.pump();
+        CAPTURED.with(|captured| std::mem::take(&mut *captured.borrow_mut()))
+    }
+
+    /// 丢弃队列中的消息和已有记录
+    pub fn clear(&self) {
+        self.take_events();
+    }
+
+    /// 等待依次收到 `expected` 中的字符或按下消息（`(类型, 代码)`），超时返回 `None`
+    ///
+    /// 匹配的记录被移除，不匹配的保留；返回最后一条匹配消息的到达时刻。
+    pub fn wait_for(&self, expected: &[(CapturedKind, u16)], timeout: Duration) -> Option<Instant> {
+        let deadline = Instant::now() + timeout;
+        let mut remaining = expected;
+        let mut last = None;
+        loop {
+            self.pump();
+            CAPTURED.with(|captured| {
+                let mut captured = captured.borrow_mut();
+                while let Some(&(kind, code)) = remaining.first() {
+                    let Some(index) = captured
+                        .iter()
+                        .position(|e| e.kind == kind && e.code == code)
+                    else {
+                        break;
+                    };
+                    last = Some(captured.remove(index).at);
+                    remaining = &remaining[1..];
+                }
+            });
+            if remaining.is_empty() {
+                return last;
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+impl Drop for CaptureWindow {
+    fn drop(&mut self) {
+        let _ = unsafe { DestroyWindow(self.0) };
+        self.clear();
+    }
+}
@@ -0,0 +1,191 @@
+// src/core/clipboard.rs
+//! 剪贴板文本读写
+use crate::error::{KeyboardSenderError, Result};
+use crate::retry::{Backoff, RetryPolicy};
+use std::time::Duration;
+
+use windows::Win32::{
+    Foundation::{GlobalFree, HANDLE, HGLOBAL, HWND},
+    System::{
+        DataExchange::{
+            CloseClipboard, EmptyClipboard, GetClipboardData, GetClipboardSequenceNumber,
+            IsClipboardFormatAvailable, OpenClipboard, SetClipboardData,
+        },
+        Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+    },
+};
+
+/// CF_UNICODETEXT 剪贴板格式
+const CF_UNICODETEXT: u32 = 13;
+
+/// 打开剪贴板的重试策略（其他进程可能正短暂占用剪贴板）
+fn open_policy() -> RetryPolicy {
+    RetryPolicy::new()
+        .attempts(10)
+        .backoff(Backoff::Fixed(Duration::from_millis(10)))
+        .jitter(0.0)
+}
+
+/// 已打开的剪贴板，离开作用域时自动关闭
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    fn open() -> Result<Self> {
+        open_policy().run(|_| unsafe {
+            OpenClipboard(HWND(0))
+                .map(|_| OpenedClipboard)
+                .map_err(|_| KeyboardSenderError::WindowsError)
+        })
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseClipboard();
+        }
+    }
+}
+
+/// 读取剪贴板文本，剪贴板中没有文本时返回 `None`
+pub fn get_clipboard_text() -> Result<Option<String>> {
+    let _clipboard = OpenedClipboard::open()?;
+    unsafe {
+        if IsClipboardFormatAvailable(CF_UNICODETEXT).is_err() {
+            return Ok(None);
+        }
+        let handle =
+            GetClipboardData(CF_UNICODETEXT).map_err(|_| KeyboardSenderError::WindowsError)?;
+        let hglobal = HGLOBAL(handle.0 as *mut _);
+        let ptr = GlobalLock(hglobal) as *const u16;
+        if ptr.is_null() {
+            return Err(KeyboardSenderError::WindowsError);
+        }
+
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+        let text = String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len));
+        let _ = GlobalUnlock(hglobal);
+        Ok(Some(text))
+    }
+}
+
+/// 写入剪贴板文本（替换剪贴板中的全部内容）
+pub fn set_clipboard_text(text: &str) -> Result<()> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+
+    let _clipboard = OpenedClipboard::open()?;
+    unsafe {
+        EmptyClipboard().map_err(|_| KeyboardSenderError::WindowsError)?;
+
+        let hglobal = GlobalAlloc(GMEM_MOVEABLE, wide.len() * std::mem::size_of::<u16>())
+            .map_err(|_| KeyboardSenderError::WindowsError)?;
+        let ptr = GlobalLock(hglobal) as *mut u16;
+        if ptr.is_null() {
+            let _ = GlobalFree(hglobal);
+            return Err(KeyboardSenderError::WindowsError);
+        }
+        std::ptr::copy_nonoverlapping(wide.as_ptr(), ptr, wide.len());
+        let _ = GlobalUnlock(hglobal);
+
+        // 成功后内存归系统所有，失败时需要自行释放
+        if SetClipboardData(CF_UNICODETEXT, HANDLE(hglobal.0 as isize)).is_err() {
+            let _ = GlobalFree(hglobal);
+            return Err(KeyboardSenderError::WindowsError);
+        }
+    }
+    Ok(())
+}
+
+/// 清空剪贴板
+pub fn clear_clipboard() -> Result<()> {
+    let _clipboard = OpenedClipboard::open()?;
+    unsafe { EmptyClipboard().map_err(|_| KeyboardSenderError::WindowsError) }
+}
+
+/// 剪贴板序列号，每次剪贴板内容变化时递增
+pub fn clipboard_sequence_number() -> u32 {
+    unsafe { GetClipboardSequenceNumber() }
+}
+
+/// 执行操作并在结束后恢复剪贴板原有文本（无论操作是否成功）
+///
+/// 仅保存和恢复文本格式；原剪贴板中没有文本时，结束后清空剪贴板。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "clipboard")]
+/// # {
+/// use sophia_keyboard_sender::*;
+///
+/// with_clipboard_preserved(|| {
+///     set_clipboard_text("临时内容")?;
+///     press_combination(&[Modifier::Control], Key::V, None)
+/// })
+/// .unwrap();
+/// # }
+/// ```
+pub fn with_clipboard_preserved<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    let saved = get_clipboard_text()?;
+    let result = f();
+    let restored = match saved {
+        Some(text) => set_clipboard_text(&text),
+        None => clear_clipboard(),
+    };
+    let value = result?;
+    restored?;
+    Ok(value)
+}
+
+/// 等待复制结果的默认超时时间
+pub const DEFAULT_COPY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 向前台窗口发送 Ctrl+C，等待剪贴板变化并返回复制的文本
+pub fn copy_selection(timeout: Duration) -> Result<String> {
+    let before = clipboard_sequence_number();
+    super::press_combination(
+        &[crate::types::Modifier::Control],
+        crate::types::Key::C,
+        None,
+    )?;
+
+    RetryPolicy::polling(timeout, Duration::from_millis(10))
+        .poll(|| (clipboard_sequence_number() != before).then_some(()))
+        .ok_or_else(|| {
+            KeyboardSenderError::Timeout(format!("clipboard did not change within {:?}", timeout))
+        })?;
+
+    Ok(get_clipboard_text()?.unwrap_or_default())
+}
+
+/// 激活指定窗口并复制其当前选中内容
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(all(feature = "clipboard", feature = "window_target"))]
+/// # {
+/// use sophia_keyboard_sender::copy_from_window;
+///
+/// let text = copy_from_window(0x1A2B).unwrap();
+/// println!("{}", text);
+/// # }
+/// ```
+#[cfg(feature = "window_target")]
+pub fn copy_from_window(hwnd: crate::types::WindowHandle) -> Result<String> {
+    copy_from_window_with_timeout(hwnd, DEFAULT_COPY_TIMEOUT)
+}
+
+/// 激活指定窗口并复制其当前选中内容，指定等待剪贴板变化的超时时间
+#[cfg(feature = "window_target")]
+pub fn copy_from_window_with_timeout(
+    hwnd: crate::types::WindowHandle,
+    timeout: Duration,
+) -> Result<String> {
+    super::set_window_focus(hwnd, true)?;
+    copy_selection(timeout)
+}
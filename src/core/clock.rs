@@ -0,0 +1,170 @@
+// src/core/clock.rs
+//! 时钟抽象
+//!
+//! 按下时长、延迟（[`DelaySpec`](super::DelaySpec)）、按键自动重复（[`key_hold`](super::key_hold)）、
+//! 终端节奏和命令回放都通过 [`clock`] 取时间和等待，而不是直接调用 `Instant::now` / `thread::sleep`。
+//! 测试中用 [`with_clock`] 换成 [`MockClock`]，等待会立即返回并推进虚拟时间，
+//! 依赖时序的行为无需真实等待即可测试。
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// 时钟：提供当前时间和等待
+pub trait Clock: Send + Sync {
+    /// 当前时间
+    fn now(&self) -> Instant;
+
+    /// 等待到 `deadline`，已过期时立即返回
+    fn sleep_until(&self, deadline: Instant);
+
+    /// 等待 `duration`
+    fn sleep(&self, duration: Duration) {
+        self.sleep_until(self.now() + duration);
+    }
+}
+
+/// 系统时钟（默认）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// 虚拟时钟：等待立即返回并把时间推进到截止时刻，用于测试
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{clock, with_clock, Clock, MockClock};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let mock = Arc::new(MockClock::new());
+/// let start = mock.now();
+/// with_clock(mock.clone(), || clock().sleep(Duration::from_secs(60)));
+/// assert_eq!(mock.elapsed(), Duration::from_secs(60));
+/// assert_eq!(mock.now() - start, Duration::from_secs(60));
+/// ```
+#[derive(Debug)]
+pub struct MockClock {
+    origin: Instant,
+    offset: Mutex<Duration>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    /// 从当前时刻开始计时的虚拟时钟
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// 手动推进时间
+    pub fn advance(&self, duration: Duration) {
+        *self.offset.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+
+    /// 自创建以来经过的虚拟时间
+    pub fn elapsed(&self) -> Duration {
+        *self.offset.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn sleep_until(&self, deadline: Instant) {
+        let mut offset = self.offset.lock().unwrap_or_else(|e| e.into_inner());
+        *offset = (*offset).max(deadline.saturating_duration_since(self.origin));
+    }
+}
+
+static GLOBAL_CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+thread_local! {
+    /// 当前线程的时钟覆盖（见 [`with_clock`]）
+    static THREAD_CLOCK: RefCell<Option<Arc<dyn Clock>>> = const { RefCell::new(None) };
+}
+
+/// 设置进程级时钟，`None` 恢复系统时钟
+pub fn set_clock(clock: Option<Arc<dyn Clock>>) {
+    *GLOBAL_CLOCK.write().unwrap_or_else(|e| e.into_inner()) = clock;
+}
+
+/// 当前使用的时钟：当前线程的覆盖优先，其次是进程级时钟，默认 [`SystemClock`]
+pub fn clock() -> Arc<dyn Clock> {
+    if let Some(clock) = THREAD_CLOCK.with(|c| c.borrow().clone()) {
+        return clock;
+    }
+    GLOBAL_CLOCK
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| Arc::new(SystemClock))
+}
+
+/// 在当前线程上使用 `clock` 执行 `f`，结束（包括 panic）后恢复
+///
+/// 只影响当前线程，并行运行的测试之间互不干扰；`f` 中新建的线程仍使用进程级时钟。
+pub fn with_clock<T>(clock: Arc<dyn Clock>, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Arc<dyn Clock>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            THREAD_CLOCK.with(|c| *c.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(THREAD_CLOCK.with(|c| c.borrow_mut().replace(clock)));
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_sleep_advances() {
+        let mock = Arc::new(MockClock::new());
+        let start = mock.now();
+        with_clock(mock.clone(), || {
+            let clock = clock();
+            clock.sleep(Duration::from_millis(40));
+            // 已过期的截止时刻不回退
+            clock.sleep_until(start);
+            mock.advance(Duration::from_millis(10));
+        });
+        assert_eq!(mock.now() - start, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_with_clock_restores() {
+        let real = std::time::Instant::now();
+        let mock = Arc::new(MockClock::new());
+        with_clock(mock.clone(), || {
+            clock().sleep(Duration::from_secs(3600));
+        });
+        assert_eq!(mock.elapsed(), Duration::from_secs(3600));
+        assert!(real.elapsed() < Duration::from_secs(60));
+        assert!(clock().now() >= real);
+    }
+}
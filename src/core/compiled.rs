@@ -0,0 +1,317 @@
+// src/core/compiled.rs
+//! 预编译的输入序列（突发模式）
+//!
+//! 速通工具、时序测试等对延迟敏感的场景中，逐条执行 [`InputAction`] 会在每一步解析按键、
+//! 查询配置并分配事件数组。[`compile_sequence`] 提前完成这些工作：解析按键、生成全部
+//! `INPUT` 事件，并把等待折算为相对开始时刻的时间表；[`CompiledSequence::run`] 执行时
+//! 只按时间表提交事件和等待，不再解析或分配内存。
+//!
+//! 编译时取当时的注入方式（虚拟键码 / 扫描码）、输入标记、默认按下时长和文本选项，
+//! 之后修改这些设置不影响已编译的序列。文本一律按 Unicode 事件发送；开启
+//! [`numpad_digits`](super::TextOptions::numpad_digits) 时数字编译为小键盘按键，执行期间临时开启 NumLock
+//! （见 [`with_num_lock`](super::with_num_lock)）。
+//! 执行不上报遥测事件，也不附加按下时长抖动。
+use crate::error::Result;
+use crate::types::Key;
+use std::time::Duration;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{INPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE};
+
+use super::global::{check_system_shortcut, push_unicode_inputs, push_vk_inputs, submit};
+use super::sequence::InputAction;
+use super::text_options::{effective_text_options, text_segments, TextSegment};
+use super::{key_to_vk, modifier_to_vk};
+
+/// 一次提交：`inputs[start..end]` 在开始后 `at` 时提交
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Burst {
+    start: usize,
+    end: usize,
+    at: Duration,
+}
+
+/// 预编译的输入序列，由 [`compile_sequence`] 生成，可重复执行
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{compile_sequence, InputAction, Key};
+/// use std::time::Duration;
+///
+/// let compiled = compile_sequence(&[
+///     InputAction::Down(Key::D),
+///     InputAction::Sleep(Duration::from_millis(120)),
+///     InputAction::Click(Key::Space),
+///     InputAction::Up(Key::D),
+/// ])
+/// .unwrap();
+/// compiled.run().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct CompiledSequence {
+    inputs: Vec<INPUT>,
+    bursts: Vec<Burst>,
+    duration: Duration,
+    /// 含小键盘数字，执行期间需要开启 NumLock
+    num_lock: bool,
+}
+
+impl std::fmt::Debug for CompiledSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompiledSequence")
+            .field("events", &self.inputs.len())
+            .field("bursts", &self.bursts.len())
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+impl CompiledSequence {
+    /// 事件总数
+    pub fn event_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// `SendInput` 提交次数（两次等待之间的事件合并为一次提交）
+    pub fn burst_count(&self) -> usize {
+        self.bursts.len()
+    }
+
+    /// 按时间表执行完所需的时间（不含提交本身的耗时）
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// 全局发送：按时间表执行
+    ///
+    /// 提交时刻按相对开始时刻的绝对时间等待，单次等待的误差不会累积。持有逻辑操作锁，
+    /// 其他线程的输入不会插入其中。任一提交失败时停止执行，已按下尚未释放的按键会被释放。
+    /// 序列含小键盘数字时，执行期间临时开启 NumLock。
+    pub fn run(&self) -> Result<()> {
+        let _op = super::begin_operation();
+        super::config::check_focus()?;
+        if self.num_lock {
+            return super::state::with_num_lock(|| self.run_schedule());
+        }
+        self.run_schedule()
+    }
+
+    fn run_schedule(&self) -> Result<()> {
+        let clock = super::clock();
+        let start = clock.now();
+        for (index, burst) in self.bursts.iter().enumerate() {
+            clock.sleep_until(start + burst.at);
+            if let Err(e) = submit(&self.inputs[burst.start..burst.end]) {
+                self.release_held(index);
+                return Err(e);
+            }
+        }
+        clock.sleep_until(start + self.duration);
+        Ok(())
+    }
+
+    /// 释放前 `failed` 次提交（以及失败的那次）中按下且尚未释放的按键（忽略错误）
+    fn release_held(&self, failed: usize) {
+        let end = self.bursts[failed].end;
+        let mut held: Vec<INPUT> = Vec::new();
+        for input in &self.inputs[..end] {
+            let ki = unsafe { input.Anonymous.ki };
+            if ki.dwFlags.contains(KEYEVENTF_UNICODE) {
+                continue;
+            }
+            held.retain(|down| {
+                let down = unsafe { down.Anonymous.ki };
+                down.wVk != ki.wVk || down.wScan != ki.wScan
+            });
+            if !ki.dwFlags.contains(KEYEVENTF_KEYUP) {
+                held.push(*input);
+            }
+        }
+        let ups: Vec<INPUT> = held
+            .into_iter()
+            .rev()
+            .map(|mut input| {
+                unsafe { input.Anonymous.ki.dwFlags |= KEYEVENTF_KEYUP };
+                input
+            })
+            .collect();
+        let _ = submit(&ups);
+    }
+}
+
+/// 编译过程中的序列
+struct Builder {
+    inputs: Vec<INPUT>,
+    bursts: Vec<Burst>,
+    at: Duration,
+    num_lock: bool,
+}
+
+impl Builder {
+    /// 结束当前提交并等待 `duration`
+    fn wait(&mut self, duration: Duration) {
+        if duration.is_zero() {
+            return;
+        }
+        self.flush();
+        self.at += duration;
+    }
+
+    /// 把尚未归入提交的事件合并为一次提交
+    fn flush(&mut self) {
+        let start = self.bursts.last().map_or(0, |burst| burst.end);
+        if start < self.inputs.len() {
+            self.bursts.push(Burst {
+                start,
+                end: self.inputs.len(),
+                at: self.at,
+            });
+        }
+    }
+
+    fn down(&mut self, vk: u16) {
+        push_vk_inputs(&mut self.inputs, vk, false);
+    }
+
+    fn up(&mut self, vk: u16) {
+        push_vk_inputs(&mut self.inputs, vk, true);
+    }
+
+    /// 依次按下 `vks`，保持 `hold` 后按相反顺序释放
+    fn press(&mut self, vks: &[u16], hold: Duration) -> Result<()> {
+        check_system_shortcut(vks)?;
+        for &vk in vks {
+            self.down(vk);
+        }
+        self.wait(hold);
+        for &vk in vks.iter().rev() {
+            self.up(vk);
+        }
+        Ok(())
+    }
+
+    fn click(&mut self, key: Key, hold: Duration) -> Result<()> {
+        self.press(&[key_to_vk(key)], hold)
+    }
+
+    fn text(&mut self, text: &str, hold: Duration) -> Result<()> {
+        let options = effective_text_options();
+        let text = options.apply(text)?;
+        for segment in text_segments(&text, &options) {
+            self.num_lock |= segment.is_numpad();
+            match segment {
+                TextSegment::Text(text) => {
+                    for c in text.chars() {
+                        push_unicode_inputs(&mut self.inputs, c);
+                    }
+                }
+                TextSegment::Key(key, _, _) => self.click(key, hold)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 预编译动作序列
+///
+/// 点击、组合键等按键的按下时长编译为时间表中的等待（`None` 取编译时的默认按下时长），
+/// 两次等待之间的全部事件合并为一次 `SendInput` 提交。按下会触发系统快捷键的按键在编译时
+/// 检查 [`allow_system_shortcuts`](super::allow_system_shortcuts)，不允许时返回 `InjectionBlocked` 错误。
+pub fn compile_sequence(actions: &[InputAction]) -> Result<CompiledSequence> {
+    let hold = super::config::default_press_duration();
+    let mut builder = Builder {
+        inputs: Vec::new(),
+        bursts: Vec::new(),
+        at: Duration::ZERO,
+        num_lock: false,
+    };
+    for action in actions {
+        match action {
+            InputAction::Down(key) => {
+                let vk = key_to_vk(*key);
+                check_system_shortcut(&[vk])?;
+                builder.down(vk);
+            }
+            InputAction::Up(key) => builder.up(key_to_vk(*key)),
+            InputAction::Click(key) => builder.click(*key, hold)?,
+            InputAction::Char(c) => {
+                let mut buffer = [0u8; 4];
+                builder.text(c.encode_utf8(&mut buffer), hold)?;
+            }
+            InputAction::Text(text) => builder.text(text, hold)?,
+            InputAction::Sleep(duration) => builder.wait(*duration),
+            InputAction::Combo(modifiers, key) => {
+                let vks: Vec<u16> = modifiers
+                    .iter()
+                    .map(|&modifier| modifier_to_vk(modifier))
+                    .chain(std::iter::once(key_to_vk(*key)))
+                    .collect();
+                builder.press(&vks, hold)?;
+            }
+            InputAction::ModifierClick(modifier) => {
+                builder.press(&[modifier_to_vk(*modifier)], hold)?
+            }
+            InputAction::VkClick(vk, duration) => {
+                builder.press(&[*vk], duration.unwrap_or(hold))?
+            }
+        }
+    }
+    builder.flush();
+    Ok(CompiledSequence {
+        inputs: builder.inputs,
+        bursts: builder.bursts,
+        duration: builder.at,
+        num_lock: builder.num_lock,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_schedule() {
+        let compiled = compile_sequence(&[
+            InputAction::Down(Key::D),
+            InputAction::Sleep(Duration::from_millis(100)),
+            InputAction::VkClick(0x41, Some(Duration::from_millis(10))),
+            InputAction::Text("ab".to_string()),
+            InputAction::VkClick(0x42, Some(Duration::from_millis(5))),
+            InputAction::Sleep(Duration::ZERO),
+            InputAction::Up(Key::D),
+        ])
+        .unwrap();
+        let schedule: Vec<(usize, Duration)> = compiled
+            .bursts
+            .iter()
+            .map(|burst| (burst.end - burst.start, burst.at))
+            .collect();
+        assert_eq!(
+            schedule,
+            vec![
+                (1, Duration::ZERO),
+                (1, Duration::from_millis(100)),
+                (1 + 4 + 1, Duration::from_millis(110)),
+                (2, Duration::from_millis(115)),
+            ]
+        );
+        assert_eq!(compiled.event_count(), 10);
+        assert_eq!(compiled.burst_count(), 4);
+        assert_eq!(compiled.duration(), Duration::from_millis(115));
+        assert!(!compiled.num_lock);
+    }
+
+    #[test]
+    fn test_compile_numpad_needs_num_lock() {
+        let options = super::super::TextOptions {
+            numpad_digits: true,
+            ..Default::default()
+        };
+        let compiled = super::super::with_text_options(options, || {
+            compile_sequence(&[InputAction::Text("a1".to_string())])
+        })
+        .unwrap();
+        assert!(compiled.num_lock);
+        // 'a' 的 Unicode 按下/释放 + Num1 的按下/释放
+        assert_eq!(compiled.event_count(), 4);
+    }
+}
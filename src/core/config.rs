@@ -0,0 +1,578 @@
+// src/core/config.rs
+//! 全局发送配置
+#[cfg(feature = "window_target")]
+use crate::types::WindowHandle;
+#[cfg(feature = "window_target")]
+use std::sync::atomic::AtomicIsize;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// 默认按下时长（微秒），`press_duration` 为 `None` 时使用
+static DEFAULT_PRESS_DURATION_US: AtomicU64 = AtomicU64::new(0);
+
+/// 同时按下的最大按键数，0 表示不限制
+static MAX_SIMULTANEOUS_KEYS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_SIMULTANEOUS_KEYS);
+
+/// 默认同时按下的最大按键数（常见 USB 键盘的 6 键无冲）
+pub const DEFAULT_MAX_SIMULTANEOUS_KEYS: usize = 6;
+
+/// 字符串输入单次 `SendInput` 提交的最大字符数，0 表示不分块
+static TYPE_CHUNK_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// 设置默认按下时长
+///
+/// 部分应用会丢弃按下与释放间隔为 0 的按键，可设置一个非零的默认值，
+/// 所有传入 `None` 的按键点击和组合键都会使用它。传入 `Some(Duration::ZERO)`
+/// 可以显式跳过默认时长。
+pub fn set_default_press_duration(duration: Duration) {
+    DEFAULT_PRESS_DURATION_US.store(duration.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// 获取默认按下时长
+pub fn default_press_duration() -> Duration {
+    Duration::from_micros(DEFAULT_PRESS_DURATION_US.load(Ordering::Relaxed))
+}
+
+/// 解析实际使用的按下时长：`None` 时回退到默认值
+pub fn resolve_press_duration(press_duration: Option<Duration>) -> Duration {
+    press_duration.unwrap_or_else(default_press_duration)
+}
+
+/// 按下时长随机抖动上限（微秒）
+static PRESS_JITTER_US: AtomicU64 = AtomicU64::new(0);
+
+/// 设置按下时长的随机抖动上限
+///
+/// 每次按键实际保持的时间为按下时长加上 `[0, jitter)` 内的随机值。
+pub fn set_press_jitter(jitter: Duration) {
+    PRESS_JITTER_US.store(jitter.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// 获取按下时长的随机抖动上限
+pub fn press_jitter() -> Duration {
+    Duration::from_micros(PRESS_JITTER_US.load(Ordering::Relaxed))
+}
+
+/// 按下时长（含抖动）非零时等待
+pub(crate) fn hold(press_duration: Option<Duration>) {
+    super::delay::DelaySpec::jitter(resolve_press_duration(press_duration), press_jitter()).sleep();
+}
+
+/// 全局按键的注入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InjectionMode {
+    /// 发送虚拟键码（默认）
+    #[default]
+    VirtualKey,
+    /// 发送扫描码，DirectInput 游戏通常只识别这种方式
+    ScanCode,
+}
+
+/// 全局文本的注入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextInjection {
+    /// 以 `KEYEVENTF_UNICODE` 直接发送字符（默认）
+    #[default]
+    Unicode,
+    /// 将字符转换为前台窗口键盘布局下的真实按键（含 Shift/AltGr 等修饰键）发送，无法映射的字符返回错误
+    KeyStrokes,
+    /// 按住 Alt 在小键盘上输入字符编码（Alt 码），用于忽略 `KEYEVENTF_UNICODE` 的旧程序
+    AltCode,
+}
+
+static INJECTION_MODE: AtomicU8 = AtomicU8::new(0);
+static TEXT_INJECTION: AtomicU8 = AtomicU8::new(0);
+
+thread_local! {
+    /// 当前线程临时覆盖的按键注入方式
+    static INJECTION_MODE_OVERRIDE: Cell<Option<InjectionMode>> = const { Cell::new(None) };
+    /// 当前线程临时覆盖的文本注入方式
+    static TEXT_INJECTION_OVERRIDE: Cell<Option<TextInjection>> = const { Cell::new(None) };
+    /// 当前线程临时覆盖的同时按下按键数上限（0 表示不限制）
+    static MAX_SIMULTANEOUS_KEYS_OVERRIDE: Cell<Option<usize>> = const { Cell::new(None) };
+    /// 当前线程临时覆盖的字符串输入分块大小（0 表示不分块）
+    static TYPE_CHUNK_SIZE_OVERRIDE: Cell<Option<usize>> = const { Cell::new(None) };
+    /// 当前线程临时覆盖的输入速度
+    static TYPING_OPTIONS_OVERRIDE: Cell<Option<TypingOptions>> = const { Cell::new(None) };
+}
+
+/// 设置全局按键的注入方式
+pub fn set_injection_mode(mode: InjectionMode) {
+    INJECTION_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// 获取全局按键的注入方式
+pub fn injection_mode() -> InjectionMode {
+    match INJECTION_MODE.load(Ordering::Relaxed) {
+        1 => InjectionMode::ScanCode,
+        _ => InjectionMode::VirtualKey,
+    }
+}
+
+/// 在当前线程内以指定按键注入方式执行操作，不影响全局设置
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{press_combination, with_injection_mode, InjectionMode, Key, Modifier};
+///
+/// // 仅这次组合键使用扫描码，供 DirectInput 游戏识别
+/// with_injection_mode(InjectionMode::ScanCode, || {
+///     press_combination(&[Modifier::Shift], Key::W, None)
+/// })
+/// .unwrap();
+/// ```
+pub fn with_injection_mode<T, F: FnOnce() -> T>(mode: InjectionMode, f: F) -> T {
+    struct Restore(Option<InjectionMode>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            INJECTION_MODE_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(INJECTION_MODE_OVERRIDE.with(|o| o.replace(Some(mode))));
+    f()
+}
+
+/// 实际生效的按键注入方式（线程内覆盖优先）
+pub(crate) fn effective_injection_mode() -> InjectionMode {
+    INJECTION_MODE_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(injection_mode)
+}
+
+/// 设置全局文本的注入方式
+pub fn set_text_injection(mode: TextInjection) {
+    TEXT_INJECTION.store(mode as u8, Ordering::Relaxed);
+}
+
+/// 获取全局文本的注入方式
+pub fn text_injection() -> TextInjection {
+    match TEXT_INJECTION.load(Ordering::Relaxed) {
+        1 => TextInjection::KeyStrokes,
+        2 => TextInjection::AltCode,
+        _ => TextInjection::Unicode,
+    }
+}
+
+/// 在当前线程内以指定文本注入方式执行操作，不影响全局设置
+pub fn with_text_injection<T, F: FnOnce() -> T>(mode: TextInjection, f: F) -> T {
+    struct Restore(Option<TextInjection>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TEXT_INJECTION_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(TEXT_INJECTION_OVERRIDE.with(|o| o.replace(Some(mode))));
+    f()
+}
+
+/// 实际生效的文本注入方式（线程内覆盖优先）
+pub(crate) fn effective_text_injection() -> TextInjection {
+    TEXT_INJECTION_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(text_injection)
+}
+
+/// 是否允许注入系统快捷键
+static SYSTEM_SHORTCUTS_ALLOWED: AtomicBool = AtomicBool::new(false);
+
+/// 允许或禁止全局发送系统快捷键（默认禁止）
+///
+/// 禁止时，按下 Win 键、在 Win 键按下期间按任意键，或组成
+/// [`INTERLOCKED_SHORTCUTS`](super::hotkeys::INTERLOCKED_SHORTCUTS) 中的组合键（如 Ctrl+Alt+Del、
+/// Ctrl+Shift+Esc）都会返回 `InjectionBlocked` 错误，避免脚本中途弹出开始菜单或锁定计算机。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{allow_system_shortcuts, press_combination, Key, Modifier};
+///
+/// assert!(press_combination(&[Modifier::Meta], Key::R, None).is_err());
+/// allow_system_shortcuts(true);
+/// press_combination(&[Modifier::Meta], Key::R, None).unwrap();
+/// ```
+pub fn allow_system_shortcuts(allow: bool) {
+    SYSTEM_SHORTCUTS_ALLOWED.store(allow, Ordering::Relaxed);
+}
+
+/// 是否允许全局发送系统快捷键
+pub fn system_shortcuts_allowed() -> bool {
+    SYSTEM_SHORTCUTS_ALLOWED.load(Ordering::Relaxed)
+}
+
+/// 焦点断言的目标窗口，0 表示不检查
+#[cfg(feature = "window_target")]
+static FOCUS_ASSERTION: AtomicIsize = AtomicIsize::new(0);
+
+/// 设置焦点断言：全局输入前检查前台窗口是否为指定窗口，不是则返回 `FocusAssertion` 错误
+///
+/// 传入 `None` 关闭检查。
+#[cfg(feature = "window_target")]
+pub fn set_focus_assertion(hwnd: Option<WindowHandle>) {
+    FOCUS_ASSERTION.store(hwnd.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// 获取焦点断言的目标窗口
+#[cfg(feature = "window_target")]
+pub fn focus_assertion() -> Option<WindowHandle> {
+    match FOCUS_ASSERTION.load(Ordering::Relaxed) {
+        0 => None,
+        hwnd => Some(hwnd),
+    }
+}
+
+/// 检查焦点断言与全屏/免打扰状态
+pub(crate) fn check_focus() -> crate::error::Result<()> {
+    #[cfg(feature = "window_target")]
+    super::fullscreen::check_quiet_state()?;
+
+    #[cfg(feature = "window_target")]
+    if let Some(expected) = focus_assertion() {
+        let actual = super::foreground::foreground_window();
+        if actual != expected {
+            return Err(crate::error::KeyboardSenderError::FocusAssertion(format!(
+                "expected foreground window {:#x}, found {:#x}",
+                expected, actual
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 全局输入配置快照，可整体应用或恢复
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSettings {
+    pub press_duration: Duration,
+    pub press_jitter: Duration,
+    pub injection_mode: InjectionMode,
+    pub text_injection: TextInjection,
+    #[cfg(feature = "window_target")]
+    pub focus_window: Option<WindowHandle>,
+}
+
+impl InputSettings {
+    /// 读取当前配置
+    pub fn current() -> Self {
+        Self {
+            press_duration: default_press_duration(),
+            press_jitter: press_jitter(),
+            injection_mode: injection_mode(),
+            text_injection: text_injection(),
+            #[cfg(feature = "window_target")]
+            focus_window: focus_assertion(),
+        }
+    }
+
+    /// 应用配置，返回应用前的配置以便恢复
+    pub fn apply(&self) -> InputSettings {
+        let previous = Self::current();
+        set_default_press_duration(self.press_duration);
+        set_press_jitter(self.press_jitter);
+        set_injection_mode(self.injection_mode);
+        set_text_injection(self.text_injection);
+        #[cfg(feature = "window_target")]
+        set_focus_assertion(self.focus_window);
+        previous
+    }
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            press_duration: Duration::ZERO,
+            press_jitter: Duration::ZERO,
+            injection_mode: InjectionMode::default(),
+            text_injection: TextInjection::default(),
+            #[cfg(feature = "window_target")]
+            focus_window: None,
+        }
+    }
+}
+
+/// 设置同时按下的最大按键数，传入 0 表示不限制
+///
+/// 同时按下过多按键时部分应用会丢键或行为异常，超过上限的按键会被分批按下和释放。
+pub fn set_max_simultaneous_keys(limit: usize) {
+    MAX_SIMULTANEOUS_KEYS.store(limit, Ordering::Relaxed);
+}
+
+/// 获取同时按下的最大按键数，`None` 表示不限制
+pub fn max_simultaneous_keys() -> Option<usize> {
+    match MAX_SIMULTANEOUS_KEYS.load(Ordering::Relaxed) {
+        0 => None,
+        limit => Some(limit),
+    }
+}
+
+/// 在当前线程内以指定的同时按下按键数上限执行操作（0 表示不限制），不影响全局设置
+pub fn with_max_simultaneous_keys<T, F: FnOnce() -> T>(limit: usize, f: F) -> T {
+    struct Restore(Option<usize>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            MAX_SIMULTANEOUS_KEYS_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(MAX_SIMULTANEOUS_KEYS_OVERRIDE.with(|o| o.replace(Some(limit))));
+    f()
+}
+
+/// 实际生效的同时按下按键数上限（线程内覆盖优先），`None` 表示不限制
+fn effective_max_simultaneous_keys() -> Option<usize> {
+    match MAX_SIMULTANEOUS_KEYS_OVERRIDE.with(Cell::get) {
+        Some(0) => None,
+        Some(limit) => Some(limit),
+        None => max_simultaneous_keys(),
+    }
+}
+
+/// 按上限计算每批按键数
+pub(crate) fn simultaneous_chunk_size(key_count: usize) -> usize {
+    match effective_max_simultaneous_keys() {
+        Some(limit) => limit.min(key_count).max(1),
+        None => key_count.max(1),
+    }
+}
+
+/// 设置字符串输入单次提交的最大字符数，传入 0 表示整段一次提交
+///
+/// `type_string` 会把整段文本的按下/释放事件合并为一次 `SendInput` 调用；
+/// 文本很长时部分应用的输入队列会溢出丢字，可设置分块大小分多次提交。
+pub fn set_type_chunk_size(chars: usize) {
+    TYPE_CHUNK_SIZE.store(chars, Ordering::Relaxed);
+}
+
+/// 获取字符串输入单次提交的最大字符数，`None` 表示不分块
+pub fn type_chunk_size() -> Option<usize> {
+    match TYPE_CHUNK_SIZE.load(Ordering::Relaxed) {
+        0 => None,
+        chars => Some(chars),
+    }
+}
+
+/// 在当前线程内以指定的分块大小执行操作（0 表示不分块），不影响全局设置
+pub fn with_type_chunk_size<T, F: FnOnce() -> T>(chars: usize, f: F) -> T {
+    struct Restore(Option<usize>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TYPE_CHUNK_SIZE_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(TYPE_CHUNK_SIZE_OVERRIDE.with(|o| o.replace(Some(chars))));
+    f()
+}
+
+/// 实际生效的分块大小（线程内覆盖优先），`None` 表示不分块
+pub(crate) fn effective_type_chunk_size() -> Option<usize> {
+    match TYPE_CHUNK_SIZE_OVERRIDE.with(Cell::get) {
+        Some(0) => None,
+        Some(chars) => Some(chars),
+        None => type_chunk_size(),
+    }
+}
+
+/// 注入事件 `dwExtraInfo` 的默认标记值（ASCII "SOPH"）
+pub const DEFAULT_INPUT_MARKER: usize = 0x534F_5048;
+
+static INPUT_MARKER: AtomicUsize = AtomicUsize::new(DEFAULT_INPUT_MARKER);
+
+/// 设置写入每个注入事件 `dwExtraInfo` 的标记值，传入 0 表示不标记
+///
+/// 运行键盘钩子的程序可以据此区分本库注入的事件和真实输入（见 [`is_own_input`]）；
+/// 与其他同样使用 `dwExtraInfo` 的工具冲突时可换一个值。
+pub fn set_input_marker(marker: usize) {
+    INPUT_MARKER.store(marker, Ordering::Relaxed);
+}
+
+/// 当前的注入事件标记值，0 表示不标记
+pub fn input_marker() -> usize {
+    INPUT_MARKER.load(Ordering::Relaxed)
+}
+
+/// 事件的 `dwExtraInfo`（如 `KBDLLHOOKSTRUCT::dwExtraInfo`）是否带有本库的标记
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{input_marker, is_own_input};
+///
+/// assert!(is_own_input(input_marker()));
+/// assert!(!is_own_input(0));
+/// ```
+pub fn is_own_input(extra_info: usize) -> bool {
+    marker_matches(input_marker(), extra_info)
+}
+
+/// `extra_info` 是否等于标记值 `marker`（0 表示不标记，任何事件都不匹配）
+fn marker_matches(marker: usize, extra_info: usize) -> bool {
+    marker != 0 && extra_info == marker
+}
+
+/// `type_string` 前是否临时释放用户按住的修饰键
+static NEUTRALIZE_MODIFIERS: AtomicBool = AtomicBool::new(false);
+
+/// 设置 `type_string` 前是否临时释放用户按住的修饰键（见 [`neutralize_modifiers`](super::state::neutralize_modifiers)）
+///
+/// 用户按住 Shift 或 Ctrl 时输入的文本会被改变大小写或触发快捷键；启用后输入前释放这些修饰键，
+/// 输入完成后重新按下。
+pub fn set_neutralize_modifiers(enabled: bool) {
+    NEUTRALIZE_MODIFIERS.store(enabled, Ordering::Relaxed);
+}
+
+/// `type_string` 前是否临时释放用户按住的修饰键
+pub fn neutralize_modifiers_enabled() -> bool {
+    NEUTRALIZE_MODIFIERS.load(Ordering::Relaxed)
+}
+
+/// 输入速度（每分钟单词数），0 表示不限速
+static TYPING_WPM: AtomicU32 = AtomicU32::new(0);
+
+/// 计算 WPM 时每个单词的字符数（打字测速的通用约定）
+pub const CHARS_PER_WORD: u32 = 5;
+
+/// 输入速度选项
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{set_typing_options, type_string, TypingOptions};
+///
+/// set_typing_options(TypingOptions { wpm: 80 }); // 每个字符间隔 150ms
+/// type_string("hello world").unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TypingOptions {
+    /// 每分钟单词数（按 [`CHARS_PER_WORD`] 个字符计一个单词），0 表示不限速
+    pub wpm: u32,
+}
+
+impl TypingOptions {
+    /// 按 WPM 指定速度
+    pub fn wpm(wpm: u32) -> Self {
+        Self { wpm }
+    }
+
+    /// 字符之间的间隔，不限速时为 `None`
+    pub fn char_delay(&self) -> Option<Duration> {
+        (self.wpm > 0).then(|| {
+            Duration::from_micros(60_000_000 / (u64::from(self.wpm) * u64::from(CHARS_PER_WORD)))
+        })
+    }
+}
+
+impl From<TypingOptions> for super::delay::DelaySpec {
+    fn from(options: TypingOptions) -> Self {
+        super::delay::DelaySpec::Fixed(options.char_delay().unwrap_or_default())
+    }
+}
+
+/// 设置 `type_string` 的输入速度
+///
+/// 限速时逐字符发送并在字符之间等待，不再整段合并提交。
+pub fn set_typing_options(options: TypingOptions) {
+    TYPING_WPM.store(options.wpm, Ordering::Relaxed);
+}
+
+/// 获取当前输入速度
+pub fn typing_options() -> TypingOptions {
+    TypingOptions::wpm(TYPING_WPM.load(Ordering::Relaxed))
+}
+
+/// 在当前线程内以指定输入速度执行操作，不影响全局设置
+pub fn with_typing_options<T, F: FnOnce() -> T>(options: TypingOptions, f: F) -> T {
+    struct Restore(Option<TypingOptions>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TYPING_OPTIONS_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(TYPING_OPTIONS_OVERRIDE.with(|o| o.replace(Some(options))));
+    f()
+}
+
+/// 实际生效的输入速度（线程内覆盖优先）
+pub(crate) fn effective_typing_options() -> TypingOptions {
+    TYPING_OPTIONS_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(typing_options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simultaneous_chunk_size() {
+        // 只在当前线程内覆盖，不影响并行运行的其他测试
+        let global = max_simultaneous_keys();
+        with_max_simultaneous_keys(3, || {
+            assert_eq!(effective_max_simultaneous_keys(), Some(3));
+            assert_eq!(simultaneous_chunk_size(8), 3);
+            assert_eq!(simultaneous_chunk_size(2), 2);
+            with_max_simultaneous_keys(0, || {
+                assert_eq!(effective_max_simultaneous_keys(), None);
+                assert_eq!(simultaneous_chunk_size(8), 8);
+                assert_eq!(simultaneous_chunk_size(0), 1);
+            });
+            assert_eq!(simultaneous_chunk_size(8), 3);
+        });
+        assert_eq!(effective_max_simultaneous_keys(), global);
+        assert_eq!(max_simultaneous_keys(), global);
+    }
+
+    #[test]
+    fn test_injection_mode_override() {
+        let global = injection_mode();
+        let inner = with_injection_mode(InjectionMode::ScanCode, || {
+            with_injection_mode(InjectionMode::VirtualKey, effective_injection_mode)
+        });
+        assert_eq!(inner, InjectionMode::VirtualKey);
+        assert_eq!(
+            with_injection_mode(InjectionMode::ScanCode, effective_injection_mode),
+            InjectionMode::ScanCode
+        );
+        assert_eq!(effective_injection_mode(), global);
+        assert_eq!(injection_mode(), global);
+    }
+
+    #[test]
+    fn test_type_chunk_size() {
+        // 只在当前线程内覆盖，不影响并行运行的其他测试
+        let global = type_chunk_size();
+        with_type_chunk_size(128, || {
+            assert_eq!(effective_type_chunk_size(), Some(128));
+            assert_eq!(with_type_chunk_size(0, effective_type_chunk_size), None);
+        });
+        assert_eq!(effective_type_chunk_size(), global);
+        assert_eq!(type_chunk_size(), global);
+    }
+
+    #[test]
+    fn test_typing_options() {
+        assert_eq!(TypingOptions::default().char_delay(), None);
+        assert_eq!(
+            TypingOptions { wpm: 60 }.char_delay(),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            super::super::delay::DelaySpec::from(TypingOptions::wpm(80)),
+            super::super::delay::DelaySpec::Fixed(Duration::from_millis(150))
+        );
+        // 只在当前线程内覆盖，不让并行运行的其他测试限速
+        let global = typing_options();
+        with_typing_options(TypingOptions::wpm(120), || {
+            assert_eq!(effective_typing_options().wpm, 120);
+        });
+        assert_eq!(effective_typing_options(), global);
+        assert_eq!(typing_options(), global);
+    }
+
+    #[test]
+    fn test_input_marker() {
+        // 钩子线程读取全局标记值，不能用线程内覆盖；不修改全局值，避免影响并行测试的注入
+        assert!(marker_matches(DEFAULT_INPUT_MARKER, DEFAULT_INPUT_MARKER));
+        assert!(!marker_matches(DEFAULT_INPUT_MARKER, 0));
+        assert!(!marker_matches(0, 0));
+    }
+}
@@ -0,0 +1,139 @@
+// src/core/delay.rs
+//! 可随机化的时长
+use std::fmt;
+use std::time::Duration;
+
+/// 固定时长或随机范围
+///
+/// 命令中的 `duration:20ms..80ms`、`delay:rand(10ms,50ms)` 解析为 [`DelaySpec::Range`]，
+/// 按下时长加抖动的配置（见 [`set_press_jitter`](super::config::set_press_jitter)、
+/// [`GameMode`](super::profile::GameMode)）也用它表示。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::DelaySpec;
+/// use std::time::Duration;
+///
+/// let spec = DelaySpec::range(Duration::from_millis(20), Duration::from_millis(80));
+/// let sample = spec.sample();
+/// assert!(sample >= spec.min() && sample <= spec.max());
+/// assert_eq!(spec.to_string(), "20ms..80ms");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DelaySpec {
+    /// 固定时长
+    Fixed(Duration),
+    /// 在 `[min, max]` 内均匀随机
+    Range { min: Duration, max: Duration },
+}
+
+impl DelaySpec {
+    /// 固定时长
+    pub fn fixed(duration: Duration) -> Self {
+        DelaySpec::Fixed(duration)
+    }
+
+    /// 随机范围，`min` 和 `max` 顺序颠倒时自动交换，两者相等时为固定时长
+    pub fn range(min: Duration, max: Duration) -> Self {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        if min == max {
+            DelaySpec::Fixed(min)
+        } else {
+            DelaySpec::Range { min, max }
+        }
+    }
+
+    /// 基础时长加上 `[0, jitter]` 内的随机抖动
+    pub fn jitter(base: Duration, jitter: Duration) -> Self {
+        Self::range(base, base.saturating_add(jitter))
+    }
+
+    /// 最短时长
+    pub fn min(&self) -> Duration {
+        match *self {
+            DelaySpec::Fixed(duration) => duration,
+            DelaySpec::Range { min, .. } => min,
+        }
+    }
+
+    /// 最长时长
+    pub fn max(&self) -> Duration {
+        match *self {
+            DelaySpec::Fixed(duration) => duration,
+            DelaySpec::Range { max, .. } => max,
+        }
+    }
+
+    /// 取一个时长，范围内均匀随机
+    pub fn sample(&self) -> Duration {
+        match *self {
+            DelaySpec::Fixed(duration) => duration,
+            DelaySpec::Range { min, max } => min + (max - min).mul_f64(crate::random::next_f64()),
+        }
+    }
+
+    /// 取一个时长并等待，时长为零时不等待
+    pub fn sleep(&self) {
+        let duration = self.sample();
+        if !duration.is_zero() {
+            super::clock().sleep(duration);
+        }
+    }
+}
+
+impl Default for DelaySpec {
+    fn default() -> Self {
+        DelaySpec::Fixed(Duration::ZERO)
+    }
+}
+
+impl From<Duration> for DelaySpec {
+    fn from(duration: Duration) -> Self {
+        DelaySpec::Fixed(duration)
+    }
+}
+
+impl fmt::Display for DelaySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DelaySpec::Fixed(duration) => write!(f, "{:?}", duration),
+            DelaySpec::Range { min, max } => write!(f, "{:?}..{:?}", min, max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_normalization() {
+        let ms = Duration::from_millis;
+        assert_eq!(
+            DelaySpec::range(ms(50), ms(10)),
+            DelaySpec::Range {
+                min: ms(10),
+                max: ms(50)
+            }
+        );
+        assert_eq!(DelaySpec::range(ms(5), ms(5)), DelaySpec::Fixed(ms(5)));
+        assert_eq!(
+            DelaySpec::jitter(ms(30), Duration::ZERO),
+            DelaySpec::Fixed(ms(30))
+        );
+        assert_eq!(DelaySpec::jitter(ms(30), ms(20)).max(), ms(50));
+    }
+
+    #[test]
+    fn test_sample_within_range() {
+        let spec = DelaySpec::range(Duration::from_millis(10), Duration::from_millis(20));
+        for _ in 0..100 {
+            let sample = spec.sample();
+            assert!(sample >= spec.min() && sample <= spec.max());
+        }
+        assert_eq!(
+            DelaySpec::Fixed(Duration::from_millis(7)).sample(),
+            Duration::from_millis(7)
+        );
+    }
+}
@@ -0,0 +1,280 @@
+// src/core/echo.rs
+//! 注入回显校验
+//!
+//! `SendInput` 返回成功只说明事件进入了系统输入队列，不保证它真正到达输入流（安全软件、
+//! UIPI、远程会话都可能让事件悄悄丢失）。[`EchoVerifier`] 安装一个临时的低级键盘钩子
+//! （`WH_KEYBOARD_LL`），在执行注入后等待钩子中出现带本库标记（见
+//! [`input_marker`](super::input_marker)）的事件回显，逐次给出「系统已接受这些输入」的确认，
+//! 并按注入方式累计丢失率。
+//!
+//! 回显按数量对应：等待期间观察到的带标记事件数达到本次提交的事件数即视为全部送达。
+//! 其他线程同时注入、或把标记设为 0 时结果不可靠，校验应在单个线程上串行进行。
+//! 事件在全局输入流中回显，窗口消息类的注入方式（`PostMessage` 等）不经过钩子，无法校验。
+use crate::error::Result;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use windows::Win32::{
+    Foundation::{LPARAM, LRESULT, WPARAM},
+    UI::WindowsAndMessaging::{CallNextHookEx, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED},
+};
+
+use super::hook_thread::{install_keyboard_hook, HookThread};
+use super::self_test::SelfTestStrategy;
+
+/// 默认的回显等待时间
+pub const DEFAULT_ECHO_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// 单次校验的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EchoOutcome {
+    /// 本次提交的事件数
+    pub sent: u64,
+    /// 超时前观察到的回显数
+    pub echoed: u64,
+    /// 从开始注入到最后一个回显的时间，没有回显时为 `None`
+    pub latency: Option<Duration>,
+}
+
+impl EchoOutcome {
+    /// 全部事件都已回显
+    pub fn confirmed(&self) -> bool {
+        self.echoed >= self.sent
+    }
+
+    /// 丢失的事件数
+    pub fn dropped(&self) -> u64 {
+        self.sent.saturating_sub(self.echoed)
+    }
+}
+
+/// 单种注入方式的累计统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EchoStat {
+    pub strategy: SelfTestStrategy,
+    /// 校验次数
+    pub checks: u64,
+    /// 提交的事件总数
+    pub sent: u64,
+    /// 回显的事件总数
+    pub echoed: u64,
+    /// 有丢失的校验次数
+    pub failed_checks: u64,
+}
+
+impl EchoStat {
+    fn new(strategy: SelfTestStrategy) -> Self {
+        Self {
+            strategy,
+            checks: 0,
+            sent: 0,
+            echoed: 0,
+            failed_checks: 0,
+        }
+    }
+
+    /// 事件丢失率（0.0 ~ 1.0）
+    pub fn drop_rate(&self) -> f64 {
+        if self.sent == 0 {
+            return 0.0;
+        }
+        self.sent.saturating_sub(self.echoed) as f64 / self.sent as f64
+    }
+}
+
+/// 钩子线程与校验线程共享的状态
+#[derive(Default)]
+struct Shared {
+    echoes: Mutex<Echoes>,
+    echoed: Condvar,
+}
+
+#[derive(Default)]
+struct Echoes {
+    /// 观察到的带标记事件总数
+    count: u64,
+    /// 最后一个回显的时间
+    last: Option<Instant>,
+}
+
+impl Shared {
+    fn echoes(&self) -> MutexGuard<'_, Echoes> {
+        self.echoes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 回显校验器，丢弃时移除钩子并结束钩子线程
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{key_click, EchoVerifier, Key, SelfTestStrategy};
+///
+/// let verifier = EchoVerifier::install().unwrap();
+/// let outcome = verifier
+///     .verify(SelfTestStrategy::SendInputVirtualKey, || key_click(Key::A, None))
+///     .unwrap();
+/// assert!(outcome.confirmed(), "{} event(s) lost", outcome.dropped());
+/// for stat in verifier.stats() {
+///     println!("{:?}: {:.1}% dropped", stat.strategy, stat.drop_rate() * 100.0);
+/// }
+/// # }
+/// ```
+pub struct EchoVerifier {
+    shared: Arc<Shared>,
+    timeout: Duration,
+    stats: Mutex<HashMap<SelfTestStrategy, EchoStat>>,
+    hook: HookThread,
+}
+
+impl std::fmt::Debug for EchoVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EchoVerifier")
+            .field("timeout", &self.timeout)
+            .field("hook", &self.hook)
+            .finish()
+    }
+}
+
+impl EchoVerifier {
+    /// 在后台线程上安装低级键盘钩子
+    pub fn install() -> Result<Self> {
+        let shared = Arc::new(Shared::default());
+        let hook = {
+            let shared = shared.clone();
+            HookThread::spawn(
+                "echo-hook",
+                move || {
+                    SHARED.with(|s| *s.borrow_mut() = Some(shared));
+                    install_keyboard_hook(Some(keyboard_proc))
+                },
+                |_| false,
+            )?
+        };
+        Ok(Self {
+            shared,
+            timeout: DEFAULT_ECHO_TIMEOUT,
+            stats: Mutex::new(HashMap::new()),
+            hook,
+        })
+    }
+
+    /// 设置等待回显的最长时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 执行 `inject` 并等待其提交的事件回显，结果计入 `strategy` 的统计
+    ///
+    /// `inject` 返回错误时直接返回该错误，不计入统计。
+    pub fn verify<F>(&self, strategy: SelfTestStrategy, inject: F) -> Result<EchoOutcome>
+    where
+        F: FnOnce() -> Result<()>,
+    {
+        let start = Instant::now();
+        let echoed_before = self.shared.echoes().count;
+        let submitted_before = super::global::submitted_events();
+        inject()?;
+        let sent = super::global::submitted_events() - submitted_before;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut echoes = self.shared.echoes();
+        while echoes.count - echoed_before < sent {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            echoes = self
+                .shared
+                .echoed
+                .wait_timeout(echoes, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        let echoed = (echoes.count - echoed_before).min(sent);
+        let latency = echoes
+            .last
+            .filter(|&last| echoed > 0 && last >= start)
+            .map(|last| last - start);
+        drop(echoes);
+
+        let outcome = EchoOutcome {
+            sent,
+            echoed,
+            latency,
+        };
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let stat = stats
+            .entry(strategy)
+            .or_insert_with(|| EchoStat::new(strategy));
+        stat.checks += 1;
+        stat.sent += outcome.sent;
+        stat.echoed += outcome.echoed;
+        if !outcome.confirmed() {
+            stat.failed_checks += 1;
+        }
+        Ok(outcome)
+    }
+
+    /// 按注入方式的累计统计（按 [`SelfTestStrategy::ALL`] 的顺序）
+    pub fn stats(&self) -> Vec<EchoStat> {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        SelfTestStrategy::ALL
+            .iter()
+            .filter_map(|strategy| stats.get(strategy).copied())
+            .collect()
+    }
+
+    /// 清空累计统计
+    pub fn reset_stats(&self) {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+thread_local! {
+    // 每个钩子线程只安装一个钩子，共享状态保存在该线程本地
+    static SHARED: RefCell<Option<Arc<Shared>>> = const { RefCell::new(None) };
+}
+
+unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        if info.flags.0 & LLKHF_INJECTED.0 != 0 && super::is_own_input(info.dwExtraInfo) {
+            SHARED.with(|s| {
+                if let Some(shared) = s.borrow().as_ref() {
+                    let mut echoes = shared.echoes();
+                    echoes.count += 1;
+                    echoes.last = Some(Instant::now());
+                    shared.echoed.notify_all();
+                }
+            });
+        }
+    }
+    CallNextHookEx(HHOOK(0), code, wparam, lparam)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_and_drop_rate() {
+        let outcome = EchoOutcome {
+            sent: 4,
+            echoed: 3,
+            latency: None,
+        };
+        assert!(!outcome.confirmed());
+        assert_eq!(outcome.dropped(), 1);
+
+        let mut stat = EchoStat::new(SelfTestStrategy::SendInputScanCode);
+        assert_eq!(stat.drop_rate(), 0.0);
+        stat.sent = 8;
+        stat.echoed = 6;
+        assert_eq!(stat.drop_rate(), 0.25);
+    }
+}
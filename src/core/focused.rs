@@ -0,0 +1,173 @@
+// src/core/focused.rs
+//! 仅在目标窗口处于前台时注入的输入队列
+//!
+//! 全局输入（SendInput）总是发往当前前台窗口。[`FocusedTypist`] 在每个字符或按键前
+//! 检查目标窗口是否在前台，用户切换到其他应用时自动暂停，切回后继续，避免输入到错误的应用。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, Modifier, WindowHandle};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::foreground::foreground_window;
+
+/// 检查前台窗口的默认间隔
+pub const DEFAULT_FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 队列中的输入项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueuedInput {
+    /// 单个字符
+    Char(char),
+    /// 按键点击
+    Key(Key),
+    /// 组合键
+    Combination(Vec<Modifier>, Key),
+}
+
+/// 绑定到窗口的输入队列
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{FocusedTypist, Key};
+///
+/// let mut typist = FocusedTypist::new(0x1A2B);
+/// typist.queue_text("long report text...").queue_key(Key::Enter);
+/// typist.run().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FocusedTypist {
+    hwnd: WindowHandle,
+    queue: VecDeque<QueuedInput>,
+    poll_interval: Duration,
+    focus_timeout: Option<Duration>,
+    paused: bool,
+}
+
+impl FocusedTypist {
+    /// 创建绑定到指定窗口的输入队列
+    pub fn new(hwnd: WindowHandle) -> Self {
+        Self {
+            hwnd,
+            queue: VecDeque::new(),
+            poll_interval: DEFAULT_FOCUS_POLL_INTERVAL,
+            focus_timeout: None,
+            paused: false,
+        }
+    }
+
+    /// 设置检查前台窗口的间隔
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 设置单次暂停的最长等待时间，超时返回 `Timeout` 错误；默认无限等待
+    pub fn focus_timeout(mut self, timeout: Duration) -> Self {
+        self.focus_timeout = Some(timeout);
+        self
+    }
+
+    /// 目标窗口句柄
+    pub fn hwnd(&self) -> WindowHandle {
+        self.hwnd
+    }
+
+    /// 将文本逐字符加入队列
+    pub fn queue_text(&mut self, text: &str) -> &mut Self {
+        self.queue.extend(text.chars().map(QueuedInput::Char));
+        self
+    }
+
+    /// 将按键加入队列
+    pub fn queue_key(&mut self, key: Key) -> &mut Self {
+        self.queue.push_back(QueuedInput::Key(key));
+        self
+    }
+
+    /// 将组合键加入队列
+    pub fn queue_combination(&mut self, modifiers: &[Modifier], key: Key) -> &mut Self {
+        self.queue
+            .push_back(QueuedInput::Combination(modifiers.to_vec(), key));
+        self
+    }
+
+    /// 尚未注入的输入项数
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 上一次检查时是否因目标窗口不在前台而暂停
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 清空队列
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// 注入队列中的全部输入，目标窗口不在前台时暂停等待
+    ///
+    /// 出错时已注入的输入项从队列中移除，剩余项保留，可再次调用继续。
+    pub fn run(&mut self) -> Result<()> {
+        while let Some(input) = self.queue.front().cloned() {
+            self.wait_for_focus()?;
+            self.inject(&input)?;
+            self.queue.pop_front();
+        }
+        Ok(())
+    }
+
+    /// 等待目标窗口回到前台
+    fn wait_for_focus(&mut self) -> Result<()> {
+        let clock = super::clock();
+        let start = clock.now();
+        loop {
+            if foreground_window() == self.hwnd {
+                self.paused = false;
+                return Ok(());
+            }
+            self.paused = true;
+            if let Some(timeout) = self.focus_timeout {
+                if clock.now().saturating_duration_since(start) >= timeout {
+                    return Err(KeyboardSenderError::Timeout(format!(
+                        "window {:#x} did not return to the foreground within {:?}",
+                        self.hwnd, timeout
+                    )));
+                }
+            }
+            clock.sleep(self.poll_interval);
+        }
+    }
+
+    fn inject(&self, input: &QueuedInput) -> Result<()> {
+        match input {
+            QueuedInput::Char(c) => super::global::send_char(*c),
+            QueuedInput::Key(key) => super::global::key_click(*key, None),
+            QueuedInput::Combination(modifiers, key) => {
+                super::global::press_combination(modifiers, *key, None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue() {
+        let mut typist = FocusedTypist::new(1);
+        typist
+            .queue_text("ab")
+            .queue_key(Key::Enter)
+            .queue_combination(&[Modifier::Control], Key::S);
+        assert_eq!(typist.pending(), 4);
+        assert!(!typist.is_paused());
+        typist.clear();
+        assert_eq!(typist.pending(), 0);
+    }
+}
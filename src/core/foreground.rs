@@ -0,0 +1,113 @@
+// src/core/foreground.rs
+//! 前台窗口切换事件
+//!
+//! 通过 WinEvent 钩子（`EVENT_SYSTEM_FOREGROUND`）监听前台窗口变化。钩子运行在独立的
+//! 后台线程上（带消息循环），回调也在该线程中调用。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::cell::RefCell;
+
+use windows::Win32::{
+    Foundation::{HMODULE, HWND},
+    UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+    UI::WindowsAndMessaging::{
+        GetForegroundWindow, EVENT_SYSTEM_FOREGROUND, WINEVENT_OUTOFCONTEXT,
+    },
+};
+
+use super::hook_thread::HookThread;
+
+type ForegroundCallback = Box<dyn Fn(WindowHandle) + Send>;
+
+thread_local! {
+    // 每个监听线程只安装一个钩子，回调保存在该线程本地
+    static CALLBACK: RefCell<Option<ForegroundCallback>> = const { RefCell::new(None) };
+}
+
+/// 前台窗口监听器，丢弃时自动移除钩子并结束监听线程
+#[derive(Debug)]
+pub struct ForegroundWatcher {
+    _hook: HookThread,
+}
+
+impl ForegroundWatcher {
+    /// 停止监听
+    pub fn stop(self) {}
+}
+
+/// 获取当前前台窗口句柄
+pub fn foreground_window() -> WindowHandle {
+    unsafe { GetForegroundWindow().0 }
+}
+
+/// 监听前台窗口切换，每次切换时以新的前台窗口句柄调用回调
+///
+/// 返回的 [`ForegroundWatcher`] 被丢弃时停止监听。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{on_foreground_changed, window_title};
+///
+/// let watcher = on_foreground_changed(|hwnd| {
+///     println!("switched to {}", window_title(hwnd));
+/// })
+/// .unwrap();
+/// std::thread::sleep(std::time::Duration::from_secs(30));
+/// drop(watcher);
+/// # }
+/// ```
+pub fn on_foreground_changed<F>(callback: F) -> Result<ForegroundWatcher>
+where
+    F: Fn(WindowHandle) + Send + 'static,
+{
+    let hook = HookThread::spawn(
+        "foreground-watcher",
+        move || install(Box::new(callback)),
+        |_| false,
+    )?;
+    Ok(ForegroundWatcher { _hook: hook })
+}
+
+/// 在监听线程上安装 WinEvent 钩子，返回移除钩子的清理函数
+fn install(callback: ForegroundCallback) -> Result<impl FnOnce()> {
+    CALLBACK.with(|c| *c.borrow_mut() = Some(callback));
+
+    let hook = unsafe {
+        SetWinEventHook(
+            EVENT_SYSTEM_FOREGROUND,
+            EVENT_SYSTEM_FOREGROUND,
+            HMODULE(0),
+            Some(win_event_proc),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT,
+        )
+    };
+    if hook.is_invalid() {
+        return Err(KeyboardSenderError::WindowsError);
+    }
+    Ok(move || unsafe {
+        UnhookWinEvent(hook);
+    })
+}
+
+unsafe extern "system" fn win_event_proc(
+    _hook: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    _id_object: i32,
+    _id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    if event != EVENT_SYSTEM_FOREGROUND || hwnd.0 == 0 {
+        return;
+    }
+    CALLBACK.with(|c| {
+        if let Some(callback) = c.borrow().as_ref() {
+            callback(hwnd.0);
+        }
+    });
+}
@@ -0,0 +1,201 @@
+// src/core/fullscreen.rs
+//! 全屏与免打扰状态检测
+//!
+//! 通过 `SHQueryUserNotificationState` 检测独占全屏程序（通常是游戏）、演示模式和
+//! 专注助手（免打扰），并可配置在这些状态下阻止或推迟全局输入，避免误向游戏中输入。
+use crate::error::{KeyboardSenderError, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use windows::Win32::UI::Shell::{
+    SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS, QUNS_APP, QUNS_BUSY,
+    QUNS_NOT_PRESENT, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME, QUNS_RUNNING_D3D_FULL_SCREEN,
+};
+
+/// 状态轮询间隔（`QuietStateGuard::Wait` 使用）
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 系统通知状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationState {
+    /// 屏幕保护运行中、机器锁定或处于非活动的快速用户切换会话
+    NotPresent,
+    /// 有全屏程序运行或处于演示设置中
+    Busy,
+    /// 有 Direct3D 独占全屏程序运行
+    FullscreenExclusive,
+    /// 演示模式
+    Presentation,
+    /// 正常状态
+    AcceptsNotifications,
+    /// 专注助手 / 免打扰时段
+    QuietTime,
+    /// Windows 应用商店应用在前台运行
+    App,
+    /// 未知状态值
+    Unknown(i32),
+}
+
+impl NotificationState {
+    /// 是否处于应避免意外输入的状态（全屏、演示或免打扰）
+    pub fn is_quiet(self) -> bool {
+        matches!(
+            self,
+            NotificationState::Busy
+                | NotificationState::FullscreenExclusive
+                | NotificationState::Presentation
+                | NotificationState::QuietTime
+        )
+    }
+}
+
+/// 查询当前的系统通知状态
+pub fn notification_state() -> Result<NotificationState> {
+    let state =
+        unsafe { SHQueryUserNotificationState() }.map_err(|_| KeyboardSenderError::WindowsError)?;
+    Ok(match state {
+        QUNS_NOT_PRESENT => NotificationState::NotPresent,
+        QUNS_BUSY => NotificationState::Busy,
+        QUNS_RUNNING_D3D_FULL_SCREEN => NotificationState::FullscreenExclusive,
+        QUNS_PRESENTATION_MODE => NotificationState::Presentation,
+        QUNS_ACCEPTS_NOTIFICATIONS => NotificationState::AcceptsNotifications,
+        QUNS_QUIET_TIME => NotificationState::QuietTime,
+        QUNS_APP => NotificationState::App,
+        other => NotificationState::Unknown(other.0),
+    })
+}
+
+/// 前台是否为 Direct3D 独占全屏程序
+pub fn foreground_is_fullscreen_exclusive() -> bool {
+    matches!(notification_state(), Ok(NotificationState::FullscreenExclusive))
+}
+
+/// 专注助手（免打扰）是否开启
+pub fn focus_assist_active() -> bool {
+    matches!(notification_state(), Ok(NotificationState::QuietTime))
+}
+
+/// 全屏/免打扰状态下的全局输入策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuietStateGuard {
+    /// 不检查（默认）
+    #[default]
+    Off,
+    /// 直接拒绝输入，返回 `InjectionBlocked` 错误
+    Block,
+    /// 等待状态解除后再输入，超时返回 `Timeout` 错误
+    Wait { timeout: Duration },
+}
+
+/// `u64::MAX` 表示 Off，`u64::MAX - 1` 表示 Block，其余为 Wait 的超时毫秒数
+static QUIET_STATE_GUARD: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// 设置全屏/免打扰状态下的全局输入策略
+///
+/// 只影响全局输入（`key_down`、`send_char` 等），窗口消息不受影响。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{set_quiet_state_guard, type_string, QuietStateGuard};
+///
+/// set_quiet_state_guard(QuietStateGuard::Block);
+/// // 游戏全屏运行时返回 InjectionBlocked 错误而不是把文字打进游戏
+/// let _ = type_string("hello");
+/// # }
+/// ```
+pub fn set_quiet_state_guard(guard: QuietStateGuard) {
+    QUIET_STATE_GUARD.store(encode_guard(guard), Ordering::Relaxed);
+}
+
+/// 获取全屏/免打扰状态下的全局输入策略
+pub fn quiet_state_guard() -> QuietStateGuard {
+    decode_guard(QUIET_STATE_GUARD.load(Ordering::Relaxed))
+}
+
+fn encode_guard(guard: QuietStateGuard) -> u64 {
+    match guard {
+        QuietStateGuard::Off => u64::MAX,
+        QuietStateGuard::Block => u64::MAX - 1,
+        QuietStateGuard::Wait { timeout } => {
+            timeout.as_millis().min(u128::from(u64::MAX - 2)) as u64
+        }
+    }
+}
+
+fn decode_guard(value: u64) -> QuietStateGuard {
+    match value {
+        u64::MAX => QuietStateGuard::Off,
+        value if value == u64::MAX - 1 => QuietStateGuard::Block,
+        millis => QuietStateGuard::Wait {
+            timeout: Duration::from_millis(millis),
+        },
+    }
+}
+
+/// 按当前策略检查全屏/免打扰状态
+pub(crate) fn check_quiet_state() -> Result<()> {
+    let quiet = || notification_state().is_ok_and(NotificationState::is_quiet);
+    match quiet_state_guard() {
+        QuietStateGuard::Off => Ok(()),
+        QuietStateGuard::Block => match notification_state() {
+            Ok(state) if state.is_quiet() => Err(KeyboardSenderError::InjectionBlocked(format!(
+                "global input blocked while {:?}",
+                state
+            ))),
+            _ => Ok(()),
+        },
+        QuietStateGuard::Wait { timeout } => {
+            let clock = super::clock();
+            // 超时过大（如 `Duration::MAX`）时不设截止时刻，一直等待
+            let deadline = clock.now().checked_add(timeout);
+            while quiet() {
+                if deadline.is_some_and(|deadline| clock.now() >= deadline) {
+                    return Err(KeyboardSenderError::Timeout(format!(
+                        "full-screen or quiet state did not end within {:?}",
+                        timeout
+                    )));
+                }
+                clock.sleep(POLL_INTERVAL);
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_roundtrip() {
+        // 只测试编码，不修改全局策略，避免影响并行测试中的输入
+        for guard in [
+            QuietStateGuard::Block,
+            QuietStateGuard::Wait {
+                timeout: Duration::from_millis(1500),
+            },
+            QuietStateGuard::Off,
+        ] {
+            assert_eq!(decode_guard(encode_guard(guard)), guard);
+        }
+        let forever = decode_guard(encode_guard(QuietStateGuard::Wait {
+            timeout: Duration::MAX,
+        }));
+        assert_eq!(
+            forever,
+            QuietStateGuard::Wait {
+                timeout: Duration::from_millis(u64::MAX - 2)
+            }
+        );
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        assert!(NotificationState::FullscreenExclusive.is_quiet());
+        assert!(NotificationState::QuietTime.is_quiet());
+        assert!(!NotificationState::AcceptsNotifications.is_quiet());
+        assert!(!NotificationState::Unknown(42).is_quiet());
+    }
+}
@@ -5,13 +5,15 @@ use crate::error::{KeyboardSenderError, Result};
 use crate::types::{Key, Modifier};
 use std::time::Duration;
 
+#[cfg(feature = "global")]
+use std::sync::atomic::{AtomicU64, Ordering};
 #[cfg(feature = "global")]
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
-    KEYEVENTF_UNICODE, VIRTUAL_KEY,
+    SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_EXTENDEDKEY,
+    KEYEVENTF_KEYUP, KEYEVENTF_SCANCODE, KEYEVENTF_UNICODE, VIRTUAL_KEY,
 };
 
-use super::{key_to_vk, modifier_to_key};
+use super::{key_to_vk, modifier_to_vk};
 
 /// 全局发送：按键按下
 pub fn key_down(key: Key) -> Result<()> {
@@ -20,25 +22,8 @@ pub fn key_down(key: Key) -> Result<()> {
 
     #[cfg(feature = "global")]
     {
-        let vk = key_to_vk(key);
-
-        let input = INPUT {
-            r#type: INPUT_KEYBOARD,
-            Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
-                ki: KEYBDINPUT {
-                    wVk: VIRTUAL_KEY(vk),
-                    wScan: 0,
-                    dwFlags: KEYBD_EVENT_FLAGS(0),
-                    time: 0,
-                    dwExtraInfo: 0,
-                },
-            },
-        };
-
-        unsafe {
-            SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
-        }
-        Ok(())
+        super::config::check_focus()?;
+        super::key_stats::record_press(key, || inject_vk(key_to_vk(key), false))
     }
 }
 
@@ -48,117 +33,776 @@ pub fn key_up(key: Key) -> Result<()> {
     return Err(KeyboardSenderError::FeatureNotEnabled("global".to_string()));
 
     #[cfg(feature = "global")]
+    inject_vk(key_to_vk(key), true)
+}
+
+/// 已按下的按键，离开作用域时自动释放
+///
+/// 由 [`key_down_guard`] 返回。提前返回、`?` 传播错误或 panic 时都会在 `Drop` 中发送释放事件
+/// （忽略错误），不会让 Ctrl/Shift 等按键一直处于按下状态；需要获取释放结果时调用 [`release`](Self::release)。
+#[derive(Debug)]
+#[must_use = "the key is released as soon as the guard is dropped"]
+pub struct PressedKey {
+    key: Key,
+    released: bool,
+}
+
+impl PressedKey {
+    /// 按下的按键
+    pub fn key(&self) -> Key {
+        self.key
+    }
+
+    /// 立即释放按键并返回结果
+    pub fn release(mut self) -> Result<()> {
+        self.released = true;
+        key_up(self.key)
+    }
+}
+
+impl Drop for PressedKey {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = key_up(self.key);
+        }
+    }
+}
+
+/// 全局发送：按下按键并返回释放守卫
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{key_click, key_down_guard, Key, Result};
+///
+/// fn run_and_jump() -> Result<()> {
+///     let _forward = key_down_guard(Key::W)?;
+///     key_click(Key::Space, None)?; // 出错返回时 W 也会被释放
+///     key_click(Key::Space, None)?;
+///     Ok(())
+/// }
+/// ```
+pub fn key_down_guard(key: Key) -> Result<PressedKey> {
+    key_down(key)?;
+    Ok(PressedKey {
+        key,
+        released: false,
+    })
+}
+
+#[cfg(feature = "global")]
+const VK_PAUSE: u16 = 0x13;
+
+/// 按当前注入方式发送一个虚拟键的按下或释放
+///
+/// 扩展键（见 [`EXTENDED_VKS`](super::mappings::EXTENDED_VKS)）自动设置 `KEYEVENTF_EXTENDEDKEY`。
+/// 按下会触发系统快捷键的按键前检查 [`allow_system_shortcuts`](super::allow_system_shortcuts)。
+#[cfg(feature = "global")]
+pub(crate) fn inject_vk(vk: u16, up: bool) -> Result<()> {
+    if !up {
+        check_system_shortcut(&[vk])?;
+    }
+    reinject_vk(vk, up)
+}
+
+/// 发送虚拟键，不检查系统快捷键互锁
+///
+/// 用于重新注入用户物理按下的按键（如辅助功能钩子、恢复被临时释放的修饰键）。
+#[cfg(feature = "global")]
+pub(crate) fn reinject_vk(vk: u16, up: bool) -> Result<()> {
+    if vk == VK_PAUSE
+        && super::config::effective_injection_mode() == super::config::InjectionMode::ScanCode
     {
-        let vk = key_to_vk(key);
-
-        let input = INPUT {
-            r#type: INPUT_KEYBOARD,
-            Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
-                ki: KEYBDINPUT {
-                    wVk: VIRTUAL_KEY(vk),
-                    wScan: 0,
-                    dwFlags: KEYEVENTF_KEYUP,
-                    time: 0,
-                    dwExtraInfo: 0,
-                },
-            },
-        };
+        return submit(&pause_scan_inputs(up));
+    }
+    submit(&[vk_input(vk, up)])
+}
 
-        unsafe {
-            SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
+/// 系统快捷键互锁：未允许时，依次按下 `vks` 中的按键（叠加当前已按下的修饰键）
+/// 会触发系统快捷键则返回 `InjectionBlocked` 错误
+#[cfg(feature = "global")]
+pub(crate) fn check_system_shortcut(vks: &[u16]) -> Result<()> {
+    use super::hotkeys::{is_interlocked, vk_modifier_bit};
+
+    if super::config::system_shortcuts_allowed() {
+        return Ok(());
+    }
+    let mut mask = [0x10, 0x11, 0x12, 0x5B, 0x5C]
+        .into_iter()
+        .filter(|&vk| super::state::is_vk_down(vk))
+        .fold(0, |mask, vk| mask | vk_modifier_bit(vk));
+    for &vk in vks {
+        if is_interlocked(mask, vk) {
+            return Err(KeyboardSenderError::InjectionBlocked(format!(
+                "virtual key {:#04x} would trigger a system shortcut; call allow_system_shortcuts(true) to send it",
+                vk
+            )));
         }
-        Ok(())
+        mask |= vk_modifier_bit(vk);
+    }
+    Ok(())
+}
+
+/// 扫描码模式下的 Pause
+///
+/// 键盘上的 Pause 发送 `E1 1D 45` 序列，`SendInput` 无法表示 `E1` 前缀。系统布局把
+/// Ctrl+NumLock 转换为 Pause，因此按下时依次发送左 Ctrl（`1D`）和 NumLock（`45`），
+/// 释放时按相反顺序；目标程序会同时看到左 Ctrl 的按下和释放。
+#[cfg(feature = "global")]
+fn pause_scan_inputs(up: bool) -> [INPUT; 2] {
+    const SCAN_LCONTROL: u16 = 0x1D;
+    const SCAN_NUMLOCK: u16 = 0x45;
+
+    if up {
+        [
+            scan_input(SCAN_NUMLOCK, true, false),
+            scan_input(SCAN_LCONTROL, true, false),
+        ]
+    } else {
+        [
+            scan_input(SCAN_LCONTROL, false, false),
+            scan_input(SCAN_NUMLOCK, false, false),
+        ]
+    }
+}
+
+/// 追加一个虚拟键的按下或释放事件，与 [`reinject_vk`] 发送的事件相同
+#[cfg(feature = "global")]
+pub(crate) fn push_vk_inputs(inputs: &mut Vec<INPUT>, vk: u16, up: bool) {
+    if vk == VK_PAUSE
+        && super::config::effective_injection_mode() == super::config::InjectionMode::ScanCode
+    {
+        inputs.extend(pause_scan_inputs(up));
+    } else {
+        inputs.push(vk_input(vk, up));
+    }
+}
+
+/// 构造一个虚拟键的按下或释放事件
+#[cfg(feature = "global")]
+fn vk_input(vk: u16, up: bool) -> INPUT {
+    let mut flags = if up {
+        KEYEVENTF_KEYUP
+    } else {
+        KEYBD_EVENT_FLAGS(0)
+    };
+    if super::mappings::is_extended_vk(vk) {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    let mut scan = 0;
+    if super::config::effective_injection_mode() == super::config::InjectionMode::ScanCode
+        && vk != VK_PAUSE
+    {
+        scan = super::mappings::vk_scan_code(vk).0;
+        // 没有对应扫描码的按键仍按虚拟键码发送
+        if scan != 0 {
+            flags |= KEYEVENTF_SCANCODE;
+        }
+    }
+
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(if scan != 0 { 0 } else { vk }),
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: super::config::input_marker(),
+            },
+        },
+    }
+}
+
+/// 本进程经 `SendInput` 成功注入的事件总数，供回显校验对账
+#[cfg(feature = "global")]
+static SUBMITTED_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// 本进程经 `SendInput` 成功注入的事件总数
+#[cfg(feature = "window_target")]
+pub(crate) fn submitted_events() -> u64 {
+    SUBMITTED_EVENTS.load(Ordering::Relaxed)
+}
+
+/// 调用 `SendInput` 提交事件，注入数量少于请求数量时返回 `InjectionFailed`
+#[cfg(feature = "global")]
+pub(crate) fn submit(inputs: &[INPUT]) -> Result<()> {
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    // 中止后仍放行只含释放事件的提交，避免按键卡住
+    let release_only = inputs
+        .iter()
+        .all(|input| unsafe { input.Anonymous.ki.dwFlags }.contains(KEYEVENTF_KEYUP));
+    super::lifecycle::check_abort(release_only)?;
+    let injected = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) } as usize;
+    SUBMITTED_EVENTS.fetch_add(injected as u64, Ordering::Relaxed);
+    if injected < inputs.len() {
+        let code = std::io::Error::last_os_error().raw_os_error().unwrap_or(0) as u32;
+        return Err(KeyboardSenderError::InjectionFailed {
+            requested: inputs.len(),
+            injected,
+            code,
+        });
+    }
+    Ok(())
+}
+
+/// 全局发送：原始虚拟键码的按下或释放
+///
+/// 用于 [`Key`] 未覆盖的按键（OEM 键、厂商自定义键等）。与 [`key_down`] / [`key_up`] 一样遵循
+/// 当前注入方式（虚拟键码或扫描码），扩展键自动设置扩展键标志。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::send_vk;
+///
+/// // VK_OEM_102：ISO 键盘上 Z 左侧的 `<>` 键
+/// send_vk(0xE2, true).unwrap();
+/// send_vk(0xE2, false).unwrap();
+/// ```
+pub fn send_vk(vk: u16, down: bool) -> Result<()> {
+    if down {
+        super::config::check_focus()?;
+    }
+    inject_vk(vk, !down)
+}
+
+/// 全局发送：点击原始虚拟键码
+///
+/// 用于 [`Key`] 未覆盖的按键，如 PrintScreen（`0x2C`）。`press_duration` 为 `None` 时使用默认按下时长。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::vk_click;
+///
+/// // PrintScreen
+/// vk_click(0x2C, None).unwrap();
+/// ```
+pub fn vk_click(vk: u16, press_duration: Option<Duration>) -> Result<()> {
+    let _op = super::begin_operation();
+    super::config::check_focus()?;
+    super::guard::press_and_release(
+        &[vk],
+        |vk| inject_vk(vk, false),
+        |vk| inject_vk(vk, true),
+        || super::config::hold(press_duration),
+    )
+}
+
+/// 全局发送：原始扫描码的按下或释放
+///
+/// 直接按扫描码发送，不经过虚拟键码映射；`extended` 对应带 `E0` 前缀的扫描码
+/// （如右 Ctrl 为 `0x1D` + `extended`）。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::send_scan;
+///
+/// // 右 Ctrl
+/// send_scan(0x1D, true, true).unwrap();
+/// send_scan(0x1D, false, true).unwrap();
+/// ```
+pub fn send_scan(scan: u16, down: bool, extended: bool) -> Result<()> {
+    if down {
+        use windows::Win32::UI::Input::KeyboardAndMouse::{MapVirtualKeyW, MAPVK_VSC_TO_VK_EX};
+
+        super::config::check_focus()?;
+        let prefixed = u32::from(scan) | if extended { 0xE000 } else { 0 };
+        let vk = unsafe { MapVirtualKeyW(prefixed, MAPVK_VSC_TO_VK_EX) } as u16;
+        check_system_shortcut(&[vk])?;
+    }
+    submit(&[scan_input(scan, !down, extended)])
+}
+
+/// 构造一个扫描码的按下或释放事件
+#[cfg(feature = "global")]
+fn scan_input(scan: u16, up: bool, extended: bool) -> INPUT {
+    let mut flags = KEYEVENTF_SCANCODE;
+    if up {
+        flags |= KEYEVENTF_KEYUP;
+    }
+    if extended {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(0),
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: super::config::input_marker(),
+            },
+        },
     }
 }
 
 /// 全局发送：按键点击
+///
+/// `press_duration` 为 `None` 时使用默认按下时长（见 `set_default_press_duration`）。
 pub fn key_click(key: Key, press_duration: Option<Duration>) -> Result<()> {
+    let _op = super::begin_operation();
+    crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::KeyClick { target: None, key });
     key_down(key)?;
+    super::config::hold(press_duration);
+    key_up(key)?;
+    Ok(())
+}
 
-    if let Some(dur) = press_duration {
-        std::thread::sleep(dur);
+/// 全局发送：重复点击按键 `n` 次，每两次点击之间等待 `gap`
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{key_click_n, Key};
+/// use std::time::Duration;
+///
+/// // 删除 5 个字符
+/// key_click_n(Key::Backspace, 5, Some(Duration::from_millis(20))).unwrap();
+/// ```
+pub fn key_click_n(key: Key, n: usize, gap: Option<Duration>) -> Result<()> {
+    let _op = super::begin_operation();
+    for i in 0..n {
+        if i > 0 {
+            if let Some(gap) = gap {
+                super::clock().sleep(gap);
+            }
+        }
+        key_click(key, None)?;
     }
-
-    key_up(key)?;
     Ok(())
 }
 
+/// 全局发送：按住按键 `duration`，期间每隔 `repeat_rate` 重复发送按下事件，最后释放
+///
+/// 模拟真人按住按键时键盘的自动重复，适用于依赖持续 `WM_KEYDOWN` 的游戏和程序。
+/// 首次重复在按下 `repeat_rate` 之后发生；`repeat_rate` 为零时不重复，等同于按住后释放。
+/// 重复发送失败时仍会释放按键。重复的按下事件不计入[按键统计](super::start_key_stats)。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{key_hold, Key};
+/// use std::time::Duration;
+///
+/// // 按住右方向键 1 秒，每 33ms 重复一次（约 30 次/秒）
+/// key_hold(Key::ArrowRight, Duration::from_secs(1), Duration::from_millis(33)).unwrap();
+/// ```
+pub fn key_hold(key: Key, duration: Duration, repeat_rate: Duration) -> Result<()> {
+    let _op = super::begin_operation();
+    crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::KeyClick { target: None, key });
+    key_down(key)?;
+    let guard = super::guard::KeyReleaseGuard::new(|| key_up(key));
+
+    let clock = super::clock();
+    let start = clock.now();
+    let deadline = start + duration;
+    if !repeat_rate.is_zero() {
+        let mut next = start + repeat_rate;
+        while next < deadline {
+            clock.sleep_until(next);
+            super::key_stats::unrecorded(|| key_down(key))?;
+            next += repeat_rate;
+        }
+    }
+    clock.sleep_until(deadline);
+    guard.release()
+}
+
 /// 全局发送：字符输入
+///
+/// 文本选项开启了 [`numpad_digits`](super::TextOptions::numpad_digits) 时，数字发送为小键盘按键。
 pub fn send_char(c: char) -> Result<()> {
     #[cfg(not(feature = "global"))]
     return Err(KeyboardSenderError::FeatureNotEnabled("global".to_string()));
 
     #[cfg(feature = "global")]
     {
-        let input_down = INPUT {
-            r#type: INPUT_KEYBOARD,
-            Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
-                ki: KEYBDINPUT {
-                    wVk: VIRTUAL_KEY(0),
-                    wScan: c as u16,
-                    dwFlags: KEYEVENTF_UNICODE,
-                    time: 0,
-                    dwExtraInfo: 0,
-                },
-            },
-        };
+        super::config::check_focus()?;
+        if super::text_options::effective_text_options().numpad_digits {
+            if let Some(key) = super::text_options::numpad_digit_key(c) {
+                return super::state::with_num_lock(|| key_click(key, None));
+            }
+        }
+        match super::config::effective_text_injection() {
+            super::config::TextInjection::KeyStrokes => return send_char_as_keystrokes(c),
+            super::config::TextInjection::AltCode => return send_char_altcode(c),
+            super::config::TextInjection::Unicode => {}
+        }
 
-        let input_up = INPUT {
-            r#type: INPUT_KEYBOARD,
-            Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
-                ki: KEYBDINPUT {
-                    wVk: VIRTUAL_KEY(0),
-                    wScan: c as u16,
-                    dwFlags: KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
-                    time: 0,
-                    dwExtraInfo: 0,
+        let mut inputs = Vec::with_capacity(4);
+        push_unicode_inputs(&mut inputs, c);
+        submit(&inputs)
+    }
+}
+
+/// 追加一个字符的 Unicode 按下/释放事件（增补平面字符按代理对发送）
+#[cfg(feature = "global")]
+pub(crate) fn push_unicode_inputs(inputs: &mut Vec<INPUT>, c: char) {
+    let mut units = [0u16; 2];
+    for &unit in c.encode_utf16(&mut units).iter() {
+        for flags in [KEYEVENTF_UNICODE, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP] {
+            inputs.push(INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: windows::Win32::UI::Input::KeyboardAndMouse::INPUT_0 {
+                    ki: KEYBDINPUT {
+                        wVk: VIRTUAL_KEY(0),
+                        wScan: unit,
+                        dwFlags: flags,
+                        time: 0,
+                        dwExtraInfo: super::config::input_marker(),
+                    },
                 },
-            },
-        };
+            });
+        }
+    }
+}
+
+/// 将字符转换为前台窗口键盘布局下的按键（含 Shift/AltGr 等修饰键）发送
+///
+/// 按前台窗口线程的布局而不是调用线程自己的布局查表，这样切换到德语、法语等布局的窗口
+/// 也能收到正确的按键；需要死键的字符（如 `´` + `e` = `é`）依次发送死键和基础键。
+#[cfg(feature = "global")]
+fn send_char_as_keystrokes(c: char) -> Result<()> {
+    let strokes = super::layout::char_strokes(super::layout::foreground_layout(), c)
+        .and_then(|strokes| {
+            strokes
+                .into_iter()
+                .map(keystroke_vks)
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| {
+            KeyboardSenderError::UnsupportedKey(format!(
+                "character {:?} has no key in the current layout",
+                c
+            ))
+        })?;
 
-        unsafe {
-            SendInput(&[input_down, input_up], std::mem::size_of::<INPUT>() as i32);
+    let _op = super::begin_operation();
+    for vks in strokes {
+        super::guard::press_and_release(
+            &vks,
+            |vk| inject_vk(vk, false),
+            |vk| inject_vk(vk, true),
+            || super::config::hold(None),
+        )?;
+    }
+    Ok(())
+}
+
+/// 将按键（`VkKeyScanExW` 格式）转换为需要依次按下的虚拟键（修饰键在前），无法映射时返回 `None`
+///
+/// 同时需要 Ctrl 和 Alt 的字符按 AltGr 发送（左 Ctrl + 右 Alt），与物理键盘上 AltGr 产生的事件一致。
+#[cfg(feature = "global")]
+fn keystroke_vks(scan: i16) -> Option<Vec<u16>> {
+    let (modifiers, vk) = super::layout::stroke_combo(scan)?;
+    Some(
+        modifiers
+            .into_iter()
+            .map(modifier_to_vk)
+            .chain(std::iter::once(vk))
+            .collect(),
+    )
+}
+
+/// 全局发送：以 Alt 码输入字符（按住 Alt，在小键盘上输入编码，再松开 Alt）
+///
+/// 部分旧程序忽略 `KEYEVENTF_UNICODE`，但接受经典的 Alt 码输入。字符在系统 ANSI 代码页中
+/// 是单字节时发送带前导 0 的代码页编码（如 `é` 在 1252 代码页下为 Alt+0233）。
+/// 不在 ANSI 代码页中的字符返回 `UnsupportedKey` 错误：十进制 Unicode 码位只有少数控件识别，
+/// 其他程序会按 OEM 代码页取模输入成错误的字符。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::send_char_altcode;
+///
+/// send_char_altcode('é').unwrap();
+/// ```
+pub fn send_char_altcode(c: char) -> Result<()> {
+    #[cfg(not(feature = "global"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled("global".to_string()));
+
+    #[cfg(feature = "global")]
+    {
+        const VK_MENU: u16 = 0x12;
+        const VK_NUMPAD0: u16 = 0x60;
+
+        let digits = altcode_digits(c, ansi_byte(c))?;
+        let _op = super::begin_operation();
+        super::config::check_focus()?;
+        let mut inputs = Vec::with_capacity(digits.len() * 2 + 1);
+        inputs.push(vk_input(VK_MENU, false));
+        for digit in digits.bytes() {
+            let vk = VK_NUMPAD0 + u16::from(digit - b'0');
+            inputs.push(vk_input(vk, false));
+            inputs.push(vk_input(vk, true));
         }
-        Ok(())
+        // 数字键注入失败时也要松开 Alt
+        let alt = super::guard::KeyReleaseGuard::new(|| inject_vk(VK_MENU, true));
+        submit(&inputs)?;
+        alt.release()
+    }
+}
+
+/// 字符在系统 ANSI 代码页中的单字节编码，不是单字节时返回 `None`
+#[cfg(feature = "global")]
+fn ansi_byte(c: char) -> Option<u8> {
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::Globalization::{WideCharToMultiByte, CP_ACP, WC_NO_BEST_FIT_CHARS};
+
+    let mut units = [0u16; 2];
+    let encoded = c.encode_utf16(&mut units);
+    let mut bytes = [0u8; 4];
+    let mut used_default = BOOL(0);
+    let len = unsafe {
+        WideCharToMultiByte(
+            CP_ACP,
+            WC_NO_BEST_FIT_CHARS,
+            encoded,
+            Some(&mut bytes),
+            windows::core::PCSTR::null(),
+            Some(&mut used_default),
+        )
+    };
+    (len == 1 && !used_default.as_bool()).then_some(bytes[0])
+}
+
+/// Alt 码的小键盘数字：代码页单字节编码带前导 0
+fn altcode_digits(c: char, ansi: Option<u8>) -> Result<String> {
+    match ansi {
+        Some(byte) => Ok(format!("0{}", byte)),
+        None => Err(KeyboardSenderError::UnsupportedKey(format!(
+            "character {:?} is not in the ANSI code page and has no Alt code",
+            c
+        ))),
     }
 }
 
+/// 全局发送：按指定文本注入方式输入字符串，不影响全局设置
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{type_string_with, TextInjection};
+///
+/// // 旧程序不接受 Unicode 注入时改用 Alt 码
+/// type_string_with("Café", TextInjection::AltCode).unwrap();
+/// ```
+pub fn type_string_with(text: &str, injection: super::config::TextInjection) -> Result<()> {
+    super::config::with_text_injection(injection, || type_string(text))
+}
+
 /// 全局发送：字符串输入
+///
+/// 发送前应用当前的文本选项（见 [`TextOptions`](super::TextOptions)，如 Unicode 规范化、
+/// 换行改为 Enter 键、数字改为小键盘按键）。
+/// Unicode 注入方式下，整段文本的按下/释放事件合并为一次 `SendInput` 调用提交，
+/// 设置了 [`set_type_chunk_size`](super::config::set_type_chunk_size) 时按分块多次提交；
+/// 按键模拟方式下仍逐字符发送。设置了 [`set_typing_options`](super::config::set_typing_options)
+/// 限速时逐字符发送，字符之间按 WPM 计算的间隔等待。启用了
+/// [`set_neutralize_modifiers`](super::config::set_neutralize_modifiers) 时，输入期间临时释放用户按住的修饰键。
 pub fn type_string(text: &str) -> Result<()> {
+    let options = super::text_options::effective_text_options();
+    let text = options.apply(text)?;
+    let text = text.as_ref();
+    let _op = super::begin_operation();
+    crate::telemetry::emit_text(None, text);
+    let _neutralized = super::config::neutralize_modifiers_enabled()
+        .then(super::state::neutralize_modifiers)
+        .transpose()?;
+
+    let delay = super::config::effective_typing_options().char_delay();
+    let segments = super::text_options::text_segments(text, &options);
+    let type_segments = || {
+        for (index, segment) in segments.iter().enumerate() {
+            if let (true, Some(delay)) = (index > 0, delay) {
+                super::clock().sleep(delay);
+            }
+            match *segment {
+                super::text_options::TextSegment::Text(text) => type_plain(text, delay)?,
+                super::text_options::TextSegment::Key(key, _, len) => {
+                    key_click(key, None)?;
+                    super::progress::add_typed(len);
+                }
+            }
+        }
+        Ok(())
+    };
+    if segments.iter().any(|segment| segment.is_numpad()) {
+        super::state::with_num_lock(type_segments)
+    } else {
+        type_segments()
+    }
+}
+
+/// 按字符输入不含按键片段的文本，`delay` 为字符之间的间隔
+fn type_plain(text: &str, delay: Option<Duration>) -> Result<()> {
+    if let Some(delay) = delay {
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            send_char(c)?;
+            super::progress::add_typed(1);
+            if chars.peek().is_some() {
+                super::clock().sleep(delay);
+            }
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "global")]
+    if super::config::effective_text_injection() == super::config::TextInjection::Unicode {
+        super::config::check_focus()?;
+        let chars: Vec<char> = text.chars().collect();
+        let chunk_size = super::config::effective_type_chunk_size()
+            .unwrap_or(chars.len())
+            .max(1);
+        for chunk in chars.chunks(chunk_size) {
+            let mut inputs = Vec::with_capacity(chunk.len() * 2);
+            for &c in chunk {
+                push_unicode_inputs(&mut inputs, c);
+            }
+            if let Err(e) = submit(&inputs) {
+                super::progress::add_typed(super::progress::delivered_unicode_chars(chunk, &e));
+                return Err(e);
+            }
+            super::progress::add_typed(chunk.len());
+        }
+        return Ok(());
+    }
+
     for c in text.chars() {
         send_char(c)?;
+        super::progress::add_typed(1);
     }
     Ok(())
 }
 
 /// 全局发送：组合键
+///
+/// 任一按键发送失败时，已按下的修饰键和主按键都会按相反顺序释放，不会残留按下状态。
+/// 区分左右的修饰键按对应一侧发送，如 `Modifier::RightAlt` 即 AltGr。
 pub fn press_combination(
     modifiers: &[Modifier],
     key: Key,
     press_duration: Option<Duration>,
 ) -> Result<()> {
-    // 按下所有修饰键
-    for &modifier in modifiers {
-        let mod_key = modifier_to_key(modifier);
-        key_down(mod_key)?;
+    // 修饰键在前、主按键在后，按下后等待，再反向释放
+    let vks: Vec<u16> = modifiers
+        .iter()
+        .map(|&modifier| modifier_to_vk(modifier))
+        .chain(std::iter::once(key_to_vk(key)))
+        .collect();
+
+    let _op = super::begin_operation();
+    crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::Combination {
+        target: None,
+        modifiers: modifiers.to_vec(),
+        key,
+    });
+    super::config::check_focus()?;
+    super::guard::press_and_release(
+        &vks,
+        |vk| inject_vk(vk, false),
+        |vk| inject_vk(vk, true),
+        || super::config::hold(press_duration),
+    )
+}
+
+/// 全局发送：单独点击修饰键（区分左右）
+pub fn modifier_click(modifier: Modifier, press_duration: Option<Duration>) -> Result<()> {
+    let _op = super::begin_operation();
+    super::config::check_focus()?;
+    let vk = modifier_to_vk(modifier);
+    super::guard::press_and_release(
+        &[vk],
+        |vk| inject_vk(vk, false),
+        |vk| inject_vk(vk, true),
+        || super::config::hold(press_duration),
+    )
+}
+
+/// 全局发送：同时按下多个按键，等待后反向释放
+///
+/// 按键数超过 [`max_simultaneous_keys`](super::config::max_simultaneous_keys) 时，
+/// 按上限分批依次按下和释放，并上报 `KeyLimitExceeded` 遥测事件。
+pub fn press_keys_simultaneously(keys: &[Key], press_duration: Option<Duration>) -> Result<()> {
+    let chunk_size = super::config::simultaneous_chunk_size(keys.len());
+    if chunk_size < keys.len() {
+        crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::KeyLimitExceeded {
+            requested: keys.len(),
+            limit: chunk_size,
+        });
     }
 
-    // 按下主按键
-    key_down(key)?;
+    let _op = super::begin_operation();
+    for chunk in keys.chunks(chunk_size) {
+        super::guard::press_and_release(chunk, key_down, key_up, || {
+            super::config::hold(press_duration)
+        })?;
+    }
+    Ok(())
+}
+
+/// 全局发送：和弦，同时按下多个任意按键（如 `A+S+D`），等待后按相反顺序释放
+///
+/// 与 [`press_keys_simultaneously`] 不同，全部按下事件合并为一次 `SendInput` 提交，
+/// 全部释放事件也合并为一次提交，目标程序在同一时刻看到所有按键按下；不受
+/// [`max_simultaneous_keys`](super::config::max_simultaneous_keys) 分批限制。
+/// 按下未完全注入或等待期间 panic 时，仍会为全部按键发送释放事件。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{press_chord, Key};
+/// use std::time::Duration;
+///
+/// // 模拟器热键 A+S+D
+/// press_chord(&[Key::A, Key::S, Key::D], Some(Duration::from_millis(50))).unwrap();
+/// ```
+pub fn press_chord(keys: &[Key], press_duration: Option<Duration>) -> Result<()> {
+    #[cfg(not(feature = "global"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled("global".to_string()));
 
-    // 等待指定延迟
-    if let Some(dur) = press_duration {
-        std::thread::sleep(dur);
+    #[cfg(feature = "global")]
+    {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let _op = super::begin_operation();
+        super::config::check_focus()?;
+        let vks: Vec<u16> = keys.iter().map(|&key| key_to_vk(key)).collect();
+        check_system_shortcut(&vks)?;
+        let release = || {
+            let ups: Vec<INPUT> = vks.iter().rev().map(|&vk| vk_input(vk, true)).collect();
+            submit(&ups)
+        };
+
+        let downs: Vec<INPUT> = vks.iter().map(|&vk| vk_input(vk, false)).collect();
+        let guard = super::guard::KeyReleaseGuard::new(release);
+        submit(&downs)?;
+        super::config::hold(press_duration);
+        guard.release()
     }
+}
 
-    // 释放主按键
-    key_up(key)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // 反向释放修饰键
-    for &modifier in modifiers.iter().rev() {
-        let mod_key = modifier_to_key(modifier);
-        key_up(mod_key)?;
+    #[test]
+    fn test_altcode_digits() {
+        assert_eq!(altcode_digits('é', Some(233)).unwrap(), "0233");
+        assert_eq!(altcode_digits('A', Some(65)).unwrap(), "065");
+        assert!(altcode_digits('中', None).is_err());
+        assert!(altcode_digits('😀', None).is_err());
     }
 
-    Ok(())
+    #[cfg(feature = "global")]
+    #[test]
+    fn test_keystroke_vks() {
+        // 'A'：Shift + A
+        assert_eq!(keystroke_vks(0x0141), Some(vec![0x10, 0x41]));
+        // 德语布局的 '@'：AltGr + Q
+        assert_eq!(keystroke_vks(0x0651), Some(vec![0xA2, 0xA5, 0x51]));
+        assert_eq!(keystroke_vks(0x0031), Some(vec![0x31]));
+        assert_eq!(keystroke_vks(-1), None);
+        assert_eq!(keystroke_vks(0x0841), None);
+    }
 }
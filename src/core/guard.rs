@@ -0,0 +1,155 @@
+// src/core/guard.rs
+//! 按键释放守卫：保证提前返回或 panic 时已按下的按键都会被释放
+use crate::error::Result;
+
+/// 单个已按下按键的释放守卫
+///
+/// 正常流程调用 [`KeyReleaseGuard::release`] 并获取释放结果；
+/// 提前返回或 panic 时在 `Drop` 中释放并忽略错误。
+pub(crate) struct KeyReleaseGuard<F: FnMut() -> Result<()>> {
+    release: Option<F>,
+}
+
+impl<F: FnMut() -> Result<()>> KeyReleaseGuard<F> {
+    pub(crate) fn new(release: F) -> Self {
+        Self {
+            release: Some(release),
+        }
+    }
+
+    /// 立即释放按键并返回结果
+    pub(crate) fn release(mut self) -> Result<()> {
+        match self.release.take() {
+            Some(mut release) => release(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<F: FnMut() -> Result<()>> Drop for KeyReleaseGuard<F> {
+    fn drop(&mut self) {
+        if let Some(mut release) = self.release.take() {
+            let _ = release();
+        }
+    }
+}
+
+/// 按下一组按键、等待、再按相反顺序释放
+///
+/// 任意一步失败（或 `hold` 中 panic）时，已经按下的按键都会按相反顺序释放。
+pub(crate) fn press_and_release<T, D, U, H>(keys: &[T], mut down: D, up: U, hold: H) -> Result<()>
+where
+    T: Copy,
+    D: FnMut(T) -> Result<()>,
+    U: Fn(T) -> Result<()>,
+    H: FnOnce(),
+{
+    let up = &up;
+    let mut pressed = GuardStack(Vec::with_capacity(keys.len()));
+
+    for &key in keys {
+        down(key)?;
+        pressed.0.push(KeyReleaseGuard::new(move || up(key)));
+    }
+
+    hold();
+
+    // 正常释放：某次释放失败时直接返回，剩余按键由 GuardStack 的 Drop 释放
+    while let Some(guard) = pressed.0.pop() {
+        guard.release()?;
+    }
+    Ok(())
+}
+
+/// 按压入的相反顺序释放的守卫栈
+struct GuardStack<F: FnMut() -> Result<()>>(Vec<KeyReleaseGuard<F>>);
+
+impl<F: FnMut() -> Result<()>> Drop for GuardStack<F> {
+    fn drop(&mut self) {
+        while let Some(guard) = self.0.pop() {
+            drop(guard);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KeyboardSenderError;
+    use std::cell::RefCell;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Event {
+        Down(u8),
+        Up(u8),
+    }
+
+    fn run(keys: &[u8], fail_down: Option<u8>, fail_up: Option<u8>) -> (Result<()>, Vec<Event>) {
+        let log = RefCell::new(Vec::new());
+        let result = press_and_release(
+            keys,
+            |k| {
+                if Some(k) == fail_down {
+                    return Err(KeyboardSenderError::WindowsError);
+                }
+                log.borrow_mut().push(Event::Down(k));
+                Ok(())
+            },
+            |k| {
+                log.borrow_mut().push(Event::Up(k));
+                if Some(k) == fail_up {
+                    return Err(KeyboardSenderError::WindowsError);
+                }
+                Ok(())
+            },
+            || {},
+        );
+        (result, log.into_inner())
+    }
+
+    #[test]
+    fn test_releases_in_reverse_order() {
+        let (result, log) = run(&[1, 2, 3], None, None);
+        assert!(result.is_ok());
+        use Event::*;
+        assert_eq!(log, vec![Down(1), Down(2), Down(3), Up(3), Up(2), Up(1)]);
+    }
+
+    #[test]
+    fn test_failed_down_releases_pressed_modifiers() {
+        let (result, log) = run(&[1, 2, 3], Some(3), None);
+        assert!(result.is_err());
+        use Event::*;
+        assert_eq!(log, vec![Down(1), Down(2), Up(2), Up(1)]);
+    }
+
+    #[test]
+    fn test_failed_up_still_releases_remaining() {
+        let (result, log) = run(&[1, 2, 3], None, Some(3));
+        assert!(result.is_err());
+        use Event::*;
+        assert_eq!(log, vec![Down(1), Down(2), Down(3), Up(3), Up(2), Up(1)]);
+    }
+
+    #[test]
+    fn test_panic_during_hold_releases_keys() {
+        let log = std::sync::Mutex::new(Vec::new());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = press_and_release(
+                &[1u8, 2],
+                |k| {
+                    log.lock().unwrap().push(Event::Down(k));
+                    Ok(())
+                },
+                |k| {
+                    log.lock().unwrap().push(Event::Up(k));
+                    Ok(())
+                },
+                || panic!("mid-combo failure"),
+            );
+        }));
+        assert!(result.is_err());
+        use Event::*;
+        assert_eq!(*log.lock().unwrap(), vec![Down(1), Down(2), Up(2), Up(1)]);
+    }
+}
@@ -0,0 +1,327 @@
+// src/core/hold.rs
+//! 修饰键保持模式
+//!
+//! [`hold_modifier`] 按下修饰键后不释放，直到调用 [`release_modifier`]，
+//! 可在此基础上实现类似"粘滞键"的辅助工具。为避免调用方崩溃或遗忘导致修饰键一直按着，
+//! 每次保持都带有看门狗：超时后自动释放，并上报 `ModifierAutoReleased` 遥测事件。
+//! 所有保持共用一个看门狗线程，在首次保持时启动。
+use crate::error::Result;
+use crate::types::{Key, Modifier};
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex, MutexGuard, Once};
+use std::time::{Duration, Instant};
+
+/// 默认看门狗超时时间
+pub const DEFAULT_HOLD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 正在保持的修饰键 -> 看门狗到期时间（`None` 表示超时过长，不会到期）
+type HeldMap = HashMap<Modifier, Option<Instant>>;
+
+static HELD: Mutex<Option<HeldMap>> = Mutex::new(None);
+/// 保持状态变化时唤醒看门狗线程
+static HELD_CHANGED: Condvar = Condvar::new();
+static WATCHDOG: Once = Once::new();
+
+fn held() -> MutexGuard<'static, Option<HeldMap>> {
+    HELD.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 看门狗线程：释放到期的修饰键，然后等待到下一个到期时间或保持状态变化
+fn run_watchdog() {
+    let mut guard = held();
+    loop {
+        let now = Instant::now();
+        let (expired, next) = take_expired(guard.get_or_insert_with(HashMap::new), now);
+        if !expired.is_empty() {
+            // 持锁释放，避免与同时进行的保持交错
+            for &modifier in &expired {
+                let _ = super::global::inject_vk(super::modifier_to_vk(modifier), true);
+            }
+            drop(guard);
+            for modifier in expired {
+                crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::ModifierAutoReleased {
+                    modifier,
+                });
+            }
+            guard = held();
+            continue;
+        }
+        guard = match next {
+            Some(next) => {
+                HELD_CHANGED
+                    .wait_timeout(guard, next - now)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0
+            }
+            None => HELD_CHANGED.wait(guard).unwrap_or_else(|e| e.into_inner()),
+        };
+    }
+}
+
+/// 移除 `now` 时已到期的修饰键，返回它们和剩余保持中最早的到期时间
+fn take_expired(map: &mut HeldMap, now: Instant) -> (Vec<Modifier>, Option<Instant>) {
+    let expired: Vec<Modifier> = map
+        .iter()
+        .filter(|(_, deadline)| deadline.is_some_and(|deadline| deadline <= now))
+        .map(|(&modifier, _)| modifier)
+        .collect();
+    for modifier in &expired {
+        map.remove(modifier);
+    }
+    (expired, map.values().flatten().min().copied())
+}
+
+/// 按下并保持修饰键，超过 [`DEFAULT_HOLD_TIMEOUT`] 未释放时自动释放
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{hold_modifier, key_click, release_modifier, Key, Modifier};
+///
+/// hold_modifier(Modifier::Control).unwrap();
+/// key_click(Key::C, None).unwrap(); // Ctrl+C
+/// release_modifier(Modifier::Control).unwrap();
+/// ```
+pub fn hold_modifier(modifier: Modifier) -> Result<()> {
+    hold_modifier_with_timeout(modifier, DEFAULT_HOLD_TIMEOUT)
+}
+
+/// 按下并保持修饰键，超过 `timeout` 未释放时自动释放
+///
+/// 对已保持的修饰键再次调用不会重复发送按下事件，只重新开始计时。
+pub fn hold_modifier_with_timeout(modifier: Modifier, timeout: Duration) -> Result<()> {
+    {
+        let mut guard = held();
+        let map = guard.get_or_insert_with(HashMap::new);
+        let already_held = map.contains_key(&modifier);
+        if !already_held {
+            super::config::check_focus()?;
+            super::global::inject_vk(super::modifier_to_vk(modifier), false)?;
+        }
+        map.insert(modifier, Instant::now().checked_add(timeout));
+    }
+    WATCHDOG.call_once(|| {
+        std::thread::spawn(run_watchdog);
+    });
+    HELD_CHANGED.notify_one();
+    Ok(())
+}
+
+/// 释放通过 [`hold_modifier`] 保持的修饰键，未保持时不做任何操作
+pub fn release_modifier(modifier: Modifier) -> Result<()> {
+    let mut guard = held();
+    if guard
+        .as_mut()
+        .is_some_and(|map| map.remove(&modifier).is_some())
+    {
+        super::global::inject_vk(super::modifier_to_vk(modifier), true)?;
+    }
+    Ok(())
+}
+
+/// 当前正在保持的修饰键
+pub fn held_modifiers() -> Vec<Modifier> {
+    held()
+        .as_ref()
+        .map(|map| map.keys().copied().collect())
+        .unwrap_or_default()
+}
+
+/// 清除保持状态（不发送释放事件），供紧急清理使用
+///
+/// panic 时锁可能正被当前线程持有，因此只尝试获取一次。
+pub(crate) fn forget_held_modifiers() {
+    if let Ok(mut guard) = HELD.try_lock() {
+        *guard = None;
+    }
+}
+
+/// 修饰键会话：在多次发送之间保持修饰键按下，离开作用域时释放
+///
+/// 由 [`ModifierSession::hold`] 或 [`with_modifiers`] 创建。修饰键通过 [`hold_modifier`] 保持，
+/// 因此同样受看门狗保护，并且不会被 [`neutralize_modifiers`](super::neutralize_modifiers) 临时释放；
+/// 会话开始前已保持的修饰键在会话结束时不会被释放。会话期间持有逻辑操作锁，
+/// 其他线程的输入不会插入到修饰键按住的这段时间里。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{Key, Modifier, ModifierSession};
+///
+/// // 按住 Ctrl 依次点击多个项目
+/// let session = ModifierSession::hold(&[Modifier::Control]).unwrap();
+/// session.click(Key::ArrowDown).unwrap();
+/// session.click(Key::Space).unwrap();
+/// session.release().unwrap();
+/// ```
+#[must_use = "the modifiers are released as soon as the session is dropped"]
+pub struct ModifierSession {
+    /// 本会话按下（需要释放）的修饰键
+    pressed: Vec<Modifier>,
+    modifiers: Vec<Modifier>,
+    _op: super::OperationGuard,
+}
+
+impl std::fmt::Debug for ModifierSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModifierSession")
+            .field("modifiers", &self.modifiers)
+            .finish()
+    }
+}
+
+impl ModifierSession {
+    /// 按下并保持修饰键，看门狗超时为 [`DEFAULT_HOLD_TIMEOUT`]
+    pub fn hold(modifiers: &[Modifier]) -> Result<Self> {
+        Self::hold_with_timeout(modifiers, DEFAULT_HOLD_TIMEOUT)
+    }
+
+    /// 按下并保持修饰键，超过 `timeout` 未结束会话时由看门狗自动释放
+    ///
+    /// 任一修饰键按下失败时，已按下的修饰键会被释放。
+    pub fn hold_with_timeout(modifiers: &[Modifier], timeout: Duration) -> Result<Self> {
+        let op = super::begin_operation();
+        let already_held = held_modifiers();
+        let mut session = Self {
+            pressed: Vec::with_capacity(modifiers.len()),
+            modifiers: modifiers.to_vec(),
+            _op: op,
+        };
+        for &modifier in modifiers {
+            if already_held.contains(&modifier) || session.pressed.contains(&modifier) {
+                continue;
+            }
+            hold_modifier_with_timeout(modifier, timeout)?;
+            session.pressed.push(modifier);
+        }
+        Ok(session)
+    }
+
+    /// 会话保持的修饰键
+    pub fn modifiers(&self) -> &[Modifier] {
+        &self.modifiers
+    }
+
+    /// 在修饰键按住时点击按键
+    pub fn click(&self, key: Key) -> Result<()> {
+        super::key_click(key, None)
+    }
+
+    /// 在修饰键按住时按下按键
+    pub fn key_down(&self, key: Key) -> Result<()> {
+        super::key_down(key)
+    }
+
+    /// 在修饰键按住时释放按键
+    pub fn key_up(&self, key: Key) -> Result<()> {
+        super::key_up(key)
+    }
+
+    /// 在修饰键按住时输入字符串
+    ///
+    /// Unicode 注入方式下多数程序忽略修饰键状态；需要 Shift 等影响输入时，
+    /// 使用 [`TextInjection::KeyStrokes`](super::TextInjection::KeyStrokes)。
+    pub fn type_string(&self, text: &str) -> Result<()> {
+        super::type_string(text)
+    }
+
+    /// 按相反顺序释放本会话按下的修饰键并返回第一个错误（其余仍会尝试释放）
+    pub fn release(mut self) -> Result<()> {
+        self.release_pressed()
+    }
+
+    fn release_pressed(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        while let Some(modifier) = self.pressed.pop() {
+            let released = release_modifier(modifier);
+            if result.is_ok() {
+                result = released;
+            }
+        }
+        result
+    }
+}
+
+impl Drop for ModifierSession {
+    fn drop(&mut self) {
+        let _ = self.release_pressed();
+    }
+}
+
+/// 在修饰键按住期间执行 `f`，结束（包括出错返回和 panic）后释放修饰键
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{with_modifiers, Key, Modifier};
+///
+/// // Ctrl+Shift 按住期间连续按两次方向键
+/// with_modifiers(&[Modifier::Control, Modifier::Shift], |session| {
+///     session.click(Key::ArrowRight)?;
+///     session.click(Key::ArrowRight)
+/// })
+/// .unwrap();
+/// ```
+pub fn with_modifiers<T, F>(modifiers: &[Modifier], f: F) -> Result<T>
+where
+    F: FnOnce(&ModifierSession) -> Result<T>,
+{
+    let session = ModifierSession::hold(modifiers)?;
+    let value = f(&session)?;
+    session.release()?;
+    Ok(value)
+}
+
+/// 释放全部正在保持的修饰键，返回第一个错误（其余仍会尝试释放）
+pub fn release_held_modifiers() -> Result<()> {
+    let modifiers: Vec<Modifier> = held()
+        .as_mut()
+        .map(|map| map.drain().map(|(modifier, _)| modifier).collect())
+        .unwrap_or_default();
+    let mut result = Ok(());
+    for modifier in modifiers {
+        let released = super::global::inject_vk(super::modifier_to_vk(modifier), true);
+        if result.is_ok() {
+            result = released;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_expired() {
+        let now = Instant::now();
+        let later = now + Duration::from_millis(10);
+        let mut map = HashMap::from([
+            (Modifier::Control, Some(now)),
+            (Modifier::Shift, Some(later)),
+            (Modifier::Alt, Some(later + Duration::from_millis(10))),
+            (Modifier::Meta, None),
+        ]);
+
+        let (expired, next) = take_expired(&mut map, now);
+        assert_eq!(expired, vec![Modifier::Control]);
+        assert_eq!(next, Some(later));
+        assert_eq!(map.len(), 3);
+
+        let (mut expired, next) = take_expired(&mut map, later + Duration::from_secs(1));
+        expired.sort_by_key(|m| format!("{:?}", m));
+        assert_eq!(expired, vec![Modifier::Alt, Modifier::Shift]);
+        // 不会到期的保持不参与计时
+        assert_eq!(next, None);
+        assert!(map.contains_key(&Modifier::Meta));
+    }
+
+    #[test]
+    fn test_retime_moves_deadline() {
+        let now = Instant::now();
+        let mut map = HashMap::from([(Modifier::Control, Some(now))]);
+        // 重新计时只更新到期时间
+        map.insert(Modifier::Control, now.checked_add(Duration::from_secs(5)));
+        let (expired, next) = take_expired(&mut map, now);
+        assert!(expired.is_empty());
+        assert_eq!(next, Some(now + Duration::from_secs(5)));
+        assert_eq!(now.checked_add(Duration::MAX), None);
+    }
+}
@@ -0,0 +1,105 @@
+// src/core/hook_thread.rs
+//! 钩子线程
+//!
+//! 低级键盘钩子、WinEvent 钩子和线程热键都要求安装它们的线程运行消息循环，回调也在该线程上调用。
+//! [`HookThread`] 启动这样的线程并等待安装结果，丢弃时发送 `WM_QUIT` 结束消息循环，
+//! 由安装时返回的清理函数在同一线程上卸载钩子。
+use crate::error::{KeyboardSenderError, Result};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use windows::Win32::{
+    Foundation::{HINSTANCE, LPARAM, WPARAM},
+    System::Threading::GetCurrentThreadId,
+    UI::WindowsAndMessaging::{
+        DispatchMessageW, GetMessageW, PostThreadMessageW, SetWindowsHookExW, TranslateMessage,
+        UnhookWindowsHookEx, HOOKPROC, MSG, WH_KEYBOARD_LL, WM_QUIT,
+    },
+};
+
+/// 运行消息循环的后台线程，丢弃时结束线程
+#[derive(Debug)]
+pub(crate) struct HookThread {
+    thread_id: u32,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HookThread {
+    /// 启动名为 `name` 的线程，在其上调用 `install` 后运行消息循环
+    ///
+    /// `install` 失败时返回其错误；成功时返回的清理函数在消息循环结束后调用。
+    /// `on_message` 返回真的消息已被处理，不再分发。
+    pub(crate) fn spawn<I, C, M>(name: &str, install: I, mut on_message: M) -> Result<Self>
+    where
+        I: FnOnce() -> Result<C> + Send + 'static,
+        C: FnOnce(),
+        M: FnMut(&MSG) -> bool + Send + 'static,
+    {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                let cleanup = match install() {
+                    Ok(cleanup) => cleanup,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(unsafe { GetCurrentThreadId() }));
+
+                let mut msg = MSG::default();
+                unsafe {
+                    while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                        if on_message(&msg) {
+                            continue;
+                        }
+                        TranslateMessage(&msg);
+                        DispatchMessageW(&msg);
+                    }
+                }
+                cleanup();
+            })
+            .map_err(|_| KeyboardSenderError::WindowsError)?;
+
+        match ready_rx.recv() {
+            Ok(Ok(thread_id)) => Ok(Self {
+                thread_id,
+                thread: Some(thread),
+            }),
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(KeyboardSenderError::WindowsError)
+            }
+        }
+    }
+
+    /// 结束消息循环并等待线程退出，重复调用不做任何操作
+    pub(crate) fn stop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            unsafe {
+                let _ = PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for HookThread {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 在当前线程上安装低级键盘钩子（`WH_KEYBOARD_LL`），返回卸载钩子的清理函数
+pub(crate) fn install_keyboard_hook(proc: HOOKPROC) -> Result<impl FnOnce()> {
+    let hook = unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, proc, HINSTANCE(0), 0) }
+        .map_err(|_| KeyboardSenderError::WindowsError)?;
+    Ok(move || unsafe {
+        let _ = UnhookWindowsHookEx(hook);
+    })
+}
@@ -0,0 +1,318 @@
+// src/core/hotkeys.rs
+//! 快捷键冲突检测
+//!
+//! 注册绑定或回放 Win+L 之类的组合键之前，检查它是否为系统保留/危险的组合键，
+//! 以及是否已被其他程序注册为全局热键（通过 `RegisterHotKey` 试探注册）。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, Modifier};
+use keyboard_codes::parse_shortcut_with_aliases;
+
+use windows::Win32::{
+    Foundation::ERROR_HOTKEY_ALREADY_REGISTERED,
+    UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT,
+        MOD_SHIFT, MOD_WIN,
+    },
+};
+
+/// 试探注册使用的热键 ID（应用程序可用范围 0x0000 ~ 0xBFFF）
+const PROBE_HOTKEY_ID: i32 = 0xBF5A;
+
+/// 系统保留或危险的快捷键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReservedShortcut {
+    /// 快捷键模式，`win+*` 表示含 Win 键的任意组合
+    pub pattern: &'static str,
+    /// 系统对该快捷键的用途
+    pub description: &'static str,
+}
+
+/// 系统保留快捷键表，按从具体到宽泛排列
+pub const SYSTEM_RESERVED_SHORTCUTS: &[ReservedShortcut] = &[
+    ReservedShortcut {
+        pattern: "ctrl+alt+delete",
+        description: "secure attention sequence",
+    },
+    ReservedShortcut {
+        pattern: "win+l",
+        description: "lock workstation",
+    },
+    ReservedShortcut {
+        pattern: "ctrl+shift+esc",
+        description: "open Task Manager",
+    },
+    ReservedShortcut {
+        pattern: "alt+tab",
+        description: "switch windows",
+    },
+    ReservedShortcut {
+        pattern: "alt+shift+tab",
+        description: "switch windows",
+    },
+    ReservedShortcut {
+        pattern: "ctrl+alt+tab",
+        description: "persistent window switcher",
+    },
+    ReservedShortcut {
+        pattern: "alt+esc",
+        description: "cycle windows",
+    },
+    ReservedShortcut {
+        pattern: "ctrl+esc",
+        description: "open Start menu",
+    },
+    ReservedShortcut {
+        pattern: "alt+f4",
+        description: "close active window",
+    },
+    ReservedShortcut {
+        pattern: "alt+space",
+        description: "open window menu",
+    },
+    ReservedShortcut {
+        pattern: "win+*",
+        description: "reserved by the Windows shell",
+    },
+];
+
+/// 需要 [`allow_system_shortcuts`](super::allow_system_shortcuts) 才能全局发送的组合键
+///
+/// 含 Win 键的任意组合（包括单独按下 Win 键）也需要，不在此表中列出。
+pub const INTERLOCKED_SHORTCUTS: &[&str] = &["ctrl+alt+delete", "ctrl+shift+esc", "ctrl+esc"];
+
+/// 快捷键冲突
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutConflict {
+    /// 系统保留或危险的组合键
+    SystemReserved(&'static ReservedShortcut),
+    /// 已被其他程序注册为全局热键
+    RegisteredHotkey,
+}
+
+/// 查找快捷键对应的系统保留项
+///
+/// 修饰键的左右两侧视为同一个修饰键，顺序和大小写不影响匹配。
+pub fn system_reserved(shortcut: &str) -> Result<Option<&'static ReservedShortcut>> {
+    let (mask, key) = parse(shortcut)?;
+    for reserved in SYSTEM_RESERVED_SHORTCUTS {
+        let matched = match reserved.pattern.strip_suffix("+*") {
+            Some(modifiers) => {
+                let (required, _) = parse(&format!("{}+a", modifiers))?;
+                mask & required == required
+            }
+            None => parse(reserved.pattern)? == (mask, key),
+        };
+        if matched {
+            return Ok(Some(reserved));
+        }
+    }
+    Ok(None)
+}
+
+/// 快捷键是否为系统保留或危险的组合键
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::is_system_reserved;
+///
+/// assert!(is_system_reserved("win+l").unwrap());
+/// assert!(is_system_reserved("Ctrl+Alt+Del").unwrap());
+/// assert!(!is_system_reserved("ctrl+s").unwrap());
+/// ```
+pub fn is_system_reserved(shortcut: &str) -> Result<bool> {
+    Ok(system_reserved(shortcut)?.is_some())
+}
+
+/// 组合键是否为系统快捷键：含 Win 键，或属于 [`INTERLOCKED_SHORTCUTS`]
+///
+/// 这类组合键需要先调用 [`allow_system_shortcuts`](super::allow_system_shortcuts) 才能全局发送。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{is_system_combination, Key, Modifier};
+///
+/// assert!(is_system_combination(&[Modifier::LeftMeta], Key::R));
+/// assert!(is_system_combination(&[Modifier::Control, Modifier::Alt], Key::Delete));
+/// assert!(!is_system_combination(&[Modifier::Control], Key::S));
+/// ```
+pub fn is_system_combination(modifiers: &[Modifier], key: Key) -> bool {
+    let mask = modifiers.iter().fold(0, |mask, &m| mask | modifier_bit(m));
+    is_interlocked(mask, super::key_to_vk(key))
+}
+
+/// 快捷键字符串是否为系统快捷键，见 [`is_system_combination`]
+pub fn is_system_shortcut(shortcut: &str) -> Result<bool> {
+    let (mask, key) = parse(shortcut)?;
+    Ok(is_interlocked(mask, super::key_to_vk(key)))
+}
+
+/// 修饰键状态为 `mask` 时按下虚拟键 `vk` 是否会触发系统快捷键
+pub(crate) fn is_interlocked(mask: u32, vk: u16) -> bool {
+    if mask & MOD_WIN.0 != 0 || vk_modifier_bit(vk) == MOD_WIN.0 {
+        return true;
+    }
+    INTERLOCKED_SHORTCUTS
+        .iter()
+        .filter_map(|pattern| parse(pattern).ok())
+        .any(|(required, key)| super::key_to_vk(key) == vk && mask & required == required)
+}
+
+/// 修饰键虚拟键码对应的修饰键位，不是修饰键时为 0
+pub(crate) fn vk_modifier_bit(vk: u16) -> u32 {
+    match vk {
+        0x10 | 0xA0 | 0xA1 => MOD_SHIFT.0,
+        0x11 | 0xA2 | 0xA3 => MOD_CONTROL.0,
+        0x12 | 0xA4 | 0xA5 => MOD_ALT.0,
+        0x5B | 0x5C => MOD_WIN.0,
+        _ => 0,
+    }
+}
+
+/// 快捷键是否已被注册为全局热键
+///
+/// 通过试探注册判断：注册成功则立即注销并返回 `false`。
+/// Ctrl+Alt+Del、Win+L 等由系统直接处理的组合键不经过热键机制，需配合 [`is_system_reserved`] 使用。
+pub fn is_hotkey_registered(shortcut: &str) -> Result<bool> {
+    let (mask, key) = parse(shortcut)?;
+    Ok(probe(mask, super::key_to_vk(key)))
+}
+
+/// 检查快捷键的全部冲突
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{check_shortcut_conflicts, ShortcutConflict};
+///
+/// for conflict in check_shortcut_conflicts("ctrl+alt+k").unwrap() {
+///     match conflict {
+///         ShortcutConflict::SystemReserved(r) => println!("reserved: {}", r.description),
+///         ShortcutConflict::RegisteredHotkey => println!("already registered"),
+///     }
+/// }
+/// ```
+pub fn check_shortcut_conflicts(shortcut: &str) -> Result<Vec<ShortcutConflict>> {
+    let mut conflicts = Vec::new();
+    if let Some(reserved) = system_reserved(shortcut)? {
+        conflicts.push(ShortcutConflict::SystemReserved(reserved));
+    }
+    if is_hotkey_registered(shortcut)? {
+        conflicts.push(ShortcutConflict::RegisteredHotkey);
+    }
+    Ok(conflicts)
+}
+
+/// 扫描常用组合键（任意修饰键组合 + 字母、数字、F1~F12）中已被注册为全局热键的项
+///
+/// 返回形如 `ctrl+alt+k` 的快捷键字符串。
+pub fn scan_registered_hotkeys() -> Vec<String> {
+    let keys = (b'A'..=b'Z')
+        .map(|c| (c as char).to_string())
+        .chain((0..=9).map(|d| format!("D{}", d)))
+        .chain((1..=12).map(|n| format!("F{}", n)))
+        .filter_map(|name| name.parse::<Key>().ok());
+
+    let keys: Vec<Key> = keys.collect();
+    let mut registered = Vec::new();
+    for mask in 1..16u32 {
+        for &key in &keys {
+            if probe(mask, super::key_to_vk(key)) {
+                registered.push(format_shortcut(mask, key));
+            }
+        }
+    }
+    registered
+}
+
+/// 修饰键位：Alt=1、Ctrl=2、Shift=4、Win=8（与 `MOD_*` 一致）
+pub(crate) fn modifier_bit(modifier: Modifier) -> u32 {
+    match modifier {
+        Modifier::Alt | Modifier::LeftAlt | Modifier::RightAlt => MOD_ALT.0,
+        Modifier::Control | Modifier::LeftControl | Modifier::RightControl => MOD_CONTROL.0,
+        Modifier::Shift | Modifier::LeftShift | Modifier::RightShift => MOD_SHIFT.0,
+        Modifier::Meta | Modifier::LeftMeta | Modifier::RightMeta => MOD_WIN.0,
+    }
+}
+
+pub(crate) fn parse(shortcut: &str) -> Result<(u32, Key)> {
+    let parsed = parse_shortcut_with_aliases(shortcut)
+        .map_err(|e| KeyboardSenderError::ParseError(e.to_string()))?;
+    let mask = parsed
+        .modifiers
+        .iter()
+        .fold(0, |mask, &m| mask | modifier_bit(m));
+    Ok((mask, parsed.key))
+}
+
+fn format_shortcut(mask: u32, key: Key) -> String {
+    let mut parts: Vec<String> = [
+        (MOD_CONTROL.0, "ctrl"),
+        (MOD_ALT.0, "alt"),
+        (MOD_SHIFT.0, "shift"),
+        (MOD_WIN.0, "win"),
+    ]
+    .iter()
+    .filter(|(bit, _)| mask & bit != 0)
+    .map(|(_, name)| name.to_string())
+    .collect();
+    parts.push(key.to_string().to_lowercase());
+    parts.join("+")
+}
+
+/// 试探注册热键，返回是否已被占用
+fn probe(mask: u32, vk: u16) -> bool {
+    let modifiers = HOT_KEY_MODIFIERS(mask) | MOD_NOREPEAT;
+    match unsafe { RegisterHotKey(None, PROBE_HOTKEY_ID, modifiers, u32::from(vk)) } {
+        Ok(()) => {
+            let _ = unsafe { UnregisterHotKey(None, PROBE_HOTKEY_ID) };
+            false
+        }
+        Err(e) => e.code() == ERROR_HOTKEY_ALREADY_REGISTERED.to_hresult(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_reserved() {
+        assert_eq!(
+            system_reserved("lctrl+alt+del").unwrap().map(|r| r.pattern),
+            Some("ctrl+alt+delete")
+        );
+        assert_eq!(
+            system_reserved("win+r").unwrap().map(|r| r.pattern),
+            Some("win+*")
+        );
+        assert_eq!(
+            system_reserved("ctrl+shift+esc")
+                .unwrap()
+                .map(|r| r.pattern),
+            Some("ctrl+shift+esc")
+        );
+        assert!(!is_system_reserved("ctrl+alt+t").unwrap());
+        assert!(is_system_reserved("nope+x").is_err());
+    }
+
+    #[test]
+    fn test_system_shortcut_interlock() {
+        assert!(is_system_shortcut("win+r").unwrap());
+        assert!(is_system_shortcut("ctrl+alt+del").unwrap());
+        assert!(is_system_shortcut("ctrl+esc").unwrap());
+        assert!(!is_system_shortcut("alt+tab").unwrap());
+        assert!(!is_system_shortcut("ctrl+delete").unwrap());
+        // 单独按下 Win 键，以及 Win 键按下期间的任意按键
+        assert!(is_interlocked(0, 0x5C));
+        assert!(is_interlocked(MOD_WIN.0, 0x41));
+        assert!(is_interlocked(MOD_CONTROL.0 | MOD_SHIFT.0, 0x1B));
+        assert!(!is_interlocked(MOD_SHIFT.0, 0x1B));
+    }
+
+    #[test]
+    fn test_format_shortcut() {
+        assert_eq!(
+            format_shortcut(MOD_ALT.0 | MOD_CONTROL.0, Key::K),
+            "ctrl+alt+k"
+        );
+    }
+}
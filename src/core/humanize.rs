@@ -0,0 +1,150 @@
+// src/core/humanize.rs
+//! 拟人化输入节奏
+//!
+//! 逐字符输入，每个字符之后等待基础间隔加随机抖动，并偶尔插入较长的停顿，
+//! 使自动输入的节奏接近真人，适用于 UI 测试和对输入节奏敏感的程序。
+use crate::error::Result;
+#[cfg(feature = "window_target")]
+use crate::types::WindowHandle;
+use std::time::Duration;
+
+use super::delay::DelaySpec;
+
+/// 拟人化输入参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypingProfile {
+    /// 字符之间的基础间隔（默认 80ms）
+    pub base_delay: Duration,
+    /// 间隔的抖动范围，实际间隔在 `base_delay ± jitter` 内随机（默认 40ms）
+    pub jitter: Duration,
+    /// 每个字符之后插入停顿的概率（0.0 ~ 1.0，默认 0.05）
+    pub pause_probability: f64,
+    /// 停顿时长（默认 300ms..900ms）
+    pub pause: DelaySpec,
+}
+
+impl Default for TypingProfile {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(80),
+            jitter: Duration::from_millis(40),
+            pause_probability: 0.05,
+            pause: DelaySpec::range(Duration::from_millis(300), Duration::from_millis(900)),
+        }
+    }
+}
+
+impl TypingProfile {
+    /// 默认参数
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置基础间隔和抖动范围
+    pub fn delay(mut self, base: Duration, jitter: Duration) -> Self {
+        self.base_delay = base;
+        self.jitter = jitter;
+        self
+    }
+
+    /// 设置停顿概率和时长
+    pub fn pauses(mut self, probability: f64, pause: DelaySpec) -> Self {
+        self.pause_probability = probability.clamp(0.0, 1.0);
+        self.pause = pause;
+        self
+    }
+
+    /// 字符间隔的取值范围
+    pub fn char_delay(&self) -> DelaySpec {
+        DelaySpec::range(
+            self.base_delay.saturating_sub(self.jitter),
+            self.base_delay.saturating_add(self.jitter),
+        )
+    }
+
+    /// 取下一个字符之后的等待时间（间隔加上可能的停顿）
+    pub fn next_delay(&self) -> Duration {
+        let mut delay = self.char_delay().sample();
+        if crate::random::next_f64() < self.pause_probability {
+            delay += self.pause.sample();
+        }
+        delay
+    }
+}
+
+/// 全局发送：按拟人化节奏输入字符串
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{type_string_humanized, TypingProfile};
+/// use std::time::Duration;
+///
+/// let profile = TypingProfile::new().delay(Duration::from_millis(120), Duration::from_millis(60));
+/// type_string_humanized("hello world", &profile).unwrap();
+/// ```
+pub fn type_string_humanized(text: &str, profile: &TypingProfile) -> Result<()> {
+    let _op = super::begin_operation();
+    crate::telemetry::emit_text(None, text);
+    type_chars(text, profile, super::global::send_char)
+}
+
+/// 向指定窗口按拟人化节奏输入字符串
+#[cfg(feature = "window_target")]
+pub fn type_string_to_window_humanized(
+    hwnd: WindowHandle,
+    text: &str,
+    profile: &TypingProfile,
+) -> Result<()> {
+    let _op = super::begin_operation();
+    crate::telemetry::emit_text(Some(hwnd), text);
+    type_chars(text, profile, |c| {
+        super::window_target::send_char_to_window(hwnd, c)
+    })
+}
+
+fn type_chars(
+    text: &str,
+    profile: &TypingProfile,
+    mut send: impl FnMut(char) -> Result<()>,
+) -> Result<()> {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        send(c)?;
+        if chars.peek().is_some() {
+            super::clock().sleep(profile.next_delay());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_delay_range() {
+        let profile =
+            TypingProfile::new().delay(Duration::from_millis(30), Duration::from_millis(50));
+        assert_eq!(profile.char_delay().min(), Duration::ZERO);
+        assert_eq!(profile.char_delay().max(), Duration::from_millis(80));
+    }
+
+    #[test]
+    fn test_next_delay() {
+        let profile = TypingProfile::new()
+            .delay(Duration::from_millis(100), Duration::from_millis(20))
+            .pauses(1.0, DelaySpec::Fixed(Duration::from_millis(500)));
+        for _ in 0..20 {
+            let delay = profile.next_delay();
+            assert!(delay >= Duration::from_millis(580) && delay <= Duration::from_millis(620));
+        }
+
+        let profile = profile.pauses(0.0, DelaySpec::Fixed(Duration::from_secs(10)));
+        crate::random::set_rng_seed(3);
+        let first: Vec<Duration> = (0..5).map(|_| profile.next_delay()).collect();
+        crate::random::set_rng_seed(3);
+        let second: Vec<Duration> = (0..5).map(|_| profile.next_delay()).collect();
+        assert_eq!(first, second);
+        assert!(first.iter().all(|d| *d <= Duration::from_millis(120)));
+    }
+}
@@ -0,0 +1,461 @@
+// src/core/ime.rs
+//! 通过输入法组字输入中文
+//!
+//! 部分程序只接受输入法组字产生的中文，不接受 Unicode 注入。此时可以驱动当前激活的
+//! 拼音输入法：逐键输入拼音，再用空格（第一个候选）或数字键选择候选词。
+//! 文字到拼音与候选序号的转换由可替换的 [`ImeConverter`] 提供，内置 [`PinyinTable`]
+//! 按用户提供的词表转换。
+//!
+//! 反过来，向有激活输入法的窗口投递字符时，输入法可能截获或重新组字；
+//! [`suspend_ime`] 在输入期间临时关闭目标窗口的输入法（需要 `window_target`）。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::Key;
+#[cfg(feature = "window_target")]
+use crate::types::WindowHandle;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 转换后的输入片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImeSegment {
+    /// 输入拼音后选择第 `candidate` 个候选（从 1 开始，最大 9）
+    Compose { pinyin: String, candidate: usize },
+    /// 不经过输入法，直接按 Unicode 注入的文本（如英文、数字、标点）
+    Literal(String),
+}
+
+/// 文字到输入法按键序列的转换器
+pub trait ImeConverter: Send + Sync {
+    /// 把文本转换为输入片段，无法转换时返回错误
+    fn convert(&self, text: &str) -> Result<Vec<ImeSegment>>;
+}
+
+/// 基于词表的转换器
+///
+/// 按最长匹配查找词表；非中文字符作为原样输入，词表中没有的中文字符返回错误。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{ImeConverter, ImeSegment, PinyinTable};
+///
+/// let table = PinyinTable::new().word("你好", "nihao", 1).word("世界", "shijie", 1);
+/// let segments = table.convert("你好, 世界").unwrap();
+/// assert_eq!(segments.len(), 3);
+/// assert_eq!(segments[1], ImeSegment::Literal(", ".to_string()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PinyinTable {
+    words: HashMap<String, (String, usize)>,
+    max_word_chars: usize,
+}
+
+impl PinyinTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加词条：拼音与候选序号（从 1 开始）
+    pub fn word(mut self, word: &str, pinyin: &str, candidate: usize) -> Self {
+        self.insert(word, pinyin, candidate);
+        self
+    }
+
+    /// 添加词条
+    pub fn insert(&mut self, word: &str, pinyin: &str, candidate: usize) {
+        self.max_word_chars = self.max_word_chars.max(word.chars().count());
+        self.words
+            .insert(word.to_string(), (pinyin.to_lowercase(), candidate));
+    }
+}
+
+impl ImeConverter for PinyinTable {
+    fn convert(&self, text: &str) -> Result<Vec<ImeSegment>> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let longest = (1..=self.max_word_chars.min(chars.len() - i))
+                .rev()
+                .find_map(|len| {
+                    let word: String = chars[i..i + len].iter().collect();
+                    self.words.get(&word).map(|entry| (len, entry))
+                });
+            match longest {
+                Some((len, (pinyin, candidate))) => {
+                    if !literal.is_empty() {
+                        segments.push(ImeSegment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(ImeSegment::Compose {
+                        pinyin: pinyin.clone(),
+                        candidate: *candidate,
+                    });
+                    i += len;
+                }
+                None if is_cjk(chars[i]) => {
+                    return Err(KeyboardSenderError::ParseError(format!(
+                        "no pinyin entry for '{}'",
+                        chars[i]
+                    )));
+                }
+                None => {
+                    literal.push(chars[i]);
+                    i += 1;
+                }
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(ImeSegment::Literal(literal));
+        }
+        Ok(segments)
+    }
+}
+
+/// 输入法输入选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImeTypingOptions {
+    /// 拼音按键之间的间隔（默认 10ms）
+    pub key_delay: Duration,
+    /// 输入拼音后、选择候选前等待候选窗口出现的时间（默认 80ms）
+    pub candidate_delay: Duration,
+    /// 选择候选后等待上屏的时间（默认 50ms）
+    pub commit_delay: Duration,
+}
+
+impl Default for ImeTypingOptions {
+    fn default() -> Self {
+        Self {
+            key_delay: Duration::from_millis(10),
+            candidate_delay: Duration::from_millis(80),
+            commit_delay: Duration::from_millis(50),
+        }
+    }
+}
+
+static CONVERTER: RwLock<Option<Arc<dyn ImeConverter>>> = RwLock::new(None);
+
+/// 安装 [`type_chinese_via_ime`] 使用的转换器
+pub fn set_ime_converter<C: ImeConverter + 'static>(converter: C) {
+    *CONVERTER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(converter));
+}
+
+/// 移除转换器
+pub fn clear_ime_converter() {
+    *CONVERTER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 使用已安装的转换器，通过当前输入法输入中文
+///
+/// 调用前需要切换到中文拼音输入法；未安装转换器时返回 `FeatureNotEnabled` 错误。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{set_ime_converter, type_chinese_via_ime, PinyinTable};
+///
+/// set_ime_converter(PinyinTable::new().word("你好", "nihao", 1));
+/// type_chinese_via_ime("你好").unwrap();
+/// ```
+pub fn type_chinese_via_ime(text: &str) -> Result<()> {
+    let converter = CONVERTER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .ok_or_else(|| {
+            KeyboardSenderError::FeatureNotEnabled("no IME converter installed".to_string())
+        })?;
+    type_chinese_via_ime_with(text, converter.as_ref(), &ImeTypingOptions::default())
+}
+
+/// 使用指定转换器与选项，通过当前输入法输入中文
+///
+/// 先完成全部转换再开始输入，转换失败时不会输入任何内容。
+pub fn type_chinese_via_ime_with(
+    text: &str,
+    converter: &dyn ImeConverter,
+    options: &ImeTypingOptions,
+) -> Result<()> {
+    let plan = segments_to_keys(&converter.convert(text)?)?;
+
+    let _op = super::begin_operation();
+    crate::telemetry::emit_text(None, text);
+    for step in plan {
+        match step {
+            ImeStep::Literal(text) => {
+                for c in text.chars() {
+                    super::send_char(c)?;
+                }
+            }
+            ImeStep::Compose { keys, select } => {
+                let clock = super::clock();
+                for key in keys {
+                    super::key_click(key, None)?;
+                    clock.sleep(options.key_delay);
+                }
+                clock.sleep(options.candidate_delay);
+                super::key_click(select, None)?;
+                clock.sleep(options.commit_delay);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 片段对应的按键
+#[derive(Debug, PartialEq, Eq)]
+enum ImeStep {
+    Literal(String),
+    Compose { keys: Vec<Key>, select: Key },
+}
+
+/// 把片段转换为按键，校验拼音只含字母、候选序号在 1..=9
+fn segments_to_keys(segments: &[ImeSegment]) -> Result<Vec<ImeStep>> {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            ImeSegment::Literal(text) => Ok(ImeStep::Literal(text.clone())),
+            ImeSegment::Compose { pinyin, candidate } => {
+                let keys = pinyin
+                    .chars()
+                    .map(|c| {
+                        if !c.is_ascii_alphabetic() {
+                            return Err(KeyboardSenderError::ParseError(format!(
+                                "invalid pinyin '{}'",
+                                pinyin
+                            )));
+                        }
+                        Key::from_str(&c.to_ascii_uppercase().to_string())
+                            .map_err(|_| KeyboardSenderError::UnsupportedKey(c.to_string()))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let select = match candidate {
+                    1 => Key::Space,
+                    2..=9 => Key::from_str(&format!("D{}", candidate))
+                        .map_err(|_| KeyboardSenderError::UnsupportedKey(candidate.to_string()))?,
+                    _ => {
+                        return Err(KeyboardSenderError::ParseError(format!(
+                            "candidate {} out of range 1..=9",
+                            candidate
+                        )))
+                    }
+                };
+                Ok(ImeStep::Compose { keys, select })
+            }
+        })
+        .collect()
+}
+
+/// 临时关闭的窗口输入法，丢弃时恢复
+///
+/// 由 [`suspend_ime`] 返回。
+#[cfg(feature = "window_target")]
+#[derive(Debug)]
+#[must_use = "the IME is restored as soon as the guard is dropped"]
+pub struct ImeSuspension {
+    hwnd: WindowHandle,
+    restore: ImeRestore,
+}
+
+#[cfg(feature = "window_target")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImeRestore {
+    /// 窗口没有输入法或输入法本来就是关闭的
+    Nothing,
+    /// 重新关联原来的输入上下文（本进程的窗口）
+    Context(isize),
+    /// 重新打开输入法（其他进程的窗口）
+    Open { ime_window: WindowHandle },
+}
+
+/// 等待目标窗口输入法处理控制消息的超时时间
+#[cfg(feature = "window_target")]
+const IME_CONTROL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 临时关闭窗口的输入法，返回的守卫丢弃时恢复
+///
+/// 本进程的窗口通过 `ImmAssociateContext` 解除输入上下文；其他进程的窗口不能解除关联，
+/// 改为向其默认输入法窗口发送 `WM_IME_CONTROL`（`IMC_SETOPENSTATUS`）关闭输入法，
+/// 恢复时重新打开。窗口没有输入法或输入法已关闭时什么也不做。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{suspend_ime, type_string_to_window};
+///
+/// let hwnd = 0x1A2B;
+/// let suspended = suspend_ime(hwnd).unwrap();
+/// type_string_to_window(hwnd, "plain ascii").unwrap();
+/// drop(suspended);
+/// # }
+/// ```
+#[cfg(feature = "window_target")]
+pub fn suspend_ime(hwnd: WindowHandle) -> Result<ImeSuspension> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Globalization::HIMC;
+    use windows::Win32::UI::Input::Ime::{ImmAssociateContext, ImmGetDefaultIMEWnd};
+
+    let restore = if super::window_process_id(hwnd) == std::process::id() {
+        match unsafe { ImmAssociateContext(HWND(hwnd), HIMC(0)) } {
+            HIMC(0) => ImeRestore::Nothing,
+            previous => ImeRestore::Context(previous.0),
+        }
+    } else {
+        match unsafe { ImmGetDefaultIMEWnd(HWND(hwnd)) }.0 {
+            0 => ImeRestore::Nothing,
+            ime_window => {
+                if ime_control(ime_window, IMC_GETOPENSTATUS, 0)? == 0 {
+                    ImeRestore::Nothing
+                } else {
+                    ime_control(ime_window, IMC_SETOPENSTATUS, 0)?;
+                    ImeRestore::Open { ime_window }
+                }
+            }
+        }
+    };
+    Ok(ImeSuspension { hwnd, restore })
+}
+
+#[cfg(feature = "window_target")]
+impl ImeSuspension {
+    /// 立即恢复输入法并返回结果
+    pub fn restore(mut self) -> Result<()> {
+        self.restore_ime()
+    }
+
+    fn restore_ime(&mut self) -> Result<()> {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::Globalization::HIMC;
+        use windows::Win32::UI::Input::Ime::ImmAssociateContext;
+
+        match std::mem::replace(&mut self.restore, ImeRestore::Nothing) {
+            ImeRestore::Nothing => {}
+            ImeRestore::Context(himc) => unsafe {
+                ImmAssociateContext(HWND(self.hwnd), HIMC(himc));
+            },
+            ImeRestore::Open { ime_window } => {
+                ime_control(ime_window, IMC_SETOPENSTATUS, 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "window_target")]
+impl Drop for ImeSuspension {
+    fn drop(&mut self) {
+        let _ = self.restore_ime();
+    }
+}
+
+#[cfg(feature = "window_target")]
+const IMC_GETOPENSTATUS: usize = 0x0005;
+#[cfg(feature = "window_target")]
+const IMC_SETOPENSTATUS: usize = 0x0006;
+
+/// 向默认输入法窗口发送 `WM_IME_CONTROL`，返回处理结果
+#[cfg(feature = "window_target")]
+fn ime_control(ime_window: WindowHandle, command: usize, value: isize) -> Result<usize> {
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_IME_CONTROL,
+    };
+
+    let mut result = 0usize;
+    let ok = unsafe {
+        SendMessageTimeoutW(
+            HWND(ime_window),
+            WM_IME_CONTROL,
+            WPARAM(command),
+            LPARAM(value),
+            SMTO_ABORTIFHUNG,
+            IME_CONTROL_TIMEOUT.as_millis() as u32,
+            Some(&mut result),
+        )
+    };
+    if ok.0 == 0 {
+        return Err(KeyboardSenderError::Timeout(format!(
+            "IME window {:#x} did not respond within {:?}",
+            ime_window, IME_CONTROL_TIMEOUT
+        )));
+    }
+    Ok(result)
+}
+
+/// 是否为中日韩统一表意文字
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_longest_match() {
+        let table = PinyinTable::new()
+            .word("中", "zhong", 1)
+            .word("中国", "zhongguo", 1)
+            .word("国", "guo", 2);
+        assert_eq!(
+            table.convert("中国a国").unwrap(),
+            vec![
+                ImeSegment::Compose {
+                    pinyin: "zhongguo".to_string(),
+                    candidate: 1
+                },
+                ImeSegment::Literal("a".to_string()),
+                ImeSegment::Compose {
+                    pinyin: "guo".to_string(),
+                    candidate: 2
+                },
+            ]
+        );
+        assert!(table.convert("汉").is_err());
+    }
+
+    #[test]
+    fn test_segments_to_keys() {
+        let steps = segments_to_keys(&[ImeSegment::Compose {
+            pinyin: "ni".to_string(),
+            candidate: 3,
+        }])
+        .unwrap();
+        assert_eq!(
+            steps,
+            vec![ImeStep::Compose {
+                keys: vec![Key::N, Key::I],
+                select: Key::D3
+            }]
+        );
+
+        let first = segments_to_keys(&[ImeSegment::Compose {
+            pinyin: "a".to_string(),
+            candidate: 1,
+        }])
+        .unwrap();
+        assert_eq!(
+            first,
+            vec![ImeStep::Compose {
+                keys: vec![Key::A],
+                select: Key::Space
+            }]
+        );
+
+        let bad = |pinyin: &str, candidate| {
+            segments_to_keys(&[ImeSegment::Compose {
+                pinyin: pinyin.to_string(),
+                candidate,
+            }])
+            .is_err()
+        };
+        assert!(bad("ni3", 1));
+        assert!(bad("ni", 0));
+        assert!(bad("ni", 10));
+    }
+}
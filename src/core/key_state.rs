@@ -0,0 +1,246 @@
+// src/core/key_state.rs
+//! 键盘状态检查
+//!
+//! 上一次运行崩溃时可能留下逻辑上仍处于按下状态的按键（最常见的是 Shift、Ctrl），
+//! 之后所有输入都会带上这些修饰键。启动时调用 [`sanity_check_keyboard_state`]
+//! 检测并释放它们。
+use crate::error::Result;
+use crate::types::Key;
+use keyboard_codes::KeyCodeMapper;
+use std::fmt;
+use std::sync::Once;
+use std::time::Duration;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+use super::state::is_vk_down;
+
+/// 不参与检查的虚拟键码：鼠标按键和不区分左右的修饰键（左右键分别检查）
+const IGNORED_VKS: &[u16] = &[0x01, 0x02, 0x04, 0x05, 0x06, 0x10, 0x11, 0x12];
+
+/// 键盘状态检查选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanityCheckOptions {
+    /// 按键持续按下且期间没有任何输入活动多久视为卡住
+    pub threshold: Duration,
+    /// 是否为卡住的按键发送释放事件
+    pub repair: bool,
+}
+
+impl Default for SanityCheckOptions {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_millis(300),
+            repair: true,
+        }
+    }
+}
+
+/// 卡住的按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StuckKey {
+    /// 虚拟键码
+    pub vk: u16,
+    /// 对应的按键（无标准按键对应时为 `None`）
+    pub key: Option<Key>,
+}
+
+impl fmt::Display for StuckKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.key {
+            Some(key) => write!(f, "{} (vk {:#04x})", key, self.vk),
+            None => write!(f, "vk {:#04x}", self.vk),
+        }
+    }
+}
+
+/// 键盘状态检查结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanityReport {
+    /// 判定为卡住的按键
+    pub stuck: Vec<StuckKey>,
+    /// 已发送释放事件的按键数
+    pub released: usize,
+    /// 等待期间有输入活动，仍按下的按键可能是用户正在按住，未判定为卡住
+    pub inconclusive: Vec<StuckKey>,
+}
+
+impl SanityReport {
+    /// 是否没有发现卡住的按键
+    pub fn is_clean(&self) -> bool {
+        self.stuck.is_empty()
+    }
+}
+
+impl fmt::Display for SanityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |keys: &[StuckKey]| {
+            keys.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if self.stuck.is_empty() {
+            write!(f, "no stuck keys")?;
+        } else {
+            write!(
+                f,
+                "stuck keys: {} ({} released)",
+                list(&self.stuck),
+                self.released
+            )?;
+        }
+        if !self.inconclusive.is_empty() {
+            write!(
+                f,
+                "; held during input activity: {}",
+                list(&self.inconclusive)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 使用默认选项检查并修复卡住的按键
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::sanity_check_keyboard_state;
+///
+/// let report = sanity_check_keyboard_state().unwrap();
+/// if !report.is_clean() {
+///     eprintln!("{}", report);
+/// }
+/// ```
+pub fn sanity_check_keyboard_state() -> Result<SanityReport> {
+    sanity_check_keyboard_state_with(&SanityCheckOptions::default())
+}
+
+/// 按选项检查卡住的按键
+///
+/// 读取逻辑按键状态，等待 `threshold` 后再读取一次；两次都处于按下状态、且期间没有
+/// 任何键盘鼠标输入的按键判定为卡住。检查结果同时以 `StuckKeysDetected` 遥测事件上报。
+pub fn sanity_check_keyboard_state_with(options: &SanityCheckOptions) -> Result<SanityReport> {
+    let _op = super::begin_operation();
+    let before = pressed_vks();
+    if before.is_empty() {
+        return Ok(SanityReport::default());
+    }
+    let activity_before = last_input_tick();
+    std::thread::sleep(options.threshold);
+    let activity_after = last_input_tick();
+
+    let held: Vec<StuckKey> = pressed_vks()
+        .into_iter()
+        .filter(|vk| before.contains(vk))
+        .map(|vk| StuckKey {
+            vk,
+            key: Key::from_code(usize::from(vk), keyboard_codes::current_platform()),
+        })
+        .collect();
+
+    let mut report = SanityReport::default();
+    if activity_before != activity_after {
+        report.inconclusive = held;
+        return Ok(report);
+    }
+    report.stuck = held;
+
+    if options.repair {
+        for stuck in &report.stuck {
+            super::global::inject_vk(stuck.vk, true)?;
+            report.released += 1;
+        }
+    }
+
+    if !report.stuck.is_empty() {
+        crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::StuckKeysDetected {
+            vks: report.stuck.iter().map(|k| k.vk).collect(),
+            released: report.released > 0,
+        });
+    }
+    Ok(report)
+}
+
+/// 紧急清理：为所有当前处于按下状态的按键发送释放事件，返回释放的虚拟键码
+///
+/// 不区分按键是由本 crate 注入还是用户物理按住的，适合在自动化失败后调用。
+/// 同时清除 [`hold_modifier`](super::hold::hold_modifier) 的保持状态。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{release_all_keys, type_string};
+///
+/// if let Err(e) = type_string("...") {
+///     eprintln!("automation failed: {}", e);
+///     release_all_keys().unwrap();
+/// }
+/// ```
+pub fn release_all_keys() -> Result<Vec<u16>> {
+    super::hold::forget_held_modifiers();
+    let pressed = pressed_vks();
+    let mut result = Ok(());
+    for &vk in &pressed {
+        // 某个按键释放失败时继续释放其余按键
+        let released = super::global::inject_vk(vk, true);
+        if result.is_ok() {
+            result = released;
+        }
+    }
+    result.map(|()| pressed)
+}
+
+/// 安装 panic 钩子：任意线程 panic 时先调用 [`release_all_keys`]，再执行之前的钩子
+///
+/// 多次调用只安装一次。
+pub fn release_all_keys_on_panic() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = release_all_keys();
+            previous(info);
+        }));
+    });
+}
+
+/// 当前逻辑上处于按下状态的虚拟键码
+fn pressed_vks() -> Vec<u16> {
+    (0x01u16..=0xFE)
+        .filter(|vk| !IGNORED_VKS.contains(vk))
+        .filter(|&vk| is_vk_down(vk))
+        .collect()
+}
+
+/// 最近一次输入活动的时间（系统启动后的毫秒数）
+fn last_input_tick() -> u32 {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        let _ = GetLastInputInfo(&mut info);
+    }
+    info.dwTime
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_display() {
+        let shift = StuckKey {
+            vk: 0xA0,
+            key: None,
+        };
+        let report = SanityReport {
+            stuck: vec![shift],
+            released: 1,
+            inconclusive: Vec::new(),
+        };
+        assert!(!report.is_clean());
+        assert_eq!(report.to_string(), "stuck keys: vk 0xa0 (1 released)");
+        assert_eq!(SanityReport::default().to_string(), "no stuck keys");
+    }
+}
@@ -0,0 +1,282 @@
+// src/core/key_stats.rs
+//! 按键统计
+//!
+//! 调用 [`start_key_stats`] 后，全局发送和窗口发送的每次按键按下都会记录：按下次数、
+//! 注入耗时（发送函数开始到系统接受事件的时间）以及距上一次按键的间隔。[`stats`] 返回
+//! 当前统计快照，可导出为 JSON 做热力图，分析自动化脚本最常发送哪些按键、延迟集中在哪里。
+//!
+//! 未开启统计时记录函数只检查一个原子标志，不产生额外开销。时间通过 [`clock`](super::clock) 获取。
+//! [`key_hold`](super::key_hold) 模拟的自动重复不是新的按键，不计入统计。
+use crate::error::Result;
+use crate::types::Key;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// 单个按键的统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyStat {
+    pub key: Key,
+    /// 按下次数
+    pub count: u64,
+    /// 注入耗时合计
+    pub total_latency: Duration,
+    /// 最长注入耗时
+    pub max_latency: Duration,
+    /// 距上一次按键的间隔合计（会话中的第一次按键不计）
+    pub total_gap: Duration,
+    /// 最长间隔
+    pub max_gap: Duration,
+}
+
+impl KeyStat {
+    fn new(key: Key) -> Self {
+        Self {
+            key,
+            count: 0,
+            total_latency: Duration::ZERO,
+            max_latency: Duration::ZERO,
+            total_gap: Duration::ZERO,
+            max_gap: Duration::ZERO,
+        }
+    }
+
+    /// 平均注入耗时
+    pub fn mean_latency(&self) -> Duration {
+        average(self.total_latency, self.count)
+    }
+
+    /// 平均间隔
+    pub fn mean_gap(&self) -> Duration {
+        average(self.total_gap, self.count)
+    }
+}
+
+fn average(total: Duration, count: u64) -> Duration {
+    if count == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_nanos((total.as_nanos() / u128::from(count)) as u64)
+}
+
+/// 统计快照，按键按按下次数从多到少排列
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeyStats {
+    /// 统计开始至今的时长
+    pub elapsed: Duration,
+    pub keys: Vec<KeyStat>,
+}
+
+impl KeyStats {
+    /// 指定按键的统计
+    pub fn get(&self, key: Key) -> Option<&KeyStat> {
+        self.keys.iter().find(|stat| stat.key == key)
+    }
+
+    /// 全部按键的按下次数
+    pub fn total_presses(&self) -> u64 {
+        self.keys.iter().map(|stat| stat.count).sum()
+    }
+
+    /// 导出为 JSON（时间单位为微秒）
+    ///
+    /// ```json
+    /// {"elapsed_us":1200000,"total":3,"keys":[{"key":"A","count":2,"mean_latency_us":40,...}]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|stat| {
+                format!(
+                    "{{\"key\":\"{:?}\",\"count\":{},\"total_latency_us\":{},\"mean_latency_us\":{},\"max_latency_us\":{},\"total_gap_us\":{},\"mean_gap_us\":{},\"max_gap_us\":{}}}",
+                    stat.key,
+                    stat.count,
+                    stat.total_latency.as_micros(),
+                    stat.mean_latency().as_micros(),
+                    stat.max_latency.as_micros(),
+                    stat.total_gap.as_micros(),
+                    stat.mean_gap().as_micros(),
+                    stat.max_gap.as_micros()
+                )
+            })
+            .collect();
+        format!(
+            "{{\"elapsed_us\":{},\"total\":{},\"keys\":[{}]}}",
+            self.elapsed.as_micros(),
+            self.total_presses(),
+            keys.join(",")
+        )
+    }
+}
+
+struct Session {
+    started: Instant,
+    last_press: Option<Instant>,
+    keys: HashMap<Key, KeyStat>,
+}
+
+impl Session {
+    fn record(&mut self, key: Key, start: Instant, latency: Duration) {
+        let gap = self
+            .last_press
+            .map(|last| start.saturating_duration_since(last));
+        self.last_press = Some(start);
+        let stat = self.keys.entry(key).or_insert_with(|| KeyStat::new(key));
+        stat.count += 1;
+        stat.total_latency += latency;
+        stat.max_latency = stat.max_latency.max(latency);
+        if let Some(gap) = gap {
+            stat.total_gap += gap;
+            stat.max_gap = stat.max_gap.max(gap);
+        }
+    }
+
+    fn snapshot(&self, now: Instant) -> KeyStats {
+        let mut keys: Vec<KeyStat> = self.keys.values().copied().collect();
+        keys.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| format!("{:?}", a.key).cmp(&format!("{:?}", b.key)))
+        });
+        KeyStats {
+            elapsed: now.saturating_duration_since(self.started),
+            keys,
+        }
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+thread_local! {
+    /// 当前线程的按键是否不计入统计
+    static UNRECORDED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn session() -> MutexGuard<'static, Option<Session>> {
+    SESSION.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 开始（或重新开始）统计，清空之前的数据
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{key_click, start_key_stats, stats, stop_key_stats, Key};
+///
+/// start_key_stats();
+/// key_click(Key::A, None).unwrap();
+/// key_click(Key::Enter, None).unwrap();
+/// println!("{}", stats().to_json());
+/// stop_key_stats();
+/// ```
+pub fn start_key_stats() {
+    *session() = Some(Session {
+        started: super::clock().now(),
+        last_press: None,
+        keys: HashMap::new(),
+    });
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// 停止统计并返回最终结果
+pub fn stop_key_stats() -> KeyStats {
+    ENABLED.store(false, Ordering::Release);
+    let now = super::clock().now();
+    session()
+        .take()
+        .map(|session| session.snapshot(now))
+        .unwrap_or_default()
+}
+
+/// 当前统计快照，未开始统计时为空
+pub fn stats() -> KeyStats {
+    let now = super::clock().now();
+    session()
+        .as_ref()
+        .map(|session| session.snapshot(now))
+        .unwrap_or_default()
+}
+
+/// 执行一次按键按下，开启统计时记录注入耗时
+pub(crate) fn record_press(key: Key, press: impl FnOnce() -> Result<()>) -> Result<()> {
+    if !ENABLED.load(Ordering::Acquire) || UNRECORDED.with(Cell::get) {
+        return press();
+    }
+    let clock = super::clock();
+    let start = clock.now();
+    press()?;
+    let latency = clock.now().saturating_duration_since(start);
+    if let Some(session) = session().as_mut() {
+        session.record(key, start, latency);
+    }
+    Ok(())
+}
+
+/// 在当前线程内执行操作，其中的按键按下不计入统计（如自动重复）
+pub(crate) fn unrecorded<T>(f: impl FnOnce() -> T) -> T {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            UNRECORDED.with(|u| u.set(self.0));
+        }
+    }
+
+    let _restore = Restore(UNRECORDED.with(|u| u.replace(true)));
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_record() {
+        let start = Instant::now();
+        let mut session = Session {
+            started: start,
+            last_press: None,
+            keys: HashMap::new(),
+        };
+        session.record(Key::A, start, Duration::from_micros(30));
+        session.record(
+            Key::B,
+            start + Duration::from_millis(10),
+            Duration::from_micros(50),
+        );
+        session.record(
+            Key::A,
+            start + Duration::from_millis(40),
+            Duration::from_micros(10),
+        );
+
+        let stats = session.snapshot(start + Duration::from_millis(100));
+        assert_eq!(stats.total_presses(), 3);
+        assert_eq!(stats.keys[0].key, Key::A);
+        let a = stats.get(Key::A).unwrap();
+        assert_eq!(a.count, 2);
+        assert_eq!(a.mean_latency(), Duration::from_micros(20));
+        assert_eq!(a.max_latency, Duration::from_micros(30));
+        assert_eq!(a.total_gap, Duration::from_millis(30));
+        assert_eq!(
+            stats.get(Key::B).unwrap().max_gap,
+            Duration::from_millis(10)
+        );
+        assert!(stats.to_json().starts_with(
+            "{\"elapsed_us\":100000,\"total\":3,\"keys\":[{\"key\":\"A\",\"count\":2,"
+        ));
+    }
+
+    #[test]
+    fn test_unrecorded_presses() {
+        start_key_stats();
+        record_press(Key::F13, || Ok(())).unwrap();
+        // 自动重复的按下不计入
+        unrecorded(|| record_press(Key::F13, || Ok(()))).unwrap();
+        unrecorded(|| record_press(Key::F13, || Ok(()))).unwrap();
+        let stats = stop_key_stats();
+        assert_eq!(stats.get(Key::F13).unwrap().count, 1);
+    }
+}
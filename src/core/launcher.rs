@@ -0,0 +1,177 @@
+// src/core/launcher.rs
+//! 应用启动与退出辅助功能：启动进程并等待其主窗口就绪，关闭窗口并确认退出
+use crate::error::{KeyboardSenderError, Result};
+use crate::retry::RetryPolicy;
+use crate::types::WindowHandle;
+use std::process::Command;
+use std::time::Duration;
+
+use super::selector::{find_window, window_exists, WindowSelector};
+use windows::Win32::{
+    Foundation::{CloseHandle, HWND, LPARAM, WPARAM},
+    System::Threading::{OpenProcess, WaitForInputIdle, PROCESS_QUERY_LIMITED_INFORMATION},
+    UI::WindowsAndMessaging::{GetWindow, PostMessageW, GW_ENABLEDPOPUP, WM_CLOSE},
+};
+
+/// 启动应用时等待主窗口出现的默认超时时间
+pub const DEFAULT_LAUNCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 轮询主窗口的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 启动进程并返回其主窗口句柄，可直接用于窗口目标发送
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{launch_and_target, type_string_to_window};
+///
+/// let hwnd = launch_and_target("notepad.exe", &[]).unwrap();
+/// type_string_to_window(hwnd, "Hello").unwrap();
+/// # }
+/// ```
+pub fn launch_and_target(program: &str, args: &[&str]) -> Result<WindowHandle> {
+    launch_and_target_with_timeout(program, args, DEFAULT_LAUNCH_TIMEOUT)
+}
+
+/// 启动进程并在指定超时时间内等待其主窗口
+pub fn launch_and_target_with_timeout(
+    program: &str,
+    args: &[&str],
+    timeout: Duration,
+) -> Result<WindowHandle> {
+    let child = Command::new(program)
+        .args(args)
+        .spawn()
+        .map_err(|e| KeyboardSenderError::LaunchError(format!("{}: {}", program, e)))?;
+
+    wait_for_main_window(child.id(), timeout)
+}
+
+/// 等待指定进程进入空闲状态并出现主窗口
+pub fn wait_for_main_window(pid: u32, timeout: Duration) -> Result<WindowHandle> {
+    // 先等待进程完成初始化（控制台程序等无消息队列的进程会立即返回）
+    wait_for_input_idle(pid, timeout);
+
+    RetryPolicy::polling(timeout, POLL_INTERVAL)
+        .poll(|| find_main_window_by_pid(pid))
+        .ok_or_else(|| {
+            KeyboardSenderError::Timeout(format!(
+                "main window of process {} did not appear within {:?}",
+                pid, timeout
+            ))
+        })
+}
+
+/// 查找进程的主窗口（可见且无所有者的顶层窗口）
+pub fn find_main_window_by_pid(pid: u32) -> Option<WindowHandle> {
+    find_window(&WindowSelector::ProcessId(pid))
+}
+
+/// 等待进程处理完初始输入（WaitForInputIdle），失败时静默返回
+fn wait_for_input_idle(pid: u32, timeout: Duration) {
+    unsafe {
+        if let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            WaitForInputIdle(process, timeout.as_millis().min(u32::MAX as u128) as u32);
+            let _ = CloseHandle(process);
+        }
+    }
+}
+
+/// 关闭窗口的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuitMethod {
+    /// 投递 WM_CLOSE 消息（无需焦点）
+    #[default]
+    CloseMessage,
+    /// 激活窗口后发送 Alt+F4
+    AltF4,
+}
+
+/// 退出应用的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuitOutcome {
+    /// 窗口已关闭
+    Closed,
+    /// 弹出了确认对话框（如"是否保存"），附带对话框句柄
+    ConfirmationDialog(WindowHandle),
+    /// 超时后窗口仍然存在
+    StillOpen,
+}
+
+/// 向窗口投递 WM_CLOSE 消息
+pub fn close_window(hwnd: WindowHandle) -> Result<()> {
+    if !window_exists(hwnd) {
+        return Err(KeyboardSenderError::InvalidWindowHandle(hwnd.to_string()));
+    }
+    unsafe {
+        PostMessageW(HWND(hwnd), WM_CLOSE, WPARAM(0), LPARAM(0))
+            .map_err(|_| KeyboardSenderError::WindowsError)
+    }
+}
+
+/// 通过 WM_CLOSE 优雅退出应用，等待窗口消失并报告是否出现确认对话框
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{quit_app_gracefully, QuitOutcome, WindowSelector};
+/// use std::time::Duration;
+///
+/// let selector = WindowSelector::ProcessName("notepad.exe".to_string());
+/// match quit_app_gracefully(selector, Duration::from_secs(3)).unwrap() {
+///     QuitOutcome::ConfirmationDialog(dialog) => println!("dialog: {}", dialog),
+///     outcome => println!("{:?}", outcome),
+/// }
+/// # }
+/// ```
+pub fn quit_app_gracefully(
+    selector: impl Into<WindowSelector>,
+    timeout: Duration,
+) -> Result<QuitOutcome> {
+    quit_app_with(selector, QuitMethod::default(), timeout)
+}
+
+/// 以指定方式退出应用
+pub fn quit_app_with(
+    selector: impl Into<WindowSelector>,
+    method: QuitMethod,
+    timeout: Duration,
+) -> Result<QuitOutcome> {
+    let selector = selector.into();
+    let hwnd = find_window(&selector).ok_or_else(|| {
+        KeyboardSenderError::InvalidWindowHandle(format!("no window matches {:?}", selector))
+    })?;
+
+    match method {
+        QuitMethod::CloseMessage => close_window(hwnd)?,
+        QuitMethod::AltF4 => {
+            super::set_window_focus(hwnd, true)?;
+            super::press_combination(&[crate::types::Modifier::Alt], crate::types::Key::F4, None)?;
+        }
+    }
+
+    let clock = super::clock();
+    // 超时过大（如 `Duration::MAX`）时不设截止时刻
+    let deadline = clock.now().checked_add(timeout);
+    loop {
+        if !window_exists(hwnd) {
+            return Ok(QuitOutcome::Closed);
+        }
+        if let Some(dialog) = enabled_popup(hwnd) {
+            return Ok(QuitOutcome::ConfirmationDialog(dialog));
+        }
+        if deadline.is_some_and(|deadline| clock.now() >= deadline) {
+            return Ok(QuitOutcome::StillOpen);
+        }
+        clock.sleep(POLL_INTERVAL);
+    }
+}
+
+/// 获取窗口当前弹出的模态对话框
+fn enabled_popup(hwnd: WindowHandle) -> Option<WindowHandle> {
+    let popup = unsafe { GetWindow(HWND(hwnd), GW_ENABLEDPOPUP) }.0;
+    (popup != 0 && popup != hwnd).then_some(popup)
+}
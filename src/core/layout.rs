@@ -0,0 +1,356 @@
+// src/core/layout.rs
+//! 键盘布局查表
+//!
+//! 按键合成（[`TextInjection::KeyStrokes`](super::TextInjection::KeyStrokes)）需要知道
+//! 在某个布局下输入一个字符要按哪些键。`VkKeyScanExW` 只能找到一次按键就能产生的字符，
+//! 因此这里用 `ToUnicodeEx` 遍历布局中各按键在无修饰、Shift、AltGr、Shift+AltGr 下的输出，
+//! 同时找出死键（如德语布局的 `´`），再枚举「死键 + 按键」的组合字符（`´` + `e` = `é`）。
+//!
+//! 每个布局（HKL）只遍历一次，结果缓存在进程内。
+//!
+//! 各线程可以使用不同的键盘布局，查表和键码转换都应使用接收输入的窗口所在线程的布局
+//! （[`window_layout`]），而不是调用线程自己的布局。
+//!
+//! 自行构造按键序列时可用 [`char_to_key_combo`] 查询字符在某个布局（[`KeyboardLayout`]）下
+//! 对应的按键和修饰键。
+use crate::types::{Key, KeyCodeMapper, Modifier, WindowHandle};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetKeyboardLayout, MapVirtualKeyExW, ToUnicodeEx, MAPVK_VK_TO_VSC,
+};
+use windows::Win32::UI::TextServices::HKL;
+
+/// 一次按键，编码与 `VkKeyScanExW` 的返回值相同：低字节为虚拟键码，高字节为修饰键状态
+/// （1 = Shift，2 = Ctrl，4 = Alt）
+pub(crate) type Stroke = i16;
+
+/// 键盘布局（Windows `HKL`）
+///
+/// 各线程可以使用不同的布局，查询时应使用接收输入的窗口所在线程的布局。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyboardLayout(isize);
+
+impl KeyboardLayout {
+    /// 调用线程的布局
+    pub fn current() -> Self {
+        Self(unsafe { GetKeyboardLayout(0) }.0)
+    }
+
+    /// 前台窗口所在线程的布局，没有前台窗口时为调用线程的布局
+    pub fn foreground() -> Self {
+        Self(foreground_layout().0)
+    }
+
+    /// 窗口所在线程的布局，窗口无效时为调用线程的布局
+    pub fn of_window(hwnd: WindowHandle) -> Self {
+        Self(window_layout(hwnd).0)
+    }
+
+    /// 由原始 `HKL` 值创建
+    pub fn from_raw(hkl: isize) -> Self {
+        Self(hkl)
+    }
+
+    /// 原始 `HKL` 值
+    pub fn raw(self) -> isize {
+        self.0
+    }
+}
+
+/// 在布局 `layout` 下按一次按键输入字符 `c` 所需的修饰键和按键，无法输入时返回 `None`
+///
+/// 需要 AltGr 的字符返回左 Ctrl + 右 Alt，与物理键盘上 AltGr 产生的事件一致。需要死键
+/// （如 `´` + `e` = `é`）或 OEM 符号键（[`Key`] 未覆盖）的字符返回 `None`，这类字符可用
+/// [`send_char`](super::send_char) 按 Unicode 或按键模拟方式输入。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{char_to_key_combo, Key, KeyboardLayout, Modifier};
+///
+/// let combo = char_to_key_combo('A', KeyboardLayout::foreground());
+/// assert_eq!(combo, Some((vec![Modifier::Shift], Key::A)));
+/// ```
+pub fn char_to_key_combo(c: char, layout: KeyboardLayout) -> Option<(Vec<Modifier>, Key)> {
+    match char_strokes(HKL(layout.0), c)?.as_slice() {
+        [stroke] => {
+            let (modifiers, vk) = stroke_combo(*stroke)?;
+            let key = Key::from_code(usize::from(vk), keyboard_codes::current_platform())?;
+            Some((modifiers, key))
+        }
+        _ => None,
+    }
+}
+
+/// 将按键（`VkKeyScanExW` 格式）拆分为修饰键和虚拟键码，无法通过按键模拟时返回 `None`
+///
+/// 同时需要 Ctrl 和 Alt 的按键按 AltGr 拆分（左 Ctrl + 右 Alt）。
+pub(crate) fn stroke_combo(stroke: Stroke) -> Option<(Vec<Modifier>, u16)> {
+    let vk = (stroke as u16) & 0xFF;
+    let shift_state = ((stroke as u16) >> 8) & 0xFF;
+    // 低字节和高字节都为 0xFF 表示没有对应按键；Hankaku 等附加位无法通过按键模拟
+    if stroke == -1 || shift_state & !0x07 != 0 {
+        return None;
+    }
+
+    let mut modifiers = Vec::with_capacity(3);
+    if shift_state & 1 != 0 {
+        modifiers.push(Modifier::Shift);
+    }
+    match shift_state & 0x06 {
+        0x06 => modifiers.extend([Modifier::LeftControl, Modifier::RightAlt]),
+        0x02 => modifiers.push(Modifier::Control),
+        0x04 => modifiers.push(Modifier::Alt),
+        _ => {}
+    }
+    Some((modifiers, vk))
+}
+
+/// 参与遍历的修饰键状态：无、Shift、AltGr（Ctrl+Alt）、Shift+AltGr
+const SHIFT_STATES: [u8; 4] = [0, 1, 6, 7];
+
+/// 参与遍历的按键：空格、主键盘数字和字母、OEM 符号键（不含小键盘，避免依赖 NumLock）
+const PROBE_VKS: &[u16] = &[
+    0x20, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x41, 0x42, 0x43, 0x44, 0x45,
+    0x46, 0x47, 0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F, 0x50, 0x51, 0x52, 0x53, 0x54, 0x55,
+    0x56, 0x57, 0x58, 0x59, 0x5A, 0xBA, 0xBB, 0xBC, 0xBD, 0xBE, 0xBF, 0xC0, 0xDB, 0xDC, 0xDD, 0xDE,
+    0xDF, 0xE2,
+];
+
+/// 按键序列的输出
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Output {
+    /// 没有输出
+    None,
+    /// 输出文本
+    Text(String),
+    /// 死键，等待下一次按键
+    Dead,
+}
+
+/// 一个布局下字符到按键序列的映射
+#[derive(Debug, Default)]
+struct LayoutTable {
+    strokes: HashMap<char, Vec<Stroke>>,
+}
+
+impl LayoutTable {
+    /// 用 `probe` 遍历按键构建映射，一次按键能输入的字符优先于死键组合
+    fn build(mut probe: impl FnMut(&[Stroke]) -> Output) -> Self {
+        let mut strokes: HashMap<char, Vec<Stroke>> = HashMap::new();
+        let mut dead_keys = Vec::new();
+        let all = || {
+            SHIFT_STATES.iter().flat_map(|&shift| {
+                PROBE_VKS
+                    .iter()
+                    .map(move |&vk| ((u16::from(shift) << 8) | vk) as Stroke)
+            })
+        };
+
+        for stroke in all() {
+            match probe(&[stroke]) {
+                Output::Text(text) => {
+                    if let Some(c) = single_char(&text) {
+                        strokes.entry(c).or_insert_with(|| vec![stroke]);
+                    }
+                }
+                Output::Dead => dead_keys.push(stroke),
+                Output::None => {}
+            }
+        }
+        for &dead in &dead_keys {
+            for stroke in all() {
+                if let Output::Text(text) = probe(&[dead, stroke]) {
+                    if let Some(c) = single_char(&text) {
+                        strokes.entry(c).or_insert_with(|| vec![dead, stroke]);
+                    }
+                }
+            }
+        }
+        Self { strokes }
+    }
+}
+
+/// 只包含一个可打印字符的文本
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_control() => Some(c),
+        _ => None,
+    }
+}
+
+static TABLES: Mutex<Option<HashMap<isize, Arc<LayoutTable>>>> = Mutex::new(None);
+
+/// 前台窗口所在线程的键盘布局，没有前台窗口时为调用线程的布局
+pub(crate) fn foreground_layout() -> HKL {
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    window_layout(unsafe { GetForegroundWindow() }.0)
+}
+
+/// 窗口所在线程的键盘布局，窗口无效时为调用线程的布局
+pub(crate) fn window_layout(hwnd: WindowHandle) -> HKL {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    unsafe {
+        let thread = GetWindowThreadProcessId(HWND(hwnd), None);
+        GetKeyboardLayout(thread)
+    }
+}
+
+/// 布局语言的 ANSI 代码页，无法获取时为 0（`CP_ACP`，系统默认代码页）
+#[cfg(feature = "window_target")]
+pub(crate) fn ansi_code_page(layout: HKL) -> u32 {
+    use windows::Win32::Globalization::{
+        GetLocaleInfoW, LOCALE_IDEFAULTANSICODEPAGE, LOCALE_RETURN_NUMBER,
+    };
+
+    // HKL 的低 16 位是语言标识，按默认排序即为区域标识
+    let locale = (layout.0 as u32) & 0xFFFF;
+    let mut buffer = [0u16; 2];
+    let len = unsafe {
+        GetLocaleInfoW(
+            locale,
+            LOCALE_IDEFAULTANSICODEPAGE | LOCALE_RETURN_NUMBER,
+            Some(&mut buffer),
+        )
+    };
+    if len == 0 {
+        return 0;
+    }
+    u32::from(buffer[0]) | (u32::from(buffer[1]) << 16)
+}
+
+/// 在布局 `layout` 下输入字符 `c` 需要依次按下的按键，无法输入时返回 `None`
+pub(crate) fn char_strokes(layout: HKL, c: char) -> Option<Vec<Stroke>> {
+    if let Some(stroke) = control_stroke(c) {
+        return Some(vec![stroke]);
+    }
+    let table = {
+        let mut tables = TABLES.lock().unwrap_or_else(|e| e.into_inner());
+        tables
+            .get_or_insert_with(HashMap::new)
+            .entry(layout.0)
+            .or_insert_with(|| Arc::new(LayoutTable::build(|strokes| probe(layout, strokes))))
+            .clone()
+    };
+    table.strokes.get(&c).cloned()
+}
+
+/// 控制字符对应的按键：换行为回车键、制表符为 Tab 键，与布局无关，不在探测表中
+fn control_stroke(c: char) -> Option<Stroke> {
+    match c {
+        '\r' | '\n' => Some(0x0D),
+        '\t' => Some(0x09),
+        _ => None,
+    }
+}
+
+/// 通过 `ToUnicodeEx` 计算按键序列在布局下的输出
+///
+/// 死键状态保存在调用线程的键盘状态中，结束时用空格清除，不影响其他线程。
+fn probe(layout: HKL, strokes: &[Stroke]) -> Output {
+    let mut output = Output::None;
+    for &stroke in strokes {
+        output = to_unicode(layout, stroke);
+    }
+    if output == Output::Dead {
+        // 死键后按空格输出死键字符本身并清除状态；部分布局的死键可以叠加，多试几次
+        for _ in 0..4 {
+            if to_unicode(layout, 0x20) != Output::Dead {
+                break;
+            }
+        }
+    }
+    output
+}
+
+fn to_unicode(layout: HKL, stroke: Stroke) -> Output {
+    const VK_SHIFT: usize = 0x10;
+    const VK_CONTROL: usize = 0x11;
+    const VK_MENU: usize = 0x12;
+    const VK_LCONTROL: usize = 0xA2;
+    const VK_RMENU: usize = 0xA5;
+
+    let vk = u32::from((stroke as u16) & 0xFF);
+    let shift = ((stroke as u16) >> 8) & 0xFF;
+    let mut state = [0u8; 256];
+    if shift & 1 != 0 {
+        state[VK_SHIFT] = 0x80;
+    }
+    if shift & 2 != 0 {
+        state[VK_CONTROL] = 0x80;
+        state[VK_LCONTROL] = 0x80;
+    }
+    if shift & 4 != 0 {
+        state[VK_MENU] = 0x80;
+        state[VK_RMENU] = 0x80;
+    }
+
+    let mut buffer = [0u16; 8];
+    let len = unsafe {
+        let scan = MapVirtualKeyExW(vk, MAPVK_VK_TO_VSC, layout);
+        ToUnicodeEx(vk, scan, &state, &mut buffer, 0, layout)
+    };
+    match len {
+        len if len < 0 => Output::Dead,
+        0 => Output::None,
+        len => Output::Text(String::from_utf16_lossy(
+            &buffer[..(len as usize).min(buffer.len())],
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_with_dead_keys() {
+        const E: Stroke = 0x45;
+        const Q_ALTGR: Stroke = 0x0651;
+        const ACUTE: Stroke = 0xDD;
+
+        // 简化的德语布局：e、AltGr+Q = @、´ 为死键，´ + e = é，´ + 其他键输出两个字符
+        let table = LayoutTable::build(|strokes| match strokes {
+            [E] => Output::Text("e".to_string()),
+            [Q_ALTGR] => Output::Text("@".to_string()),
+            [ACUTE] => Output::Dead,
+            [ACUTE, E] => Output::Text("é".to_string()),
+            [ACUTE, 0x20] => Output::Text("´".to_string()),
+            [ACUTE, _] => Output::Text("´x".to_string()),
+            _ => Output::None,
+        });
+
+        assert_eq!(table.strokes[&'e'], vec![E]);
+        assert_eq!(table.strokes[&'@'], vec![Q_ALTGR]);
+        assert_eq!(table.strokes[&'é'], vec![ACUTE, E]);
+        assert_eq!(table.strokes[&'´'], vec![ACUTE, 0x20]);
+        assert_eq!(table.strokes.len(), 4);
+    }
+
+    #[test]
+    fn test_control_stroke() {
+        assert_eq!(control_stroke('\n'), Some(0x0D));
+        assert_eq!(control_stroke('\r'), Some(0x0D));
+        assert_eq!(control_stroke('\t'), Some(0x09));
+        assert_eq!(control_stroke('a'), None);
+        assert_eq!(stroke_combo(0x0D), Some((vec![], 0x0D)));
+    }
+
+    #[test]
+    fn test_stroke_combo() {
+        assert_eq!(stroke_combo(0x0041), Some((vec![], 0x41)));
+        assert_eq!(stroke_combo(0x0141), Some((vec![Modifier::Shift], 0x41)));
+        assert_eq!(
+            stroke_combo(0x0651),
+            Some((vec![Modifier::LeftControl, Modifier::RightAlt], 0x51))
+        );
+        assert_eq!(stroke_combo(0x0231), Some((vec![Modifier::Control], 0x31)));
+        assert_eq!(stroke_combo(-1), None);
+        assert_eq!(stroke_combo(0x0841), None);
+    }
+}
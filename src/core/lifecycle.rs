@@ -0,0 +1,350 @@
+// src/core/lifecycle.rs
+//! 库级初始化与清理
+//!
+//! [`init`] 集中设置进程级资源：系统计时器精度、panic 时释放按键、启动时的键盘状态检查
+//! 以及中止热键；[`shutdown`] 按相反顺序撤销，并释放仍按下的按键。以相同选项重复调用
+//! `init` 只增加引用计数，最后一个 [`Initialized`] 被丢弃（或显式调用 `shutdown`）时才清理。
+//!
+//! 按键钩子线程（回显过滤、物理按键跟踪、前台窗口监视）和消息监视不由这里管理：
+//! 它们在使用时创建，由各自返回的守卫在丢弃时停止，`shutdown` 不会停止它们。
+//! 修饰键保持的看门狗随 `shutdown` 释放保持的修饰键一同失效。
+//!
+//! 中止热键按下后，之后的发送都返回 [`KeyboardSenderError::Aborted`]（释放事件除外，
+//! 避免按键卡住），直到调用 [`clear_abort`]。
+use crate::error::{KeyboardSenderError, Result};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use windows::Win32::Media::{timeBeginPeriod, timeEndPeriod};
+
+/// 中止标志
+static ABORT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+static RUNTIME: Mutex<Option<Runtime>> = Mutex::new(None);
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// 初始化选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitOptions {
+    /// 系统计时器精度（`timeBeginPeriod`），`None` 表示不修改；默认 1ms
+    pub timer_resolution: Option<Duration>,
+    /// 是否安装 panic 钩子，panic 时释放全部按键（默认启用，安装后不会移除）
+    pub release_keys_on_panic: bool,
+    /// 清理时是否释放保持的修饰键和仍按下的按键（默认启用）
+    pub release_keys_on_shutdown: bool,
+    /// 是否在初始化时检查并修复卡住的按键（默认不检查）
+    pub sanity_check: bool,
+    /// 中止热键，如 `ctrl+alt+pause`（默认不注册）
+    #[cfg(feature = "window_target")]
+    pub abort_hotkey: Option<String>,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            timer_resolution: Some(Duration::from_millis(1)),
+            release_keys_on_panic: true,
+            release_keys_on_shutdown: true,
+            sanity_check: false,
+            #[cfg(feature = "window_target")]
+            abort_hotkey: None,
+        }
+    }
+}
+
+impl InitOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置系统计时器精度，`None` 表示不修改
+    pub fn timer_resolution(mut self, resolution: Option<Duration>) -> Self {
+        self.timer_resolution = resolution;
+        self
+    }
+
+    /// 是否在 panic 时释放按键
+    pub fn release_keys_on_panic(mut self, enabled: bool) -> Self {
+        self.release_keys_on_panic = enabled;
+        self
+    }
+
+    /// 是否在清理时释放按键
+    pub fn release_keys_on_shutdown(mut self, enabled: bool) -> Self {
+        self.release_keys_on_shutdown = enabled;
+        self
+    }
+
+    /// 是否在初始化时检查卡住的按键
+    pub fn sanity_check(mut self, enabled: bool) -> Self {
+        self.sanity_check = enabled;
+        self
+    }
+
+    /// 注册中止热键
+    #[cfg(feature = "window_target")]
+    pub fn abort_hotkey(mut self, shortcut: &str) -> Self {
+        self.abort_hotkey = Some(shortcut.to_string());
+        self
+    }
+}
+
+/// 已初始化的运行时
+struct Runtime {
+    generation: u64,
+    refs: usize,
+    options: InitOptions,
+    timer_period: Option<u32>,
+    release_keys_on_shutdown: bool,
+    #[cfg(feature = "window_target")]
+    abort_hotkey: Option<abort_hotkey::AbortHotkey>,
+}
+
+fn runtime() -> MutexGuard<'static, Option<Runtime>> {
+    RUNTIME.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 初始化守卫，最后一个守卫被丢弃时执行 [`shutdown`]
+#[must_use = "dropping the guard shuts the library down"]
+#[derive(Debug)]
+pub struct Initialized {
+    generation: u64,
+}
+
+impl Drop for Initialized {
+    fn drop(&mut self) {
+        let mut guard = runtime();
+        let last = match guard.as_mut() {
+            // 运行时已被 shutdown 或重新初始化，守卫已失效
+            Some(rt) if rt.generation == self.generation => {
+                rt.refs -= 1;
+                rt.refs == 0
+            }
+            _ => false,
+        };
+        if last {
+            let rt = guard.take();
+            drop(guard);
+            if let Some(rt) = rt {
+                teardown(rt);
+            }
+        }
+    }
+}
+
+/// 初始化库
+///
+/// 已初始化时不会重复设置：`options` 与生效中的选项相同时返回新的守卫，
+/// 不同时返回 `AlreadyInitialized` 错误（需要先 [`shutdown`] 再以新选项初始化）。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{init, type_string, InitOptions};
+///
+/// let _lib = init(InitOptions::new().sanity_check(true)).unwrap();
+/// type_string("hello").unwrap();
+/// // _lib 被丢弃时恢复计时器精度并释放仍按下的按键
+/// ```
+pub fn init(options: InitOptions) -> Result<Initialized> {
+    let mut guard = runtime();
+    if let Some(rt) = guard.as_mut() {
+        check_same_options(&rt.options, &options)?;
+        rt.refs += 1;
+        return Ok(Initialized {
+            generation: rt.generation,
+        });
+    }
+
+    #[cfg(feature = "window_target")]
+    let abort_hotkey = options
+        .abort_hotkey
+        .as_deref()
+        .map(abort_hotkey::AbortHotkey::register)
+        .transpose()?;
+    if options.sanity_check {
+        super::key_state::sanity_check_keyboard_state()?;
+    }
+    if options.release_keys_on_panic {
+        super::key_state::release_all_keys_on_panic();
+    }
+    let timer_period = options.timer_resolution.and_then(|resolution| {
+        let period = resolution.as_millis().clamp(1, u128::from(u32::MAX)) as u32;
+        // TIMERR_NOERROR
+        (unsafe { timeBeginPeriod(period) } == 0).then_some(period)
+    });
+
+    let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+    *guard = Some(Runtime {
+        generation,
+        refs: 1,
+        options: options.clone(),
+        timer_period,
+        release_keys_on_shutdown: options.release_keys_on_shutdown,
+        #[cfg(feature = "window_target")]
+        abort_hotkey,
+    });
+    Ok(Initialized { generation })
+}
+
+/// 已初始化时，新的选项必须与生效中的选项一致
+fn check_same_options(current: &InitOptions, requested: &InitOptions) -> Result<()> {
+    if current == requested {
+        return Ok(());
+    }
+    Err(KeyboardSenderError::AlreadyInitialized(format!(
+        "initialized with {:?}, requested {:?}",
+        current, requested
+    )))
+}
+
+/// 立即清理（不论还有多少守卫），未初始化时不做任何操作
+pub fn shutdown() {
+    let rt = runtime().take();
+    if let Some(rt) = rt {
+        teardown(rt);
+    }
+}
+
+/// 是否已初始化
+pub fn is_initialized() -> bool {
+    runtime().is_some()
+}
+
+fn teardown(rt: Runtime) {
+    #[cfg(feature = "window_target")]
+    drop(rt.abort_hotkey);
+    if let Some(period) = rt.timer_period {
+        unsafe {
+            timeEndPeriod(period);
+        }
+    }
+    if rt.release_keys_on_shutdown {
+        let _ = super::hold::release_held_modifiers();
+        let _ = super::key_state::release_all_keys();
+    }
+    clear_abort();
+}
+
+/// 请求中止：之后的发送返回 [`KeyboardSenderError::Aborted`]
+pub fn request_abort() {
+    ABORT_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// 是否已请求中止
+pub fn abort_requested() -> bool {
+    ABORT_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// 清除中止标志，恢复发送
+pub fn clear_abort() {
+    ABORT_REQUESTED.store(false, Ordering::SeqCst);
+}
+
+/// 已请求中止时返回错误；`release_only` 为真（只含释放事件）时始终放行
+pub(crate) fn check_abort(release_only: bool) -> Result<()> {
+    abort_check(abort_requested(), release_only)
+}
+
+/// 按中止标志的值判断是否放行
+fn abort_check(requested: bool, release_only: bool) -> Result<()> {
+    if requested && !release_only {
+        return Err(KeyboardSenderError::Aborted);
+    }
+    Ok(())
+}
+
+/// 中止热键：在独立线程上通过 `RegisterHotKey` 注册，按下时请求中止并释放全部按键
+#[cfg(feature = "window_target")]
+mod abort_hotkey {
+    use crate::core::hook_thread::HookThread;
+    use crate::error::{KeyboardSenderError, Result};
+
+    use windows::Win32::{
+        UI::Input::KeyboardAndMouse::{
+            RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_NOREPEAT,
+        },
+        UI::WindowsAndMessaging::{MSG, WM_HOTKEY},
+    };
+
+    /// 中止热键使用的热键 ID
+    const ABORT_HOTKEY_ID: i32 = 0xBF5B;
+
+    pub(super) struct AbortHotkey {
+        _thread: HookThread,
+    }
+
+    impl AbortHotkey {
+        pub(super) fn register(shortcut: &str) -> Result<Self> {
+            let (mask, key) = crate::core::hotkeys::parse(shortcut)?;
+            let vk = crate::core::key_to_vk(key);
+            let thread = HookThread::spawn(
+                "keyboard-sender-abort",
+                move || register_hotkey(mask, vk),
+                on_message,
+            )?;
+            Ok(Self { _thread: thread })
+        }
+    }
+
+    /// 在热键线程上注册热键，返回注销热键的清理函数
+    fn register_hotkey(mask: u32, vk: u16) -> Result<impl FnOnce()> {
+        let modifiers = HOT_KEY_MODIFIERS(mask) | MOD_NOREPEAT;
+        if unsafe { RegisterHotKey(None, ABORT_HOTKEY_ID, modifiers, u32::from(vk)) }.is_err() {
+            return Err(KeyboardSenderError::ExecutionDenied(
+                "abort hotkey is already registered by another program".to_string(),
+            ));
+        }
+        Ok(|| unsafe {
+            let _ = UnregisterHotKey(None, ABORT_HOTKEY_ID);
+        })
+    }
+
+    /// 按下中止热键时请求中止并释放全部按键
+    fn on_message(msg: &MSG) -> bool {
+        if msg.message != WM_HOTKEY || msg.wParam.0 != ABORT_HOTKEY_ID as usize {
+            return false;
+        }
+        super::request_abort();
+        let _ = crate::core::key_state::release_all_keys();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_abort() {
+        // 不修改全局中止标志，避免并行测试中的发送被中止
+        assert!(matches!(
+            abort_check(true, false),
+            Err(KeyboardSenderError::Aborted)
+        ));
+        assert!(abort_check(true, true).is_ok());
+        assert!(abort_check(false, false).is_ok());
+    }
+
+    #[test]
+    fn test_options_builder() {
+        let options = InitOptions::new()
+            .timer_resolution(None)
+            .sanity_check(true)
+            .release_keys_on_shutdown(false);
+        assert_eq!(options.timer_resolution, None);
+        assert!(options.sanity_check);
+        assert!(!options.release_keys_on_shutdown);
+        assert!(options.release_keys_on_panic);
+    }
+
+    #[test]
+    fn test_check_same_options() {
+        let current = InitOptions::new();
+        assert!(check_same_options(&current, &InitOptions::new()).is_ok());
+        assert!(matches!(
+            check_same_options(&current, &InitOptions::new().sanity_check(true)),
+            Err(KeyboardSenderError::AlreadyInitialized(_))
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+// src/core/mappings.rs
+//! 键码映射导出与比较
+//!
+//! 导出 `keyboard-codes` 标准映射表中每个按键实际发送的虚拟键码和扫描码，
+//! 并支持与快照比较，用于在依赖升级时发现悄悄变化的键码。
+use crate::error::{KeyboardSenderError, Result};
+use keyboard_codes::mapping::standard::STANDARD_KEY_MAPPINGS;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{MapVirtualKeyW, MAPVK_VK_TO_VSC};
+
+use super::key_to_vk;
+use crate::types::Key;
+
+/// 需要 `KEYEVENTF_EXTENDEDKEY` 的虚拟键码（增强型键盘上带 `E0` 前缀的按键）
+///
+/// 方向键、Insert/Delete/Home/End/PageUp/PageDown 与小键盘上的同名键共用扫描码，
+/// 右侧 Ctrl/Alt 与左侧共用扫描码，只有扩展键标志能区分。
+/// 小键盘 Enter 与主键盘 Enter 共用虚拟键码，无法由 [`Key`] 表示，不在表中。
+pub const EXTENDED_VKS: &[u16] = &[
+    0x21, // PageUp
+    0x22, // PageDown
+    0x23, // End
+    0x24, // Home
+    0x25, // ArrowLeft
+    0x26, // ArrowUp
+    0x27, // ArrowRight
+    0x28, // ArrowDown
+    0x2C, // PrintScreen
+    0x2D, // Insert
+    0x2E, // Delete
+    0x5B, // LeftMeta
+    0x5C, // RightMeta
+    0x5D, // Apps
+    0x5F, // Sleep
+    0x6F, // NumDivide
+    0x90, // NumLock
+    0xA3, // RightControl
+    0xA5, // RightAlt
+    0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, // 浏览器键
+    0xAD, 0xAE, 0xAF, // 音量键
+    0xB0, 0xB1, 0xB2, 0xB3, // 媒体键
+    0xB4, 0xB5, 0xB6, 0xB7, // 启动应用键
+];
+
+/// 按键是否为扩展键（发送时需要设置扩展键标志）
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{is_extended_key, Key};
+///
+/// assert!(is_extended_key(Key::ArrowLeft));
+/// assert!(!is_extended_key(Key::A));
+/// ```
+pub fn is_extended_key(key: Key) -> bool {
+    is_extended_vk(key_to_vk(key))
+}
+
+/// 虚拟键码是否为扩展键
+pub(crate) fn is_extended_vk(vk: u16) -> bool {
+    EXTENDED_VKS.contains(&vk)
+}
+
+/// 虚拟键码在当前布局下的扫描码和扩展键标志，没有对应扫描码时扫描码为 0
+///
+/// 以下按键不能直接使用 `MapVirtualKeyW` 的结果：
+/// - PrintScreen：`MapVirtualKeyW` 返回 Alt+PrintScreen 的 SysRq 扫描码 `54`，
+///   单独按下时键盘发送的是 `E0 37`
+/// - Pause：键盘发送 `E1 1D 45` 序列，系统在按键消息中报告为不带扩展标志的 `45`
+///   （NumLock 同为 `45` 但带扩展标志）
+#[cfg_attr(not(feature = "global"), allow(dead_code))]
+pub(crate) fn vk_scan_code(vk: u16) -> (u16, bool) {
+    scan_code_with(vk, |vk| unsafe { MapVirtualKeyW(vk, MAPVK_VK_TO_VSC) })
+}
+
+/// 虚拟键码在指定布局下的扫描码和扩展键标志，特殊按键的处理同 [`vk_scan_code`]
+#[cfg(feature = "window_target")]
+pub(crate) fn vk_scan_code_in(
+    vk: u16,
+    layout: windows::Win32::UI::TextServices::HKL,
+) -> (u16, bool) {
+    use windows::Win32::UI::Input::KeyboardAndMouse::MapVirtualKeyExW;
+
+    scan_code_with(vk, |vk| unsafe {
+        MapVirtualKeyExW(vk, MAPVK_VK_TO_VSC, layout)
+    })
+}
+
+#[cfg_attr(not(feature = "global"), allow(dead_code))]
+fn scan_code_with(vk: u16, map: impl FnOnce(u32) -> u32) -> (u16, bool) {
+    match vk {
+        VK_SNAPSHOT => (0x37, true),
+        VK_PAUSE => (0x45, false),
+        _ => (map(u32::from(vk)) as u16, is_extended_vk(vk)),
+    }
+}
+
+const VK_PAUSE: u16 = 0x13;
+const VK_SNAPSHOT: u16 = 0x2C;
+
+/// 单个按键的键码映射
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMapping {
+    /// 按键名（与 `keyboard-codes` 一致）
+    pub name: String,
+    /// 虚拟键码
+    pub vk: u16,
+    /// 当前键盘布局下的扫描码，没有对应扫描码或快照中未记录时为 `None`
+    pub scan: Option<u16>,
+}
+
+/// 两份映射之间的差异
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingChange {
+    /// 新增的按键
+    Added(KeyMapping),
+    /// 移除的按键
+    Removed(KeyMapping),
+    /// 键码发生变化的按键
+    Changed { old: KeyMapping, new: KeyMapping },
+}
+
+impl fmt::Display for MappingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MappingChange::Added(m) => write!(f, "+ {}", format_line(m)),
+            MappingChange::Removed(m) => write!(f, "- {}", format_line(m)),
+            MappingChange::Changed { old, new } => {
+                write!(f, "~ {} -> {}", format_line(old), format_line(new))
+            }
+        }
+    }
+}
+
+/// 导出所有标准按键的映射（按 `keyboard-codes` 映射表顺序）
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{dump_key_mappings, format_key_mappings};
+///
+/// print!("{}", format_key_mappings(&dump_key_mappings()));
+/// ```
+pub fn dump_key_mappings() -> Vec<KeyMapping> {
+    STANDARD_KEY_MAPPINGS
+        .iter()
+        .map(|&(name, key, _, _, _)| {
+            let vk = key_to_vk(key);
+            let (scan, _) = vk_scan_code(vk);
+            KeyMapping {
+                name: name.to_string(),
+                vk,
+                scan: (scan != 0).then_some(scan),
+            }
+        })
+        .collect()
+}
+
+/// 将映射格式化为文本（每行 `名称\t虚拟键码[\t扫描码]`）
+pub fn format_key_mappings(mappings: &[KeyMapping]) -> String {
+    mappings.iter().map(|m| format_line(m) + "\n").collect()
+}
+
+/// 解析 [`format_key_mappings`] 的输出，忽略空行和 `#` 注释
+pub fn parse_key_mappings(text: &str) -> Result<Vec<KeyMapping>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            let invalid = || {
+                KeyboardSenderError::ParseError(format!(
+                    "invalid key mapping on line {}: {}",
+                    index + 1,
+                    line
+                ))
+            };
+            let mut columns = line.split_whitespace();
+            let name = columns.next().ok_or_else(invalid)?;
+            let vk = columns.next().and_then(parse_hex).ok_or_else(invalid)?;
+            let scan = match columns.next() {
+                Some(scan) => Some(parse_hex(scan).ok_or_else(invalid)?),
+                None => None,
+            };
+            Ok(KeyMapping {
+                name: name.to_string(),
+                vk,
+                scan,
+            })
+        })
+        .collect()
+}
+
+/// 比较两份映射，扫描码仅在两边都有记录时参与比较
+pub fn diff_key_mappings(old: &[KeyMapping], new: &[KeyMapping]) -> Vec<MappingChange> {
+    let old_by_name: BTreeMap<&str, &KeyMapping> =
+        old.iter().map(|m| (m.name.as_str(), m)).collect();
+    let new_by_name: BTreeMap<&str, &KeyMapping> =
+        new.iter().map(|m| (m.name.as_str(), m)).collect();
+
+    let mut changes = Vec::new();
+    for (name, old_mapping) in &old_by_name {
+        match new_by_name.get(name) {
+            None => changes.push(MappingChange::Removed((*old_mapping).clone())),
+            Some(new_mapping) => {
+                let scan_changed = matches!(
+                    (old_mapping.scan, new_mapping.scan),
+                    (Some(a), Some(b)) if a != b
+                );
+                if old_mapping.vk != new_mapping.vk || scan_changed {
+                    changes.push(MappingChange::Changed {
+                        old: (*old_mapping).clone(),
+                        new: (*new_mapping).clone(),
+                    });
+                }
+            }
+        }
+    }
+    for (name, new_mapping) in &new_by_name {
+        if !old_by_name.contains_key(name) {
+            changes.push(MappingChange::Added((*new_mapping).clone()));
+        }
+    }
+    changes
+}
+
+fn format_line(mapping: &KeyMapping) -> String {
+    match mapping.scan {
+        Some(scan) => format!("{}\t0x{:02X}\t0x{:02X}", mapping.name, mapping.vk, scan),
+        None => format!("{}\t0x{:02X}", mapping.name, mapping.vk),
+    }
+}
+
+fn parse_hex(value: &str) -> Option<u16> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))?;
+    u16::from_str_radix(digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(name: &str, vk: u16, scan: Option<u16>) -> KeyMapping {
+        KeyMapping {
+            name: name.to_string(),
+            vk,
+            scan,
+        }
+    }
+
+    #[test]
+    fn test_quirky_scan_codes() {
+        assert_eq!(vk_scan_code(0x2C), (0x37, true));
+        assert_eq!(vk_scan_code(0x13), (0x45, false));
+    }
+
+    #[test]
+    fn test_format_and_parse_roundtrip() {
+        let mappings = vec![
+            mapping("Enter", 0x0D, Some(0x1C)),
+            mapping("F13", 0x7C, None),
+        ];
+        let text = format!("# header\n{}", format_key_mappings(&mappings));
+        assert_eq!(parse_key_mappings(&text).unwrap(), mappings);
+        assert!(parse_key_mappings("Enter 13").is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = vec![mapping("A", 0x41, Some(0x1E)), mapping("B", 0x42, None)];
+        let new = vec![mapping("A", 0x41, None), mapping("C", 0x43, None)];
+        let changes = diff_key_mappings(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                MappingChange::Removed(mapping("B", 0x42, None)),
+                MappingChange::Added(mapping("C", 0x43, None)),
+            ]
+        );
+
+        let changed = diff_key_mappings(&old, &[mapping("A", 0x61, None)]);
+        assert!(matches!(&changed[0], MappingChange::Changed { new, .. } if new.vk == 0x61));
+    }
+}
@@ -0,0 +1,127 @@
+// src/core/minimal.rs
+//! 最小构建的全局输入
+//!
+//! 只启用 `minimal` 特性（未启用 `global`）时使用：直接通过 `SendInput` 注入按键和 Unicode 字符，
+//! 只依赖 `windows` crate 的键盘输入 API。焦点检查、操作锁、遥测、文本选项、按键模拟注入方式等
+//! 由 `global` 特性提供，这里不包含；启用 `global` 后同名函数由完整实现替代。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, Modifier};
+use std::time::Duration;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+    KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VIRTUAL_KEY,
+};
+
+use super::mappings::is_extended_vk;
+use super::{key_to_vk, modifier_to_vk};
+
+/// 构造一个键盘事件
+fn keyboard_input(vk: u16, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(vk),
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+/// 虚拟键的按下或释放事件，扩展键带扩展键标志
+fn vk_input(vk: u16, up: bool) -> INPUT {
+    let mut flags = if up {
+        KEYEVENTF_KEYUP
+    } else {
+        KEYBD_EVENT_FLAGS(0)
+    };
+    if is_extended_vk(vk) {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    keyboard_input(vk, 0, flags)
+}
+
+/// 调用 `SendInput` 提交事件，注入数量少于请求数量时返回 `InjectionFailed`
+fn submit(inputs: &[INPUT]) -> Result<()> {
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    let injected = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) } as usize;
+    if injected < inputs.len() {
+        let code = std::io::Error::last_os_error().raw_os_error().unwrap_or(0) as u32;
+        return Err(KeyboardSenderError::InjectionFailed {
+            requested: inputs.len(),
+            injected,
+            code,
+        });
+    }
+    Ok(())
+}
+
+/// 全局发送：按键按下
+pub fn key_down(key: Key) -> Result<()> {
+    submit(&[vk_input(key_to_vk(key), false)])
+}
+
+/// 全局发送：按键释放
+pub fn key_up(key: Key) -> Result<()> {
+    submit(&[vk_input(key_to_vk(key), true)])
+}
+
+/// 全局发送：按键点击，`press_duration` 为 `None` 时按下后立即释放
+pub fn key_click(key: Key, press_duration: Option<Duration>) -> Result<()> {
+    let vk = key_to_vk(key);
+    match press_duration {
+        None => submit(&[vk_input(vk, false), vk_input(vk, true)]),
+        Some(duration) => {
+            submit(&[vk_input(vk, false)])?;
+            std::thread::sleep(duration);
+            submit(&[vk_input(vk, true)])
+        }
+    }
+}
+
+/// 全局发送：组合键（依次按下修饰键和按键，再按相反顺序释放）
+pub fn press_combination(
+    modifiers: &[Modifier],
+    key: Key,
+    press_duration: Option<Duration>,
+) -> Result<()> {
+    let mut vks: Vec<u16> = modifiers.iter().map(|&m| modifier_to_vk(m)).collect();
+    vks.push(key_to_vk(key));
+    let downs: Vec<INPUT> = vks.iter().map(|&vk| vk_input(vk, false)).collect();
+    let ups: Vec<INPUT> = vks.iter().rev().map(|&vk| vk_input(vk, true)).collect();
+    submit(&downs)?;
+    if let Some(duration) = press_duration {
+        std::thread::sleep(duration);
+    }
+    submit(&ups)
+}
+
+/// 全局发送：以 Unicode 事件输入字符（增补平面字符按代理对发送）
+pub fn send_char(c: char) -> Result<()> {
+    let mut inputs = Vec::with_capacity(4);
+    push_unicode_inputs(&mut inputs, c);
+    submit(&inputs)
+}
+
+/// 全局发送：以 Unicode 事件输入字符串，整段一次提交
+pub fn type_string(text: &str) -> Result<()> {
+    let mut inputs = Vec::with_capacity(text.len() * 2);
+    for c in text.chars() {
+        push_unicode_inputs(&mut inputs, c);
+    }
+    submit(&inputs)
+}
+
+fn push_unicode_inputs(inputs: &mut Vec<INPUT>, c: char) {
+    let mut units = [0u16; 2];
+    for &unit in c.encode_utf16(&mut units).iter() {
+        inputs.push(keyboard_input(0, unit, KEYEVENTF_UNICODE));
+        inputs.push(keyboard_input(0, unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP));
+    }
+}
@@ -1,16 +1,262 @@
 // src/core/mod.rs
 //! 核心键盘操作功能
 
+#[cfg(feature = "window_target")]
+pub mod accessibility;
+
+#[cfg(feature = "global")]
+pub mod atomic_op;
+
+#[cfg(feature = "window_target")]
+pub mod capture;
+
+#[cfg(feature = "window_target")]
+pub mod capture_window;
+
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+
+#[cfg(feature = "global")]
+pub mod clock;
+
+#[cfg(feature = "global")]
+pub mod compiled;
+
+#[cfg(feature = "global")]
+pub mod config;
+
+#[cfg(feature = "global")]
+pub mod delay;
+
+#[cfg(feature = "window_target")]
+pub mod echo;
+
+#[cfg(feature = "window_target")]
+pub mod focused;
+
+#[cfg(feature = "window_target")]
+pub mod foreground;
+
+#[cfg(feature = "window_target")]
+pub mod fullscreen;
+
 #[cfg(feature = "global")]
 pub mod global;
 
+#[cfg(feature = "global")]
+mod guard;
+
+#[cfg(feature = "global")]
+pub mod hold;
+
+#[cfg(feature = "global")]
+mod hook_thread;
+
+#[cfg(feature = "global")]
+pub mod hotkeys;
+
+#[cfg(feature = "global")]
+pub mod humanize;
+
+#[cfg(feature = "global")]
+pub mod ime;
+
+#[cfg(feature = "global")]
+pub mod key_state;
+
+#[cfg(feature = "global")]
+pub mod key_stats;
+
+#[cfg(feature = "global")]
+pub mod layout;
+
+#[cfg(feature = "window_target")]
+pub mod launcher;
+
+#[cfg(feature = "global")]
+pub mod lifecycle;
+
+#[cfg(feature = "minimal")]
+pub mod mappings;
+
+#[cfg(all(feature = "minimal", not(feature = "global")))]
+pub mod minimal;
+
+#[cfg(feature = "window_target")]
+pub mod pacing;
+
+#[cfg(feature = "global")]
+pub mod profile;
+
+#[cfg(feature = "global")]
+mod progress;
+
+#[cfg(feature = "global")]
+pub mod qpc;
+
+#[cfg(feature = "window_target")]
+pub mod resolved_target;
+
+#[cfg(feature = "window_target")]
+pub mod secure_input;
+
+#[cfg(feature = "window_target")]
+pub mod selector;
+
+#[cfg(feature = "window_target")]
+pub mod self_test;
+
+#[cfg(feature = "global")]
+pub mod sequence;
+
+#[cfg(feature = "global")]
+pub mod sender;
+
+#[cfg(feature = "window_target")]
+pub mod spy;
+
+#[cfg(feature = "global")]
+pub mod state;
+
+#[cfg(feature = "window_target")]
+pub mod strategy;
+
+#[cfg(feature = "global")]
+pub mod text_entry;
+
+#[cfg(feature = "global")]
+pub mod text_options;
+
+#[cfg(feature = "window_target")]
+pub mod virtual_keyboard;
+
 #[cfg(feature = "window_target")]
 pub mod window_target;
 
 // 重新导出
+#[cfg(feature = "window_target")]
+pub use accessibility::*;
+
+#[cfg(feature = "global")]
+pub use atomic_op::*;
+
+#[cfg(feature = "window_target")]
+pub use capture::*;
+
+#[cfg(feature = "window_target")]
+pub use capture_window::*;
+
+#[cfg(feature = "clipboard")]
+pub use clipboard::*;
+
+#[cfg(feature = "global")]
+pub use clock::*;
+
+#[cfg(feature = "global")]
+pub use compiled::*;
+
+#[cfg(feature = "global")]
+pub use config::*;
+
+#[cfg(feature = "global")]
+pub use delay::*;
+
+#[cfg(feature = "window_target")]
+pub use echo::*;
+
+#[cfg(feature = "window_target")]
+pub use focused::*;
+
+#[cfg(feature = "window_target")]
+pub use foreground::*;
+
+#[cfg(feature = "window_target")]
+pub use fullscreen::*;
+
 #[cfg(feature = "global")]
 pub use global::*;
 
+#[cfg(feature = "global")]
+pub use hold::*;
+
+#[cfg(feature = "global")]
+pub use hotkeys::*;
+
+#[cfg(feature = "global")]
+pub use humanize::*;
+
+#[cfg(feature = "global")]
+pub use ime::*;
+
+#[cfg(feature = "global")]
+pub use key_state::*;
+
+#[cfg(feature = "global")]
+pub use key_stats::*;
+
+#[cfg(feature = "global")]
+pub use layout::*;
+
+#[cfg(feature = "window_target")]
+pub use launcher::*;
+
+#[cfg(feature = "global")]
+pub use lifecycle::*;
+
+#[cfg(feature = "global")]
+pub use mappings::*;
+
+#[cfg(all(feature = "minimal", not(feature = "global")))]
+pub use minimal::*;
+
+#[cfg(feature = "window_target")]
+pub use pacing::*;
+
+#[cfg(feature = "global")]
+pub use profile::*;
+
+#[cfg(feature = "global")]
+pub use qpc::*;
+
+#[cfg(feature = "window_target")]
+pub use resolved_target::*;
+
+#[cfg(feature = "window_target")]
+pub use secure_input::*;
+
+#[cfg(feature = "window_target")]
+pub use selector::*;
+
+#[cfg(feature = "window_target")]
+pub use self_test::*;
+
+#[cfg(feature = "global")]
+pub use sequence::*;
+
+#[cfg(feature = "global")]
+pub use sender::*;
+
+#[cfg(feature = "window_target")]
+pub use spy::*;
+
+#[cfg(feature = "global")]
+pub use state::*;
+
+#[cfg(feature = "window_target")]
+pub use strategy::*;
+
+#[cfg(feature = "global")]
+pub use text_entry::*;
+
+#[cfg(feature = "global")]
+pub use text_options::*;
+
+#[cfg(feature = "command_parser")]
+pub(crate) use progress::track_typed;
+
+#[cfg(feature = "window_target")]
+pub use virtual_keyboard::*;
+
 #[cfg(feature = "window_target")]
 pub use window_target::*;
 
@@ -24,30 +270,16 @@ mod utils {
         key.to_code(keyboard_codes::current_platform()) as u16
     }
 
-    /// 将 Modifier 转换为对应的 Key
-    pub fn modifier_to_key(modifier: crate::types::Modifier) -> Key {
-        use std::str::FromStr;
-
-        match modifier {
-            crate::types::Modifier::Shift
-            | crate::types::Modifier::LeftShift
-            | crate::types::Modifier::RightShift => Key::from_str("Shift").unwrap_or(Key::Escape),
-            crate::types::Modifier::Control
-            | crate::types::Modifier::LeftControl
-            | crate::types::Modifier::RightControl => {
-                Key::from_str("Control").unwrap_or(Key::Escape)
-            }
-            crate::types::Modifier::Alt
-            | crate::types::Modifier::LeftAlt
-            | crate::types::Modifier::RightAlt => Key::from_str("Alt").unwrap_or(Key::Escape),
-            crate::types::Modifier::Meta
-            | crate::types::Modifier::LeftMeta
-            | crate::types::Modifier::RightMeta => Key::from_str("Meta").unwrap_or(Key::Escape),
-        }
+    /// 将 Modifier 转换为 Windows 虚拟键码
+    ///
+    /// 区分左右的修饰键映射到对应一侧的键码（如 `RightAlt` → `VK_RMENU`，即 AltGr），
+    /// 不区分左右的映射到通用键码（如 `Shift` → `VK_SHIFT`）。
+    pub fn modifier_to_vk(modifier: crate::types::Modifier) -> u16 {
+        modifier.to_code(keyboard_codes::current_platform()) as u16
     }
 }
 
-#[cfg(feature = "global")]
+#[cfg(feature = "minimal")]
 #[allow(unused_imports)]
 pub(crate) use utils::*;
 
@@ -0,0 +1,333 @@
+// src/core/pacing.rs
+//! 自适应输入节奏
+//!
+//! 长文本投递到窗口时，目标程序可能处理不过来（消息堆积、界面卡顿）。
+//! 自适应模式每隔若干字符用 `SendMessageTimeout(WM_NULL)` 探测目标线程的响应延迟：
+//! 响应变慢时成倍增加字符间隔，恢复后再逐步缩短。
+//!
+//! 终端程序会把快速连续的 `WM_KEYDOWN` 当作自动重复处理甚至合并，发往已知终端
+//! （或在兼容性数据库中登记了按键间隔的进程）的按键之间会自动保持最小间隔，
+//! 同一按键连续按下之间补发释放消息，见 [`key_repeat_gap`]。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::WindowsAndMessaging::{SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_NULL},
+};
+
+use super::selector::window_exists;
+
+/// 自适应节奏参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptivePacing {
+    /// 最小字符间隔（默认 0）
+    pub min_delay: Duration,
+    /// 最大字符间隔（默认 100ms）
+    pub max_delay: Duration,
+    /// 每输入多少个字符探测一次（默认 16）
+    pub probe_every: usize,
+    /// 探测超时时间，超时视为目标无响应（默认 500ms）
+    pub probe_timeout: Duration,
+    /// 响应延迟超过该值时视为跟不上（默认 20ms）
+    pub lag_threshold: Duration,
+}
+
+impl Default for AdaptivePacing {
+    fn default() -> Self {
+        Self {
+            min_delay: Duration::ZERO,
+            max_delay: Duration::from_millis(100),
+            probe_every: 16,
+            probe_timeout: Duration::from_millis(500),
+            lag_threshold: Duration::from_millis(20),
+        }
+    }
+}
+
+impl AdaptivePacing {
+    /// 默认参数
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置字符间隔范围
+    pub fn delay_range(mut self, min: Duration, max: Duration) -> Self {
+        self.min_delay = min;
+        self.max_delay = max.max(min);
+        self
+    }
+
+    /// 设置探测频率（字符数，至少为 1）
+    pub fn probe_every(mut self, chars: usize) -> Self {
+        self.probe_every = chars.max(1);
+        self
+    }
+
+    /// 设置探测超时
+    pub fn probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// 设置延迟阈值
+    pub fn lag_threshold(mut self, threshold: Duration) -> Self {
+        self.lag_threshold = threshold;
+        self
+    }
+
+    /// 根据探测结果计算下一段的字符间隔
+    ///
+    /// `probe` 为 `None` 表示探测超时。跟不上时间隔加倍（至少 1ms），
+    /// 响应延迟低于阈值一半时间隔减半，结果限制在 `min_delay..=max_delay`。
+    pub fn next_delay(&self, current: Duration, probe: Option<Duration>) -> Duration {
+        let next = match probe {
+            Some(latency) if latency <= self.lag_threshold / 2 => current / 2,
+            Some(latency) if latency <= self.lag_threshold => current,
+            _ => (current * 2).max(Duration::from_millis(1)),
+        };
+        next.clamp(self.min_delay, self.max_delay)
+    }
+}
+
+/// 自适应输入报告
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PacingReport {
+    /// 输入的字符数
+    pub chars: usize,
+    /// 探测次数
+    pub probes: usize,
+    /// 响应过慢或超时的探测次数
+    pub slow_probes: usize,
+    /// 使用过的最大字符间隔
+    pub max_delay: Duration,
+    /// 结束时的字符间隔
+    pub final_delay: Duration,
+    /// 总耗时
+    pub total_duration: Duration,
+}
+
+/// 以自适应节奏向窗口输入字符串
+///
+/// 字符通过 `WM_CHAR` 投递；窗口在输入过程中被关闭时返回 `InvalidWindowHandle` 错误。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{type_string_to_window_adaptive, AdaptivePacing};
+///
+/// let text = "a long document ...".repeat(100);
+/// let report = type_string_to_window_adaptive(0x1A2B, &text, &AdaptivePacing::new()).unwrap();
+/// println!("{} slow probes, max delay {:?}", report.slow_probes, report.max_delay);
+/// # }
+/// ```
+pub fn type_string_to_window_adaptive(
+    hwnd: WindowHandle,
+    text: &str,
+    pacing: &AdaptivePacing,
+) -> Result<PacingReport> {
+    let _op = super::begin_operation();
+    crate::telemetry::emit_text(Some(hwnd), text);
+
+    let clock = super::clock();
+    let start = clock.now();
+    let probe_every = pacing.probe_every.max(1);
+    let mut delay = pacing.min_delay;
+    let mut report = PacingReport::default();
+    for (index, c) in text.chars().enumerate() {
+        if index > 0 {
+            if index % probe_every == 0 {
+                if !window_exists(hwnd) {
+                    return Err(KeyboardSenderError::InvalidWindowHandle(format!(
+                        "window {:#x} closed while typing",
+                        hwnd
+                    )));
+                }
+                let latency = probe(hwnd, pacing.probe_timeout);
+                report.probes += 1;
+                if latency.is_none_or(|l| l > pacing.lag_threshold) {
+                    report.slow_probes += 1;
+                }
+                delay = pacing.next_delay(delay, latency);
+                report.max_delay = report.max_delay.max(delay);
+            }
+            if !delay.is_zero() {
+                clock.sleep(delay);
+            }
+        }
+        super::send_char_to_window(hwnd, c)?;
+        report.chars += 1;
+    }
+    report.final_delay = delay;
+    report.total_duration = clock.now().saturating_duration_since(start);
+    Ok(report)
+}
+
+/// 已知终端程序的进程名（小写）
+pub const KNOWN_TERMINALS: &[&str] = &[
+    "windowsterminal.exe",
+    "openconsole.exe",
+    "conhost.exe",
+    "cmd.exe",
+    "powershell.exe",
+    "pwsh.exe",
+    "mintty.exe",
+    "putty.exe",
+    "alacritty.exe",
+    "wezterm-gui.exe",
+    "conemu.exe",
+    "conemu64.exe",
+    "tabby.exe",
+    "hyper.exe",
+];
+
+/// 已知终端的默认按键间隔
+pub const DEFAULT_TERMINAL_KEY_GAP: Duration = Duration::from_millis(20);
+
+thread_local! {
+    /// 最近一次发往窗口的按键消息：(窗口, 虚拟键码, 是否为按下, 时间)
+    static LAST_KEY: Cell<Option<(WindowHandle, u16, bool, Instant)>> = const { Cell::new(None) };
+    /// 窗口 -> (兼容性数据库版本, 按键间隔)
+    static GAP_CACHE: RefCell<HashMap<WindowHandle, (u64, Option<Duration>)>> =
+        RefCell::new(HashMap::new());
+}
+
+/// 进程是否为已知终端
+pub fn is_known_terminal(process: &str) -> bool {
+    KNOWN_TERMINALS.contains(&process.to_lowercase().as_str())
+}
+
+/// 发往该进程窗口的按键之间的最小间隔，不限制时为 `None`
+///
+/// 兼容性数据库中的登记（[`set_key_repeat_gap`](super::strategy::set_key_repeat_gap)）优先，
+/// 否则已知终端使用 [`DEFAULT_TERMINAL_KEY_GAP`]。
+pub fn key_repeat_gap_for_process(process: &str) -> Option<Duration> {
+    let gap = super::strategy::registered_key_repeat_gap(process)
+        .or_else(|| is_known_terminal(process).then_some(DEFAULT_TERMINAL_KEY_GAP))?;
+    (!gap.is_zero()).then_some(gap)
+}
+
+/// 发往窗口的按键之间的最小间隔，不限制时为 `None`（按窗口缓存进程查询结果）
+pub fn key_repeat_gap(hwnd: WindowHandle) -> Option<Duration> {
+    let version = super::strategy::compatibility_version();
+    if let Some(gap) = GAP_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .get(&hwnd)
+            .filter(|(cached, _)| *cached == version)
+            .map(|(_, gap)| *gap)
+    }) {
+        return gap;
+    }
+    let gap = super::process_name(super::window_process_id(hwnd))
+        .and_then(|name| key_repeat_gap_for_process(&name));
+    GAP_CACHE.with(|cache| cache.borrow_mut().insert(hwnd, (version, gap)));
+    gap
+}
+
+/// 向终端类窗口发送按下消息前调用：同一按键仍处于按下状态时先调用 `release` 补发释放，
+/// 再等待到距上一条按键消息至少间隔 [`key_repeat_gap`]
+pub(crate) fn pace_key_down(
+    hwnd: WindowHandle,
+    vk: u16,
+    release: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let Some(gap) = key_repeat_gap(hwnd) else {
+        return Ok(());
+    };
+    let Some((last_hwnd, last_vk, down, mut at)) = LAST_KEY.with(Cell::get) else {
+        return Ok(());
+    };
+    if last_hwnd != hwnd {
+        return Ok(());
+    }
+    let clock = super::clock();
+    if down && last_vk == vk {
+        release()?;
+        at = clock.now();
+    }
+    clock.sleep_until(at + gap);
+    Ok(())
+}
+
+/// 记录发往窗口的按键消息
+pub(crate) fn record_key(hwnd: WindowHandle, vk: u16, down: bool) {
+    LAST_KEY.with(|last| last.set(Some((hwnd, vk, down, super::clock().now()))));
+}
+
+/// 探测窗口线程的响应延迟，超时返回 `None`
+fn probe(hwnd: WindowHandle, timeout: Duration) -> Option<Duration> {
+    let start = Instant::now();
+    let ok = unsafe {
+        SendMessageTimeoutW(
+            HWND(hwnd),
+            WM_NULL,
+            WPARAM(0),
+            LPARAM(0),
+            SMTO_ABORTIFHUNG,
+            timeout.as_millis().min(u128::from(u32::MAX)) as u32,
+            None,
+        )
+    };
+    (ok.0 != 0).then(|| start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_gap() {
+        assert!(is_known_terminal("WindowsTerminal.exe"));
+        assert!(!is_known_terminal("notepad.exe"));
+        assert_eq!(
+            key_repeat_gap_for_process("pwsh.exe"),
+            Some(DEFAULT_TERMINAL_KEY_GAP)
+        );
+        assert_eq!(key_repeat_gap_for_process("notepad.exe"), None);
+
+        super::super::strategy::set_key_repeat_gap("mintty.exe", Duration::ZERO);
+        assert_eq!(key_repeat_gap_for_process("mintty.exe"), None);
+        super::super::strategy::set_key_repeat_gap("notepad.exe", Duration::from_millis(30));
+        assert_eq!(
+            key_repeat_gap_for_process("notepad.exe"),
+            Some(Duration::from_millis(30))
+        );
+        super::super::strategy::clear_compatibility("mintty.exe");
+        super::super::strategy::clear_compatibility("notepad.exe");
+    }
+
+    #[test]
+    fn test_next_delay_backs_off_and_recovers() {
+        let pacing = AdaptivePacing::new().delay_range(Duration::ZERO, Duration::from_millis(10));
+        let ms = Duration::from_millis;
+
+        // 超时与响应慢都会加倍，且至少 1ms
+        assert_eq!(pacing.next_delay(Duration::ZERO, None), ms(1));
+        assert_eq!(pacing.next_delay(ms(4), Some(ms(50))), ms(8));
+        assert_eq!(pacing.next_delay(ms(8), None), ms(10));
+
+        // 接近阈值时保持，明显恢复后减半
+        assert_eq!(pacing.next_delay(ms(8), Some(ms(15))), ms(8));
+        assert_eq!(pacing.next_delay(ms(8), Some(ms(1))), ms(4));
+        assert_eq!(
+            pacing.next_delay(Duration::ZERO, Some(ms(1))),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_min_delay_is_respected() {
+        let pacing =
+            AdaptivePacing::new().delay_range(Duration::from_millis(5), Duration::from_millis(2));
+        assert_eq!(pacing.max_delay, Duration::from_millis(5));
+        assert_eq!(
+            pacing.next_delay(Duration::from_millis(5), Some(Duration::ZERO)),
+            Duration::from_millis(5)
+        );
+    }
+}
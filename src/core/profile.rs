@@ -0,0 +1,116 @@
+// src/core/profile.rs
+//! 输入配置预设
+use super::config::{InjectionMode, InputSettings, TextInjection};
+use super::delay::DelaySpec;
+#[cfg(feature = "window_target")]
+use crate::types::WindowHandle;
+use std::time::Duration;
+
+/// 游戏模式预设
+///
+/// 面向 DirectInput 游戏：使用扫描码注入、非零按下时长和随机抖动，
+/// 关闭 Unicode 文本注入（改为按当前布局合成按键），并可断言目标窗口处于前台。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::GameMode;
+///
+/// let previous = GameMode::default().apply();
+/// // ... 游戏自动化 ...
+/// previous.apply();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameMode {
+    press_duration: Duration,
+    jitter: Duration,
+    #[cfg(feature = "window_target")]
+    focus_window: Option<WindowHandle>,
+}
+
+impl GameMode {
+    /// 默认按下时长
+    pub const DEFAULT_PRESS_DURATION: Duration = Duration::from_millis(30);
+    /// 默认按下时长抖动上限
+    pub const DEFAULT_JITTER: Duration = Duration::from_millis(20);
+
+    /// 使用默认参数创建预设
+    pub fn new() -> Self {
+        Self {
+            press_duration: Self::DEFAULT_PRESS_DURATION,
+            jitter: Self::DEFAULT_JITTER,
+            #[cfg(feature = "window_target")]
+            focus_window: None,
+        }
+    }
+
+    /// 为指定游戏窗口创建预设，输入前断言该窗口处于前台
+    #[cfg(feature = "window_target")]
+    pub fn for_window(hwnd: WindowHandle) -> Self {
+        Self::new().focus_window(hwnd)
+    }
+
+    /// 设置按下时长
+    pub fn press_duration(mut self, duration: Duration) -> Self {
+        self.press_duration = duration;
+        self
+    }
+
+    /// 设置按下时长抖动上限
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 设置焦点断言的目标窗口
+    #[cfg(feature = "window_target")]
+    pub fn focus_window(mut self, hwnd: WindowHandle) -> Self {
+        self.focus_window = Some(hwnd);
+        self
+    }
+
+    /// 按下时长及其抖动范围
+    pub fn press_delay(&self) -> DelaySpec {
+        DelaySpec::jitter(self.press_duration, self.jitter)
+    }
+
+    /// 预设对应的全局输入配置
+    pub fn settings(&self) -> InputSettings {
+        InputSettings {
+            press_duration: self.press_duration,
+            press_jitter: self.jitter,
+            injection_mode: InjectionMode::ScanCode,
+            text_injection: TextInjection::KeyStrokes,
+            #[cfg(feature = "window_target")]
+            focus_window: self.focus_window,
+        }
+    }
+
+    /// 应用预设，返回之前的配置以便恢复
+    pub fn apply(&self) -> InputSettings {
+        self.settings().apply()
+    }
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_mode_settings() {
+        let settings = GameMode::new().jitter(Duration::ZERO).settings();
+        assert_eq!(settings.injection_mode, InjectionMode::ScanCode);
+        assert_eq!(settings.text_injection, TextInjection::KeyStrokes);
+        assert_eq!(settings.press_duration, GameMode::DEFAULT_PRESS_DURATION);
+        assert_eq!(settings.press_jitter, Duration::ZERO);
+        assert_eq!(
+            GameMode::new().press_delay(),
+            DelaySpec::range(Duration::from_millis(30), Duration::from_millis(50))
+        );
+    }
+}
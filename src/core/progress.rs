@@ -0,0 +1,101 @@
+// src/core/progress.rs
+//! 文本输入进度
+//!
+//! 字符串输入在中途失败时，调用方需要知道已经送达了多少字符，才能只补发剩余部分而不是重新输入整段文本。
+//! [`track_typed`] 在当前线程上记录其中的字符串输入已送达的字符数（按应用文本选项后的文本计）。
+use crate::error::{KeyboardSenderError, Result};
+use std::cell::Cell;
+
+thread_local! {
+    /// 当前线程正在记录的已送达字符数，`None` 表示未在记录
+    static TYPED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// 执行 `f` 并返回其结果和期间送达的字符数
+///
+/// 嵌套调用时内层的字符数同样计入外层。
+#[cfg_attr(
+    not(any(feature = "window_target", feature = "command_parser")),
+    allow(dead_code)
+)]
+pub(crate) fn track_typed<T>(f: impl FnOnce() -> Result<T>) -> (Result<T>, usize) {
+    let outer = TYPED.with(|typed| typed.replace(Some(0)));
+    let result = f();
+    let typed = TYPED.with(|typed| typed.replace(outer)).unwrap_or(0);
+    if outer.is_some() {
+        add_typed(typed);
+    }
+    (result, typed)
+}
+
+/// 记录送达的字符数（未在记录时忽略）
+pub(crate) fn add_typed(chars: usize) {
+    TYPED.with(|typed| {
+        if let Some(count) = typed.get() {
+            typed.set(Some(count + chars));
+        }
+    });
+}
+
+/// 一次 Unicode 提交部分失败时，`chars` 中已送达的字符数
+///
+/// Unicode 字符在按下事件时即产生输入，按下事件（代理对为第二个按下事件）已注入的字符视为已送达。
+pub(crate) fn delivered_unicode_chars(chars: &[char], error: &KeyboardSenderError) -> usize {
+    let KeyboardSenderError::InjectionFailed { injected, .. } = *error else {
+        return 0;
+    };
+    let mut events = 0;
+    let mut delivered = 0;
+    for c in chars {
+        let units = c.len_utf16();
+        // 最后一个按下事件的位置：每个 UTF-16 单元依次为按下、释放
+        if events + (units - 1) * 2 >= injected {
+            break;
+        }
+        events += units * 2;
+        delivered += 1;
+    }
+    delivered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_typed_nested() {
+        let (result, outer) = track_typed(|| {
+            add_typed(2);
+            let (_, inner) = track_typed(|| {
+                add_typed(3);
+                Ok(())
+            });
+            assert_eq!(inner, 3);
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(outer, 5);
+        // 未在记录时忽略
+        add_typed(1);
+        assert_eq!(track_typed(|| Ok(())).1, 0);
+    }
+
+    #[test]
+    fn test_delivered_unicode_chars() {
+        let failed = |injected| KeyboardSenderError::InjectionFailed {
+            requested: 8,
+            injected,
+            code: 5,
+        };
+        let chars = ['a', '😀', 'b'];
+        assert_eq!(delivered_unicode_chars(&chars, &failed(0)), 0);
+        assert_eq!(delivered_unicode_chars(&chars, &failed(1)), 1);
+        assert_eq!(delivered_unicode_chars(&chars, &failed(4)), 1);
+        assert_eq!(delivered_unicode_chars(&chars, &failed(5)), 2);
+        assert_eq!(delivered_unicode_chars(&chars, &failed(7)), 3);
+        assert_eq!(
+            delivered_unicode_chars(&chars, &KeyboardSenderError::Aborted),
+            0
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// src/core/qpc.rs
+//! 基于 QueryPerformanceCounter 的高精度时间戳
+//!
+//! 注入事件的 `time` 字段为 0，由系统填入毫秒级的消息时间；需要精确测量
+//! “请求发送”到“钩子中观察到”的延迟时，使用 [`now_qpc`] 记录的时间戳。
+//! 同一台机器上不同进程取得的 QPC 值可以直接比较。
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+
+/// QPC 时间戳（计数器刻度）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QpcTimestamp(i64);
+
+impl QpcTimestamp {
+    /// 由计数器刻度构造（如钩子进程中记录的 QPC 值）
+    pub fn from_ticks(ticks: i64) -> Self {
+        Self(ticks)
+    }
+
+    /// 计数器刻度
+    pub fn ticks(self) -> i64 {
+        self.0
+    }
+
+    /// 距 `earlier` 经过的时间；`earlier` 更晚时返回零
+    pub fn duration_since(self, earlier: QpcTimestamp) -> Duration {
+        ticks_to_duration(self.0.saturating_sub(earlier.0))
+    }
+
+    /// 距今经过的时间
+    pub fn elapsed(self) -> Duration {
+        now_qpc().duration_since(self)
+    }
+}
+
+/// 当前 QPC 时间戳
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{key_click, now_qpc, Key};
+///
+/// let start = now_qpc();
+/// key_click(Key::A, None).unwrap();
+/// println!("injected in {:?}", start.elapsed());
+/// ```
+pub fn now_qpc() -> QpcTimestamp {
+    let mut ticks = 0i64;
+    // Windows XP 以后 QPC 不会失败
+    let _ = unsafe { QueryPerformanceCounter(&mut ticks) };
+    QpcTimestamp(ticks)
+}
+
+/// QPC 计数器频率（每秒刻度数）
+pub fn qpc_frequency() -> i64 {
+    static FREQUENCY: OnceLock<i64> = OnceLock::new();
+    *FREQUENCY.get_or_init(|| {
+        let mut frequency = 0i64;
+        let _ = unsafe { QueryPerformanceFrequency(&mut frequency) };
+        frequency.max(1)
+    })
+}
+
+fn ticks_to_duration(ticks: i64) -> Duration {
+    ticks_to_duration_with(ticks, qpc_frequency())
+}
+
+fn ticks_to_duration_with(ticks: i64, frequency: i64) -> Duration {
+    if ticks <= 0 {
+        return Duration::ZERO;
+    }
+    let nanos = ticks as u128 * 1_000_000_000 / frequency as u128;
+    Duration::from_nanos(nanos.min(u64::MAX as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_to_duration() {
+        assert_eq!(
+            ticks_to_duration_with(10_000_000, 10_000_000),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            ticks_to_duration_with(15, 10_000_000),
+            Duration::from_nanos(1500)
+        );
+        assert_eq!(ticks_to_duration_with(-5, 10_000_000), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_now_qpc_is_monotonic() {
+        let a = now_qpc();
+        let b = now_qpc();
+        assert!(b >= a);
+        assert_eq!(a.duration_since(b), Duration::ZERO);
+    }
+}
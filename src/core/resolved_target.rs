@@ -0,0 +1,209 @@
+// src/core/resolved_target.rs
+//! 长期使用的目标窗口：句柄失效时按选择器重新查找
+//!
+//! 脚本开始时取得的窗口句柄会在程序重启后失效。[`ResolvedTarget`] 记住查找窗口用的选择器，
+//! 每次发送前检查句柄是否仍然有效，发送失败且窗口已不存在时重新查找并重试一次，
+//! 换到新窗口时上报 [`TelemetryEvent::TargetReacquired`](crate::telemetry::TelemetryEvent::TargetReacquired)。
+use crate::error::{KeyboardSenderError, Result};
+use crate::retry::RetryPolicy;
+use crate::types::{Key, Modifier, WindowHandle};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::selector::{
+    find_window, process_name, wait_for_window, window_exists, window_process_id, window_title,
+    WindowSelector,
+};
+
+/// 可自动重新查找的目标窗口
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{Key, ResolvedTarget, RetryPolicy, WindowSelector};
+/// use std::time::Duration;
+///
+/// let target = ResolvedTarget::new(WindowSelector::ProcessName("notepad.exe".to_string()))
+///     .unwrap()
+///     // 记事本重启后最多等待 5 秒
+///     .reacquire_policy(RetryPolicy::polling(Duration::from_secs(5), Duration::from_millis(200)));
+/// target.type_string("hello").unwrap();
+/// target.key_click(Key::Enter, None).unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ResolvedTarget {
+    selector: WindowSelector,
+    hwnd: Mutex<WindowHandle>,
+    policy: RetryPolicy,
+}
+
+impl ResolvedTarget {
+    /// 按选择器查找窗口，没有匹配的窗口时返回错误
+    pub fn new(selector: impl Into<WindowSelector>) -> Result<Self> {
+        let selector = selector.into();
+        let hwnd = find_window(&selector).ok_or_else(|| {
+            KeyboardSenderError::InvalidWindowHandle(format!("no window matches {:?}", selector))
+        })?;
+        Ok(Self {
+            selector,
+            hwnd: Mutex::new(hwnd),
+            policy: RetryPolicy::no_retry(),
+        })
+    }
+
+    /// 由已取得的窗口句柄创建，以其进程名（取不到时为标题）作为重新查找的选择器
+    ///
+    /// 进程名和标题都取不到时返回错误：空标题会匹配任意无标题窗口。
+    pub fn from_handle(hwnd: WindowHandle) -> Result<Self> {
+        if !window_exists(hwnd) {
+            return Err(KeyboardSenderError::InvalidWindowHandle(format!(
+                "{:#x}",
+                hwnd
+            )));
+        }
+        let selector = derive_selector(process_name(window_process_id(hwnd)), window_title(hwnd))
+            .ok_or_else(|| {
+            KeyboardSenderError::InvalidWindowHandle(format!(
+                "{:#x} has neither a process name nor a title to find it again",
+                hwnd
+            ))
+        })?;
+        Ok(Self {
+            selector,
+            hwnd: Mutex::new(hwnd),
+            policy: RetryPolicy::no_retry(),
+        })
+    }
+
+    /// 设置重新查找时等待窗口出现的策略（默认只查找一次）
+    pub fn reacquire_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 重新查找使用的选择器
+    pub fn selector(&self) -> &WindowSelector {
+        &self.selector
+    }
+
+    /// 当前窗口句柄，已失效时重新查找
+    pub fn hwnd(&self) -> Result<WindowHandle> {
+        let mut hwnd = self.hwnd.lock().unwrap_or_else(|e| e.into_inner());
+        if !window_exists(*hwnd) {
+            let current = wait_for_window(&self.selector, &self.policy)?;
+            let previous = std::mem::replace(&mut *hwnd, current);
+            crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::TargetReacquired {
+                previous,
+                current,
+            });
+        }
+        Ok(*hwnd)
+    }
+
+    /// 以当前窗口句柄执行发送；失败且窗口已不存在时重新查找并重试一次
+    ///
+    /// 重试会从头执行 `send`，窗口在发送中途关闭时新窗口会收到完整的输入。
+    pub fn run<T>(&self, send: impl Fn(WindowHandle) -> Result<T>) -> Result<T> {
+        retry_if_gone(self.hwnd()?, send, window_exists, || self.hwnd())
+    }
+
+    /// 按键点击
+    pub fn key_click(&self, key: Key, press_duration: Option<Duration>) -> Result<()> {
+        self.run(|hwnd| super::send_key_click_to_window(hwnd, key, press_duration))
+    }
+
+    /// 组合键
+    pub fn press_combination(
+        &self,
+        modifiers: &[Modifier],
+        key: Key,
+        press_duration: Option<Duration>,
+    ) -> Result<()> {
+        self.run(|hwnd| super::press_combination_to_window(hwnd, modifiers, key, press_duration))
+    }
+
+    /// 字符输入
+    pub fn send_char(&self, c: char) -> Result<()> {
+        self.run(|hwnd| super::send_char_to_window(hwnd, c))
+    }
+
+    /// 字符串输入
+    pub fn type_string(&self, text: &str) -> Result<()> {
+        self.run(|hwnd| super::type_string_to_window(hwnd, text))
+    }
+}
+
+/// 重新查找用的选择器：优先进程名，其次非空标题，都没有时返回 `None`
+fn derive_selector(process: Option<String>, title: String) -> Option<WindowSelector> {
+    match process {
+        Some(name) if !name.is_empty() => Some(WindowSelector::ProcessName(name)),
+        _ if !title.trim().is_empty() => Some(WindowSelector::Title(title)),
+        _ => None,
+    }
+}
+
+/// 向 `hwnd` 执行 `send`，失败且窗口已不存在时向 `reacquire` 取得的窗口重试一次
+fn retry_if_gone<T>(
+    hwnd: WindowHandle,
+    send: impl Fn(WindowHandle) -> Result<T>,
+    exists: impl Fn(WindowHandle) -> bool,
+    reacquire: impl FnOnce() -> Result<WindowHandle>,
+) -> Result<T> {
+    match send(hwnd) {
+        Err(_) if !exists(hwnd) => send(reacquire()?),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_derive_selector() {
+        assert_eq!(
+            derive_selector(Some("notepad.exe".to_string()), "Untitled".to_string()),
+            Some(WindowSelector::ProcessName("notepad.exe".to_string()))
+        );
+        assert_eq!(
+            derive_selector(None, "Untitled".to_string()),
+            Some(WindowSelector::Title("Untitled".to_string()))
+        );
+        assert_eq!(derive_selector(Some(String::new()), " ".to_string()), None);
+        assert_eq!(derive_selector(None, String::new()), None);
+    }
+
+    #[test]
+    fn test_retry_if_gone() {
+        let calls = RefCell::new(Vec::new());
+        let send = |hwnd| {
+            calls.borrow_mut().push(hwnd);
+            if hwnd == 1 {
+                Err(KeyboardSenderError::WindowsError)
+            } else {
+                Ok(hwnd)
+            }
+        };
+
+        // 窗口已关闭：向重新查找到的窗口重试一次
+        assert_eq!(retry_if_gone(1, send, |_| false, || Ok(2)).unwrap(), 2);
+        assert_eq!(*calls.borrow(), vec![1, 2]);
+
+        // 窗口仍在：原样返回错误，不重试
+        calls.borrow_mut().clear();
+        let reacquire = || -> Result<WindowHandle> { panic!("should not reacquire") };
+        assert!(retry_if_gone(1, send, |_| true, reacquire).is_err());
+        assert_eq!(*calls.borrow(), vec![1]);
+
+        // 发送成功：不检查窗口
+        calls.borrow_mut().clear();
+        assert_eq!(
+            retry_if_gone(3, send, |_| unreachable!(), reacquire).unwrap(),
+            3
+        );
+        assert_eq!(*calls.borrow(), vec![3]);
+    }
+}
@@ -0,0 +1,178 @@
+// src/core/secure_input.rs
+//! 密码输入框检测与安全输入
+//!
+//! 通过 UI Automation 的 `IsPassword` 属性判断获得焦点的控件是否为密码框，
+//! 对没有 UIA 支持的经典 Edit 控件再检查 `ES_PASSWORD` 样式。
+//! [`type_secret`] 可在焦点不是密码框时拒绝输入，避免凭据被输入到聊天窗口等位置。
+use crate::error::{KeyboardSenderError, Result};
+use crate::telemetry::{with_redaction, RedactionPolicy};
+use crate::types::WindowHandle;
+
+use windows::Win32::{
+    Foundation::{HWND, RPC_E_CHANGED_MODE},
+    System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED,
+    },
+    UI::Accessibility::{CUIAutomation, IUIAutomation},
+    UI::WindowsAndMessaging::{
+        GetClassNameW, GetGUIThreadInfo, GetWindowLongW, GetWindowThreadProcessId, ES_PASSWORD,
+        GUITHREADINFO, GWL_STYLE,
+    },
+};
+
+use super::foreground::foreground_window;
+
+/// 当前前台窗口中获得焦点的控件是否为密码框
+///
+/// 无法判断时返回 `false`。
+pub fn is_secure_input_active() -> bool {
+    focused_control_is_password(foreground_window()).unwrap_or(false)
+}
+
+/// 指定窗口中获得焦点的控件是否为密码框
+///
+/// 窗口位于前台时使用 UIA 的系统焦点元素（可识别浏览器等自绘控件中的密码框），
+/// 否则检查该窗口所在线程的焦点控件。
+pub fn focused_control_is_password(hwnd: WindowHandle) -> Result<bool> {
+    let focus = focused_control(hwnd);
+    if focus != 0 && is_password_edit(focus) {
+        return Ok(true);
+    }
+
+    // 先于 COM 对象声明，最后丢弃
+    let _com = ComApartment::enter()?;
+    let automation = automation()?;
+    let element = unsafe {
+        if hwnd == foreground_window() {
+            automation.GetFocusedElement()
+        } else if focus != 0 {
+            automation.ElementFromHandle(HWND(focus))
+        } else {
+            return Ok(false);
+        }
+    }
+    .map_err(|_| KeyboardSenderError::WindowsError)?;
+
+    let is_password =
+        unsafe { element.CurrentIsPassword() }.map_err(|_| KeyboardSenderError::WindowsError)?;
+    Ok(is_password.as_bool())
+}
+
+/// 安全输入选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecretOptions {
+    /// 仅当焦点控件是密码框时才输入（默认开启）
+    pub require_password_field: bool,
+}
+
+impl Default for SecretOptions {
+    fn default() -> Self {
+        Self {
+            require_password_field: true,
+        }
+    }
+}
+
+/// 向前台窗口输入密码等敏感文本
+///
+/// 遥测中不会记录文本的任何信息。`require_password_field` 开启时，
+/// 焦点控件不是密码框则返回 `InsecureTarget` 错误且不输入任何字符。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{type_secret, SecretOptions};
+///
+/// type_secret("p@ssw0rd", &SecretOptions::default()).unwrap();
+/// # }
+/// ```
+pub fn type_secret(secret: &str, options: &SecretOptions) -> Result<()> {
+    let _op = super::begin_operation();
+    if options.require_password_field && !is_secure_input_active() {
+        return Err(KeyboardSenderError::InsecureTarget(
+            "focused control is not a password field".to_string(),
+        ));
+    }
+    with_redaction(RedactionPolicy::Nothing, || super::type_string(secret))
+}
+
+/// 窗口所在线程中获得焦点的控件
+fn focused_control(hwnd: WindowHandle) -> WindowHandle {
+    unsafe {
+        let thread_id = GetWindowThreadProcessId(HWND(hwnd), None);
+        let mut info = GUITHREADINFO {
+            cbSize: std::mem::size_of::<GUITHREADINFO>() as u32,
+            ..Default::default()
+        };
+        match GetGUIThreadInfo(thread_id, &mut info) {
+            Ok(()) => info.hwndFocus.0,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// 是否为带 `ES_PASSWORD` 样式的经典 Edit 控件
+fn is_password_edit(hwnd: WindowHandle) -> bool {
+    let mut class = [0u16; 64];
+    let len = unsafe { GetClassNameW(HWND(hwnd), &mut class) };
+    let class = String::from_utf16_lossy(&class[..len.max(0) as usize]);
+    class.eq_ignore_ascii_case("Edit")
+        && unsafe { GetWindowLongW(HWND(hwnd), GWL_STYLE) } & ES_PASSWORD != 0
+}
+
+/// 创建 UI Automation 实例，调用线程需已初始化 COM（见 [`ComApartment`]）
+fn automation() -> Result<IUIAutomation> {
+    unsafe { CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER) }
+        .map_err(|_| KeyboardSenderError::WindowsError)
+}
+
+/// 当前线程上的 COM 初始化，丢弃时与成功的 `CoInitializeEx` 配对调用 `CoUninitialize`
+///
+/// 线程已按多线程模式初始化 COM 时（`RPC_E_CHANGED_MODE`）沿用现有的初始化，丢弃时不做任何事。
+struct ComApartment {
+    initialized: bool,
+}
+
+impl ComApartment {
+    fn enter() -> Result<Self> {
+        let result = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
+        Ok(Self {
+            initialized: owns_initialization(result)?,
+        })
+    }
+}
+
+impl Drop for ComApartment {
+    fn drop(&mut self) {
+        if self.initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+/// `CoInitializeEx` 的结果是否需要配对的 `CoUninitialize`，COM 不可用时返回错误
+///
+/// 成功（包括线程已按相同模式初始化时的 `S_FALSE`）都增加了引用计数；
+/// `RPC_E_CHANGED_MODE` 表示线程已按其他模式初始化，COM 可用但没有增加计数。
+fn owns_initialization(result: windows::core::Result<()>) -> Result<bool> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if e.code() == RPC_E_CHANGED_MODE => Ok(false),
+        Err(_) => Err(KeyboardSenderError::WindowsError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::Foundation::E_OUTOFMEMORY;
+
+    #[test]
+    fn test_owns_initialization() {
+        assert!(owns_initialization(Ok(())).unwrap());
+        assert!(!owns_initialization(Err(RPC_E_CHANGED_MODE.into())).unwrap());
+        assert!(owns_initialization(Err(E_OUTOFMEMORY.into())).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// src/core/selector.rs
+//! 窗口选择器：按句柄、标题或进程查找顶层窗口
+use crate::error::{KeyboardSenderError, Result};
+use crate::retry::RetryPolicy;
+use crate::types::WindowHandle;
+
+use windows::core::PWSTR;
+use windows::Win32::{
+    Foundation::{CloseHandle, BOOL, HWND, LPARAM},
+    System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    },
+    UI::WindowsAndMessaging::{
+        EnumWindows, GetWindow, GetWindowTextW, GetWindowThreadProcessId, IsWindow,
+        IsWindowVisible, GW_OWNER,
+    },
+};
+
+/// 窗口选择器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowSelector {
+    /// 直接指定窗口句柄
+    Handle(WindowHandle),
+    /// 标题完全匹配
+    Title(String),
+    /// 标题包含指定文本（不区分大小写）
+    TitleContains(String),
+    /// 进程 ID 的主窗口
+    ProcessId(u32),
+    /// 进程可执行文件名的主窗口（如 `notepad.exe`，不区分大小写）
+    ProcessName(String),
+}
+
+impl WindowSelector {
+    /// 查找第一个匹配的窗口
+    pub fn resolve(&self) -> Option<WindowHandle> {
+        find_window(self)
+    }
+}
+
+impl From<WindowHandle> for WindowSelector {
+    fn from(hwnd: WindowHandle) -> Self {
+        WindowSelector::Handle(hwnd)
+    }
+}
+
+impl From<&str> for WindowSelector {
+    fn from(title: &str) -> Self {
+        WindowSelector::TitleContains(title.to_string())
+    }
+}
+
+/// 查找第一个匹配选择器的窗口
+pub fn find_window(selector: &WindowSelector) -> Option<WindowHandle> {
+    match selector {
+        WindowSelector::Handle(hwnd) => window_exists(*hwnd).then_some(*hwnd),
+        _ => find_windows(selector).into_iter().next(),
+    }
+}
+
+/// 按重试策略等待匹配的窗口出现
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{wait_for_window, RetryPolicy, WindowSelector};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::polling(Duration::from_secs(5), Duration::from_millis(100));
+/// let hwnd = wait_for_window(&WindowSelector::Title("Save As".to_string()), &policy).unwrap();
+/// # }
+/// ```
+pub fn wait_for_window(selector: &WindowSelector, policy: &RetryPolicy) -> Result<WindowHandle> {
+    policy.poll(|| find_window(selector)).ok_or_else(|| {
+        KeyboardSenderError::Timeout(format!(
+            "no window matches {:?} after {} attempts",
+            selector, policy.attempts
+        ))
+    })
+}
+
+/// 查找所有匹配选择器的可见顶层窗口
+pub fn find_windows(selector: &WindowSelector) -> Vec<WindowHandle> {
+    enum_top_level_windows()
+        .into_iter()
+        .filter(|&hwnd| matches_selector(hwnd, selector))
+        .collect()
+}
+
+/// 检查窗口句柄是否仍然有效
+pub fn window_exists(hwnd: WindowHandle) -> bool {
+    hwnd != 0 && unsafe { IsWindow(HWND(hwnd)).as_bool() }
+}
+
+/// 获取窗口标题
+pub fn window_title(hwnd: WindowHandle) -> String {
+    let mut buf = [0u16; 512];
+    let len = unsafe { GetWindowTextW(HWND(hwnd), &mut buf) };
+    String::from_utf16_lossy(&buf[..len.max(0) as usize])
+}
+
+/// 获取窗口所属进程 ID
+pub fn window_process_id(hwnd: WindowHandle) -> u32 {
+    let mut pid = 0u32;
+    unsafe {
+        GetWindowThreadProcessId(HWND(hwnd), Some(&mut pid));
+    }
+    pid
+}
+
+/// 获取进程的可执行文件名（不含路径）
+pub fn process_name(pid: u32) -> Option<String> {
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buf = [0u16; 1024];
+        let mut size = buf.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+
+        let path = String::from_utf16_lossy(&buf[..size as usize]);
+        path.rsplit(['\\', '/']).next().map(|s| s.to_string())
+    }
+}
+
+/// 窗口是否可见
+fn is_visible(hwnd: WindowHandle) -> bool {
+    unsafe { IsWindowVisible(HWND(hwnd)).as_bool() }
+}
+
+/// 是否为主窗口（可见且无所有者）
+fn is_main_window(hwnd: WindowHandle) -> bool {
+    is_visible(hwnd) && unsafe { GetWindow(HWND(hwnd), GW_OWNER).0 == 0 }
+}
+
+fn matches_selector(hwnd: WindowHandle, selector: &WindowSelector) -> bool {
+    match selector {
+        WindowSelector::Handle(h) => *h == hwnd,
+        WindowSelector::Title(title) => is_visible(hwnd) && window_title(hwnd) == *title,
+        WindowSelector::TitleContains(part) => {
+            is_visible(hwnd)
+                && window_title(hwnd)
+                    .to_lowercase()
+                    .contains(&part.to_lowercase())
+        }
+        WindowSelector::ProcessId(pid) => is_main_window(hwnd) && window_process_id(hwnd) == *pid,
+        WindowSelector::ProcessName(name) => {
+            is_main_window(hwnd)
+                && process_name(window_process_id(hwnd))
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        }
+    }
+}
+
+/// 枚举所有顶层窗口
+pub(crate) fn enum_top_level_windows() -> Vec<WindowHandle> {
+    unsafe extern "system" fn enum_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam.0 as *mut Vec<WindowHandle>);
+        windows.push(hwnd.0);
+        BOOL(1)
+    }
+
+    let mut windows: Vec<WindowHandle> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(
+            Some(enum_proc),
+            LPARAM(&mut windows as *mut Vec<WindowHandle> as isize),
+        );
+    }
+    windows
+}
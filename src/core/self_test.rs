@@ -0,0 +1,356 @@
+// src/core/self_test.rs
+//! 注入方式自检
+//!
+//! 创建一个捕获窗口，用每种注入方式向它发送固定的测试按键/字符，统计实际收到的比例和
+//! 从发送到窗口过程收到消息的延迟，用于判断当前机器与配置下哪些方式可用
+//! （如被安全软件拦截、UIPI 限制或远程桌面下的扫描码问题）。
+//!
+//! 全局注入类的方式需要捕获窗口位于前台，自检期间会短暂抢占焦点。
+use crate::types::Key;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::capture_window::{CaptureWindow, CapturedKind};
+use super::config::{InjectionMode, InputSettings, TextInjection};
+use super::window_target::{window_send_mode, WindowSendMode};
+
+/// 每个测试事件等待窗口收到的最长时间
+const EVENT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// 测试按键
+const TEST_KEYS: &[Key] = &[Key::A, Key::Z, Key::D5];
+
+/// 测试字符（含需要 Shift 的字符、带重音的字符、中文和增补平面的 emoji）
+const TEST_CHARS: &[char] = &['a', 'Z', '5', '!', 'é', '中', '😀'];
+
+/// 自检的注入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelfTestStrategy {
+    /// SendInput 虚拟键码
+    SendInputVirtualKey,
+    /// SendInput 扫描码
+    SendInputScanCode,
+    /// SendInput Unicode 字符
+    SendInputUnicode,
+    /// SendInput 按键模拟字符（VkKeyScan）
+    SendInputKeyStrokes,
+    /// PostMessage WM_CHAR
+    PostWmChar,
+    /// SendMessageTimeout WM_CHAR
+    SendWmChar,
+    /// PostMessage WM_KEYDOWN/WM_KEYUP
+    PostWmKey,
+}
+
+impl SelfTestStrategy {
+    /// 全部注入方式
+    pub const ALL: &'static [SelfTestStrategy] = &[
+        SelfTestStrategy::SendInputVirtualKey,
+        SelfTestStrategy::SendInputScanCode,
+        SelfTestStrategy::SendInputUnicode,
+        SelfTestStrategy::SendInputKeyStrokes,
+        SelfTestStrategy::PostWmChar,
+        SelfTestStrategy::SendWmChar,
+        SelfTestStrategy::PostWmKey,
+    ];
+
+    /// 是否为全局注入（需要捕获窗口位于前台）
+    pub fn is_global(self) -> bool {
+        matches!(
+            self,
+            SelfTestStrategy::SendInputVirtualKey
+                | SelfTestStrategy::SendInputScanCode
+                | SelfTestStrategy::SendInputUnicode
+                | SelfTestStrategy::SendInputKeyStrokes
+        )
+    }
+}
+
+/// 单种注入方式的自检结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyResult {
+    pub strategy: SelfTestStrategy,
+    /// 发送的事件数
+    pub sent: usize,
+    /// 窗口收到的事件数
+    pub received: usize,
+    /// 收到的事件从发送到被观察到的平均延迟
+    pub mean_latency: Option<Duration>,
+    /// 发送时返回的错误
+    pub error: Option<String>,
+}
+
+impl StrategyResult {
+    /// 送达率（0.0 ~ 1.0）
+    pub fn delivery_rate(&self) -> f64 {
+        if self.sent == 0 {
+            return 0.0;
+        }
+        self.received as f64 / self.sent as f64
+    }
+
+    /// 是否全部送达且没有错误
+    pub fn works(&self) -> bool {
+        self.error.is_none() && self.sent > 0 && self.received == self.sent
+    }
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub results: Vec<StrategyResult>,
+    /// 捕获窗口是否成功成为前台窗口（否则全局注入方式的结果无参考价值）
+    pub foreground_acquired: bool,
+}
+
+impl SelfTestReport {
+    /// 可用的注入方式
+    pub fn working(&self) -> Vec<SelfTestStrategy> {
+        self.results
+            .iter()
+            .filter(|r| r.works())
+            .map(|r| r.strategy)
+            .collect()
+    }
+
+    /// 是否全部可用
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(StrategyResult::works)
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.foreground_acquired {
+            writeln!(f, "warning: capture window did not get foreground focus")?;
+        }
+        for (index, result) in self.results.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:<20} {} {}/{}",
+                format!("{:?}", result.strategy),
+                if result.works() { "ok  " } else { "FAIL" },
+                result.received,
+                result.sent
+            )?;
+            if let Some(latency) = result.mean_latency {
+                write!(f, " {:?}", latency)?;
+            }
+            if let Some(error) = &result.error {
+                write!(f, " ({})", error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 运行全部注入方式的自检
+///
+/// 自检结束后恢复输入配置与窗口发送方式。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::self_test;
+///
+/// let report = self_test();
+/// println!("{}", report);
+/// println!("working: {:?}", report.working());
+/// # }
+/// ```
+pub fn self_test() -> SelfTestReport {
+    let _op = super::begin_operation();
+    let Ok(window) = CaptureWindow::open("sophia self test") else {
+        return SelfTestReport {
+            results: SelfTestStrategy::ALL
+                .iter()
+                .map(|&strategy| StrategyResult {
+                    strategy,
+                    sent: 0,
+                    received: 0,
+                    mean_latency: None,
+                    error: Some("failed to create capture window".to_string()),
+                })
+                .collect(),
+            foreground_acquired: false,
+        };
+    };
+
+    let foreground_acquired = window.focus();
+
+    let settings = InputSettings::current();
+    let send_mode = window_send_mode();
+    let base = InputSettings {
+        press_duration: Duration::ZERO,
+        press_jitter: Duration::ZERO,
+        focus_window: None,
+        ..settings.clone()
+    };
+
+    let results = SelfTestStrategy::ALL
+        .iter()
+        .map(|&strategy| {
+            window.clear();
+            base.apply();
+            run_strategy(&window, strategy)
+        })
+        .collect();
+
+    settings.apply();
+    super::window_target::set_window_send_mode(send_mode);
+    SelfTestReport {
+        results,
+        foreground_acquired,
+    }
+}
+
+/// 发送单个测试事件
+type SendFn = Box<dyn Fn() -> crate::error::Result<()>>;
+
+/// 用一种方式发送全部测试事件
+fn run_strategy(window: &CaptureWindow, strategy: SelfTestStrategy) -> StrategyResult {
+    let hwnd = window.handle();
+    let mut events: Vec<(Vec<(CapturedKind, u16)>, SendFn)> = Vec::new();
+    match strategy {
+        SelfTestStrategy::SendInputVirtualKey
+        | SelfTestStrategy::SendInputScanCode
+        | SelfTestStrategy::PostWmKey => {
+            super::config::set_injection_mode(if strategy == SelfTestStrategy::SendInputScanCode {
+                InjectionMode::ScanCode
+            } else {
+                InjectionMode::VirtualKey
+            });
+            for &key in TEST_KEYS {
+                let expected = vec![(CapturedKind::KeyDown, super::key_to_vk(key))];
+                if strategy == SelfTestStrategy::PostWmKey {
+                    events.push((
+                        expected,
+                        Box::new(move || {
+                            super::send_key_click_to_window(hwnd, key, Some(Duration::ZERO))
+                        }),
+                    ));
+                } else {
+                    events.push((
+                        expected,
+                        Box::new(move || super::key_click(key, Some(Duration::ZERO))),
+                    ));
+                }
+            }
+        }
+        SelfTestStrategy::SendInputUnicode | SelfTestStrategy::SendInputKeyStrokes => {
+            super::config::set_text_injection(
+                if strategy == SelfTestStrategy::SendInputKeyStrokes {
+                    TextInjection::KeyStrokes
+                } else {
+                    TextInjection::Unicode
+                },
+            );
+            // 按键模拟只能输入当前布局上有按键的字符，只测试 ASCII 字符
+            let chars = TEST_CHARS
+                .iter()
+                .filter(|c| strategy == SelfTestStrategy::SendInputUnicode || c.is_ascii());
+            for &c in chars {
+                events.push((expected_chars(c), Box::new(move || super::send_char(c))));
+            }
+        }
+        SelfTestStrategy::PostWmChar | SelfTestStrategy::SendWmChar => {
+            super::window_target::set_window_send_mode(
+                if strategy == SelfTestStrategy::SendWmChar {
+                    WindowSendMode::synchronous()
+                } else {
+                    WindowSendMode::Post
+                },
+            );
+            for &c in TEST_CHARS {
+                events.push((
+                    expected_chars(c),
+                    Box::new(move || super::send_char_to_window(hwnd, c)),
+                ));
+            }
+        }
+    }
+
+    let mut result = StrategyResult {
+        strategy,
+        sent: 0,
+        received: 0,
+        mean_latency: None,
+        error: None,
+    };
+    let mut total_latency = Duration::ZERO;
+    for (expected, send) in events {
+        let sent_at = Instant::now();
+        if let Err(e) = send() {
+            result.error = Some(e.to_string());
+            break;
+        }
+        result.sent += 1;
+        if let Some(at) = window.wait_for(&expected, EVENT_TIMEOUT) {
+            result.received += 1;
+            total_latency += at.saturating_duration_since(sent_at);
+        }
+    }
+    if result.received > 0 {
+        result.mean_latency = Some(total_latency / result.received as u32);
+    }
+    result
+}
+
+/// 字符对应的 `WM_CHAR` 消息（增补平面字符为两个代理项）
+fn expected_chars(c: char) -> Vec<(CapturedKind, u16)> {
+    let mut units = [0u16; 2];
+    c.encode_utf16(&mut units)
+        .iter()
+        .map(|&unit| (CapturedKind::Char, unit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(sent: usize, received: usize, error: Option<&str>) -> StrategyResult {
+        StrategyResult {
+            strategy: SelfTestStrategy::PostWmChar,
+            sent,
+            received,
+            mean_latency: Some(Duration::from_micros(150)),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_strategy_result() {
+        assert!(result(4, 4, None).works());
+        assert!(!result(4, 3, None).works());
+        assert!(!result(0, 0, None).works());
+        assert!(!result(2, 2, Some("blocked")).works());
+        assert_eq!(result(4, 3, None).delivery_rate(), 0.75);
+    }
+
+    #[test]
+    fn test_expected_chars() {
+        assert_eq!(expected_chars('é'), vec![(CapturedKind::Char, 0xE9)]);
+        assert_eq!(
+            expected_chars('😀'),
+            vec![(CapturedKind::Char, 0xD83D), (CapturedKind::Char, 0xDE00)]
+        );
+    }
+
+    #[test]
+    fn test_report_summary() {
+        let report = SelfTestReport {
+            results: vec![result(4, 4, None), result(4, 0, Some("blocked"))],
+            foreground_acquired: true,
+        };
+        assert_eq!(report.working(), vec![SelfTestStrategy::PostWmChar]);
+        assert!(!report.all_passed());
+        let text = report.to_string();
+        assert!(text.contains("ok"));
+        assert!(text.contains("FAIL 0/4 150µs (blocked)"));
+    }
+}
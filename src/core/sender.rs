@@ -0,0 +1,136 @@
+// src/core/sender.rs
+//! 可配置的键盘发送器：绑定目标窗口和默认按下时长
+#[allow(unused_imports)]
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, Modifier, WindowHandle};
+use std::time::Duration;
+
+/// 键盘发送器
+///
+/// 在全局默认配置之上为一组调用单独指定目标窗口和默认按下时长。
+/// 按下时长的优先级：调用参数 > 发送器设置 > 全局默认值。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{Key, KeyboardSender};
+/// use std::time::Duration;
+///
+/// let sender = KeyboardSender::new().press_duration(Duration::from_millis(30));
+/// sender.key_click(Key::Enter, None).unwrap();            // 按下 30ms
+/// sender.key_click(Key::Tab, Some(Duration::ZERO)).unwrap(); // 不保持按下
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyboardSender {
+    target: Option<WindowHandle>,
+    press_duration: Option<Duration>,
+}
+
+impl KeyboardSender {
+    /// 创建全局发送器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建向指定窗口发送的发送器（需要启用 `window_target` 特性）
+    pub fn for_window(hwnd: WindowHandle) -> Self {
+        Self {
+            target: Some(hwnd),
+            press_duration: None,
+        }
+    }
+
+    /// 设置该发送器的默认按下时长
+    pub fn press_duration(mut self, duration: Duration) -> Self {
+        self.press_duration = Some(duration);
+        self
+    }
+
+    /// 目标窗口，`None` 表示全局发送
+    pub fn target(&self) -> Option<WindowHandle> {
+        self.target
+    }
+
+    fn resolve(&self, press_duration: Option<Duration>) -> Option<Duration> {
+        press_duration.or(self.press_duration)
+    }
+
+    /// 按键按下
+    pub fn key_down(&self, key: Key) -> Result<()> {
+        match self.target {
+            None => super::key_down(key),
+            #[cfg(feature = "window_target")]
+            Some(hwnd) => super::send_key_down_to_window(hwnd, key),
+            #[cfg(not(feature = "window_target"))]
+            Some(_) => Err(window_target_disabled()),
+        }
+    }
+
+    /// 按键释放
+    pub fn key_up(&self, key: Key) -> Result<()> {
+        match self.target {
+            None => super::key_up(key),
+            #[cfg(feature = "window_target")]
+            Some(hwnd) => super::send_key_up_to_window(hwnd, key),
+            #[cfg(not(feature = "window_target"))]
+            Some(_) => Err(window_target_disabled()),
+        }
+    }
+
+    /// 按键点击
+    pub fn key_click(&self, key: Key, press_duration: Option<Duration>) -> Result<()> {
+        let press_duration = self.resolve(press_duration);
+        match self.target {
+            None => super::key_click(key, press_duration),
+            #[cfg(feature = "window_target")]
+            Some(hwnd) => super::send_key_click_to_window(hwnd, key, press_duration),
+            #[cfg(not(feature = "window_target"))]
+            Some(_) => Err(window_target_disabled()),
+        }
+    }
+
+    /// 组合键
+    pub fn press_combination(
+        &self,
+        modifiers: &[Modifier],
+        key: Key,
+        press_duration: Option<Duration>,
+    ) -> Result<()> {
+        let press_duration = self.resolve(press_duration);
+        match self.target {
+            None => super::press_combination(modifiers, key, press_duration),
+            #[cfg(feature = "window_target")]
+            Some(hwnd) => {
+                super::press_combination_to_window(hwnd, modifiers, key, press_duration)
+            }
+            #[cfg(not(feature = "window_target"))]
+            Some(_) => Err(window_target_disabled()),
+        }
+    }
+
+    /// 字符输入
+    pub fn send_char(&self, c: char) -> Result<()> {
+        match self.target {
+            None => super::send_char(c),
+            #[cfg(feature = "window_target")]
+            Some(hwnd) => super::send_char_to_window(hwnd, c),
+            #[cfg(not(feature = "window_target"))]
+            Some(_) => Err(window_target_disabled()),
+        }
+    }
+
+    /// 字符串输入
+    pub fn type_string(&self, text: &str) -> Result<()> {
+        match self.target {
+            None => super::type_string(text),
+            #[cfg(feature = "window_target")]
+            Some(hwnd) => super::type_string_to_window(hwnd, text),
+            #[cfg(not(feature = "window_target"))]
+            Some(_) => Err(window_target_disabled()),
+        }
+    }
+}
+
+#[cfg(not(feature = "window_target"))]
+fn window_target_disabled() -> KeyboardSenderError {
+    KeyboardSenderError::FeatureNotEnabled("window_target".to_string())
+}
@@ -0,0 +1,196 @@
+// src/core/sequence.rs
+//! 输入动作序列
+//!
+//! [`InputAction`] 描述一步输入，[`send_sequence`] 把一组动作作为一个不可交错的逻辑操作执行。
+//! 命令解析器、智能输入和宏（脚本、调试器）都先把输入转换为动作序列，再交给这里统一执行。
+//!
+//! 序列中途失败（或 panic）时，由 [`InputAction::Down`] 按下且尚未释放的按键按相反顺序释放；
+//! 正常结束时保留未释放的按键，与单独调用 [`key_down`](super::key_down) 的行为一致。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, Modifier, WindowHandle};
+use std::time::Duration;
+
+/// 一步输入动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputAction {
+    /// 按下按键
+    Down(Key),
+    /// 释放按键
+    Up(Key),
+    /// 点击按键（使用默认按下时长）
+    Click(Key),
+    /// 输入单个字符
+    Char(char),
+    /// 输入字符串
+    Text(String),
+    /// 等待
+    Sleep(Duration),
+    /// 组合键（使用默认按下时长）
+    Combo(Vec<Modifier>, Key),
+    /// 单独点击修饰键（使用默认按下时长，区分左右）
+    ModifierClick(Modifier),
+    /// 点击原始虚拟键码，用于 [`Key`] 未覆盖的按键（如 PrintScreen）；`None` 使用默认按下时长
+    VkClick(u16, Option<Duration>),
+}
+
+/// 全局发送：执行动作序列
+///
+/// 整个序列持有逻辑操作锁，其他线程的输入不会插入其中。任一步失败时停止执行并返回错误，
+/// 序列中按下但尚未释放的按键会按相反顺序释放。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{send_sequence, InputAction, Key, Modifier};
+/// use std::time::Duration;
+///
+/// send_sequence(&[
+///     InputAction::Combo(vec![Modifier::Control], Key::L),
+///     InputAction::Text("example.com".to_string()),
+///     InputAction::Sleep(Duration::from_millis(50)),
+///     InputAction::Click(Key::Enter),
+/// ])
+/// .unwrap();
+/// ```
+pub fn send_sequence(actions: &[InputAction]) -> Result<()> {
+    send_sequence_to(0, actions)
+}
+
+/// 向指定窗口发送：执行动作序列，失败时的清理与 [`send_sequence`] 相同
+#[cfg(feature = "window_target")]
+pub fn send_sequence_to_window(hwnd: WindowHandle, actions: &[InputAction]) -> Result<()> {
+    if hwnd == 0 {
+        return Err(KeyboardSenderError::InvalidWindowHandle(
+            "window handle cannot be 0".to_string(),
+        ));
+    }
+    send_sequence_to(hwnd, actions)
+}
+
+/// 执行动作序列，`hwnd` 为 0 时全局发送（与命令的 `hwnd:0` 约定一致）
+pub(crate) fn send_sequence_to(hwnd: WindowHandle, actions: &[InputAction]) -> Result<()> {
+    let _op = super::begin_operation();
+    let mut held = HeldKeys::new(|key| perform(hwnd, &InputAction::Up(key)));
+    for action in actions {
+        perform(hwnd, action)?;
+        match *action {
+            InputAction::Down(key) => held.press(key),
+            InputAction::Up(key) => held.release(key),
+            _ => {}
+        }
+    }
+    held.keep();
+    Ok(())
+}
+
+/// 执行单个动作
+fn perform(hwnd: WindowHandle, action: &InputAction) -> Result<()> {
+    if let InputAction::Sleep(duration) = action {
+        super::clock().sleep(*duration);
+        return Ok(());
+    }
+    if hwnd == 0 {
+        return match action {
+            InputAction::Down(key) => super::key_down(*key),
+            InputAction::Up(key) => super::key_up(*key),
+            InputAction::Click(key) => super::key_click(*key, None),
+            InputAction::Char(c) => super::send_char(*c),
+            InputAction::Text(text) => super::type_string(text),
+            InputAction::Combo(modifiers, key) => super::press_combination(modifiers, *key, None),
+            InputAction::ModifierClick(modifier) => super::modifier_click(*modifier, None),
+            InputAction::VkClick(vk, duration) => super::vk_click(*vk, *duration),
+            InputAction::Sleep(_) => Ok(()),
+        };
+    }
+
+    #[cfg(not(feature = "window_target"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled(
+        "window_target".to_string(),
+    ));
+
+    #[cfg(feature = "window_target")]
+    match action {
+        InputAction::Down(key) => super::send_key_down_to_window(hwnd, *key),
+        InputAction::Up(key) => super::send_key_up_to_window(hwnd, *key),
+        InputAction::Click(key) => super::send_key_click_to_window(hwnd, *key, None),
+        InputAction::Char(c) => super::send_char_to_window(hwnd, *c),
+        InputAction::Text(text) => super::type_string_to_window(hwnd, text),
+        InputAction::Combo(modifiers, key) => {
+            super::press_combination_to_window(hwnd, modifiers, *key, None)
+        }
+        InputAction::ModifierClick(modifier) => {
+            super::send_modifier_click_to_window(hwnd, *modifier, None)
+        }
+        InputAction::VkClick(vk, duration) => super::send_vk_click_to_window(hwnd, *vk, *duration),
+        InputAction::Sleep(_) => Ok(()),
+    }
+}
+
+/// 序列中按下且尚未释放的按键，丢弃时按相反顺序释放（忽略错误）
+struct HeldKeys<F: FnMut(Key) -> Result<()>> {
+    keys: Vec<Key>,
+    release: F,
+}
+
+impl<F: FnMut(Key) -> Result<()>> HeldKeys<F> {
+    fn new(release: F) -> Self {
+        Self {
+            keys: Vec::new(),
+            release,
+        }
+    }
+
+    fn press(&mut self, key: Key) {
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+    }
+
+    fn release(&mut self, key: Key) {
+        self.keys.retain(|&k| k != key);
+    }
+
+    /// 序列正常结束，保留仍按下的按键
+    fn keep(mut self) {
+        self.keys.clear();
+    }
+}
+
+impl<F: FnMut(Key) -> Result<()>> Drop for HeldKeys<F> {
+    fn drop(&mut self) {
+        while let Some(key) = self.keys.pop() {
+            let _ = (self.release)(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_held_keys_released_in_reverse() {
+        let released = RefCell::new(Vec::new());
+        {
+            let mut held = HeldKeys::new(|key| {
+                released.borrow_mut().push(key);
+                Ok(())
+            });
+            held.press(Key::A);
+            held.press(Key::B);
+            held.press(Key::A);
+            held.press(Key::C);
+            held.release(Key::B);
+        }
+        assert_eq!(*released.borrow(), vec![Key::C, Key::A]);
+
+        released.borrow_mut().clear();
+        let mut held = HeldKeys::new(|key| {
+            released.borrow_mut().push(key);
+            Ok(())
+        });
+        held.press(Key::A);
+        held.keep();
+        assert!(released.borrow().is_empty());
+    }
+}
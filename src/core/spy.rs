@@ -0,0 +1,406 @@
+// src/core/spy.rs
+//! 窗口消息监视（调试投递问题）
+//!
+//! 在目标窗口所在线程上安装 `WH_GETMESSAGE`（投递消息）和 `WH_CALLWNDPROC`
+//! （同步发送的消息）钩子，观察 `WM_KEYDOWN` / `WM_CHAR` 等消息是否真正到达目标窗口。
+//! 不注入 DLL 时 Windows 只允许对本进程的线程安装这两种钩子，因此钩子只用于本进程的窗口
+//! （例如测试程序自己创建的窗口）。其他进程的窗口改为在发送端监视：报告本库经
+//! `PostMessage` / `SendMessageTimeout` 向该窗口发出的消息（[`SpySource::Sender`]），
+//! 看不到目标如何处理这些消息，也看不到其他程序发送的消息。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use windows::Win32::{
+    Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+    System::Threading::{GetCurrentProcessId, GetCurrentThreadId},
+    UI::WindowsAndMessaging::{
+        CallNextHookEx, GetWindowThreadProcessId, IsChild, SetWindowsHookExW, UnhookWindowsHookEx,
+        CWPSTRUCT, HHOOK, MSG, PM_REMOVE, WH_CALLWNDPROC, WH_GETMESSAGE, WM_CHAR, WM_DEADCHAR,
+        WM_IME_CHAR, WM_KEYDOWN, WM_KEYFIRST, WM_KEYLAST, WM_KEYUP, WM_SYSCHAR, WM_SYSDEADCHAR,
+        WM_SYSKEYDOWN, WM_SYSKEYUP, WM_UNICHAR,
+    },
+};
+
+/// 消息到达方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpyDelivery {
+    /// 经消息队列投递（`PostMessage`，被 `GetMessage`/`PeekMessage` 取出）
+    Posted,
+    /// 同步发送（`SendMessage` / `SendMessageTimeout`）
+    Sent,
+}
+
+/// 消息的观察位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpySource {
+    /// 目标窗口线程上的钩子（本进程的窗口）
+    TargetThread,
+    /// 本库的发送端（其他进程的窗口）
+    Sender,
+}
+
+/// 监视到的一条消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpiedMessage {
+    /// 实际接收消息的窗口（可能是目标窗口的子窗口）
+    pub hwnd: WindowHandle,
+    pub message: u32,
+    pub wparam: usize,
+    pub lparam: isize,
+    pub delivery: SpyDelivery,
+    pub source: SpySource,
+}
+
+impl SpiedMessage {
+    /// 消息名称，键盘类消息以外返回 `None`
+    pub fn name(&self) -> Option<&'static str> {
+        message_name(self.message)
+    }
+}
+
+impl fmt::Display for SpiedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "0x{:04X}", self.message)?,
+        }
+        write!(
+            f,
+            " hwnd={:#x} wParam={:#x} lParam={:#x} ({:?})",
+            self.hwnd, self.wparam, self.lparam, self.delivery
+        )?;
+        if self.source == SpySource::Sender {
+            write!(f, " [sender]")?;
+        }
+        Ok(())
+    }
+}
+
+/// 消息过滤条件
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum MessageFilter {
+    /// 键盘类消息（`WM_KEYFIRST..=WM_KEYLAST` 及 `WM_IME_CHAR`）
+    #[default]
+    Keyboard,
+    /// 全部消息
+    All,
+    /// 仅指定的消息
+    Only(Vec<u32>),
+}
+
+impl MessageFilter {
+    /// 判断消息是否符合条件
+    pub fn matches(&self, message: u32) -> bool {
+        match self {
+            MessageFilter::Keyboard => {
+                (WM_KEYFIRST..=WM_KEYLAST).contains(&message) || message == WM_IME_CHAR
+            }
+            MessageFilter::All => true,
+            MessageFilter::Only(messages) => messages.contains(&message),
+        }
+    }
+}
+
+/// 键盘类消息的名称
+pub fn message_name(message: u32) -> Option<&'static str> {
+    Some(match message {
+        WM_KEYDOWN => "WM_KEYDOWN",
+        WM_KEYUP => "WM_KEYUP",
+        WM_CHAR => "WM_CHAR",
+        WM_DEADCHAR => "WM_DEADCHAR",
+        WM_SYSKEYDOWN => "WM_SYSKEYDOWN",
+        WM_SYSKEYUP => "WM_SYSKEYUP",
+        WM_SYSCHAR => "WM_SYSCHAR",
+        WM_SYSDEADCHAR => "WM_SYSDEADCHAR",
+        WM_UNICHAR => "WM_UNICHAR",
+        WM_IME_CHAR => "WM_IME_CHAR",
+        _ => return None,
+    })
+}
+
+type SpyCallback = Arc<dyn Fn(&SpiedMessage) + Send + Sync>;
+
+struct SpyEntry {
+    id: u64,
+    hwnd: WindowHandle,
+    thread_id: u32,
+    source: SpySource,
+    filter: MessageFilter,
+    callback: SpyCallback,
+}
+
+/// 每个被监视线程上的钩子（原始句柄值）及引用计数
+struct ThreadHooks {
+    get_message: isize,
+    call_wnd_proc: isize,
+    refs: usize,
+}
+
+#[derive(Default)]
+struct SpyRegistry {
+    entries: Vec<SpyEntry>,
+    hooks: HashMap<u32, ThreadHooks>,
+}
+
+static REGISTRY: Mutex<Option<SpyRegistry>> = Mutex::new(None);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn registry() -> MutexGuard<'static, Option<SpyRegistry>> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 消息监视器，丢弃时停止监视（最后一个监视器移除该线程上的钩子）
+#[derive(Debug)]
+pub struct MessageSpy {
+    id: u64,
+    source: SpySource,
+}
+
+impl MessageSpy {
+    /// 观察位置：本进程的窗口为目标线程，其他进程的窗口为发送端
+    pub fn source(&self) -> SpySource {
+        self.source
+    }
+
+    /// 停止监视
+    pub fn stop(self) {}
+}
+
+impl Drop for MessageSpy {
+    fn drop(&mut self) {
+        let mut guard = registry();
+        let Some(reg) = guard.as_mut() else {
+            return;
+        };
+        let Some(index) = reg.entries.iter().position(|e| e.id == self.id) else {
+            return;
+        };
+        let entry = reg.entries.remove(index);
+        if entry.source == SpySource::Sender {
+            return;
+        }
+        if let Some(hooks) = reg.hooks.get_mut(&entry.thread_id) {
+            hooks.refs -= 1;
+            if hooks.refs == 0 {
+                unsafe {
+                    let _ = UnhookWindowsHookEx(HHOOK(hooks.get_message));
+                    let _ = UnhookWindowsHookEx(HHOOK(hooks.call_wnd_proc));
+                }
+                reg.hooks.remove(&entry.thread_id);
+            }
+        }
+    }
+}
+
+/// 监视发往窗口（及其子窗口）的消息，每条符合 `filter` 的消息都会调用 `callback`
+///
+/// 本进程的窗口：回调在目标窗口的线程上、消息被处理之前调用，应尽快返回。
+/// 其他进程的窗口：回调在发送线程上、消息投递或发送之后调用，只报告本库发出的消息
+/// （见 [`SpySource::Sender`]）。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{send_key_click_to_window, spy_window_messages, Key, MessageFilter};
+///
+/// let hwnd = 0x1A2B;
+/// let spy = spy_window_messages(hwnd, MessageFilter::Keyboard, |msg| println!("{}", msg)).unwrap();
+/// send_key_click_to_window(hwnd, Key::A, None).unwrap();
+/// drop(spy);
+/// # }
+/// ```
+pub fn spy_window_messages<F>(
+    hwnd: WindowHandle,
+    filter: MessageFilter,
+    callback: F,
+) -> Result<MessageSpy>
+where
+    F: Fn(&SpiedMessage) + Send + Sync + 'static,
+{
+    let mut process_id = 0u32;
+    let thread_id = unsafe { GetWindowThreadProcessId(HWND(hwnd), Some(&mut process_id)) };
+    if thread_id == 0 {
+        return Err(KeyboardSenderError::InvalidWindowHandle(format!(
+            "window {:#x} does not exist",
+            hwnd
+        )));
+    }
+    let source = if process_id == unsafe { GetCurrentProcessId() } {
+        SpySource::TargetThread
+    } else {
+        SpySource::Sender
+    };
+
+    let mut guard = registry();
+    let reg = guard.get_or_insert_with(SpyRegistry::default);
+    if source == SpySource::TargetThread {
+        match reg.hooks.get_mut(&thread_id) {
+            Some(hooks) => hooks.refs += 1,
+            None => {
+                let hooks = install_hooks(thread_id)?;
+                reg.hooks.insert(thread_id, hooks);
+            }
+        }
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    reg.entries.push(SpyEntry {
+        id,
+        hwnd,
+        thread_id,
+        source,
+        filter,
+        callback: Arc::new(callback),
+    });
+    Ok(MessageSpy { id, source })
+}
+
+/// 发送端报告本库发给窗口的消息，分发给监视其他进程窗口的监视器
+pub(crate) fn notify_sent(
+    hwnd: WindowHandle,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+    delivery: SpyDelivery,
+) {
+    dispatch_to(
+        SpiedMessage {
+            hwnd,
+            message,
+            wparam,
+            lparam,
+            delivery,
+            source: SpySource::Sender,
+        },
+        |entry| entry.source == SpySource::Sender,
+    );
+}
+
+fn install_hooks(thread_id: u32) -> Result<ThreadHooks> {
+    unsafe {
+        let get_message = SetWindowsHookExW(
+            WH_GETMESSAGE,
+            Some(get_message_proc),
+            HINSTANCE(0),
+            thread_id,
+        )
+        .map_err(|_| KeyboardSenderError::WindowsError)?;
+        let call_wnd_proc =
+            match SetWindowsHookExW(WH_CALLWNDPROC, Some(call_wnd_proc), HINSTANCE(0), thread_id) {
+                Ok(hook) => hook,
+                Err(_) => {
+                    let _ = UnhookWindowsHookEx(get_message);
+                    return Err(KeyboardSenderError::WindowsError);
+                }
+            };
+        Ok(ThreadHooks {
+            get_message: get_message.0,
+            call_wnd_proc: call_wnd_proc.0,
+            refs: 1,
+        })
+    }
+}
+
+/// 把钩子观察到的消息分发给当前线程上匹配的监视器
+fn dispatch(msg: SpiedMessage) {
+    let thread_id = unsafe { GetCurrentThreadId() };
+    dispatch_to(msg, |entry| {
+        entry.source == SpySource::TargetThread && entry.thread_id == thread_id
+    });
+}
+
+/// 把消息分发给满足 `select` 且窗口、过滤条件匹配的监视器
+fn dispatch_to(msg: SpiedMessage, select: impl Fn(&SpyEntry) -> bool) {
+    // 先复制出回调再释放锁，回调中可以安全地丢弃监视器
+    let callbacks: Vec<SpyCallback> = match registry().as_ref() {
+        Some(reg) => reg
+            .entries
+            .iter()
+            .filter(|e| select(e) && e.filter.matches(msg.message))
+            .filter(|e| {
+                e.hwnd == msg.hwnd || unsafe { IsChild(HWND(e.hwnd), HWND(msg.hwnd)).as_bool() }
+            })
+            .map(|e| Arc::clone(&e.callback))
+            .collect(),
+        None => return,
+    };
+    for callback in callbacks {
+        callback(&msg);
+    }
+}
+
+unsafe extern "system" fn get_message_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    // 只报告被取出的消息，PM_NOREMOVE 的窥视会在之后再次出现
+    if code >= 0 && wparam.0 == PM_REMOVE.0 as usize && lparam.0 != 0 {
+        let msg = &*(lparam.0 as *const MSG);
+        dispatch(SpiedMessage {
+            hwnd: msg.hwnd.0,
+            message: msg.message,
+            wparam: msg.wParam.0,
+            lparam: msg.lParam.0,
+            delivery: SpyDelivery::Posted,
+            source: SpySource::TargetThread,
+        });
+    }
+    CallNextHookEx(HHOOK(0), code, wparam, lparam)
+}
+
+unsafe extern "system" fn call_wnd_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 && lparam.0 != 0 {
+        let cwp = &*(lparam.0 as *const CWPSTRUCT);
+        dispatch(SpiedMessage {
+            hwnd: cwp.hwnd.0,
+            message: cwp.message,
+            wparam: cwp.wParam.0,
+            lparam: cwp.lParam.0,
+            delivery: SpyDelivery::Sent,
+            source: SpySource::TargetThread,
+        });
+    }
+    CallNextHookEx(HHOOK(0), code, wparam, lparam)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        assert!(MessageFilter::Keyboard.matches(WM_KEYDOWN));
+        assert!(MessageFilter::Keyboard.matches(WM_IME_CHAR));
+        assert!(!MessageFilter::Keyboard.matches(0x000F));
+        assert!(MessageFilter::All.matches(0x000F));
+        assert!(MessageFilter::Only(vec![WM_CHAR]).matches(WM_CHAR));
+        assert!(!MessageFilter::Only(vec![WM_CHAR]).matches(WM_KEYDOWN));
+    }
+
+    #[test]
+    fn test_display() {
+        let msg = SpiedMessage {
+            hwnd: 0x10,
+            message: WM_CHAR,
+            wparam: 0x41,
+            lparam: 1,
+            delivery: SpyDelivery::Posted,
+            source: SpySource::TargetThread,
+        };
+        assert_eq!(
+            msg.to_string(),
+            "WM_CHAR hwnd=0x10 wParam=0x41 lParam=0x1 (Posted)"
+        );
+        let sent = SpiedMessage {
+            delivery: SpyDelivery::Sent,
+            source: SpySource::Sender,
+            ..msg
+        };
+        assert_eq!(
+            sent.to_string(),
+            "WM_CHAR hwnd=0x10 wParam=0x41 lParam=0x1 (Sent) [sender]"
+        );
+        assert_eq!(message_name(0x000F), None);
+    }
+}
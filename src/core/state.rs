@@ -0,0 +1,346 @@
+// src/core/state.rs
+//! 按键状态查询
+//!
+//! 发送前查询按键的实时状态，例如确认 Shift 没有被用户按住、CapsLock 是否开启，
+//! 以免输入的大小写或快捷键被意外改变；[`set_caps_lock`] 等函数把锁定键设置为指定状态。
+use crate::error::Result;
+use crate::types::{Key, Modifier};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use windows::Win32::{
+    Foundation::{LPARAM, LRESULT, WPARAM},
+    UI::{
+        Input::KeyboardAndMouse::{GetAsyncKeyState, GetKeyState},
+        WindowsAndMessaging::{CallNextHookEx, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED, LLKHF_UP},
+    },
+};
+
+use super::hook_thread::{install_keyboard_hook, HookThread};
+
+/// 按键状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KeyState {
+    /// 当前是否处于按下状态（`GetAsyncKeyState`，包括物理按下和注入的按下）
+    pub down: bool,
+    /// 锁定键是否处于开启状态（`GetKeyState`，仅对 CapsLock、NumLock、ScrollLock 等有意义）
+    pub toggled: bool,
+}
+
+impl fmt::Display for KeyState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let down = if self.down { "down" } else { "up" };
+        if self.toggled {
+            write!(f, "{}, toggled", down)
+        } else {
+            f.write_str(down)
+        }
+    }
+}
+
+/// 查询按键状态
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{get_key_state, get_modifier_state, type_string, Key, Modifier};
+///
+/// if get_key_state(Key::CapsLock).toggled {
+///     println!("CapsLock is on");
+/// }
+/// if !get_modifier_state(Modifier::Shift).down {
+///     type_string("hello").unwrap();
+/// }
+/// ```
+pub fn get_key_state(key: Key) -> KeyState {
+    vk_state(super::key_to_vk(key))
+}
+
+/// 查询修饰键状态，区分左右的修饰键只查询对应一侧
+pub fn get_modifier_state(modifier: Modifier) -> KeyState {
+    vk_state(super::modifier_to_vk(modifier))
+}
+
+/// 按键当前是否处于按下状态
+pub fn is_key_down(key: Key) -> bool {
+    get_key_state(key).down
+}
+
+/// 锁定键当前是否处于开启状态
+pub fn is_toggled(key: Key) -> bool {
+    get_key_state(key).toggled
+}
+
+/// 确保 CapsLock 处于指定状态，只在需要改变时发送按键
+///
+/// CapsLock 开启时按键模拟方式输入的字母大小写会颠倒，输入前可先关闭。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{set_caps_lock, set_text_injection, type_string, TextInjection};
+///
+/// set_text_injection(TextInjection::KeyStrokes);
+/// set_caps_lock(false).unwrap();
+/// type_string("Hello").unwrap();
+/// ```
+pub fn set_caps_lock(on: bool) -> Result<()> {
+    set_lock_key(Key::CapsLock, on)
+}
+
+/// 确保 NumLock 处于指定状态，只在需要改变时发送按键
+pub fn set_num_lock(on: bool) -> Result<()> {
+    set_lock_key(Key::NumLock, on)
+}
+
+/// 确保 ScrollLock 处于指定状态，只在需要改变时发送按键
+pub fn set_scroll_lock(on: bool) -> Result<()> {
+    set_lock_key(Key::ScrollLock, on)
+}
+
+/// 临时开启 NumLock 执行操作，结束后（包括出错时）恢复关闭状态
+///
+/// NumLock 关闭时小键盘数字键会被当作方向键、Home 等导航键处理。
+pub fn with_num_lock<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    struct Restore;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let _ = super::global::key_click(Key::NumLock, None);
+        }
+    }
+
+    if is_toggled(Key::NumLock) {
+        return f();
+    }
+    super::global::key_click(Key::NumLock, None)?;
+    let _restore = Restore;
+    f()
+}
+
+fn set_lock_key(key: Key, on: bool) -> Result<()> {
+    if is_toggled(key) != on {
+        super::global::key_click(key, None)?;
+    }
+    Ok(())
+}
+
+/// 区分左右的修饰键，用于检查物理按下的修饰键
+const SIDED_MODIFIERS: [Modifier; 8] = [
+    Modifier::LeftShift,
+    Modifier::RightShift,
+    Modifier::LeftControl,
+    Modifier::RightControl,
+    Modifier::LeftAlt,
+    Modifier::RightAlt,
+    Modifier::LeftMeta,
+    Modifier::RightMeta,
+];
+
+/// 未分配的虚拟键，释放 Alt/Win 前按一次，避免单独释放激活菜单栏或开始菜单
+const MASK_VK: u16 = 0xE8;
+
+/// 被临时释放的修饰键，调用 [`NeutralizedModifiers::restore`] 或丢弃时重新按下
+///
+/// 释放期间用户可能已经松开了修饰键，而注入的松开事件让 `GetAsyncKeyState` 无法区分这种情况，
+/// 因此释放期间用低级键盘钩子跟踪物理按键，恢复时只重新按下仍被物理按住的修饰键。
+#[must_use = "dropping the guard presses the modifiers again immediately"]
+#[derive(Debug, Default)]
+pub struct NeutralizedModifiers {
+    released: Vec<Modifier>,
+    tracker: Option<PhysicalKeyTracker>,
+}
+
+impl NeutralizedModifiers {
+    /// 被释放的修饰键
+    pub fn released(&self) -> &[Modifier] {
+        &self.released
+    }
+
+    /// 重新按下被释放的修饰键，返回第一个错误（其余仍会尝试按下）
+    pub fn restore(mut self) -> Result<()> {
+        self.restore_all()
+    }
+
+    fn restore_all(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        // 先移除钩子，重新按下的注入事件不会被记录
+        let physically_up = self
+            .tracker
+            .take()
+            .map(PhysicalKeyTracker::finish)
+            .unwrap_or_default();
+        for modifier in self.released.drain(..).rev() {
+            let vk = super::modifier_to_vk(modifier);
+            // 自动重复可能已经让它重新处于按下状态；用户已松开的修饰键不再按下
+            if is_vk_down(vk) || physically_up.contains(&vk) {
+                continue;
+            }
+            let pressed = super::global::reinject_vk(vk, false);
+            if result.is_ok() {
+                result = pressed;
+            }
+        }
+        result
+    }
+}
+
+impl Drop for NeutralizedModifiers {
+    fn drop(&mut self) {
+        let _ = self.restore_all();
+    }
+}
+
+/// 临时释放用户正按住的修饰键，避免输入的文本被改变大小写或触发快捷键
+///
+/// 通过 [`hold_modifier`](super::hold::hold_modifier) 保持的修饰键不受影响。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{neutralize_modifiers, type_string};
+///
+/// let neutral = neutralize_modifiers().unwrap();
+/// type_string("hello").unwrap();
+/// neutral.restore().unwrap();
+/// ```
+pub fn neutralize_modifiers() -> Result<NeutralizedModifiers> {
+    let held = super::hold::held_modifiers();
+    let mut neutralized = NeutralizedModifiers::default();
+    if !SIDED_MODIFIERS
+        .iter()
+        .any(|&modifier| is_vk_down(super::modifier_to_vk(modifier)))
+    {
+        return Ok(neutralized);
+    }
+    // 钩子安装失败时无法得知物理状态，恢复时按仍被按住处理
+    neutralized.tracker = PhysicalKeyTracker::install().ok();
+    for modifier in SIDED_MODIFIERS {
+        let family = super::hotkeys::modifier_bit(modifier);
+        if held
+            .iter()
+            .any(|&h| super::hotkeys::modifier_bit(h) == family)
+        {
+            continue;
+        }
+        let vk = super::modifier_to_vk(modifier);
+        if !is_vk_down(vk) {
+            continue;
+        }
+        if matches!(
+            modifier,
+            Modifier::LeftAlt | Modifier::RightAlt | Modifier::LeftMeta | Modifier::RightMeta
+        ) {
+            // Win 键仍按下，跳过系统快捷键互锁
+            super::global::reinject_vk(MASK_VK, false)?;
+            super::global::reinject_vk(MASK_VK, true)?;
+        }
+        // 失败时已释放的修饰键随守卫丢弃被重新按下
+        super::global::inject_vk(vk, true)?;
+        neutralized.released.push(modifier);
+    }
+    Ok(neutralized)
+}
+
+/// 物理按键跟踪器：在后台线程上安装低级键盘钩子，记录被物理松开（且之后未再按下）的虚拟键
+#[derive(Debug)]
+struct PhysicalKeyTracker {
+    released: Arc<Mutex<HashSet<u16>>>,
+    hook: HookThread,
+}
+
+impl PhysicalKeyTracker {
+    fn install() -> Result<Self> {
+        let released = Arc::new(Mutex::new(HashSet::new()));
+        let hook = {
+            let released = released.clone();
+            HookThread::spawn(
+                "modifier-tracker",
+                move || {
+                    TRACKED.with(|t| *t.borrow_mut() = Some(released));
+                    install_keyboard_hook(Some(tracker_proc))
+                },
+                |_| false,
+            )?
+        };
+        Ok(Self { released, hook })
+    }
+
+    /// 移除钩子，返回期间被物理松开的虚拟键
+    fn finish(mut self) -> HashSet<u16> {
+        self.hook.stop();
+        std::mem::take(&mut *self.released.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+thread_local! {
+    static TRACKED: RefCell<Option<Arc<Mutex<HashSet<u16>>>>> = const { RefCell::new(None) };
+}
+
+unsafe extern "system" fn tracker_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        if info.flags.0 & LLKHF_INJECTED.0 == 0 {
+            TRACKED.with(|t| {
+                if let Some(released) = t.borrow().as_ref() {
+                    let mut released = released.lock().unwrap_or_else(|e| e.into_inner());
+                    record_physical(
+                        &mut released,
+                        info.vkCode as u16,
+                        info.flags.0 & LLKHF_UP.0 != 0,
+                    );
+                }
+            });
+        }
+    }
+    CallNextHookEx(HHOOK(0), code, wparam, lparam)
+}
+
+/// 记录一次物理按键事件：松开时加入，再次按下时移除
+fn record_physical(released: &mut HashSet<u16>, vk: u16, up: bool) {
+    if up {
+        released.insert(vk);
+    } else {
+        released.remove(&vk);
+    }
+}
+
+/// 虚拟键的状态
+pub(crate) fn vk_state(vk: u16) -> KeyState {
+    KeyState {
+        down: is_vk_down(vk),
+        toggled: is_vk_toggled(vk),
+    }
+}
+
+/// 虚拟键当前是否处于按下状态
+pub(crate) fn is_vk_down(vk: u16) -> bool {
+    (unsafe { GetAsyncKeyState(i32::from(vk)) }) as u16 & 0x8000 != 0
+}
+
+/// 虚拟键是否处于开启状态
+pub(crate) fn is_vk_toggled(vk: u16) -> bool {
+    (unsafe { GetKeyState(i32::from(vk)) }) & 1 != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(KeyState::default().to_string(), "up");
+        let state = KeyState {
+            down: true,
+            toggled: true,
+        };
+        assert_eq!(state.to_string(), "down, toggled");
+    }
+
+    #[test]
+    fn test_record_physical() {
+        let mut released = HashSet::new();
+        record_physical(&mut released, 0xA0, true);
+        record_physical(&mut released, 0xA2, true);
+        record_physical(&mut released, 0xA2, false);
+        assert_eq!(released, HashSet::from([0xA0]));
+    }
+}
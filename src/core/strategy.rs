@@ -0,0 +1,496 @@
+// src/core/strategy.rs
+//! 注入方式协商与降级
+//!
+//! 首选的注入方式不可用时（目标不在前台、被 UIPI 拦截、远程会话下虚拟键码不可靠等），
+//! 由 [`StrategyNegotiator`] 按顺序尝试下一种可行的方式，而不是让每个函数各自失败。
+//! 候选顺序依次参考：兼容性数据库中为目标进程登记的方式（[`set_compatibility`]）、
+//! 自检报告（[`self_test`](super::self_test::self_test)）中不可用的方式、当前是否为远程会话。
+//! 目标进程的完整性级别高于本进程时（如以管理员身份运行），UIPI 会拦截全部注入方式，
+//! 协商直接报告原因而不逐一尝试；[`StrategyNegotiator::capabilities`] 给出这些检测结果。
+//! 结果 [`Negotiated`] 标明最终使用的方式和被跳过的方式及原因。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, WindowHandle};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use windows::Win32::{
+    Foundation::{CloseHandle, HANDLE},
+    Security::{
+        GetSidSubAuthority, GetSidSubAuthorityCount, GetTokenInformation, TokenIntegrityLevel,
+        TOKEN_MANDATORY_LABEL, TOKEN_QUERY,
+    },
+    System::Threading::{
+        GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+    },
+    UI::WindowsAndMessaging::{GetSystemMetrics, SM_REMOTESESSION},
+};
+
+use super::config::{InjectionMode, TextInjection};
+use super::self_test::{SelfTestReport, SelfTestStrategy};
+use super::text_options::{effective_text_options, with_text_options, TextOptions};
+use super::window_target::WindowSendMode;
+
+/// 按键的默认降级顺序
+pub const DEFAULT_KEY_STRATEGIES: &[SelfTestStrategy] = &[
+    SelfTestStrategy::SendInputVirtualKey,
+    SelfTestStrategy::SendInputScanCode,
+    SelfTestStrategy::PostWmKey,
+];
+
+/// 文本的默认降级顺序
+pub const DEFAULT_TEXT_STRATEGIES: &[SelfTestStrategy] = &[
+    SelfTestStrategy::SendInputUnicode,
+    SelfTestStrategy::SendInputKeyStrokes,
+    SelfTestStrategy::PostWmChar,
+    SelfTestStrategy::SendWmChar,
+];
+
+/// 兼容性数据库中一个进程的登记
+#[derive(Debug, Clone, Default)]
+struct CompatibilityEntry {
+    /// 优先使用的注入方式
+    strategies: Vec<SelfTestStrategy>,
+    /// 发往该进程窗口的按键之间的最小间隔
+    key_repeat_gap: Option<Duration>,
+}
+
+/// 兼容性数据库：进程名（小写）-> 登记
+static COMPATIBILITY: Mutex<Option<HashMap<String, CompatibilityEntry>>> = Mutex::new(None);
+/// 每次修改数据库时递增，用于使缓存失效
+static COMPATIBILITY_VERSION: AtomicU64 = AtomicU64::new(0);
+
+fn compatibility_db() -> MutexGuard<'static, Option<HashMap<String, CompatibilityEntry>>> {
+    COMPATIBILITY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn update_entry(process: &str, update: impl FnOnce(&mut CompatibilityEntry)) {
+    update(
+        compatibility_db()
+            .get_or_insert_with(HashMap::new)
+            .entry(process.to_lowercase())
+            .or_default(),
+    );
+    COMPATIBILITY_VERSION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 为进程登记优先使用的注入方式（如 `"game.exe"` 只接受扫描码）
+///
+/// 协商时这些方式排在默认顺序之前，与当前操作无关的方式被忽略。
+pub fn set_compatibility(process: &str, strategies: &[SelfTestStrategy]) {
+    update_entry(process, |entry| entry.strategies = strategies.to_vec());
+}
+
+/// 查询进程登记的注入方式
+pub fn compatibility(process: &str) -> Option<Vec<SelfTestStrategy>> {
+    compatibility_db()
+        .as_ref()
+        .and_then(|db| db.get(&process.to_lowercase()))
+        .filter(|entry| !entry.strategies.is_empty())
+        .map(|entry| entry.strategies.clone())
+}
+
+/// 为进程登记按键之间的最小间隔（见 [`key_repeat_gap`](super::pacing::key_repeat_gap)），
+/// 传入零表示不限制（可用于关闭已知终端的默认间隔）
+pub fn set_key_repeat_gap(process: &str, gap: Duration) {
+    update_entry(process, |entry| entry.key_repeat_gap = Some(gap));
+}
+
+/// 查询进程登记的按键间隔
+pub fn registered_key_repeat_gap(process: &str) -> Option<Duration> {
+    compatibility_db()
+        .as_ref()
+        .and_then(|db| db.get(&process.to_lowercase()))
+        .and_then(|entry| entry.key_repeat_gap)
+}
+
+/// 移除进程的全部登记
+pub fn clear_compatibility(process: &str) {
+    if let Some(db) = compatibility_db().as_mut() {
+        db.remove(&process.to_lowercase());
+    }
+    COMPATIBILITY_VERSION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 兼容性数据库的版本
+pub(crate) fn compatibility_version() -> u64 {
+    COMPATIBILITY_VERSION.load(Ordering::Relaxed)
+}
+
+/// 跳过某种注入方式的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// 自检报告显示该方式不可用
+    FailedSelfTest,
+    /// 全局注入需要目标位于前台，但无法切换焦点
+    NoFocus,
+    /// 目标进程的完整性级别高于本进程，注入被 UIPI 拦截
+    Elevated,
+    /// 尝试发送时出错
+    Failed(String),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::FailedSelfTest => f.write_str("failed self test"),
+            SkipReason::NoFocus => f.write_str("target is not in the foreground"),
+            SkipReason::Elevated => f.write_str("target runs at a higher integrity level (UIPI)"),
+            SkipReason::Failed(error) => write!(f, "failed: {}", error),
+        }
+    }
+}
+
+/// 协商结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiated {
+    /// 最终使用的注入方式
+    pub strategy: SelfTestStrategy,
+    /// 之前被跳过的方式及原因（按尝试顺序）
+    pub skipped: Vec<(SelfTestStrategy, SkipReason)>,
+}
+
+impl Negotiated {
+    /// 是否降级（首选方式未被使用）
+    pub fn degraded(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+}
+
+/// 目标窗口的注入能力检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// 当前为远程桌面会话（扫描码优先于虚拟键码）
+    pub remote_session: bool,
+    /// 目标进程的完整性级别高于本进程，全部注入方式都会被 UIPI 拦截
+    pub target_elevated: bool,
+    /// 目标当前位于前台
+    pub foreground: bool,
+}
+
+/// 注入方式协商器
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{self_test, set_compatibility, SelfTestStrategy, StrategyNegotiator};
+///
+/// set_compatibility("game.exe", &[SelfTestStrategy::SendInputScanCode]);
+/// let negotiator = StrategyNegotiator::new().with_report(self_test());
+/// let result = negotiator.type_string(0x1A2B, "hello").unwrap();
+/// println!("typed via {:?}, skipped {:?}", result.strategy, result.skipped);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StrategyNegotiator {
+    report: Option<SelfTestReport>,
+    focus_target: bool,
+}
+
+impl Default for StrategyNegotiator {
+    fn default() -> Self {
+        Self {
+            report: None,
+            focus_target: true,
+        }
+    }
+}
+
+impl StrategyNegotiator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用自检报告跳过不可用的方式
+    pub fn with_report(mut self, report: SelfTestReport) -> Self {
+        self.report = Some(report);
+        self
+    }
+
+    /// 全局注入前是否尝试把目标切换到前台（默认是）
+    pub fn focus_target(mut self, enabled: bool) -> Self {
+        self.focus_target = enabled;
+        self
+    }
+
+    /// 向窗口点击按键
+    pub fn key_click(&self, hwnd: WindowHandle, key: Key) -> Result<Negotiated> {
+        self.negotiate(hwnd, DEFAULT_KEY_STRATEGIES, |strategy| match strategy {
+            SelfTestStrategy::PostWmKey => super::send_key_click_to_window(hwnd, key, None),
+            SelfTestStrategy::SendInputScanCode => {
+                super::with_injection_mode(InjectionMode::ScanCode, || super::key_click(key, None))
+            }
+            _ => super::with_injection_mode(InjectionMode::VirtualKey, || {
+                super::key_click(key, None)
+            }),
+        })
+    }
+
+    /// 向窗口输入文本
+    ///
+    /// 某种方式在中途失败时，已送达的部分不会撤回，下一种方式从中断处继续输入剩余文本。
+    /// 每种方式只在本次调用内生效，不修改全局的注入设置。
+    pub fn type_string(&self, hwnd: WindowHandle, text: &str) -> Result<Negotiated> {
+        let options = effective_text_options();
+        let text = options.apply(text)?;
+        // 文本已规范化，续发剩余部分时不再转换
+        let options = TextOptions {
+            normalize: None,
+            ..options
+        };
+        let mut offset = 0;
+        self.negotiate(hwnd, DEFAULT_TEXT_STRATEGIES, |strategy| {
+            let rest = &text[offset..];
+            let (result, typed) = super::progress::track_typed(|| {
+                with_text_options(options, || match strategy {
+                    SelfTestStrategy::PostWmChar => {
+                        super::type_string_to_window_with(hwnd, rest, WindowSendMode::Post)
+                            .map(drop)
+                    }
+                    SelfTestStrategy::SendWmChar => {
+                        super::type_string_to_window_with(hwnd, rest, WindowSendMode::synchronous())
+                            .map(drop)
+                    }
+                    SelfTestStrategy::SendInputKeyStrokes => {
+                        super::type_string_with(rest, TextInjection::KeyStrokes)
+                    }
+                    _ => super::type_string_with(rest, TextInjection::Unicode),
+                })
+            });
+            offset += rest
+                .char_indices()
+                .nth(typed)
+                .map_or(rest.len(), |(index, _)| index);
+            result
+        })
+    }
+
+    /// 检测目标窗口的注入能力
+    pub fn capabilities(&self, hwnd: WindowHandle) -> Capabilities {
+        Capabilities {
+            remote_session: is_remote_session(),
+            target_elevated: is_target_elevated(hwnd),
+            foreground: super::foreground_window() == hwnd,
+        }
+    }
+
+    /// 目标窗口的候选方式（按尝试顺序）
+    pub fn candidates(
+        &self,
+        hwnd: WindowHandle,
+        defaults: &[SelfTestStrategy],
+    ) -> Vec<SelfTestStrategy> {
+        self.ordered_candidates(hwnd, defaults, is_remote_session())
+    }
+
+    fn ordered_candidates(
+        &self,
+        hwnd: WindowHandle,
+        defaults: &[SelfTestStrategy],
+        remote: bool,
+    ) -> Vec<SelfTestStrategy> {
+        let preferred = super::process_name(super::window_process_id(hwnd))
+            .and_then(|name| compatibility(&name))
+            .unwrap_or_default();
+        order_candidates(&preferred, defaults, remote)
+    }
+
+    fn negotiate<F>(
+        &self,
+        hwnd: WindowHandle,
+        defaults: &[SelfTestStrategy],
+        mut send: F,
+    ) -> Result<Negotiated>
+    where
+        F: FnMut(SelfTestStrategy) -> Result<()>,
+    {
+        let _op = super::begin_operation();
+        let capabilities = self.capabilities(hwnd);
+        let mut skipped = Vec::new();
+        for strategy in self.ordered_candidates(hwnd, defaults, capabilities.remote_session) {
+            // UIPI 同时拦截 SendInput 和窗口消息，没有可行的方式
+            if capabilities.target_elevated {
+                skipped.push((strategy, SkipReason::Elevated));
+                continue;
+            }
+            if self.failed_self_test(strategy) {
+                skipped.push((strategy, SkipReason::FailedSelfTest));
+                continue;
+            }
+            if strategy.is_global() && !self.ensure_foreground(hwnd) {
+                skipped.push((strategy, SkipReason::NoFocus));
+                continue;
+            }
+            match send(strategy) {
+                Ok(()) => return Ok(Negotiated { strategy, skipped }),
+                // 中止请求不应触发降级
+                Err(KeyboardSenderError::Aborted) => return Err(KeyboardSenderError::Aborted),
+                Err(e) => skipped.push((strategy, SkipReason::Failed(e.to_string()))),
+            }
+        }
+        let summary: Vec<String> = skipped
+            .iter()
+            .map(|(strategy, reason)| format!("{:?}: {}", strategy, reason))
+            .collect();
+        Err(KeyboardSenderError::InjectionBlocked(format!(
+            "no viable injection strategy for window {:#x} ({})",
+            hwnd,
+            summary.join("; ")
+        )))
+    }
+
+    fn failed_self_test(&self, strategy: SelfTestStrategy) -> bool {
+        self.report.as_ref().is_some_and(|report| {
+            report
+                .results
+                .iter()
+                .any(|r| r.strategy == strategy && !r.works())
+        })
+    }
+
+    fn ensure_foreground(&self, hwnd: WindowHandle) -> bool {
+        if super::foreground_window() == hwnd {
+            return true;
+        }
+        if !self.focus_target || super::set_window_focus(hwnd, true).is_err() {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+        super::foreground_window() == hwnd
+    }
+}
+
+/// 当前是否为远程桌面会话
+pub fn is_remote_session() -> bool {
+    unsafe { GetSystemMetrics(SM_REMOTESESSION) != 0 }
+}
+
+/// 窗口所属进程的完整性级别是否高于本进程（此时 UIPI 拦截本进程向其注入的输入）
+///
+/// 取不到完整性级别（如进程已退出）时返回 `false`。
+pub fn is_target_elevated(hwnd: WindowHandle) -> bool {
+    let pid = super::window_process_id(hwnd);
+    let target = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) };
+    let Ok(target) = target else {
+        return false;
+    };
+    let target_level = integrity_level(target);
+    unsafe {
+        let _ = CloseHandle(target);
+    }
+    match (
+        target_level,
+        integrity_level(unsafe { GetCurrentProcess() }),
+    ) {
+        (Some(target), Some(own)) => target > own,
+        _ => false,
+    }
+}
+
+/// 进程令牌的完整性级别（`SECURITY_MANDATORY_*_RID`）
+fn integrity_level(process: HANDLE) -> Option<u32> {
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(process, TOKEN_QUERY, &mut token).ok()?;
+        // TOKEN_MANDATORY_LABEL 之后紧跟 SID，按 8 字节对齐
+        let mut buffer = [0u64; 16];
+        let mut len = 0u32;
+        let queried = GetTokenInformation(
+            token,
+            TokenIntegrityLevel,
+            Some(buffer.as_mut_ptr().cast()),
+            std::mem::size_of_val(&buffer) as u32,
+            &mut len,
+        );
+        let _ = CloseHandle(token);
+        queried.ok()?;
+        let label = &*(buffer.as_ptr() as *const TOKEN_MANDATORY_LABEL);
+        let count = *GetSidSubAuthorityCount(label.Label.Sid);
+        Some(*GetSidSubAuthority(
+            label.Label.Sid,
+            u32::from(count).checked_sub(1)?,
+        ))
+    }
+}
+
+/// 排列候选方式：登记的方式在前，其余按默认顺序；远程会话下扫描码优先于虚拟键码
+fn order_candidates(
+    preferred: &[SelfTestStrategy],
+    defaults: &[SelfTestStrategy],
+    remote: bool,
+) -> Vec<SelfTestStrategy> {
+    let mut ordered: Vec<SelfTestStrategy> = preferred
+        .iter()
+        .copied()
+        .filter(|s| defaults.contains(s))
+        .collect();
+    let mut rest: Vec<SelfTestStrategy> = defaults
+        .iter()
+        .copied()
+        .filter(|s| !ordered.contains(s))
+        .collect();
+    if remote {
+        let vk = rest
+            .iter()
+            .position(|&s| s == SelfTestStrategy::SendInputVirtualKey);
+        let scan = rest
+            .iter()
+            .position(|&s| s == SelfTestStrategy::SendInputScanCode);
+        if let (Some(vk), Some(scan)) = (vk, scan) {
+            if scan > vk {
+                rest.swap(vk, scan);
+            }
+        }
+    }
+    ordered.append(&mut rest);
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_candidates() {
+        assert_eq!(
+            order_candidates(&[], DEFAULT_KEY_STRATEGIES, false),
+            DEFAULT_KEY_STRATEGIES
+        );
+        assert_eq!(
+            order_candidates(
+                &[SelfTestStrategy::PostWmKey, SelfTestStrategy::PostWmChar],
+                DEFAULT_KEY_STRATEGIES,
+                false
+            ),
+            [
+                SelfTestStrategy::PostWmKey,
+                SelfTestStrategy::SendInputVirtualKey,
+                SelfTestStrategy::SendInputScanCode
+            ]
+        );
+        assert_eq!(
+            order_candidates(&[], DEFAULT_KEY_STRATEGIES, true)[0],
+            SelfTestStrategy::SendInputScanCode
+        );
+    }
+
+    #[test]
+    fn test_compatibility_db() {
+        set_compatibility("Game.EXE", &[SelfTestStrategy::SendInputScanCode]);
+        assert_eq!(
+            compatibility("game.exe"),
+            Some(vec![SelfTestStrategy::SendInputScanCode])
+        );
+        set_key_repeat_gap("game.exe", Duration::from_millis(30));
+        assert_eq!(
+            registered_key_repeat_gap("GAME.exe"),
+            Some(Duration::from_millis(30))
+        );
+        clear_compatibility("game.exe");
+        assert_eq!(compatibility("game.exe"), None);
+        assert_eq!(registered_key_repeat_gap("game.exe"), None);
+    }
+}
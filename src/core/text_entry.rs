@@ -0,0 +1,319 @@
+// src/core/text_entry.rs
+//! 自动选择输入方式的文本录入
+//!
+//! 短文本直接逐字符输入；长文本通过剪贴板粘贴（需要 `clipboard` 特性，完成后恢复剪贴板）；
+//! 不允许使用剪贴板时改为分块输入，每块之间稍作停顿，避免目标应用丢字。
+//!
+//! 多行代码另见 [`type_code`]，可避免编辑器自动缩进造成的缩进叠加。
+use crate::error::Result;
+use crate::types::WindowHandle;
+use std::time::Duration;
+
+/// 文本录入实际使用的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryStrategy {
+    /// 一次性逐字符输入
+    Typed,
+    /// 通过剪贴板粘贴
+    Pasted,
+    /// 分块输入
+    ChunkedTyping { chunks: usize },
+}
+
+/// 文本录入选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEntryOptions {
+    /// 字符数达到该值时视为长文本
+    pub paste_threshold: usize,
+    /// 是否允许使用剪贴板（部分目标会拦截或记录剪贴板）
+    pub allow_clipboard: bool,
+    /// 分块输入时每块的字符数
+    pub chunk_size: usize,
+    /// 分块输入时两块之间的等待时间
+    pub chunk_delay: Duration,
+    /// 粘贴后等待目标读取剪贴板的时间，之后再恢复剪贴板
+    pub paste_settle: Duration,
+}
+
+impl Default for TextEntryOptions {
+    fn default() -> Self {
+        Self {
+            paste_threshold: 200,
+            allow_clipboard: true,
+            chunk_size: 64,
+            chunk_delay: Duration::from_millis(30),
+            paste_settle: Duration::from_millis(100),
+        }
+    }
+}
+
+impl TextEntryOptions {
+    /// 设置长文本阈值
+    pub fn paste_threshold(mut self, chars: usize) -> Self {
+        self.paste_threshold = chars;
+        self
+    }
+
+    /// 设置是否允许使用剪贴板
+    pub fn allow_clipboard(mut self, allow: bool) -> Self {
+        self.allow_clipboard = allow;
+        self
+    }
+
+    /// 设置分块大小和间隔
+    pub fn chunks(mut self, chunk_size: usize, delay: Duration) -> Self {
+        self.chunk_size = chunk_size;
+        self.chunk_delay = delay;
+        self
+    }
+
+    /// 为指定长度的文本选择录入方式
+    pub fn choose(&self, char_count: usize) -> EntryStrategy {
+        if char_count < self.paste_threshold {
+            EntryStrategy::Typed
+        } else if self.allow_clipboard && cfg!(feature = "clipboard") {
+            EntryStrategy::Pasted
+        } else {
+            EntryStrategy::ChunkedTyping {
+                chunks: char_count.div_ceil(self.chunk_size.max(1)),
+            }
+        }
+    }
+}
+
+/// 使用默认选项录入文本，返回实际使用的方式
+///
+/// `target` 为 `None` 时输入到前台窗口；指定窗口时逐字符输入发送窗口消息，
+/// 粘贴则会先激活该窗口。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{enter_text, EntryStrategy};
+///
+/// let report = std::fs::read_to_string("report.txt").unwrap();
+/// let strategy = enter_text(None, &report).unwrap();
+/// assert_eq!(strategy, EntryStrategy::Pasted);
+/// ```
+pub fn enter_text(target: Option<WindowHandle>, text: &str) -> Result<EntryStrategy> {
+    enter_text_with(target, text, &TextEntryOptions::default())
+}
+
+/// 按选项录入文本，返回实际使用的方式
+pub fn enter_text_with(
+    target: Option<WindowHandle>,
+    text: &str,
+    options: &TextEntryOptions,
+) -> Result<EntryStrategy> {
+    let strategy = options.choose(text.chars().count());
+    let _op = super::begin_operation();
+    match strategy {
+        EntryStrategy::Typed => type_to(target, text)?,
+        EntryStrategy::Pasted => {
+            paste_to(target, text, PasteShortcut::CtrlV, options.paste_settle)?
+        }
+        EntryStrategy::ChunkedTyping { .. } => {
+            let chars: Vec<char> = text.chars().collect();
+            for (i, chunk) in chars.chunks(options.chunk_size.max(1)).enumerate() {
+                if i > 0 {
+                    super::clock().sleep(options.chunk_delay);
+                }
+                type_to(target, &chunk.iter().collect::<String>())?;
+            }
+        }
+    }
+    Ok(strategy)
+}
+
+fn type_to(target: Option<WindowHandle>, text: &str) -> Result<()> {
+    match target {
+        None => super::type_string(text),
+        #[cfg(feature = "window_target")]
+        Some(hwnd) => super::type_string_to_window(hwnd, text),
+        #[cfg(not(feature = "window_target"))]
+        Some(_) => Err(crate::error::KeyboardSenderError::FeatureNotEnabled(
+            "window_target".to_string(),
+        )),
+    }
+}
+
+/// 粘贴使用的快捷键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PasteShortcut {
+    /// Ctrl+V（默认）
+    #[default]
+    CtrlV,
+    /// Shift+Insert，适用于终端等将 Ctrl+V 另作他用的程序
+    ShiftInsert,
+}
+
+/// 多行代码输入选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodePasteOptions {
+    /// 逐行输入时去掉每行的前导空白，由编辑器的自动缩进生成缩进
+    pub strip_indent: bool,
+    /// 通过剪贴板粘贴整段代码（需要 `clipboard` 特性），编辑器不会对粘贴内容自动缩进
+    pub use_paste: bool,
+    /// 粘贴使用的快捷键
+    pub paste_shortcut: PasteShortcut,
+    /// 用括号粘贴转义序列（`ESC[200~` … `ESC[201~`）包裹输入，支持该模式的终端会将其视为粘贴
+    pub bracketed_paste: bool,
+    /// 粘贴后等待目标读取剪贴板的时间
+    pub paste_settle: Duration,
+}
+
+impl Default for CodePasteOptions {
+    fn default() -> Self {
+        Self {
+            strip_indent: false,
+            use_paste: false,
+            paste_shortcut: PasteShortcut::default(),
+            bracketed_paste: false,
+            paste_settle: TextEntryOptions::default().paste_settle,
+        }
+    }
+}
+
+/// 向前台窗口输入多行代码
+///
+/// - `use_paste`：整段粘贴，优先级最高
+/// - `bracketed_paste`：在括号粘贴序列内输入，换行以回车发送
+/// - 否则逐行输入，行间按 Enter（末尾的换行同样发送）；`strip_indent` 时去掉每行前导空白
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{type_code, CodePasteOptions};
+///
+/// let code = "fn main() {\n    println!(\"hi\");\n}";
+/// let options = CodePasteOptions {
+///     strip_indent: true,
+///     ..CodePasteOptions::default()
+/// };
+/// type_code(code, &options).unwrap();
+/// ```
+pub fn type_code(text: &str, options: &CodePasteOptions) -> Result<()> {
+    let _op = super::begin_operation();
+    if options.use_paste {
+        return paste_to(None, text, options.paste_shortcut, options.paste_settle);
+    }
+
+    if options.bracketed_paste {
+        return super::type_string(&bracketed_paste_text(text));
+    }
+
+    for (i, line) in code_lines(text).enumerate() {
+        if i > 0 {
+            super::key_click(crate::types::Key::Enter, None)?;
+        }
+        let line = if options.strip_indent {
+            line.trim_start()
+        } else {
+            line
+        };
+        super::type_string(line)?;
+    }
+    Ok(())
+}
+
+/// 按 `\n` 或 `\r\n` 拆分行；与 `str::lines` 不同，末尾的换行产生一个空行
+fn code_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+/// 用括号粘贴序列包裹文本，换行转换为终端使用的回车
+fn bracketed_paste_text(text: &str) -> String {
+    let body: String = text
+        .replace("\r\n", "\n")
+        .chars()
+        .map(|c| if c == '\n' { '\r' } else { c })
+        .collect();
+    format!("\x1b[200~{}\x1b[201~", body)
+}
+
+#[cfg(feature = "clipboard")]
+fn paste_to(
+    target: Option<WindowHandle>,
+    text: &str,
+    shortcut: PasteShortcut,
+    settle: Duration,
+) -> Result<()> {
+    use crate::types::{Key, Modifier};
+
+    if let Some(_hwnd) = target {
+        #[cfg(feature = "window_target")]
+        super::set_window_focus(_hwnd, true)?;
+        #[cfg(not(feature = "window_target"))]
+        return Err(crate::error::KeyboardSenderError::FeatureNotEnabled(
+            "window_target".to_string(),
+        ));
+    }
+
+    super::with_clipboard_preserved(|| {
+        super::set_clipboard_text(text)?;
+        match shortcut {
+            PasteShortcut::CtrlV => super::press_combination(&[Modifier::Control], Key::V, None)?,
+            PasteShortcut::ShiftInsert => {
+                super::press_combination(&[Modifier::Shift], Key::Insert, None)?
+            }
+        }
+        super::clock().sleep(settle);
+        Ok(())
+    })
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn paste_to(
+    _target: Option<WindowHandle>,
+    _text: &str,
+    _shortcut: PasteShortcut,
+    _settle: Duration,
+) -> Result<()> {
+    Err(crate::error::KeyboardSenderError::FeatureNotEnabled(
+        "clipboard".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bracketed_paste_text() {
+        assert_eq!(
+            bracketed_paste_text("a\r\n  b\nc"),
+            "\x1b[200~a\r  b\rc\x1b[201~"
+        );
+    }
+
+    #[test]
+    fn test_code_lines_keep_trailing_newline() {
+        assert_eq!(
+            code_lines("a\r\n  b\n").collect::<Vec<_>>(),
+            ["a", "  b", ""]
+        );
+        assert_eq!(code_lines("a").collect::<Vec<_>>(), ["a"]);
+    }
+
+    #[test]
+    fn test_choose_strategy() {
+        let options = TextEntryOptions::default().paste_threshold(10);
+        assert_eq!(options.choose(3), EntryStrategy::Typed);
+
+        let no_clipboard = options
+            .clone()
+            .allow_clipboard(false)
+            .chunks(4, Duration::ZERO);
+        assert_eq!(
+            no_clipboard.choose(10),
+            EntryStrategy::ChunkedTyping { chunks: 3 }
+        );
+
+        #[cfg(feature = "clipboard")]
+        assert_eq!(options.choose(10), EntryStrategy::Pasted);
+        #[cfg(not(feature = "clipboard"))]
+        assert_eq!(
+            options.choose(10),
+            EntryStrategy::ChunkedTyping { chunks: 1 }
+        );
+    }
+}
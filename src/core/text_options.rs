@@ -0,0 +1,313 @@
+// src/core/text_options.rs
+//! 文本输入选项
+//!
+//! 部分来源（macOS 文件名、某些网页和 PDF）中的文本是分解形式（如 `e` + U+0301），
+//! 逐字符注入后目标程序可能把组合附加符号显示成重复的重音。[`TextOptions::normalize`]
+//! 在发送前将文本规范化为 NFC/NFD 等形式（使用系统的 `NormalizeString`）。
+//!
+//! 许多编辑控件忽略 `WM_CHAR` 形式的 `\n`，[`TextOptions::translate_newlines`] 将换行（`\n`、
+//! `\r\n`、`\r`）改为发送 Enter 键、`\t` 改为发送 Tab 键，而不是控制字符。
+//!
+//! 部分收银和旧式程序区分小键盘输入，[`TextOptions::numpad_digits`] 将数字改为发送小键盘按键
+//! （`Num0`-`Num9`），全局发送时临时开启 NumLock。
+//!
+//! 选项可全局设置（[`set_text_options`]），也可在当前线程内临时覆盖（[`with_text_options`]）。
+//! [`type_string`](super::type_string) 和 [`type_string_to_window`](super::type_string_to_window)
+//! 在发送前应用当前生效的选项。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::Key;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::sync::Mutex;
+
+use windows::Win32::{
+    Foundation::ERROR_INSUFFICIENT_BUFFER,
+    Globalization::{
+        NormalizationC, NormalizationD, NormalizationKC, NormalizationKD, NormalizeString,
+        NORM_FORM,
+    },
+};
+
+/// Unicode 规范化形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NormalizationForm {
+    /// 标准组合形式（`e` + U+0301 → `é`）
+    Nfc,
+    /// 标准分解形式（`é` → `e` + U+0301）
+    Nfd,
+    /// 兼容组合形式（另将全角字母、连字等替换为兼容字符）
+    Nfkc,
+    /// 兼容分解形式
+    Nfkd,
+}
+
+impl NormalizationForm {
+    fn norm_form(self) -> NORM_FORM {
+        match self {
+            NormalizationForm::Nfc => NormalizationC,
+            NormalizationForm::Nfd => NormalizationD,
+            NormalizationForm::Nfkc => NormalizationKC,
+            NormalizationForm::Nfkd => NormalizationKD,
+        }
+    }
+}
+
+/// 文本输入选项
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{type_string_with_options, NormalizationForm, TextOptions};
+///
+/// // "Cafe\u{301}" 以分解形式给出，组合为 "Café" 后再发送
+/// let options = TextOptions::new().normalize(NormalizationForm::Nfc);
+/// type_string_with_options("Cafe\u{301}", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextOptions {
+    /// 发送前的规范化形式，`None` 表示原样发送（默认）
+    pub normalize: Option<NormalizationForm>,
+    /// 换行发送为 Enter 键、`\t` 发送为 Tab 键（默认关闭，作为控制字符发送）
+    pub translate_newlines: bool,
+    /// 数字发送为小键盘按键（默认关闭）
+    pub numpad_digits: bool,
+}
+
+impl TextOptions {
+    /// 默认选项：原样发送
+    pub const fn new() -> Self {
+        Self {
+            normalize: None,
+            translate_newlines: false,
+            numpad_digits: false,
+        }
+    }
+
+    /// 发送前规范化为指定形式
+    pub fn normalize(mut self, form: NormalizationForm) -> Self {
+        self.normalize = Some(form);
+        self
+    }
+
+    /// 换行和制表符是否改为发送 Enter / Tab 键
+    pub fn translate_newlines(mut self, translate: bool) -> Self {
+        self.translate_newlines = translate;
+        self
+    }
+
+    /// 数字是否改为发送小键盘按键
+    pub fn numpad_digits(mut self, numpad: bool) -> Self {
+        self.numpad_digits = numpad;
+        self
+    }
+
+    /// 按选项转换文本，无需转换时不复制
+    pub fn apply<'a>(&self, text: &'a str) -> Result<Cow<'a, str>> {
+        match self.normalize {
+            Some(form) => normalize_text(text, form),
+            None => Ok(Cow::Borrowed(text)),
+        }
+    }
+}
+
+/// 将文本规范化为指定形式，ASCII 文本直接返回
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{normalize_text, NormalizationForm};
+///
+/// assert_eq!(normalize_text("e\u{301}", NormalizationForm::Nfc).unwrap(), "\u{e9}");
+/// assert_eq!(normalize_text("\u{e9}", NormalizationForm::Nfd).unwrap(), "e\u{301}");
+/// ```
+pub fn normalize_text(text: &str, form: NormalizationForm) -> Result<Cow<'_, str>> {
+    // ASCII 在所有规范化形式下保持不变
+    if text.is_ascii() {
+        return Ok(Cow::Borrowed(text));
+    }
+
+    let source: Vec<u16> = text.encode_utf16().collect();
+    // 返回值为估计长度，缓冲区不足时返回负值并给出新的估计
+    let mut capacity = unsafe { NormalizeString(form.norm_form(), &source, None) };
+    for _ in 0..10 {
+        if capacity <= 0 {
+            break;
+        }
+        let mut buffer = vec![0u16; capacity as usize];
+        let len = unsafe { NormalizeString(form.norm_form(), &source, Some(&mut buffer)) };
+        if len > 0 {
+            buffer.truncate(len as usize);
+            return Ok(Cow::Owned(String::from_utf16_lossy(&buffer)));
+        }
+        let code = std::io::Error::last_os_error().raw_os_error().unwrap_or(0) as u32;
+        if code != ERROR_INSUFFICIENT_BUFFER.0 {
+            break;
+        }
+        capacity = -len;
+    }
+    Err(KeyboardSenderError::WindowsError)
+}
+
+/// 按选项切分后的文本片段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextSegment<'a> {
+    /// 按字符发送的文本
+    Text(&'a str),
+    /// 代替字符发送的按键、对应字符（Enter 为 `\r`，Tab 为 `\t`，小键盘数字为数字本身）
+    /// 及其在原文中占的字符数（`\r\n` 为 2）
+    Key(Key, char, usize),
+}
+
+impl TextSegment<'_> {
+    /// 是否为小键盘数字按键
+    pub(crate) fn is_numpad(&self) -> bool {
+        matches!(self, TextSegment::Key(_, c, _) if c.is_ascii_digit())
+    }
+}
+
+/// 数字对应的小键盘按键
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{numpad_digit_key, Key};
+///
+/// assert_eq!(numpad_digit_key('7'), Some(Key::Num7));
+/// assert_eq!(numpad_digit_key('x'), None);
+/// ```
+pub fn numpad_digit_key(c: char) -> Option<Key> {
+    const KEYS: [Key; 10] = [
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+    ];
+    c.to_digit(10).map(|digit| KEYS[digit as usize])
+}
+
+/// 按选项将换行（`\r\n` 视为一个）、`\t` 和数字切分为按键片段，其余部分作为文本
+pub(crate) fn text_segments<'a>(text: &'a str, options: &TextOptions) -> Vec<TextSegment<'a>> {
+    let is_key = |c: char| {
+        (options.translate_newlines && matches!(c, '\r' | '\n' | '\t'))
+            || (options.numpad_digits && c.is_ascii_digit())
+    };
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find(is_key) {
+        if pos > 0 {
+            segments.push(TextSegment::Text(&rest[..pos]));
+        }
+        let (key, c, len) = match &rest[pos..] {
+            tail if tail.starts_with("\r\n") => (Key::Enter, '\r', 2),
+            tail if tail.starts_with('\t') => (Key::Tab, '\t', 1),
+            tail if tail.starts_with(['\r', '\n']) => (Key::Enter, '\r', 1),
+            tail => {
+                let c = tail.chars().next().unwrap_or_default();
+                (numpad_digit_key(c).unwrap_or(Key::Num0), c, 1)
+            }
+        };
+        segments.push(TextSegment::Key(key, c, len));
+        rest = &rest[pos + len..];
+    }
+    if !rest.is_empty() {
+        segments.push(TextSegment::Text(rest));
+    }
+    segments
+}
+
+static TEXT_OPTIONS: Mutex<TextOptions> = Mutex::new(TextOptions::new());
+
+thread_local! {
+    /// 当前线程临时覆盖的文本选项
+    static TEXT_OPTIONS_OVERRIDE: Cell<Option<TextOptions>> = const { Cell::new(None) };
+}
+
+/// 设置全局文本输入选项
+pub fn set_text_options(options: TextOptions) {
+    *TEXT_OPTIONS.lock().unwrap_or_else(|e| e.into_inner()) = options;
+}
+
+/// 获取全局文本输入选项
+pub fn text_options() -> TextOptions {
+    *TEXT_OPTIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 在当前线程内以指定文本选项执行操作，不影响全局设置
+pub fn with_text_options<T, F: FnOnce() -> T>(options: TextOptions, f: F) -> T {
+    struct Restore(Option<TextOptions>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TEXT_OPTIONS_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(TEXT_OPTIONS_OVERRIDE.with(|o| o.replace(Some(options))));
+    f()
+}
+
+/// 实际生效的文本选项（线程内覆盖优先）
+pub(crate) fn effective_text_options() -> TextOptions {
+    TEXT_OPTIONS_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(text_options)
+}
+
+/// 全局发送：按指定选项输入字符串，不影响全局设置
+pub fn type_string_with_options(text: &str, options: &TextOptions) -> Result<()> {
+    with_text_options(*options, || super::type_string(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_options_override() {
+        let nfc = TextOptions::new().normalize(NormalizationForm::Nfc);
+        assert_eq!(effective_text_options(), text_options());
+        with_text_options(nfc, || {
+            assert_eq!(
+                effective_text_options().normalize,
+                Some(NormalizationForm::Nfc)
+            );
+        });
+        assert!(matches!(
+            TextOptions::new().apply("e\u{301}").unwrap(),
+            Cow::Borrowed("e\u{301}")
+        ));
+        assert!(matches!(
+            nfc.apply("plain").unwrap(),
+            Cow::Borrowed("plain")
+        ));
+    }
+
+    #[test]
+    fn test_text_segments() {
+        use TextSegment::*;
+        let translate = TextOptions::new().translate_newlines(true);
+        assert_eq!(
+            text_segments("a\nb", &TextOptions::new()),
+            vec![Text("a\nb")]
+        );
+        assert_eq!(
+            text_segments("a\r\nb\tc\n\r", &translate),
+            vec![
+                Text("a"),
+                Key(crate::types::Key::Enter, '\r', 2),
+                Text("b"),
+                Key(crate::types::Key::Tab, '\t', 1),
+                Text("c"),
+                Key(crate::types::Key::Enter, '\r', 1),
+                Key(crate::types::Key::Enter, '\r', 1),
+            ]
+        );
+        assert!(text_segments("", &translate).is_empty());
+        assert_eq!(
+            text_segments("a1\n", &TextOptions::new().numpad_digits(true)),
+            vec![Text("a"), Key(crate::types::Key::Num1, '1', 1), Text("\n")]
+        );
+    }
+}
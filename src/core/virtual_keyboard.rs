@@ -0,0 +1,170 @@
+// src/core/virtual_keyboard.rs
+//! 屏幕键盘驱动
+//!
+//! 屏幕键盘（OSK）程序把界面按钮映射为按键注入时，需要自己维护修饰键锁存状态，
+//! 并记住用户原本在操作的窗口（点击键盘窗口可能抢走前台）。[`VirtualKeyboard`]
+//! 封装了这两部分状态：按下修饰键按钮时锁存，作用于下一个按键后自动取消；
+//! 注入前把记录的目标窗口切回前台。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, Modifier, WindowHandle};
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::Arc;
+
+use super::foreground::{foreground_window, on_foreground_changed, ForegroundWatcher};
+
+/// 屏幕键盘驱动
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{Key, Modifier, VirtualKeyboard};
+///
+/// let osk_window = 0x1A2B; // 屏幕键盘自身的窗口
+/// let mut vk = VirtualKeyboard::tracking(osk_window).unwrap();
+///
+/// // 用户依次点击 Ctrl、A 按钮
+/// vk.toggle_modifier(Modifier::Control);
+/// vk.press(Key::A).unwrap();
+/// vk.commit_text("你好").unwrap();
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct VirtualKeyboard {
+    latched: Vec<Modifier>,
+    /// 目标窗口，0 表示当前前台窗口
+    target: Arc<AtomicIsize>,
+    /// 跟踪前台窗口的监听器，丢弃时停止跟踪
+    _watcher: Option<ForegroundWatcher>,
+}
+
+impl VirtualKeyboard {
+    /// 创建驱动，输入发往当前前台窗口
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建驱动并跟踪前台窗口：目标为最近一次处于前台的、不是 `own_window` 的窗口
+    ///
+    /// `own_window` 为屏幕键盘自身的窗口，点击它不会改变目标。
+    pub fn tracking(own_window: WindowHandle) -> Result<Self> {
+        let target = Arc::new(AtomicIsize::new(0));
+        let current = foreground_window();
+        if current != own_window {
+            target.store(current, Ordering::Relaxed);
+        }
+        let tracked = Arc::clone(&target);
+        let watcher = on_foreground_changed(move |hwnd| {
+            if hwnd != own_window {
+                tracked.store(hwnd, Ordering::Relaxed);
+            }
+        })?;
+        Ok(Self {
+            latched: Vec::new(),
+            target,
+            _watcher: Some(watcher),
+        })
+    }
+
+    /// 当前目标窗口，`None` 表示发往前台窗口
+    pub fn target(&self) -> Option<WindowHandle> {
+        match self.target.load(Ordering::Relaxed) {
+            0 => None,
+            hwnd => Some(hwnd),
+        }
+    }
+
+    /// 手动指定目标窗口（跟踪模式下会被之后的前台切换覆盖）
+    pub fn set_target(&mut self, hwnd: Option<WindowHandle>) {
+        self.target.store(hwnd.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// 切换修饰键的锁存状态，返回切换后是否锁存
+    pub fn toggle_modifier(&mut self, modifier: Modifier) -> bool {
+        match self.latched.iter().position(|&m| m == modifier) {
+            Some(index) => {
+                self.latched.remove(index);
+                false
+            }
+            None => {
+                self.latched.push(modifier);
+                true
+            }
+        }
+    }
+
+    /// 当前锁存的修饰键（按锁存顺序）
+    pub fn latched_modifiers(&self) -> &[Modifier] {
+        &self.latched
+    }
+
+    /// 取消全部锁存的修饰键
+    pub fn clear_modifiers(&mut self) {
+        self.latched.clear();
+    }
+
+    /// 点击按键，带上锁存的修饰键，成功后取消锁存；发送失败时保留锁存状态
+    pub fn press(&mut self, key: Key) -> Result<()> {
+        self.activate_target()?;
+        if self.latched.is_empty() {
+            super::global::key_click(key, None)
+        } else {
+            super::global::press_combination(&self.latched, key, None)?;
+            self.latched.clear();
+            Ok(())
+        }
+    }
+
+    /// 输入文本（候选词、整词补全等），成功后取消锁存的修饰键
+    pub fn commit_text(&mut self, text: &str) -> Result<()> {
+        self.activate_target()?;
+        super::global::type_string(text)?;
+        self.latched.clear();
+        Ok(())
+    }
+
+    /// 目标窗口不在前台时切回前台
+    fn activate_target(&self) -> Result<()> {
+        let Some(hwnd) = self.target() else {
+            return Ok(());
+        };
+        if !super::selector::window_exists(hwnd) {
+            return Err(KeyboardSenderError::InvalidWindowHandle(format!(
+                "target window {:#x} no longer exists",
+                hwnd
+            )));
+        }
+        if foreground_window() != hwnd {
+            super::window_target::set_window_focus(hwnd, true)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_modifier() {
+        let mut vk = VirtualKeyboard::new();
+        assert!(vk.toggle_modifier(Modifier::Control));
+        assert!(vk.toggle_modifier(Modifier::Shift));
+        assert_eq!(
+            vk.latched_modifiers(),
+            &[Modifier::Control, Modifier::Shift]
+        );
+        assert!(!vk.toggle_modifier(Modifier::Control));
+        assert_eq!(vk.latched_modifiers(), &[Modifier::Shift]);
+        vk.clear_modifiers();
+        assert!(vk.latched_modifiers().is_empty());
+    }
+
+    #[test]
+    fn test_target() {
+        let mut vk = VirtualKeyboard::new();
+        assert_eq!(vk.target(), None);
+        vk.set_target(Some(0x1A2B));
+        assert_eq!(vk.target(), Some(0x1A2B));
+    }
+}
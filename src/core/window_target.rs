@@ -1,22 +1,30 @@
 // src/core/window_target.rs
 //! 窗口目标键盘操作功能
+//!
+//! 按键消息的扫描码和字符消息的编码都按目标窗口所在线程的键盘布局计算，
+//! 目标使用与调用方不同的布局（如俄语、日语）时也能收到正确的字符。
 #[cfg(not(feature = "window_target"))]
 use crate::error::KeyboardSenderError;
 
 use crate::error::Result;
 
 use crate::types::{Key, WindowHandle};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "window_target")]
 use windows::Win32::{
-    Foundation::{HWND, LPARAM, WPARAM},
+    Foundation::{ERROR_NOT_ENOUGH_QUOTA, HWND, LPARAM, WPARAM},
+    UI::TextServices::HKL,
     UI::WindowsAndMessaging::{
-        BringWindowToTop, PostMessageA, SetForegroundWindow, WM_CHAR, WM_KEYDOWN, WM_KEYUP,
+        BringWindowToTop, IsWindowUnicode, PostMessageA, PostMessageW, SendMessageTimeoutA,
+        SendMessageTimeoutW, SetForegroundWindow, SMTO_ABORTIFHUNG, WM_CHAR, WM_IME_CHAR,
+        WM_KEYDOWN, WM_KEYUP, WM_NULL,
     },
 };
 
-use super::key_to_vk;
+use super::{key_to_vk, modifier_to_vk};
+use crate::types::Modifier;
 
 /// 将 isize 转换为 HWND
 #[cfg(feature = "window_target")]
@@ -25,6 +33,492 @@ fn to_hwnd(handle: WindowHandle) -> HWND {
     HWND(handle as isize)
 }
 
+/// 窗口消息的发送方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowSendMode {
+    /// `PostMessage`：放入目标窗口的消息队列后立即返回（默认）
+    #[default]
+    Post,
+    /// `SendMessageTimeout`：等待目标窗口处理完每条消息，超时返回 `Timeout` 错误
+    Synchronous { timeout: Duration },
+}
+
+impl WindowSendMode {
+    /// 同步模式的默认超时时间
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// 使用默认超时时间的同步模式
+    pub fn synchronous() -> Self {
+        WindowSendMode::Synchronous {
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// 向窗口输入文本时对输入法的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowImeMode {
+    /// 直接投递 `WM_CHAR`（默认）
+    #[default]
+    Off,
+    /// 投递 `WM_IME_CHAR`，由窗口的默认处理转换为 `WM_CHAR`
+    ///
+    /// 有激活输入法的窗口把它当作输入法上屏的字符处理，不会再经过组字；
+    /// ANSI 窗口的双字节字符作为一条消息发送。
+    ImeChar,
+    /// 输入期间临时关闭目标窗口的输入法（见 [`suspend_ime`](super::suspend_ime)），结束后恢复
+    ///
+    /// 为保证字符在恢复输入法之前处理完，此模式下字符总是同步发送
+    /// （Post 模式使用 [`WindowSendMode::DEFAULT_TIMEOUT`]）。
+    DisableIme,
+}
+
+static WINDOW_IME_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// 设置窗口目标文本输入对输入法的处理方式
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{set_window_ime_mode, type_string_to_window, WindowImeMode};
+///
+/// // 目标窗口开着中文输入法，直接投递的 WM_CHAR 会被输入法截获
+/// set_window_ime_mode(WindowImeMode::ImeChar);
+/// type_string_to_window(0x1A2B, "你好，世界").unwrap();
+/// # }
+/// ```
+pub fn set_window_ime_mode(mode: WindowImeMode) {
+    WINDOW_IME_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// 获取窗口目标文本输入对输入法的处理方式
+pub fn window_ime_mode() -> WindowImeMode {
+    match WINDOW_IME_MODE.load(Ordering::Relaxed) {
+        1 => WindowImeMode::ImeChar,
+        2 => WindowImeMode::DisableIme,
+        _ => WindowImeMode::Off,
+    }
+}
+
+/// Post 模式下输入文本时，每投递这么多条消息就等待目标线程处理一次
+///
+/// 等待使用 `SendMessageTimeout(WM_NULL)`，它返回只说明目标线程又取过消息，
+/// 不能确认之前投递的消息已经处理，因此这只是按目标的处理速度节流，并不限制队列中未处理的消息数。
+///
+/// 队列中的消息数由系统限制：目标线程的消息队列默认最多容纳 10000 条消息，队列已满时 `PostMessage` 失败。
+/// 此时暂停投递，直到队列腾出位置、投递重新成功后再继续；5 秒内一直没有空位则返回 `Timeout` 错误，
+/// 错误中注明已投递的字符数。
+pub const MAX_IN_FLIGHT_MESSAGES: usize = 500;
+
+/// 等待目标线程处理消息或队列腾出位置的超时时间
+const QUEUE_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 同步模式超时（毫秒），`u64::MAX` 表示 Post 模式
+static WINDOW_SEND_MODE: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// 设置窗口目标函数默认使用的发送方式
+pub fn set_window_send_mode(mode: WindowSendMode) {
+    let value = match mode {
+        WindowSendMode::Post => u64::MAX,
+        WindowSendMode::Synchronous { timeout } => {
+            (timeout.as_millis() as u64).min(u64::from(u32::MAX))
+        }
+    };
+    WINDOW_SEND_MODE.store(value, Ordering::Relaxed);
+}
+
+/// 获取窗口目标函数默认使用的发送方式
+pub fn window_send_mode() -> WindowSendMode {
+    match WINDOW_SEND_MODE.load(Ordering::Relaxed) {
+        u64::MAX => WindowSendMode::Post,
+        millis => WindowSendMode::Synchronous {
+            timeout: Duration::from_millis(millis),
+        },
+    }
+}
+
+/// 单条窗口消息的投递结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageDelivery {
+    /// 消息类型（如 `WM_KEYDOWN`）
+    pub message: u32,
+    /// 发送耗时；同步模式下即目标窗口处理该消息的延迟
+    pub latency: Duration,
+    /// 同步模式下窗口过程的返回值，Post 模式为 `None`
+    pub result: Option<usize>,
+}
+
+/// 一组窗口消息的投递报告
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeliveryReport {
+    pub deliveries: Vec<MessageDelivery>,
+}
+
+impl DeliveryReport {
+    /// 所有消息的耗时之和
+    pub fn total_latency(&self) -> Duration {
+        self.deliveries.iter().map(|d| d.latency).sum()
+    }
+
+    /// 耗时最长的一条消息
+    pub fn max_latency(&self) -> Duration {
+        self.deliveries
+            .iter()
+            .map(|d| d.latency)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// 按键消息的 `lParam`：重复次数 1、扫描码、扩展键标志，释放时带上前一状态和转换状态位
+///
+/// 扫描码按目标窗口的键盘布局 `layout` 计算。
+#[cfg(feature = "window_target")]
+fn key_lparam(layout: HKL, vk: u16, up: bool) -> isize {
+    let (scan, extended) = super::mappings::vk_scan_code_in(vk, layout);
+    let mut lparam = 1 | ((scan as isize & 0xFF) << 16);
+    if extended {
+        lparam |= 1 << 24;
+    }
+    if up {
+        lparam |= (1 << 30) | (1 << 31);
+    }
+    lparam
+}
+
+/// 按键消息的 `wParam`
+///
+/// 真实键盘产生的消息中，左右修饰键都使用通用键码（`VK_SHIFT` 等），
+/// 左右之分只体现在 `lParam` 的扫描码和扩展键标志上。
+#[cfg(feature = "window_target")]
+fn message_vk(vk: u16) -> usize {
+    let vk = match vk {
+        0xA0 | 0xA1 => 0x10,
+        0xA2 | 0xA3 => 0x11,
+        0xA4 | 0xA5 => 0x12,
+        vk => vk,
+    };
+    usize::from(vk)
+}
+
+/// 按指定方式向窗口投递一条按键消息
+#[cfg(feature = "window_target")]
+fn deliver_key(
+    hwnd: WindowHandle,
+    vk: u16,
+    up: bool,
+    mode: WindowSendMode,
+) -> Result<MessageDelivery> {
+    let layout = super::layout::window_layout(hwnd);
+    if !up {
+        super::pacing::pace_key_down(hwnd, vk, || {
+            let lparam = key_lparam(layout, vk, true);
+            deliver(hwnd, WM_KEYUP, message_vk(vk), lparam, mode, false).map(|_| ())
+        })?;
+    }
+    let message = if up { WM_KEYUP } else { WM_KEYDOWN };
+    let lparam = key_lparam(layout, vk, up);
+    let delivery = deliver(hwnd, message, message_vk(vk), lparam, mode, false)?;
+    super::pacing::record_key(hwnd, vk, !up);
+    Ok(delivery)
+}
+
+/// 按指定方式向窗口投递一条消息，`ansi` 为真时使用 A 版本 API（`wParam` 为 ANSI 字符）
+#[cfg(feature = "window_target")]
+fn deliver(
+    hwnd: WindowHandle,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+    mode: WindowSendMode,
+    ansi: bool,
+) -> Result<MessageDelivery> {
+    super::lifecycle::check_abort(message == WM_KEYUP)?;
+    let start = Instant::now();
+    let result = match mode {
+        WindowSendMode::Post => {
+            post_message(hwnd, message, wparam, lparam, ansi)?;
+            None
+        }
+        WindowSendMode::Synchronous { timeout } => {
+            let mut result = 0usize;
+            let timeout_ms = timeout.as_millis().min(u128::from(u32::MAX)) as u32;
+            let (wparam, lparam) = (WPARAM(wparam), LPARAM(lparam));
+            let ok = unsafe {
+                if ansi {
+                    SendMessageTimeoutA(
+                        to_hwnd(hwnd),
+                        message,
+                        wparam,
+                        lparam,
+                        SMTO_ABORTIFHUNG,
+                        timeout_ms,
+                        Some(&mut result),
+                    )
+                } else {
+                    SendMessageTimeoutW(
+                        to_hwnd(hwnd),
+                        message,
+                        wparam,
+                        lparam,
+                        SMTO_ABORTIFHUNG,
+                        timeout_ms,
+                        Some(&mut result),
+                    )
+                }
+            };
+            if ok.0 == 0 {
+                return Err(crate::error::KeyboardSenderError::Timeout(format!(
+                    "window {:#x} did not process message {:#06x} within {:?}",
+                    hwnd, message, timeout
+                )));
+            }
+            Some(result)
+        }
+    };
+    let delivery = if result.is_some() {
+        super::spy::SpyDelivery::Sent
+    } else {
+        super::spy::SpyDelivery::Posted
+    };
+    super::spy::notify_sent(hwnd, message, wparam, lparam, delivery);
+    Ok(MessageDelivery {
+        message,
+        latency: start.elapsed(),
+        result,
+    })
+}
+
+/// 投递一条消息，目标队列已满时等待队列腾出位置后重试
+#[cfg(feature = "window_target")]
+fn post_message(
+    hwnd: WindowHandle,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+    ansi: bool,
+) -> Result<()> {
+    let clock = super::clock();
+    let deadline = clock.now() + QUEUE_DRAIN_TIMEOUT;
+    loop {
+        let posted = unsafe {
+            if ansi {
+                PostMessageA(to_hwnd(hwnd), message, WPARAM(wparam), LPARAM(lparam))
+            } else {
+                PostMessageW(to_hwnd(hwnd), message, WPARAM(wparam), LPARAM(lparam))
+            }
+        };
+        match posted {
+            Err(e) if e.code() == ERROR_NOT_ENOUGH_QUOTA.to_hresult() => {
+                if clock.now() >= deadline {
+                    return Err(crate::error::KeyboardSenderError::Timeout(format!(
+                        "message queue of window {:#x} stayed full for {:?}",
+                        hwnd, QUEUE_DRAIN_TIMEOUT
+                    )));
+                }
+                wait_for_queue(hwnd)?;
+                clock.sleep(Duration::from_millis(10));
+            }
+            // 其他失败（如窗口已销毁）保持原有行为，不中断输入
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// 用 `SendMessageTimeout(WM_NULL)` 等待目标线程取走消息
+///
+/// 发送的消息会在投递的消息之前处理，返回只说明目标线程仍在处理消息，
+/// 用于按目标的处理速度节流，而不是保证队列已空。
+#[cfg(feature = "window_target")]
+fn wait_for_queue(hwnd: WindowHandle) -> Result<()> {
+    let ok = unsafe {
+        SendMessageTimeoutW(
+            to_hwnd(hwnd),
+            WM_NULL,
+            WPARAM(0),
+            LPARAM(0),
+            SMTO_ABORTIFHUNG,
+            QUEUE_DRAIN_TIMEOUT.as_millis() as u32,
+            None,
+        )
+    };
+    if ok.0 == 0 {
+        return Err(crate::error::KeyboardSenderError::Timeout(format!(
+            "window {:#x} did not process its messages within {:?}",
+            hwnd, QUEUE_DRAIN_TIMEOUT
+        )));
+    }
+    Ok(())
+}
+
+/// 目标窗口接收 `WM_CHAR` 的编码
+#[cfg(feature = "window_target")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharEncoding {
+    /// Unicode 窗口：UTF-16 编码单元，通过 W 版本 API 发送
+    Utf16,
+    /// ANSI 窗口：指定代码页的字节，通过 A 版本 API 发送
+    Ansi(u32),
+}
+
+#[cfg(feature = "window_target")]
+impl CharEncoding {
+    /// 目标窗口的字符编码，ANSI 窗口使用其所在线程键盘布局的代码页
+    fn of(hwnd: WindowHandle) -> Self {
+        if unsafe { IsWindowUnicode(to_hwnd(hwnd)) }.as_bool() {
+            CharEncoding::Utf16
+        } else {
+            let layout = super::layout::window_layout(hwnd);
+            CharEncoding::Ansi(super::layout::ansi_code_page(layout))
+        }
+    }
+
+    /// 字符对应的 `WM_CHAR` 参数，代码页中没有的字符按系统默认字符（通常为 `?`）发送
+    fn units(self, c: char) -> Vec<usize> {
+        let mut utf16 = [0u16; 2];
+        let utf16 = c.encode_utf16(&mut utf16);
+        match self {
+            CharEncoding::Utf16 => utf16.iter().map(|&unit| usize::from(unit)).collect(),
+            CharEncoding::Ansi(code_page) => {
+                use windows::core::PCSTR;
+                use windows::Win32::Globalization::WideCharToMultiByte;
+
+                let mut bytes = [0u8; 8];
+                let len = unsafe {
+                    WideCharToMultiByte(code_page, 0, utf16, Some(&mut bytes), PCSTR::null(), None)
+                };
+                bytes[..len.max(0) as usize]
+                    .iter()
+                    .map(|&byte| usize::from(byte))
+                    .collect()
+            }
+        }
+    }
+
+    /// 字符对应的 `WM_IME_CHAR` 参数：ANSI 窗口的双字节字符合并为一条（前导字节在高位）
+    fn ime_units(self, c: char) -> Vec<usize> {
+        let units = self.units(c);
+        match self {
+            CharEncoding::Ansi(_) if units.len() > 1 => {
+                vec![units.iter().fold(0, |wparam, &byte| (wparam << 8) | byte)]
+            }
+            _ => units,
+        }
+    }
+}
+
+/// 按 [`window_ime_mode`] 依次投递字符消息，Post 模式下每 [`MAX_IN_FLIGHT_MESSAGES`] 条
+/// 等待目标线程处理一次
+#[cfg(feature = "window_target")]
+fn deliver_chars(
+    hwnd: WindowHandle,
+    chars: impl Iterator<Item = char>,
+    mode: WindowSendMode,
+    mut on_delivery: impl FnMut(MessageDelivery),
+) -> Result<()> {
+    let ime_mode = window_ime_mode();
+    let (mode, suspended) = match (ime_mode, mode) {
+        (WindowImeMode::DisableIme, WindowSendMode::Post) => (
+            WindowSendMode::synchronous(),
+            Some(super::ime::suspend_ime(hwnd)?),
+        ),
+        (WindowImeMode::DisableIme, mode) => (mode, Some(super::ime::suspend_ime(hwnd)?)),
+        (_, mode) => (mode, None),
+    };
+
+    let encoding = CharEncoding::of(hwnd);
+    let ansi = encoding != CharEncoding::Utf16;
+    let message = match ime_mode {
+        WindowImeMode::ImeChar => WM_IME_CHAR,
+        _ => WM_CHAR,
+    };
+    let mut index = 0;
+    for c in chars {
+        let units = match ime_mode {
+            WindowImeMode::ImeChar => encoding.ime_units(c),
+            _ => encoding.units(c),
+        };
+        for wparam in units {
+            if mode == WindowSendMode::Post && index > 0 && index % MAX_IN_FLIGHT_MESSAGES == 0 {
+                wait_for_queue(hwnd)?;
+            }
+            on_delivery(deliver(hwnd, message, wparam, 0, mode, ansi)?);
+            index += 1;
+        }
+        super::progress::add_typed(1);
+    }
+    if let Some(suspended) = suspended {
+        suspended.restore()?;
+    }
+    Ok(())
+}
+
+/// 投递文本，按选项将换行、`\t` 和数字按 Enter / Tab / 小键盘按键投递
+///
+/// 中途超时时错误中注明已送达的字符数。
+#[cfg(feature = "window_target")]
+fn deliver_text(
+    hwnd: WindowHandle,
+    text: &str,
+    options: &super::TextOptions,
+    mode: WindowSendMode,
+    on_delivery: impl FnMut(MessageDelivery),
+) -> Result<()> {
+    let (result, typed) =
+        super::progress::track_typed(|| deliver_segments(hwnd, text, options, mode, on_delivery));
+    result.map_err(|error| partial_timeout(error, typed, text.chars().count()))
+}
+
+/// 在超时错误中注明已送达的字符数，其他错误原样返回
+#[cfg(feature = "window_target")]
+fn partial_timeout(
+    error: crate::error::KeyboardSenderError,
+    typed: usize,
+    total: usize,
+) -> crate::error::KeyboardSenderError {
+    use crate::error::KeyboardSenderError;
+
+    match error {
+        KeyboardSenderError::Timeout(reason) => KeyboardSenderError::Timeout(format!(
+            "{} ({} of {} characters delivered)",
+            reason, typed, total
+        )),
+        other => other,
+    }
+}
+
+#[cfg(feature = "window_target")]
+fn deliver_segments(
+    hwnd: WindowHandle,
+    text: &str,
+    options: &super::TextOptions,
+    mode: WindowSendMode,
+    mut on_delivery: impl FnMut(MessageDelivery),
+) -> Result<()> {
+    use super::text_options::{text_segments, TextSegment};
+
+    for segment in text_segments(text, options) {
+        match segment {
+            TextSegment::Text(text) => deliver_chars(hwnd, text.chars(), mode, &mut on_delivery)?,
+            TextSegment::Key(key, c, len) => {
+                // 投递的按键消息由目标线程的 TranslateMessage 生成字符，同步发送时需补发字符消息
+                // 字符在按下（投递）或补发字符消息时产生，此时整段计为已送达
+                let vk = key_to_vk(key);
+                on_delivery(deliver_key(hwnd, vk, false, mode)?);
+                if mode == WindowSendMode::Post {
+                    super::progress::add_typed(len);
+                } else {
+                    deliver_chars(hwnd, std::iter::once(c), mode, &mut on_delivery)?;
+                    super::progress::add_typed(len - 1);
+                }
+                on_delivery(deliver_key(hwnd, vk, true, mode)?);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// 向指定窗口发送：按键按下
 pub fn send_key_down_to_window(hwnd: WindowHandle, key: Key) -> Result<()> {
     #[cfg(not(feature = "window_target"))]
@@ -34,13 +528,10 @@ pub fn send_key_down_to_window(hwnd: WindowHandle, key: Key) -> Result<()> {
 
     #[cfg(feature = "window_target")]
     {
-        let vk = key_to_vk(key);
-        let window_handle = to_hwnd(hwnd);
-
-        unsafe {
-            let _ = PostMessageA(window_handle, WM_KEYDOWN, WPARAM(vk as _), LPARAM(0));
-        }
-        Ok(())
+        super::key_stats::record_press(key, || {
+            deliver_key(hwnd, key_to_vk(key), false, window_send_mode())?;
+            Ok(())
+        })
     }
 }
 
@@ -53,12 +544,7 @@ pub fn send_key_up_to_window(hwnd: WindowHandle, key: Key) -> Result<()> {
 
     #[cfg(feature = "window_target")]
     {
-        let vk = key_to_vk(key);
-        let window_handle = to_hwnd(hwnd);
-
-        unsafe {
-            let _ = PostMessageA(window_handle, WM_KEYUP, WPARAM(vk as _), LPARAM(0));
-        }
+        deliver_key(hwnd, key_to_vk(key), true, window_send_mode())?;
         Ok(())
     }
 }
@@ -69,40 +555,191 @@ pub fn send_key_click_to_window(
     key: Key,
     press_duration: Option<Duration>,
 ) -> Result<()> {
+    let _op = super::begin_operation();
+    crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::KeyClick {
+        target: Some(hwnd),
+        key,
+    });
     send_key_down_to_window(hwnd, key)?;
+    super::config::hold(press_duration);
+    send_key_up_to_window(hwnd, key)?;
+    Ok(())
+}
 
-    if let Some(dur) = press_duration {
-        std::thread::sleep(dur);
+/// 向指定窗口发送：重复点击按键 `n` 次，每两次点击之间等待 `gap`
+pub fn send_key_click_to_window_n(
+    hwnd: WindowHandle,
+    key: Key,
+    n: usize,
+    gap: Option<Duration>,
+) -> Result<()> {
+    let _op = super::begin_operation();
+    for i in 0..n {
+        if i > 0 {
+            if let Some(gap) = gap {
+                super::clock().sleep(gap);
+            }
+        }
+        send_key_click_to_window(hwnd, key, None)?;
     }
-
-    send_key_up_to_window(hwnd, key)?;
     Ok(())
 }
 
+/// 向指定窗口发送：组合键
+///
+/// 任一按键发送失败时，已按下的按键都会按相反顺序释放。
+pub fn press_combination_to_window(
+    hwnd: WindowHandle,
+    modifiers: &[Modifier],
+    key: Key,
+    press_duration: Option<Duration>,
+) -> Result<()> {
+    let vks: Vec<u16> = modifiers
+        .iter()
+        .map(|&modifier| modifier_to_vk(modifier))
+        .chain(std::iter::once(key_to_vk(key)))
+        .collect();
+
+    let _op = super::begin_operation();
+    crate::telemetry::emit(|| crate::telemetry::TelemetryEvent::Combination {
+        target: Some(hwnd),
+        modifiers: modifiers.to_vec(),
+        key,
+    });
+    press_vks_to_window(hwnd, &vks, press_duration)
+}
+
+/// 向指定窗口发送：单独点击修饰键（区分左右）
+pub fn send_modifier_click_to_window(
+    hwnd: WindowHandle,
+    modifier: Modifier,
+    press_duration: Option<Duration>,
+) -> Result<()> {
+    let _op = super::begin_operation();
+    press_vks_to_window(hwnd, &[modifier_to_vk(modifier)], press_duration)
+}
+
+/// 向指定窗口发送：点击原始虚拟键码（如 PrintScreen `0x2C`）
+pub fn send_vk_click_to_window(
+    hwnd: WindowHandle,
+    vk: u16,
+    press_duration: Option<Duration>,
+) -> Result<()> {
+    let _op = super::begin_operation();
+    press_vks_to_window(hwnd, &[vk], press_duration)
+}
+
+/// 依次按下一组虚拟键，等待后按相反顺序释放
+fn press_vks_to_window(
+    hwnd: WindowHandle,
+    vks: &[u16],
+    press_duration: Option<Duration>,
+) -> Result<()> {
+    let mode = window_send_mode();
+    super::guard::press_and_release(
+        vks,
+        |vk| deliver_key(hwnd, vk, false, mode).map(|_| ()),
+        |vk| deliver_key(hwnd, vk, true, mode).map(|_| ()),
+        || super::config::hold(press_duration),
+    )
+}
+
 /// 向指定窗口发送：字符输入
+///
+/// Unicode 窗口按 UTF-16 编码单元发送（补充平面字符为两条消息）；ANSI 窗口按其所在线程
+/// 键盘布局的代码页编码发送，双字节字符为两条消息。
 pub fn send_char_to_window(hwnd: WindowHandle, c: char) -> Result<()> {
     #[cfg(not(feature = "window_target"))]
     return Err(KeyboardSenderError::FeatureNotEnabled(
         "window_target".to_string(),
     ));
 
+    #[cfg(feature = "window_target")]
+    deliver_chars(hwnd, std::iter::once(c), window_send_mode(), |_| {})
+}
+
+/// 向指定窗口发送：字符串输入
+///
+/// 发送前应用当前的文本选项（见 [`TextOptions`](super::TextOptions)）。Post 模式下分批投递，见 [`MAX_IN_FLIGHT_MESSAGES`]。
+pub fn type_string_to_window(hwnd: WindowHandle, text: &str) -> Result<()> {
+    #[cfg(not(feature = "window_target"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled(
+        "window_target".to_string(),
+    ));
+
     #[cfg(feature = "window_target")]
     {
-        let window_handle = to_hwnd(hwnd);
+        let options = super::text_options::effective_text_options();
+        let text = options.apply(text)?;
+        let _op = super::begin_operation();
+        crate::telemetry::emit_text(Some(hwnd), &text);
+        deliver_text(hwnd, &text, &options, window_send_mode(), |_| {})
+    }
+}
 
-        unsafe {
-            let _ = PostMessageA(window_handle, WM_CHAR, WPARAM(c as _), LPARAM(0));
-        }
-        Ok(())
+/// 按指定方式向窗口发送按键点击，返回每条消息的投递结果
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "window_target")]
+/// # {
+/// use sophia_keyboard_sender::{send_key_click_to_window_with, Key, WindowSendMode};
+///
+/// let report =
+///     send_key_click_to_window_with(0x1A2B, Key::Enter, None, WindowSendMode::synchronous())
+///         .unwrap();
+/// println!("processed in {:?}", report.total_latency());
+/// # }
+/// ```
+pub fn send_key_click_to_window_with(
+    hwnd: WindowHandle,
+    key: Key,
+    press_duration: Option<Duration>,
+    mode: WindowSendMode,
+) -> Result<DeliveryReport> {
+    #[cfg(not(feature = "window_target"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled(
+        "window_target".to_string(),
+    ));
+
+    #[cfg(feature = "window_target")]
+    {
+        let _op = super::begin_operation();
+        let vk = key_to_vk(key);
+        let mut report = DeliveryReport::default();
+        report.deliveries.push(deliver_key(hwnd, vk, false, mode)?);
+        super::config::hold(press_duration);
+        report.deliveries.push(deliver_key(hwnd, vk, true, mode)?);
+        Ok(report)
     }
 }
 
-/// 向指定窗口发送：字符串输入
-pub fn type_string_to_window(hwnd: WindowHandle, text: &str) -> Result<()> {
-    for c in text.chars() {
-        send_char_to_window(hwnd, c)?;
+/// 按指定方式向窗口输入字符串，返回每条消息的投递结果
+///
+/// 字符编码同 [`send_char_to_window`]。同步模式下任一消息超时即返回错误；
+/// Post 模式下分批投递，见 [`MAX_IN_FLIGHT_MESSAGES`]。
+pub fn type_string_to_window_with(
+    hwnd: WindowHandle,
+    text: &str,
+    mode: WindowSendMode,
+) -> Result<DeliveryReport> {
+    #[cfg(not(feature = "window_target"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled(
+        "window_target".to_string(),
+    ));
+
+    #[cfg(feature = "window_target")]
+    {
+        let options = super::text_options::effective_text_options();
+        let text = options.apply(text)?;
+        let _op = super::begin_operation();
+        crate::telemetry::emit_text(Some(hwnd), &text);
+        let mut report = DeliveryReport::default();
+        deliver_text(hwnd, &text, &options, mode, |delivery| {
+            report.deliveries.push(delivery)
+        })?;
+        Ok(report)
     }
-    Ok(())
 }
 
 /// 控制窗口焦点
@@ -125,3 +762,123 @@ pub fn set_window_focus(hwnd: WindowHandle, bring_to_top: bool) -> Result<()> {
         Ok(())
     }
 }
+
+/// 激活匹配的窗口并输入文本，窗口未出现或输入失败时按策略重试
+///
+/// 使用全局输入（SendInput），因此会先将窗口置于前台。输入在中途失败时，
+/// 已送达的部分不会撤回，重试只输入剩余的文本。
+pub fn activate_and_type(
+    selector: &super::WindowSelector,
+    text: &str,
+    policy: &crate::retry::RetryPolicy,
+) -> Result<()> {
+    let options = super::text_options::effective_text_options();
+    let text = options.apply(text)?;
+    // 文本已规范化，续发剩余部分时不再转换
+    let options = super::TextOptions {
+        normalize: None,
+        ..options
+    };
+    let mut offset = 0;
+    policy.run(|_| {
+        let hwnd = super::find_window(selector).ok_or_else(|| {
+            crate::error::KeyboardSenderError::InvalidWindowHandle(format!(
+                "no window matches {:?}",
+                selector
+            ))
+        })?;
+        set_window_focus(hwnd, true)?;
+        let rest = &text[offset..];
+        let (result, typed) = super::progress::track_typed(|| {
+            super::with_text_options(options, || super::type_string(rest))
+        });
+        offset += rest
+            .char_indices()
+            .nth(typed)
+            .map_or(rest.len(), |(index, _)| index);
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_specific_modifiers() {
+        let right_alt = modifier_to_vk(Modifier::RightAlt);
+        assert_eq!(right_alt, 0xA5);
+        assert_eq!(modifier_to_vk(Modifier::LeftShift), 0xA0);
+        assert_eq!(modifier_to_vk(Modifier::Control), 0x11);
+        // 消息中使用通用键码，右 Alt 由扩展键标志区分
+        assert_eq!(message_vk(right_alt), 0x12);
+        assert_ne!(key_lparam(HKL(0), right_alt, false) & (1 << 24), 0);
+        assert_eq!(message_vk(0x41), 0x41);
+    }
+
+    #[test]
+    fn test_partial_timeout() {
+        use crate::error::KeyboardSenderError;
+
+        let error = partial_timeout(KeyboardSenderError::Timeout("queue full".into()), 3, 10);
+        assert_eq!(
+            error.to_string(),
+            "Timeout: queue full (3 of 10 characters delivered)"
+        );
+        assert!(matches!(
+            partial_timeout(KeyboardSenderError::WindowsError, 3, 10),
+            KeyboardSenderError::WindowsError
+        ));
+    }
+
+    #[test]
+    fn test_char_encoding_units() {
+        assert_eq!(CharEncoding::Utf16.units('a'), vec![0x61]);
+        assert_eq!(CharEncoding::Utf16.units('😀'), vec![0xD83D, 0xDE00]);
+        // Windows-1251（西里尔文）中 `Ж` 为 0xC6，Windows-1252 中没有该字符
+        assert_eq!(CharEncoding::Ansi(1251).units('Ж'), vec![0xC6]);
+        assert_eq!(CharEncoding::Ansi(1252).units('Ж'), vec![usize::from(b'?')]);
+        // Shift-JIS 中 `あ` 为双字节 0x82 0xA0
+        assert_eq!(CharEncoding::Ansi(932).units('あ'), vec![0x82, 0xA0]);
+        assert_eq!(CharEncoding::Ansi(932).ime_units('あ'), vec![0x82A0]);
+        assert_eq!(CharEncoding::Utf16.ime_units('😀'), vec![0xD83D, 0xDE00]);
+    }
+
+    #[test]
+    fn test_window_ime_mode_roundtrip() {
+        set_window_ime_mode(WindowImeMode::DisableIme);
+        assert_eq!(window_ime_mode(), WindowImeMode::DisableIme);
+        set_window_ime_mode(WindowImeMode::Off);
+        assert_eq!(window_ime_mode(), WindowImeMode::Off);
+    }
+
+    #[test]
+    fn test_window_send_mode_roundtrip() {
+        set_window_send_mode(WindowSendMode::Synchronous {
+            timeout: Duration::from_millis(250),
+        });
+        assert_eq!(
+            window_send_mode(),
+            WindowSendMode::Synchronous {
+                timeout: Duration::from_millis(250)
+            }
+        );
+        set_window_send_mode(WindowSendMode::Post);
+        assert_eq!(window_send_mode(), WindowSendMode::Post);
+    }
+
+    #[test]
+    fn test_delivery_report_latency() {
+        let delivery = |ms| MessageDelivery {
+            message: 0x0100,
+            latency: Duration::from_millis(ms),
+            result: Some(0),
+        };
+        let report = DeliveryReport {
+            deliveries: vec![delivery(3), delivery(7)],
+        };
+        assert_eq!(report.total_latency(), Duration::from_millis(10));
+        assert_eq!(report.max_latency(), Duration::from_millis(7));
+        assert_eq!(DeliveryReport::default().max_latency(), Duration::ZERO);
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "sleep")]
 use sleep_utils::SleepError;
 use thiserror::Error;
 
@@ -20,6 +21,40 @@ pub enum KeyboardSenderError {
     FeatureNotEnabled(String),
     #[error("Windows API error")]
     WindowsError,
+    #[error("Process launch error: {0}")]
+    LaunchError(String),
+    #[error("Timeout: {0}")]
+    Timeout(String),
+    #[error("Focus assertion failed: {0}")]
+    FocusAssertion(String),
+    #[error("Insecure input target: {0}")]
+    InsecureTarget(String),
+    #[error("Execution denied: {0}")]
+    ExecutionDenied(String),
+    #[error("Policy violation: {0}")]
+    PolicyViolation(String),
+    /// 已请求中止（如按下了中止热键），见 `request_abort` / `clear_abort`
+    #[error("Operation aborted")]
+    Aborted,
+    #[error("Injection blocked: {0}")]
+    InjectionBlocked(String),
+    /// `SendInput` 注入的事件少于请求数量（如被 UIPI 拦截或输入被阻止）
+    #[error("Injection failed: {injected} of {requested} events injected (error code {code})")]
+    InjectionFailed {
+        requested: usize,
+        injected: usize,
+        code: u32,
+    },
+    /// 命令处理函数在执行中 panic
+    #[error("Command handler panicked: {0}")]
+    HandlerPanicked(String),
+    /// 库已按不同的选项初始化，见 `init`
+    #[error("Already initialized: {0}")]
+    AlreadyInitialized(String),
+    /// 从序列化的回放记录中读回的错误，只保留原错误的描述
+    #[error("{0}")]
+    Recorded(String),
+    #[cfg(feature = "sleep")]
     #[error("Sleep error: {0}")]
     SleepError(#[from] SleepError),
 }
@@ -3,9 +3,20 @@
 //!
 //! # 特性
 //! - `global`: 全局键盘模拟功能（默认启用）
+//! - `minimal`: 仅基于 `SendInput` 的全局按键、组合键和 Unicode 文本输入，不启用 `global` 时用于精简构建
 //! - `window_target`: 窗口目标功能
 //! - `command_parser`: 命令解析器功能（默认启用）
 //! - `smart`: 智能输入包装函数（可选）
+//! - `convenience`: 便捷函数，如方向键导航（可选）
+//! - `clipboard`: 剪贴板文本读写（可选）
+//! - `workflow`: 多步骤 UI 工作流状态机（可选，实验性）
+//! - `unstable`: 启用实验性子系统（时间线录制 `timeline`、工作流引擎 `workflow`），
+//!   其 API 可能在小版本中变化；未启用时使用这些子系统会在编译期报错
+//!
+//! # 线程安全
+//! 每个逻辑操作（按键点击、组合键、字符串输入等）执行期间持有进程级锁，
+//! 多线程同时发送时不同操作的事件不会交错，但操作之间的顺序不作保证。
+//! 需要把多次调用合并为一个不可交错的整体时，使用 `atomic_operation`。
 //!
 //! # 示例
 //! ```
@@ -29,31 +40,213 @@
 //! type_auto("ctrl+c").unwrap();          // 自动检测为快捷键
 //! # }
 //! ```
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[cfg(all(feature = "workflow", not(feature = "unstable")))]
+compile_error!(
+    "the `workflow` feature is experimental; enable the `unstable` feature to opt in"
+);
 
 // 模块声明
+#[cfg(feature = "convenience")]
+pub mod convenience;
 pub mod core;
 pub mod error;
+pub mod locale;
 pub mod parser;
+pub mod random;
+pub mod retry;
 pub mod smart;
+#[cfg(feature = "global")]
+pub mod telemetry;
+#[cfg(all(feature = "global", feature = "unstable"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+pub mod timeline;
 pub mod types;
+#[cfg(feature = "workflow")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "workflow", feature = "unstable"))))]
+pub mod workflow;
 
 // 重新导出主要类型和函数
 pub use error::{KeyboardSenderError, Result};
+pub use locale::*;
+pub use random::{set_random_source, set_rng_seed, RandomSource, XorShift64};
+pub use retry::{Backoff, RetryPolicy};
 pub use types::{Key, Modifier, WindowHandle};
 
 // 重新导出 sleep-utils 的功能
+#[cfg(feature = "sleep")]
 pub use sleep_utils::{parse_sleep_duration, sleep, smart_sleep};
 
 // 根据特性条件导出
+#[cfg(feature = "window_target")]
+pub use core::accessibility::*;
+
+#[cfg(feature = "global")]
+pub use core::atomic_op::*;
+
+#[cfg(feature = "window_target")]
+pub use core::capture::*;
+
+#[cfg(feature = "window_target")]
+pub use core::capture_window::*;
+
+#[cfg(feature = "clipboard")]
+pub use core::clipboard::*;
+
+#[cfg(feature = "global")]
+pub use core::clock::*;
+
+#[cfg(feature = "global")]
+pub use core::compiled::*;
+
+#[cfg(feature = "global")]
+pub use core::config::*;
+
+#[cfg(feature = "global")]
+pub use core::delay::*;
+
 #[cfg(feature = "global")]
 pub use core::global::*;
 
+#[cfg(feature = "global")]
+pub use core::mappings::*;
+
+#[cfg(all(feature = "minimal", not(feature = "global")))]
+pub use core::minimal::*;
+
+#[cfg(feature = "window_target")]
+pub use core::pacing::*;
+
+#[cfg(feature = "global")]
+pub use core::profile::*;
+
+#[cfg(feature = "global")]
+pub use core::sender::*;
+
+#[cfg(feature = "window_target")]
+pub use core::echo::*;
+
+#[cfg(feature = "window_target")]
+pub use core::focused::*;
+
+#[cfg(feature = "window_target")]
+pub use core::foreground::*;
+
+#[cfg(feature = "window_target")]
+pub use core::fullscreen::*;
+
+#[cfg(feature = "global")]
+pub use core::hold::*;
+
+#[cfg(feature = "global")]
+pub use core::hotkeys::*;
+
+#[cfg(feature = "global")]
+pub use core::humanize::*;
+
+#[cfg(feature = "global")]
+pub use core::ime::*;
+
+#[cfg(feature = "global")]
+pub use core::key_state::*;
+
+#[cfg(feature = "global")]
+pub use core::key_stats::*;
+
+#[cfg(feature = "global")]
+pub use core::layout::*;
+
+#[cfg(feature = "window_target")]
+pub use core::launcher::*;
+
+#[cfg(feature = "global")]
+pub use core::lifecycle::*;
+
+#[cfg(feature = "global")]
+pub use core::qpc::*;
+
+#[cfg(feature = "window_target")]
+pub use core::resolved_target::*;
+
+#[cfg(feature = "window_target")]
+pub use core::secure_input::*;
+
+#[cfg(feature = "window_target")]
+pub use core::selector::*;
+
+#[cfg(feature = "window_target")]
+pub use core::self_test::*;
+
+#[cfg(feature = "global")]
+pub use core::sequence::*;
+
+#[cfg(feature = "window_target")]
+pub use core::spy::*;
+
+#[cfg(feature = "global")]
+pub use core::state::*;
+
+#[cfg(feature = "window_target")]
+pub use core::strategy::*;
+
+#[cfg(feature = "global")]
+pub use core::text_entry::*;
+
+#[cfg(feature = "global")]
+pub use core::text_options::*;
+
+#[cfg(feature = "window_target")]
+pub use core::virtual_keyboard::*;
+
 #[cfg(feature = "window_target")]
 pub use core::window_target::*;
 
+#[cfg(feature = "command_parser")]
+pub use parser::action::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::batch::*;
+
 #[cfg(feature = "command_parser")]
 pub use parser::command::*;
 
+#[cfg(feature = "command_parser")]
+pub use parser::confirm::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::debugger::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::duration::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::executor::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::grammar::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::journal_diff::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::keys::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::policy::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::prompt::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::replay::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::script::*;
+
+#[cfg(feature = "command_parser")]
+pub use parser::template::*;
+
 /// 智能输入函数（需要启用 `smart` 特性）
 ///
 /// 自动检测输入类型：
@@ -87,7 +280,7 @@ pub fn type_multiple(inputs: &[&str]) -> Result<()> {
 
 /// 带延迟的智能输入（需要启用 `smart` 特性）
 #[cfg(feature = "smart")]
-pub fn type_with_delay(text: &str, delay: std::time::Duration) -> Result<()> {
+pub fn type_with_delay(text: &str, delay: impl Into<core::DelaySpec>) -> Result<()> {
     crate::smart::type_with_delay(text, delay)
 }
 
@@ -97,6 +290,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "sleep")]
     fn test_sleep_functions() -> Result<()> {
         smart_sleep(10)?;
         smart_sleep("10ms")?;
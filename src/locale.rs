@@ -0,0 +1,301 @@
+// src/locale.rs
+//! 按键名称本地化（用于界面显示）
+//!
+//! 内置英文（`en`）和简体中文（`zh-CN`）名称表，可通过 [`register_localization`]
+//! 为任意语言注册或覆盖名称，例如在中文界面中把 `ctrl+enter` 显示为 “Ctrl+回车”。
+use crate::types::{Key, Modifier};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// 自定义本地化名称表
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalizationTable {
+    pub keys: HashMap<Key, String>,
+    pub modifiers: HashMap<Modifier, String>,
+}
+
+impl LocalizationTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置按键名称
+    pub fn key(mut self, key: Key, name: &str) -> Self {
+        self.keys.insert(key, name.to_string());
+        self
+    }
+
+    /// 设置修饰键名称
+    pub fn modifier(mut self, modifier: Modifier, name: &str) -> Self {
+        self.modifiers.insert(modifier, name.to_string());
+        self
+    }
+}
+
+type LocalizationRegistry = RwLock<HashMap<String, LocalizationTable>>;
+
+/// 已注册的自定义名称表（键为规范化后的语言标记）
+fn registry() -> &'static LocalizationRegistry {
+    static REGISTRY: OnceLock<LocalizationRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// 注册语言的名称表，与之前注册的同一语言名称表合并
+///
+/// 自定义名称优先于内置名称；未覆盖的按键仍使用内置名称。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{localized_key_name, register_localization, Key, LocalizationTable};
+///
+/// register_localization("zh-TW", LocalizationTable::new().key(Key::Enter, "輸入"));
+/// assert_eq!(localized_key_name(Key::Enter, "zh-TW"), "輸入");
+/// ```
+pub fn register_localization(locale: &str, table: LocalizationTable) {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    let entry = registry.entry(normalize_locale(locale)).or_default();
+    entry.keys.extend(table.keys);
+    entry.modifiers.extend(table.modifiers);
+}
+
+/// 移除语言的自定义名称表，返回之前是否已注册
+pub fn unregister_localization(locale: &str) -> bool {
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&normalize_locale(locale))
+        .is_some()
+}
+
+/// 按键的本地化显示名称
+///
+/// 依次查找自定义名称表、内置名称表（`zh*` 使用简体中文，其余使用英文），
+/// 都没有时返回按键的标准名称。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{localized_key_name, Key};
+///
+/// assert_eq!(localized_key_name(Key::Enter, "zh-CN"), "回车");
+/// assert_eq!(localized_key_name(Key::ArrowLeft, "en"), "Left");
+/// ```
+pub fn localized_key_name(key: Key, locale: &str) -> String {
+    let locale = normalize_locale(locale);
+    if let Some(name) = registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&locale)
+        .and_then(|table| table.keys.get(&key))
+    {
+        return name.clone();
+    }
+
+    let builtin = if is_chinese(&locale) {
+        zh_cn_key_name(key)
+    } else {
+        None
+    };
+    builtin
+        .or_else(|| en_key_name(key))
+        .unwrap_or_else(|| key.as_str())
+        .to_string()
+}
+
+/// 修饰键的本地化显示名称
+pub fn localized_modifier_name(modifier: Modifier, locale: &str) -> String {
+    let locale = normalize_locale(locale);
+    if let Some(name) = registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&locale)
+        .and_then(|table| table.modifiers.get(&modifier))
+    {
+        return name.clone();
+    }
+
+    let base = match modifier {
+        Modifier::Control | Modifier::LeftControl | Modifier::RightControl => "Ctrl",
+        Modifier::Alt | Modifier::LeftAlt | Modifier::RightAlt => "Alt",
+        Modifier::Shift | Modifier::LeftShift | Modifier::RightShift => "Shift",
+        Modifier::Meta | Modifier::LeftMeta | Modifier::RightMeta => "Win",
+    };
+    let (left, right) = if is_chinese(&locale) {
+        ("左 ", "右 ")
+    } else {
+        ("Left ", "Right ")
+    };
+    match modifier {
+        Modifier::LeftControl | Modifier::LeftAlt | Modifier::LeftShift | Modifier::LeftMeta => {
+            format!("{}{}", left, base)
+        }
+        Modifier::RightControl
+        | Modifier::RightAlt
+        | Modifier::RightShift
+        | Modifier::RightMeta => format!("{}{}", right, base),
+        _ => base.to_string(),
+    }
+}
+
+/// 快捷键的本地化显示文本，如 `Ctrl+回车`
+pub fn localized_shortcut(modifiers: &[Modifier], key: Key, locale: &str) -> String {
+    modifiers
+        .iter()
+        .map(|m| localized_modifier_name(*m, locale))
+        .chain(std::iter::once(localized_key_name(key, locale)))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// 规范化语言标记：小写，`_` 替换为 `-`
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_lowercase()
+}
+
+fn is_chinese(locale: &str) -> bool {
+    locale == "zh" || locale.starts_with("zh-")
+}
+
+/// 内置英文名称（与标准名称相同的按键返回 `None`）
+fn en_key_name(key: Key) -> Option<&'static str> {
+    Some(match key {
+        Key::Escape => "Esc",
+        Key::PageUp => "PgUp",
+        Key::PageDown => "PgDn",
+        Key::ArrowLeft => "Left",
+        Key::ArrowUp => "Up",
+        Key::ArrowRight => "Right",
+        Key::ArrowDown => "Down",
+        Key::D0 => "0",
+        Key::D1 => "1",
+        Key::D2 => "2",
+        Key::D3 => "3",
+        Key::D4 => "4",
+        Key::D5 => "5",
+        Key::D6 => "6",
+        Key::D7 => "7",
+        Key::D8 => "8",
+        Key::D9 => "9",
+        Key::Num0 => "Num 0",
+        Key::Num1 => "Num 1",
+        Key::Num2 => "Num 2",
+        Key::Num3 => "Num 3",
+        Key::Num4 => "Num 4",
+        Key::Num5 => "Num 5",
+        Key::Num6 => "Num 6",
+        Key::Num7 => "Num 7",
+        Key::Num8 => "Num 8",
+        Key::Num9 => "Num 9",
+        Key::NumMultiply => "Num *",
+        Key::NumAdd => "Num +",
+        Key::NumSubtract => "Num -",
+        Key::NumDivide => "Num /",
+        Key::NumDecimal => "Num .",
+        Key::CapsLock => "Caps Lock",
+        Key::NumLock => "Num Lock",
+        Key::ScrollLock => "Scroll Lock",
+        Key::Apps => "Menu",
+        Key::MediaPlayPause => "Play/Pause",
+        Key::MediaStop => "Stop",
+        Key::MediaNext => "Next Track",
+        Key::MediaPrevious => "Previous Track",
+        Key::VolumeUp => "Volume Up",
+        Key::VolumeDown => "Volume Down",
+        Key::VolumeMute => "Mute",
+        Key::BrowserBack => "Browser Back",
+        Key::BrowserForward => "Browser Forward",
+        Key::BrowserRefresh => "Browser Refresh",
+        Key::BrowserHome => "Browser Home",
+        _ => return None,
+    })
+}
+
+/// 内置简体中文名称（与英文相同的按键返回 `None`）
+fn zh_cn_key_name(key: Key) -> Option<&'static str> {
+    Some(match key {
+        Key::Enter => "回车",
+        Key::Backspace => "退格",
+        Key::Space => "空格",
+        Key::Insert => "插入",
+        Key::Delete => "删除",
+        Key::PageUp => "上翻页",
+        Key::PageDown => "下翻页",
+        Key::ArrowLeft => "左方向键",
+        Key::ArrowUp => "上方向键",
+        Key::ArrowRight => "右方向键",
+        Key::ArrowDown => "下方向键",
+        Key::Num0 => "小键盘 0",
+        Key::Num1 => "小键盘 1",
+        Key::Num2 => "小键盘 2",
+        Key::Num3 => "小键盘 3",
+        Key::Num4 => "小键盘 4",
+        Key::Num5 => "小键盘 5",
+        Key::Num6 => "小键盘 6",
+        Key::Num7 => "小键盘 7",
+        Key::Num8 => "小键盘 8",
+        Key::Num9 => "小键盘 9",
+        Key::NumMultiply => "小键盘 *",
+        Key::NumAdd => "小键盘 +",
+        Key::NumSubtract => "小键盘 -",
+        Key::NumDivide => "小键盘 /",
+        Key::NumDecimal => "小键盘 .",
+        Key::CapsLock => "大写锁定",
+        Key::NumLock => "数字锁定",
+        Key::ScrollLock => "滚动锁定",
+        Key::Pause => "暂停",
+        Key::Apps => "菜单键",
+        Key::Sleep => "睡眠",
+        Key::MediaPlayPause => "播放/暂停",
+        Key::MediaStop => "停止播放",
+        Key::MediaNext => "下一曲",
+        Key::MediaPrevious => "上一曲",
+        Key::VolumeUp => "音量+",
+        Key::VolumeDown => "音量-",
+        Key::VolumeMute => "静音",
+        Key::BrowserBack => "浏览器后退",
+        Key::BrowserForward => "浏览器前进",
+        Key::BrowserRefresh => "浏览器刷新",
+        Key::BrowserHome => "浏览器主页",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_names() {
+        assert_eq!(localized_key_name(Key::Enter, "zh-CN"), "回车");
+        assert_eq!(localized_key_name(Key::Enter, "zh_cn"), "回车");
+        assert_eq!(localized_key_name(Key::Enter, "en-US"), "Enter");
+        assert_eq!(localized_key_name(Key::D5, "zh-CN"), "5");
+        assert_eq!(localized_key_name(Key::F5, "fr"), "F5");
+        assert_eq!(localized_modifier_name(Modifier::RightAlt, "zh"), "右 Alt");
+        assert_eq!(
+            localized_shortcut(&[Modifier::Control, Modifier::Shift], Key::Escape, "zh-CN"),
+            "Ctrl+Shift+Esc"
+        );
+        assert_eq!(
+            localized_shortcut(&[Modifier::Control], Key::Enter, "zh-CN"),
+            "Ctrl+回车"
+        );
+    }
+
+    #[test]
+    fn test_custom_table_overrides_builtin() {
+        register_localization(
+            "ja-test",
+            LocalizationTable::new()
+                .key(Key::Enter, "エンター")
+                .modifier(Modifier::Control, "コントロール"),
+        );
+        assert_eq!(
+            localized_shortcut(&[Modifier::Control], Key::Enter, "JA_TEST"),
+            "コントロール+エンター"
+        );
+        // 未覆盖的按键回退到英文
+        assert_eq!(localized_key_name(Key::Escape, "ja-test"), "Esc");
+        assert!(unregister_localization("ja-test"));
+        assert_eq!(localized_key_name(Key::Enter, "ja-test"), "Enter");
+    }
+}
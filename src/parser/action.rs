@@ -0,0 +1,144 @@
+// src/parser/action.rs
+//! 自定义命令动作（插件）注册
+//!
+//! 允许用户为 `action:` 参数注册新的处理函数，使领域相关的命令
+//! （如鼠标点击、启动应用）复用 `send()` 的解析与执行流程。
+use crate::error::{KeyboardSenderError, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// 自定义动作处理函数，接收解析后的全部命令参数
+pub type ActionHandler = dyn Fn(&HashMap<String, String>) -> Result<()> + Send + Sync;
+
+/// 内置动作名，不允许被自定义动作覆盖
+pub const BUILTIN_ACTIONS: &[&str] = &[
+    "key_down", "keydown", "key_up", "keyup", "key_click", "keyclick", "char", "text",
+];
+
+type ActionRegistry = RwLock<HashMap<String, Arc<ActionHandler>>>;
+
+/// 已注册的自定义动作
+fn actions() -> &'static ActionRegistry {
+    static ACTIONS: OnceLock<ActionRegistry> = OnceLock::new();
+    ACTIONS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// 注册自定义动作
+///
+/// 动作名不区分大小写；重复注册会替换之前的处理函数。
+///
+/// # 示例
+/// ```
+/// # #[cfg(feature = "command_parser")]
+/// # {
+/// use sophia_keyboard_sender::{register_action, send};
+///
+/// register_action("log", |params| {
+///     println!("{:?}", params.get("msg"));
+///     Ok(())
+/// })
+/// .unwrap();
+///
+/// send("action:log,msg:hello").unwrap();
+/// # }
+/// ```
+pub fn register_action<F>(name: &str, handler: F) -> Result<()>
+where
+    F: Fn(&HashMap<String, String>) -> Result<()> + Send + Sync + 'static,
+{
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(KeyboardSenderError::CommandParseError(
+            "Action name cannot be empty".to_string(),
+        ));
+    }
+    if BUILTIN_ACTIONS.contains(&name.as_str()) {
+        return Err(KeyboardSenderError::CommandParseError(format!(
+            "Cannot override builtin action: {}",
+            name
+        )));
+    }
+
+    actions()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name, Arc::new(handler));
+    Ok(())
+}
+
+/// 注销自定义动作，返回该动作之前是否已注册
+pub fn unregister_action(name: &str) -> bool {
+    actions()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&name.trim().to_lowercase())
+        .is_some()
+}
+
+/// 检查自定义动作是否已注册
+pub fn is_action_registered(name: &str) -> bool {
+    actions()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains_key(&name.trim().to_lowercase())
+}
+
+/// 列出所有已注册的自定义动作名（已排序）
+pub fn registered_actions() -> Vec<String> {
+    let mut names: Vec<String> = actions()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
+
+/// 执行自定义动作；未注册时返回 `None`
+pub(crate) fn dispatch_action(
+    name: &str,
+    params: &HashMap<String, String>,
+) -> Option<Result<()>> {
+    // 先克隆处理函数再调用，避免处理函数内部再次注册时死锁
+    let handler = actions()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&name.trim().to_lowercase())
+        .cloned()?;
+    Some(handler(params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_and_dispatch() {
+        register_action("test_echo", |params| {
+            if params.contains_key("fail") {
+                Err(KeyboardSenderError::CommandParseError("fail".to_string()))
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap();
+
+        assert!(is_action_registered("TEST_ECHO"));
+        assert!(registered_actions().contains(&"test_echo".to_string()));
+
+        let mut params = HashMap::new();
+        assert!(dispatch_action("test_echo", &params).unwrap().is_ok());
+        params.insert("fail".to_string(), "1".to_string());
+        assert!(dispatch_action("test_echo", &params).unwrap().is_err());
+
+        assert!(unregister_action("test_echo"));
+        assert!(dispatch_action("test_echo", &params).is_none());
+    }
+
+    #[test]
+    fn test_builtin_action_rejected() {
+        assert!(register_action("text", |_| Ok(())).is_err());
+        assert!(register_action("  ", |_| Ok(())).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+// src/parser/batch.rs
+//! 批量执行文本命令
+//!
+//! 先解析校验全部命令，任一命令无效时直接返回错误（带行号），不执行任何操作；
+//! 全部有效后再依次执行，并统计每条命令的结果与耗时。
+use crate::error::{KeyboardSenderError, Result};
+use std::time::Duration;
+
+use super::command::{send, validate_command};
+use super::confirm::{confirmed, ExecutionPlan};
+
+/// 批量执行选项
+#[derive(Debug, Clone, Default)]
+pub struct BatchOptions {
+    /// 两条命令之间的等待时间
+    pub delay: Option<Duration>,
+    /// 某条命令执行失败后是否停止执行后续命令
+    pub stop_on_error: bool,
+}
+
+impl BatchOptions {
+    /// 设置命令间隔
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// 设置失败后是否停止
+    pub fn stop_on_error(mut self, stop: bool) -> Self {
+        self.stop_on_error = stop;
+        self
+    }
+}
+
+/// 单条命令的执行结果
+#[derive(Debug)]
+pub struct CommandResult {
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 原始命令
+    pub command: String,
+    /// 执行结果
+    pub result: Result<()>,
+    /// 执行耗时（不含命令间隔）
+    pub duration: Duration,
+}
+
+/// 批量执行报告
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// 已执行命令的结果（按执行顺序）
+    pub results: Vec<CommandResult>,
+    /// 因失败停止而未执行的命令数
+    pub skipped: usize,
+    /// 总耗时（含命令间隔）
+    pub total_duration: Duration,
+}
+
+impl BatchReport {
+    /// 成功执行的命令数
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|r| r.result.is_ok()).count()
+    }
+
+    /// 执行失败的命令数
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.succeeded()
+    }
+
+    /// 是否全部执行成功
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0 && self.skipped == 0
+    }
+
+    /// 所有命令执行耗时之和（不含命令间隔）
+    pub fn command_duration(&self) -> Duration {
+        self.results.iter().map(|r| r.duration).sum()
+    }
+}
+
+/// 批量执行命令（无间隔，失败后继续执行）
+///
+/// 空行和以 `#` 开头的行会被跳过，但仍计入行号。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::send_batch;
+///
+/// let report = send_batch(&["text:hello", "key:tab", "text:world", "key:enter"]).unwrap();
+/// println!("{} ok, {} failed", report.succeeded(), report.failed());
+/// ```
+pub fn send_batch(commands: &[&str]) -> Result<BatchReport> {
+    send_batch_with(commands, &BatchOptions::default())
+}
+
+/// 按选项批量执行命令
+///
+/// 安装了确认处理函数时，整批命令只确认一次。确认后一次性取出全部运行时输入
+/// （见 [`bind_runtime_inputs`](super::template::bind_runtime_inputs)），取值失败时返回错误。
+pub fn send_batch_with(commands: &[&str], options: &BatchOptions) -> Result<BatchReport> {
+    let lines = parse_batch(commands)?;
+    confirmed(
+        || ExecutionPlan::from_commands(&lines.iter().map(|(_, c)| *c).collect::<Vec<_>>()),
+        || {
+            let commands: Vec<&str> = lines.iter().map(|(_, c)| *c).collect();
+            let _inputs = super::template::bind_runtime_inputs(&commands)?;
+            Ok(execute_batch(&lines, options))
+        },
+    )
+}
+
+/// 依次执行已校验的命令
+fn execute_batch(lines: &[(usize, &str)], options: &BatchOptions) -> BatchReport {
+    let clock = crate::core::clock();
+    let start = clock.now();
+    let mut report = BatchReport::default();
+    for (index, (line, command)) in lines.iter().enumerate() {
+        if index > 0 {
+            if let Some(delay) = options.delay {
+                clock.sleep(delay);
+            }
+        }
+
+        let command_start = clock.now();
+        let result = send(command);
+        let failed = result.is_err();
+        report.results.push(CommandResult {
+            line: *line,
+            command: command.to_string(),
+            result,
+            duration: clock.now().saturating_duration_since(command_start),
+        });
+
+        if failed && options.stop_on_error {
+            report.skipped = lines.len() - index - 1;
+            break;
+        }
+    }
+    report.total_duration = clock.now().saturating_duration_since(start);
+    report
+}
+
+/// 校验全部命令，返回需执行的 (行号, 命令)
+fn parse_batch<'a>(commands: &[&'a str]) -> Result<Vec<(usize, &'a str)>> {
+    let mut lines = Vec::new();
+    for (index, command) in commands.iter().enumerate() {
+        let command = command.trim();
+        if command.is_empty() || command.starts_with('#') {
+            continue;
+        }
+        validate_command(command).map_err(|e| {
+            KeyboardSenderError::CommandParseError(format!("line {}: {}", index + 1, e))
+        })?;
+        lines.push((index + 1, command));
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_batch_skips_comments_and_reports_line() {
+        let lines = parse_batch(&["# header", "key:a", "", "text:hi"]).unwrap();
+        assert_eq!(lines, vec![(2, "key:a"), (4, "text:hi")]);
+
+        let err = parse_batch(&["key:a", "key:a,duration:soon"]).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_invalid_batch_executes_nothing() {
+        assert!(send_batch(&["action:test_batch_missing"]).is_err());
+    }
+}
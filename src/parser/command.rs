@@ -4,13 +4,16 @@ use crate::error::{KeyboardSenderError, Result};
 #[allow(unused_imports)]
 use crate::types::{Key, Modifier, WindowHandle};
 use std::collections::HashMap;
+use std::time::Duration;
 
-#[cfg(feature = "command_parser")]
-use lazy_static::lazy_static;
 #[cfg(feature = "command_parser")]
 use regex::Regex;
+#[cfg(feature = "command_parser")]
+use std::sync::OnceLock;
 
-use sleep_utils::parse_sleep_duration;
+use super::duration::DurationParser;
+use super::keys::KeyName;
+use crate::core::{DelaySpec, InputAction};
 
 /// 解析窗口句柄
 pub fn parse_hwnd(hwnd_str: &str) -> Result<WindowHandle> {
@@ -26,22 +29,99 @@ pub fn parse_hwnd(hwnd_str: &str) -> Result<WindowHandle> {
     .map_err(|_| KeyboardSenderError::InvalidWindowHandle(hwnd_str.to_string()))
 }
 
-/// 解析命令参数
-pub fn parse_command_params(command: &str) -> HashMap<String, String> {
-    let mut params = HashMap::new();
+/// 解析 `duration:` 参数
+///
+/// - `"default"`：返回 `None`，使用默认按下时长
+/// - `"none"`：返回 `Some(Duration::ZERO)`，不保持按下
+/// - 其他值按 `sleep-utils` 的时长格式解析（如 `"100ms"`、`"2s"`），无效时返回错误
+pub fn parse_duration_param(duration_str: &str) -> Result<Option<Duration>> {
+    DurationParser::shared().parse_param(duration_str)
+}
 
-    #[cfg(feature = "command_parser")]
-    {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"(\w+):([^,]+)").unwrap();
-        }
+/// 解析可随机化的 `duration:`、`gap:`、`delay:` 参数
+///
+/// 在 [`parse_duration_param`] 的基础上支持 `"20ms..80ms"` 和 `"rand(10ms,50ms)"`，
+/// 每次使用时在范围内随机取值。
+pub fn parse_delay_param(delay_str: &str) -> Result<Option<DelaySpec>> {
+    DurationParser::shared().parse_spec_param(delay_str)
+}
+
+/// 解析 `count:` 参数（重复次数）
+pub fn parse_count(count_str: &str) -> Result<usize> {
+    count_str.trim().parse().map_err(|_| {
+        KeyboardSenderError::CommandParseError(format!("Invalid count: {}", count_str))
+    })
+}
+
+/// 解析开关参数（如 `numpad:true`）：`true`/`yes`/`on`/`1` 或 `false`/`no`/`off`/`0`
+pub fn parse_flag(flag_str: &str) -> Result<bool> {
+    match flag_str.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(KeyboardSenderError::CommandParseError(format!(
+            "Invalid flag: {}",
+            flag_str
+        ))),
+    }
+}
+
+/// 预编译的命令参数解析器
+///
+/// 正则只编译一次，解析大量命令时可复用同一个实例。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::CommandParser;
+///
+/// let parser = CommandParser::new();
+/// let params = parser.parse("key:a,duration:100ms");
+/// assert_eq!(params["key"], "a");
+/// ```
+#[cfg(feature = "command_parser")]
+#[derive(Debug, Clone)]
+pub struct CommandParser {
+    pattern: Regex,
+}
 
-        for cap in RE.captures_iter(command) {
-            params.insert(cap[1].to_string(), cap[2].to_string());
+#[cfg(feature = "command_parser")]
+impl CommandParser {
+    /// 创建解析器并预编译参数正则
+    pub fn new() -> Self {
+        Self {
+            // `rand(a,b)` 中的逗号不分隔参数；其后的多余文本留在值中，由时长解析拒绝
+            pattern: Regex::new(r"(\w+):(rand\([^)]*\)[^,]*|[^,]+)").unwrap(),
         }
     }
 
-    params
+    /// 进程内共享的解析器实例
+    pub fn shared() -> &'static CommandParser {
+        static SHARED: OnceLock<CommandParser> = OnceLock::new();
+        SHARED.get_or_init(CommandParser::new)
+    }
+
+    /// 解析命令参数
+    pub fn parse(&self, command: &str) -> HashMap<String, String> {
+        self.pattern
+            .captures_iter(command)
+            .map(|cap| (cap[1].to_string(), cap[2].to_string()))
+            .collect()
+    }
+}
+
+#[cfg(feature = "command_parser")]
+impl Default for CommandParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析命令参数
+pub fn parse_command_params(command: &str) -> HashMap<String, String> {
+    #[cfg(not(feature = "command_parser"))]
+    return HashMap::new();
+
+    #[cfg(feature = "command_parser")]
+    CommandParser::shared().parse(command)
 }
 
 /// 发送快捷键
@@ -53,157 +133,353 @@ pub fn shortcut(shortcut_str: &str) -> Result<()> {
 
     #[cfg(any(feature = "global", feature = "window_target"))]
     {
-        use keyboard_codes::{parse_shortcut_with_aliases};
-        
-        let parsed = parse_shortcut_with_aliases(shortcut_str)
-            .map_err(|e| KeyboardSenderError::ParseError(e.to_string()))?;
+        let (modifiers, key) = super::keys::parse_shortcut(shortcut_str)?;
 
         // 使用现有的组合键功能
-        crate::core::press_combination(&parsed.modifiers, parsed.key, None)
+        crate::core::press_combination(&modifiers, key, None)
     }
 }
 
 /// 执行文本命令
+///
+/// 安装了确认处理函数（见 [`set_confirmation_handler`](super::confirm::set_confirmation_handler)）时，
+/// 先请求确认，被拒绝则返回 `ExecutionDenied` 错误。
 pub fn send(command: &str) -> Result<()> {
+    super::confirm::confirmed(
+        || super::confirm::ExecutionPlan::from_commands(&[command]),
+        || execute_command(command, 0),
+    )
+}
+
+/// 执行文本命令（不经过确认），文本跳过已送达的前 `typed` 个字符（按规范化后的文本计）
+fn execute_command(command: &str, typed: usize) -> Result<()> {
     #[cfg(not(feature = "command_parser"))]
     return Err(KeyboardSenderError::FeatureNotEnabled("command_parser".to_string()));
 
     #[cfg(feature = "command_parser")]
     {
-        use keyboard_codes::{parse_keyboard_input, KeyboardInput};
-
-        let params = parse_command_params(command);
+        // 运行时输入（复制选中文本、询问用户）在获取操作锁之前取值，避免阻塞其他线程
+        let _inputs = super::template::bind_runtime_inputs(&[command])?;
+        // 一条命令作为一个逻辑操作，避免与其他线程的输入交错
+        let _op = crate::core::begin_operation();
+        let params = super::grammar::parse_params(command)?;
 
         let action = params.get("action").or_else(|| params.get("type"));
         let key_str = params.get("key");
         let char_str = params.get("char");
-        let text_str = params.get("text");
+        // `template:true` 时文本按模板渲染，其他参数可作为变量（如 `text:Hi {name},name:Bob,template:true`）
+        let text_rendered = super::template::command_text(&params)?;
+        if let Some(text) = &text_rendered {
+            super::policy::enforce_text_len(text.chars().count())?;
+        }
+        let text_str = text_rendered.as_ref();
         let shortcut_str = params.get("shortcut");
         let hwnd_str = params.get("hwnd").map(|s| s.as_str()).unwrap_or("0");
         let duration_str = params.get("duration");
 
         let hwnd = parse_hwnd(hwnd_str)?;
-        let duration = duration_str.and_then(|dur| parse_sleep_duration(dur).ok());
+        let duration = match duration_str {
+            Some(dur) => parse_delay_param(dur)?,
+            None => None,
+        };
+        let count = match params.get("count") {
+            Some(count) => parse_count(count)?,
+            None => 1,
+        };
+        let gap = match params.get("gap") {
+            Some(gap) => parse_delay_param(gap)?,
+            None => None,
+        };
+        let mut actions = Vec::new();
+        if let Some(delay) = params.get("delay") {
+            if let Some(delay) = parse_delay_param(delay)? {
+                actions.push(InputAction::Sleep(delay.sample()));
+            }
+        }
 
-        // 根据参数执行相应操作
+        // 根据参数生成动作序列；快捷键始终全局发送
+        let mut target = hwnd;
         if let Some(shortcut_cmd) = shortcut_str {
-            shortcut(shortcut_cmd)?;
+            actions.push(shortcut_action(shortcut_cmd)?);
+            target = 0;
         } else if let Some(action_type) = action {
             match action_type.as_str() {
                 "key_down" | "keydown" => {
-                    if let Some(key) = key_str {
-                        let keyboard_input = parse_keyboard_input(key)
-                            .map_err(|e| KeyboardSenderError::ParseError(e.to_string()))?;
-                        
-                        if let KeyboardInput::Key(key) = keyboard_input {
-                            if hwnd == 0 {
-                                #[cfg(feature = "global")]
-                                crate::core::key_down(key)?;
-                            } else {
-                                #[cfg(feature = "window_target")]
-                                crate::core::send_key_down_to_window(hwnd, key)?;
-                            }
-                        }
+                    if let Some(key) = parse_held_key_param(key_str)? {
+                        actions.push(InputAction::Down(key));
                     }
                 }
                 "key_up" | "keyup" => {
-                    if let Some(key) = key_str {
-                        let keyboard_input = parse_keyboard_input(key)
-                            .map_err(|e| KeyboardSenderError::ParseError(e.to_string()))?;
-                        
-                        if let KeyboardInput::Key(key) = keyboard_input {
-                            if hwnd == 0 {
-                                #[cfg(feature = "global")]
-                                crate::core::key_up(key)?;
-                            } else {
-                                #[cfg(feature = "window_target")]
-                                crate::core::send_key_up_to_window(hwnd, key)?;
-                            }
-                        }
+                    if let Some(key) = parse_held_key_param(key_str)? {
+                        actions.push(InputAction::Up(key));
                     }
                 }
                 "key_click" | "keyclick" => {
-                    if let Some(key) = key_str {
-                        let keyboard_input = parse_keyboard_input(key)
-                            .map_err(|e| KeyboardSenderError::ParseError(e.to_string()))?;
-                        
-                        if let KeyboardInput::Key(key) = keyboard_input {
-                            if hwnd == 0 {
-                                #[cfg(feature = "global")]
-                                crate::core::key_click(key, duration)?;
-                            } else {
-                                #[cfg(feature = "window_target")]
-                                crate::core::send_key_click_to_window(hwnd, key, duration)?;
-                            }
-                        }
+                    if let Some(key) = parse_key_param(key_str)? {
+                        push_clicks(&mut actions, key, duration, count, gap);
                     }
                 }
                 "char" => {
-                    if let Some(char_val) = char_str {
-                        if let Some(c) = char_val.chars().next() {
-                            if hwnd == 0 {
-                                #[cfg(feature = "global")]
-                                crate::core::send_char(c)?;
-                            } else {
-                                #[cfg(feature = "window_target")]
-                                crate::core::send_char_to_window(hwnd, c)?;
-                            }
-                        }
+                    if let Some(c) = char_str.and_then(|c| c.chars().next()) {
+                        actions.push(InputAction::Char(c));
                     }
                 }
                 "text" => {
                     if let Some(text) = text_str {
-                        if hwnd == 0 {
-                            #[cfg(feature = "global")]
-                            crate::core::type_string(text)?;
-                        } else {
-                            #[cfg(feature = "window_target")]
-                            crate::core::type_string_to_window(hwnd, text)?;
-                        }
+                        actions.push(InputAction::Text(text.clone()));
                     }
                 }
-                _ => return Err(KeyboardSenderError::CommandParseError(format!("Unknown action: {}", action_type)))
+                custom => {
+                    crate::core::send_sequence_to(target, &actions)?;
+                    return match crate::parser::action::dispatch_action(custom, &params) {
+                        Some(result) => result,
+                        None => Err(KeyboardSenderError::CommandParseError(format!("Unknown action: {}", action_type))),
+                    };
+                }
             }
         } else {
             // 向后兼容
-            if let Some(key) = key_str {
-                let keyboard_input = parse_keyboard_input(key)
-                    .map_err(|e| KeyboardSenderError::ParseError(e.to_string()))?;
-                
-                if let KeyboardInput::Key(key) = keyboard_input {
-                    if hwnd == 0 {
-                        #[cfg(feature = "global")]
-                        crate::core::key_click(key, duration)?;
-                    } else {
-                        #[cfg(feature = "window_target")]
-                        crate::core::send_key_click_to_window(hwnd, key, duration)?;
-                    }
+            if key_str.is_some() {
+                if let Some(key) = parse_key_param(key_str)? {
+                    push_clicks(&mut actions, key, duration, count, gap);
                 }
             } else if let Some(char_val) = char_str {
                 if let Some(c) = char_val.chars().next() {
-                    if hwnd == 0 {
-                        #[cfg(feature = "global")]
-                        crate::core::send_char(c)?;
-                    } else {
-                        #[cfg(feature = "window_target")]
-                        crate::core::send_char_to_window(hwnd, c)?;
-                    }
+                    actions.push(InputAction::Char(c));
                 }
             } else if let Some(text) = text_str {
-                if hwnd == 0 {
-                    #[cfg(feature = "global")]
-                    crate::core::type_string(text)?;
-                } else {
-                    #[cfg(feature = "window_target")]
-                    crate::core::type_string_to_window(hwnd, text)?;
-                }
-            } else if let Some(shortcut_cmd) = shortcut_str {
-                shortcut(shortcut_cmd)?;
+                actions.push(InputAction::Text(text.clone()));
             } else {
                 return Err(KeyboardSenderError::CommandParseError("No valid command found".to_string()));
             }
         }
 
+        // `numpad:true` 时本条命令的数字以小键盘按键发送
+        let mut text_options = crate::core::text_options::effective_text_options();
+        if let Some(numpad) = params.get("numpad") {
+            text_options.numpad_digits = parse_flag(numpad)?;
+        }
+        // 文本先规范化，续发时按规范化后的字符跳过已送达的部分
+        for action in &mut actions {
+            if let InputAction::Text(text) = action {
+                *text = text_options.apply(text)?.chars().skip(typed).collect();
+            }
+        }
+        text_options.normalize = None;
+        crate::core::with_text_options(text_options, || {
+            crate::core::send_sequence_to(target, &actions)
+        })
+    }
+}
+
+/// 解析 `key:` 参数；缺少参数或是修饰键时返回 `None`
+///
+/// 特殊按键（如 PrintScreen）返回 [`KeyName::Vk`]，只支持点击。
+#[cfg(feature = "command_parser")]
+fn parse_key_param(key: Option<&String>) -> Result<Option<KeyName>> {
+    let Some(key) = key else {
+        return Ok(None);
+    };
+    match super::keys::parse_key_name(key)? {
+        KeyName::Modifier(_) => Ok(None),
+        key => Ok(Some(key)),
+    }
+}
+
+/// 解析按下/释放动作的 `key:` 参数；特殊按键只支持点击，返回 `UnsupportedKey`
+#[cfg(feature = "command_parser")]
+fn parse_held_key_param(key: Option<&String>) -> Result<Option<Key>> {
+    match parse_key_param(key)? {
+        Some(KeyName::Key(key)) => Ok(Some(key)),
+        Some(KeyName::Vk(_)) => Err(KeyboardSenderError::UnsupportedKey(format!(
+            "{} only supports key_click",
+            key.map_or("", |k| k.as_str())
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// 将快捷键字符串转换为组合键动作
+#[cfg(feature = "command_parser")]
+fn shortcut_action(shortcut_str: &str) -> Result<InputAction> {
+    let (modifiers, key) = super::keys::parse_shortcut(shortcut_str)?;
+    Ok(InputAction::Combo(modifiers, key))
+}
+
+/// 追加 `count` 次点击，每两次点击之间等待 `gap`；指定了按下时长时拆分为按下、等待、释放
+#[cfg(feature = "command_parser")]
+fn push_clicks(
+    actions: &mut Vec<InputAction>,
+    key: KeyName,
+    duration: Option<DelaySpec>,
+    count: usize,
+    gap: Option<DelaySpec>,
+) {
+    for i in 0..count {
+        if i > 0 {
+            if let Some(gap) = gap {
+                actions.push(InputAction::Sleep(gap.sample()));
+            }
+        }
+        match (key, duration) {
+            (KeyName::Key(key), Some(duration)) => actions.extend([
+                InputAction::Down(key),
+                InputAction::Sleep(duration.sample()),
+                InputAction::Up(key),
+            ]),
+            (KeyName::Key(key), None) => actions.push(InputAction::Click(key)),
+            (KeyName::Vk(vk), duration) => {
+                actions.push(InputAction::VkClick(vk, duration.map(|d| d.sample())))
+            }
+            (KeyName::Modifier(_), _) => {}
+        }
+    }
+}
+
+/// 检查命令能否解析，不执行任何操作
+///
+/// 校验窗口句柄、时长、次数、键名、快捷键和动作名；文本模板在执行时才渲染，不在此校验。
+pub fn validate_command(command: &str) -> Result<()> {
+    #[cfg(not(feature = "command_parser"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled("command_parser".to_string()));
+
+    #[cfg(feature = "command_parser")]
+    {
+        let params = super::grammar::parse_params(command)?;
+        if let Some(hwnd) = params.get("hwnd") {
+            parse_hwnd(hwnd)?;
+        }
+        for name in ["duration", "gap", "delay"] {
+            if let Some(value) = params.get(name) {
+                parse_delay_param(value)?;
+            }
+        }
+        if let Some(count) = params.get("count") {
+            parse_count(count)?;
+        }
+        if let Some(numpad) = params.get("numpad") {
+            parse_flag(numpad)?;
+        }
+        super::template::is_template(&params)?;
+        if let Some(key) = params.get("key") {
+            super::keys::parse_key_name(key)?;
+        }
+        if let Some(shortcut) = params.get("shortcut") {
+            super::keys::parse_shortcut(shortcut)?;
+        }
+
+        let action = params.get("action").or_else(|| params.get("type"));
+        match action {
+            Some(action) => {
+                let builtin = crate::parser::action::BUILTIN_ACTIONS.contains(&action.as_str());
+                if !builtin && !crate::parser::action::is_action_registered(action) {
+                    return Err(KeyboardSenderError::CommandParseError(format!(
+                        "Unknown action: {}",
+                        action
+                    )));
+                }
+            }
+            None => {
+                if !["key", "char", "text", "shortcut"]
+                    .iter()
+                    .any(|name| params.contains_key(*name))
+                {
+                    return Err(KeyboardSenderError::CommandParseError(
+                        "No valid command found".to_string(),
+                    ));
+                }
+            }
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// 执行文本命令，失败时按重试策略重试
+///
+/// 文本输入在中途失败时，已送达的部分不会撤回，重试只输入剩余的文本。
+pub fn send_reliable(command: &str, policy: &crate::retry::RetryPolicy) -> Result<()> {
+    super::confirm::confirmed(
+        || super::confirm::ExecutionPlan::from_commands(&[command]),
+        || {
+            let mut typed = 0;
+            policy.run(|_| {
+                let (result, delivered) =
+                    crate::core::track_typed(|| execute_command(command, typed));
+                typed += delivered;
+                result
+            })
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_param() {
+        assert_eq!(
+            parse_duration_param("100ms").unwrap(),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(parse_duration_param("default").unwrap(), None);
+        assert_eq!(parse_duration_param("none").unwrap(), Some(Duration::ZERO));
+        assert!(matches!(
+            parse_duration_param("soon"),
+            Err(KeyboardSenderError::InvalidDuration(s)) if s == "soon"
+        ));
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("5").unwrap(), 5);
+        assert_eq!(parse_count(" 0 ").unwrap(), 0);
+        assert!(parse_count("-1").is_err());
+        assert!(parse_count("many").is_err());
+    }
+
+    #[test]
+    fn test_parse_flag() {
+        assert!(parse_flag("True").unwrap());
+        assert!(!parse_flag(" off ").unwrap());
+        assert!(parse_flag("maybe").is_err());
+        assert!(validate_command("text:42,numpad:yes").is_ok());
+        assert!(validate_command("text:42,numpad:2").is_err());
+    }
+
+    #[test]
+    fn test_held_key_rejects_click_only_keys() {
+        assert_eq!(
+            parse_held_key_param(Some(&"a".to_string())).unwrap(),
+            Some(Key::A)
+        );
+        assert!(matches!(
+            parse_held_key_param(Some(&"printscreen".to_string())),
+            Err(KeyboardSenderError::UnsupportedKey(s)) if s == "printscreen only supports key_click"
+        ));
+        assert!(send("action:key_down,key:prtsc").is_err());
+    }
+
+    #[test]
+    fn test_validate_command() {
+        assert!(validate_command("key:a,count:3,gap:10ms").is_ok());
+        assert!(validate_command("key:a,duration:20ms..80ms,delay:rand(10ms,50ms)").is_ok());
+        assert!(validate_command("key:a,delay:rand(10ms)").is_err());
+        assert!(validate_command("key:a,delay:rand(10ms,50ms)5ms").is_err());
+        let params = parse_command_params("key:a,delay:rand(1ms,2ms)x,count:2");
+        assert_eq!(params["delay"], "rand(1ms,2ms)x");
+        assert_eq!(params["count"], "2");
+        assert!(validate_command("text:hello,hwnd:0x1A2B").is_ok());
+        assert!(validate_command("action:no_such_action").is_err());
+        assert!(validate_command("key:a,hwnd:zz").is_err());
+        assert!(validate_command("hello").is_err());
+    }
+
+    #[test]
+    fn test_send_rejects_invalid_duration() {
+        assert!(matches!(
+            send("key:a,duration:abc"),
+            Err(KeyboardSenderError::InvalidDuration(_))
+        ));
+    }
+}
@@ -0,0 +1,319 @@
+// src/parser/confirm.rs
+//! 执行前确认钩子
+//!
+//! 安装了命令权限策略（见 [`Policy`](super::policy::Policy)）时，同样在这里先检查策略。
+//!
+//! 安装确认处理函数后，`send()`、`send_batch()` 和 `replay()` 在执行前会先生成
+//! [`ExecutionPlan`]（目标窗口、按键、文本长度等摘要）交给处理函数，
+//! 返回 `false` 时不执行任何操作并返回 `ExecutionDenied` 错误。
+//! 计划中不包含文本内容，只记录字符数。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::cell::Cell;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use super::command::{parse_count, parse_hwnd};
+
+/// 确认处理函数
+pub type ConfirmationHandler = dyn Fn(&ExecutionPlan) -> bool + Send + Sync;
+
+/// 单条命令将执行的操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedAction {
+    /// 点击按键
+    KeyClick { key: String, count: usize },
+    /// 按下按键
+    KeyDown(String),
+    /// 释放按键
+    KeyUp(String),
+    /// 输入单个字符
+    Char,
+    /// 输入文本（字符数，`template:true` 时为渲染后的字符数，尚未取值的运行时输入按占位符计）
+    Text { len: usize },
+    /// 快捷键
+    Shortcut(String),
+    /// 自定义动作
+    Custom(String),
+}
+
+impl PlannedAction {
+    /// 是否为系统快捷键：含 Win 键或 Ctrl+Alt+Del 一类的组合键（见
+    /// [`is_system_combination`](crate::core::hotkeys::is_system_combination)），以及单独按下 Win 键
+    ///
+    /// 无法解析的键名不算系统快捷键，执行时会另行报错。
+    pub fn is_system_shortcut(&self) -> bool {
+        match self {
+            PlannedAction::Shortcut(shortcut) => super::keys::parse_shortcut(shortcut)
+                .is_ok_and(|(modifiers, key)| {
+                    crate::core::hotkeys::is_system_combination(&modifiers, key)
+                }),
+            PlannedAction::KeyClick { key, .. } | PlannedAction::KeyDown(key) => {
+                super::keys::parse_key_name(key).is_ok_and(|key| key.is_system_key())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlannedAction::KeyClick { key, count } if *count == 1 => write!(f, "click {}", key),
+            PlannedAction::KeyClick { key, count } => write!(f, "click {} x{}", key, count),
+            PlannedAction::KeyDown(key) => write!(f, "press {}", key),
+            PlannedAction::KeyUp(key) => write!(f, "release {}", key),
+            PlannedAction::Char => write!(f, "type 1 char"),
+            PlannedAction::Text { len } => write!(f, "type {} chars", len),
+            PlannedAction::Shortcut(shortcut) => write!(f, "shortcut {}", shortcut),
+            PlannedAction::Custom(name) => write!(f, "action {}", name),
+        }
+    }
+}
+
+/// 计划中的一步
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStep {
+    /// 目标窗口（`None` 表示前台窗口）
+    pub target: Option<WindowHandle>,
+    /// 操作
+    pub action: PlannedAction,
+}
+
+impl fmt::Display for PlannedStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.target {
+            Some(hwnd) => write!(f, "{} -> window 0x{:X}", self.action, hwnd),
+            None => write!(f, "{} -> foreground", self.action),
+        }
+    }
+}
+
+/// 即将执行的操作摘要
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionPlan {
+    /// 按执行顺序排列的步骤
+    pub steps: Vec<PlannedStep>,
+}
+
+impl ExecutionPlan {
+    /// 从文本命令生成计划（命令无效时返回错误）
+    pub fn from_commands<S: AsRef<str>>(commands: &[S]) -> Result<Self> {
+        let steps = commands
+            .iter()
+            .map(|command| plan_command(command.as_ref()))
+            .collect::<Result<_>>()?;
+        Ok(Self { steps })
+    }
+
+    /// 涉及的目标窗口（去重，保持出现顺序；`None` 表示前台窗口）
+    pub fn targets(&self) -> Vec<Option<WindowHandle>> {
+        let mut targets = Vec::new();
+        for step in &self.steps {
+            if !targets.contains(&step.target) {
+                targets.push(step.target);
+            }
+        }
+        targets
+    }
+
+    /// 将输入的文本字符总数
+    pub fn text_len(&self) -> usize {
+        self.steps
+            .iter()
+            .map(|step| match step.action {
+                PlannedAction::Text { len } => len,
+                PlannedAction::Char => 1,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for ExecutionPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}. {}", index + 1, step)?;
+        }
+        Ok(())
+    }
+}
+
+static HANDLER: RwLock<Option<Arc<ConfirmationHandler>>> = RwLock::new(None);
+
+thread_local! {
+    static CONFIRMED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 安装确认处理函数（替换之前的处理函数）
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{clear_confirmation_handler, send, set_confirmation_handler};
+///
+/// set_confirmation_handler(|plan| {
+///     println!("about to run:\n{}", plan);
+///     plan.text_len() < 1000
+/// });
+/// # clear_confirmation_handler();
+/// ```
+pub fn set_confirmation_handler<F>(handler: F)
+where
+    F: Fn(&ExecutionPlan) -> bool + Send + Sync + 'static,
+{
+    *HANDLER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(handler));
+}
+
+/// 移除确认处理函数
+pub fn clear_confirmation_handler() {
+    *HANDLER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 检查权限策略并请求确认后执行 `f`
+///
+/// 未安装策略和处理函数，或已在确认过的执行中（如批量中的单条命令）时直接执行。
+pub(crate) fn confirmed<T>(
+    plan: impl FnOnce() -> Result<ExecutionPlan>,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    if CONFIRMED.with(Cell::get) {
+        return f();
+    }
+    let handler = HANDLER.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if handler.is_none() && !super::policy::has_policy() {
+        return f();
+    }
+
+    let plan = plan()?;
+    super::policy::enforce(&plan)?;
+    if handler.is_some_and(|handler| !handler(&plan)) {
+        return Err(KeyboardSenderError::ExecutionDenied(format!(
+            "{} step(s) rejected by confirmation handler",
+            plan.steps.len()
+        )));
+    }
+
+    let previous = CONFIRMED.with(|c| c.replace(true));
+    let _restore = RestoreConfirmed(previous);
+    f()
+}
+
+/// 退出确认作用域时恢复标记（包括 panic）
+struct RestoreConfirmed(bool);
+
+impl Drop for RestoreConfirmed {
+    fn drop(&mut self) {
+        CONFIRMED.with(|c| c.set(self.0));
+    }
+}
+
+/// 生成单条命令的计划步骤
+fn plan_command(command: &str) -> Result<PlannedStep> {
+    let params = super::grammar::parse_params(command)?;
+    let hwnd = parse_hwnd(params.get("hwnd").map(|s| s.as_str()).unwrap_or("0"))?;
+    let count = match params.get("count") {
+        Some(count) => parse_count(count)?,
+        None => 1,
+    };
+    let key = || params.get("key").cloned().unwrap_or_default();
+    let len = super::template::preview_command_text(&params)?.map_or(0, |t| t.chars().count());
+    let text = || PlannedAction::Text { len };
+
+    // 快捷键始终全局发送，与执行时一致，不记录 `hwnd:` 目标
+    let mut target = (hwnd != 0).then_some(hwnd);
+    let action = if let Some(shortcut) = params.get("shortcut") {
+        target = None;
+        PlannedAction::Shortcut(shortcut.clone())
+    } else {
+        match params
+            .get("action")
+            .or_else(|| params.get("type"))
+            .map(|s| s.as_str())
+        {
+            Some("key_down" | "keydown") => PlannedAction::KeyDown(key()),
+            Some("key_up" | "keyup") => PlannedAction::KeyUp(key()),
+            Some("key_click" | "keyclick") => PlannedAction::KeyClick { key: key(), count },
+            Some("char") => PlannedAction::Char,
+            Some("text") => text(),
+            Some(custom) => PlannedAction::Custom(custom.to_string()),
+            None if params.contains_key("key") => PlannedAction::KeyClick { key: key(), count },
+            None if params.contains_key("char") => PlannedAction::Char,
+            None if params.contains_key("text") => text(),
+            None => {
+                return Err(KeyboardSenderError::CommandParseError(
+                    "No valid command found".to_string(),
+                ))
+            }
+        }
+    };
+
+    Ok(PlannedStep { target, action })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_summarizes_commands() {
+        let plan = ExecutionPlan::from_commands(&[
+            "text:secret",
+            "key:tab,count:2,hwnd:0x10",
+            "shortcut:ctrl+s,hwnd:0x10",
+            "action:key_down,key:shift",
+        ])
+        .unwrap();
+
+        assert_eq!(plan.text_len(), 6);
+        assert_eq!(plan.targets(), vec![None, Some(0x10)]);
+        assert_eq!(
+            plan.to_string(),
+            "1. type 6 chars -> foreground\n\
+             2. click tab x2 -> window 0x10\n\
+             3. shortcut ctrl+s -> foreground\n\
+             4. press shift -> foreground"
+        );
+        assert!(!plan.to_string().contains("secret"));
+
+        let plan = ExecutionPlan::from_commands(&[
+            "text:{x}{{,x:abcdef,template:true",
+            "text:{x}{{,x:abcdef",
+        ])
+        .unwrap();
+        assert_eq!(plan.text_len(), 7 + 6);
+    }
+
+    #[test]
+    fn test_system_shortcut_classification() {
+        let plan = ExecutionPlan::from_commands(&[
+            "shortcut:win+r",
+            "shortcut:ctrl+alt+del",
+            "action:key_down,key:lwin",
+            "shortcut:ctrl+s",
+            "key:menu",
+        ])
+        .unwrap();
+        let system: Vec<bool> = plan
+            .steps
+            .iter()
+            .map(|step| step.action.is_system_shortcut())
+            .collect();
+        assert_eq!(system, vec![true, true, true, false, false]);
+    }
+
+    #[test]
+    fn test_confirmed_scope() {
+        // 未安装处理函数时直接执行，且不生成计划
+        let result = confirmed(|| panic!("plan should not be built"), || Ok(1));
+        assert_eq!(result.unwrap(), 1);
+
+        // 已确认的作用域内不再重复确认
+        CONFIRMED.with(|c| c.set(true));
+        let result = confirmed(|| panic!("plan should not be built"), || Ok(2));
+        CONFIRMED.with(|c| c.set(false));
+        assert_eq!(result.unwrap(), 2);
+    }
+}
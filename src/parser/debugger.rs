@@ -0,0 +1,359 @@
+// src/parser/debugger.rs
+//! 宏单步调试
+//!
+//! [`MacroDebugger`] 按调用方的控制逐条执行脚本命令：单步执行、运行到标签、在断点处停下，
+//! 每执行一步都会通过回调报告当前状态（仍按下的按键、下一条命令、目标窗口），
+//! 便于交互式排查较长的自动化流程。
+//!
+//! 脚本格式与 [`Script`] 相同，支持 `label:`、`goto:`、`call:` 和 `return`。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use super::command::send;
+use super::confirm::{ExecutionPlan, PlannedAction, PlannedStep};
+use super::script::{Interpreter, Peek, Script, ScriptOptions, ScriptStep};
+
+/// 脚本中的一条可执行命令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugEvent {
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 原始命令
+    pub command: String,
+    /// 命令的操作摘要
+    pub step: PlannedStep,
+}
+
+/// 调试器当前状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugState {
+    /// 下一条命令的序号（从 0 开始）
+    pub position: usize,
+    /// 下一条将执行的命令，已执行完时为 `None`
+    pub next: Option<DebugEvent>,
+    /// 通过 `key_down` 按下且尚未释放的按键
+    pub pressed_keys: Vec<String>,
+    /// 下一条命令的目标窗口（`None` 表示前台窗口）
+    pub target: Option<WindowHandle>,
+    /// 下一条命令取决于尚未判断的条件（需要轮询等待或询问用户）时，该条件所在的行号（此时 `next` 为 `None`）
+    pub waiting_on: Option<usize>,
+}
+
+impl fmt::Display for DebugState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.next, self.waiting_on) {
+            (Some(event), _) => write!(
+                f,
+                "#{} line {}: {} ({})",
+                self.position, event.line, event.command, event.step
+            )?,
+            (None, Some(line)) => write!(f, "#{} waiting on line {}", self.position, line)?,
+            (None, None) => write!(f, "#{} finished", self.position)?,
+        }
+        if !self.pressed_keys.is_empty() {
+            write!(f, " [held: {}]", self.pressed_keys.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// 连续执行停下的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// 到达断点（命令序号），断点处的命令尚未执行
+    Breakpoint(usize),
+    /// 到达目标标签
+    Label(String),
+    /// 全部命令已执行
+    Finished,
+}
+
+/// 状态回调
+type StateObserver = Box<dyn FnMut(&DebugState)>;
+
+/// 宏单步调试器
+///
+/// 断点和状态中的序号均为命令在 [`events`](Self::events) 中的序号。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{MacroDebugger, StopReason};
+///
+/// let mut debugger = MacroDebugger::new(&[
+///     "text:user",
+///     "key:tab",
+///     "label:submit",
+///     "key:enter",
+/// ])
+/// .unwrap();
+/// debugger.on_state(|state| println!("{}", state));
+/// debugger.set_breakpoint(1);
+///
+/// assert_eq!(debugger.run().unwrap(), StopReason::Breakpoint(1));
+/// debugger.step().unwrap();
+/// debugger.run_to("submit").unwrap();
+/// debugger.run().unwrap();
+/// ```
+pub struct MacroDebugger {
+    script: Script,
+    events: Vec<DebugEvent>,
+    /// 指令序号 -> 命令序号
+    event_index: HashMap<usize, usize>,
+    interpreter: Interpreter,
+    breakpoints: BTreeSet<usize>,
+    pressed: Vec<String>,
+    observer: Option<StateObserver>,
+}
+
+impl fmt::Debug for MacroDebugger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MacroDebugger")
+            .field("events", &self.events)
+            .field("breakpoints", &self.breakpoints)
+            .field("pressed", &self.pressed)
+            .finish()
+    }
+}
+
+impl MacroDebugger {
+    /// 解析并校验脚本，任一命令无效时返回错误（带行号）
+    pub fn new(script: &[&str]) -> Result<Self> {
+        let script = Script::from_lines(script)?;
+        let mut events = Vec::new();
+        let mut event_index = HashMap::new();
+        for (index, line) in script.lines().iter().enumerate() {
+            if let ScriptStep::Command(command) = &line.step {
+                let step = ExecutionPlan::from_commands(&[command])?.steps.remove(0);
+                event_index.insert(index, events.len());
+                events.push(DebugEvent {
+                    line: line.line,
+                    command: command.clone(),
+                    step,
+                });
+            }
+        }
+        Ok(Self {
+            script,
+            events,
+            event_index,
+            interpreter: Interpreter::new(&ScriptOptions::default()),
+            breakpoints: BTreeSet::new(),
+            pressed: Vec::new(),
+            observer: None,
+        })
+    }
+
+    /// 脚本中的全部可执行命令
+    pub fn events(&self) -> &[DebugEvent] {
+        &self.events
+    }
+
+    /// 安装状态回调，每执行一步后调用
+    pub fn on_state<F: FnMut(&DebugState) + 'static>(&mut self, observer: F) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// 在指定序号的命令前设置断点
+    pub fn set_breakpoint(&mut self, index: usize) {
+        self.breakpoints.insert(index);
+    }
+
+    /// 移除断点
+    pub fn clear_breakpoint(&mut self, index: usize) {
+        self.breakpoints.remove(&index);
+    }
+
+    /// 已设置的断点
+    pub fn breakpoints(&self) -> Vec<usize> {
+        self.breakpoints.iter().copied().collect()
+    }
+
+    /// 当前状态
+    ///
+    /// 下一步会陷入死循环或超过调用深度时，`next` 为 `None`，错误在执行时返回。
+    /// 下一条命令取决于需要轮询等待或询问用户（`confirm:`）的条件时，`waiting_on` 为该条件的行号，
+    /// `position` 为源码顺序上的下一条命令；预览不会询问用户，条件在执行时才判断一次。
+    pub fn state(&self) -> DebugState {
+        let peek = self.peek();
+        let next = match peek {
+            Some(Peek::Command(index)) => Some(self.event_index[&index]),
+            _ => None,
+        };
+        let waiting_on = match peek {
+            Some(Peek::Waiting(line)) => Some(line),
+            _ => None,
+        };
+        let position = next.unwrap_or_else(|| {
+            let following = waiting_on.and_then(|_| {
+                (self.interpreter.pc()..self.script.lines().len())
+                    .find_map(|index| self.event_index.get(&index).copied())
+            });
+            following.unwrap_or(self.events.len())
+        });
+        DebugState {
+            position,
+            target: next.and_then(|index| self.events[index].step.target),
+            next: next.map(|index| self.events[index].clone()),
+            pressed_keys: self.pressed.clone(),
+            waiting_on,
+        }
+    }
+
+    /// 是否已执行完全部命令
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.peek(), Some(Peek::Command(_) | Peek::Waiting(_)))
+    }
+
+    /// 执行下一条命令，返回其序号；已执行完时返回 `None`
+    ///
+    /// 命令执行失败时返回错误且不前进，可修复环境后重试或用 [`skip`](Self::skip) 跳过。
+    pub fn step(&mut self) -> Result<Option<usize>> {
+        let Some(index) = self.resolve()? else {
+            return Ok(None);
+        };
+        let event = &self.events[index];
+        send(&event.command)?;
+        match &event.step.action {
+            PlannedAction::KeyDown(key) if !self.pressed.contains(key) => {
+                self.pressed.push(key.clone())
+            }
+            PlannedAction::KeyUp(key) => self.pressed.retain(|k| k != key),
+            _ => {}
+        }
+        self.interpreter.advance();
+        self.notify();
+        Ok(Some(index))
+    }
+
+    /// 跳过下一条命令，不执行
+    pub fn skip(&mut self) -> Result<()> {
+        if self.resolve()?.is_some() {
+            self.interpreter.advance();
+            self.notify();
+        }
+        Ok(())
+    }
+
+    /// 连续执行，直到遇到断点或执行完
+    ///
+    /// 当前位置上的断点不会再次停下，便于从断点继续执行。
+    pub fn run(&mut self) -> Result<StopReason> {
+        self.run_until(None)
+    }
+
+    /// 连续执行到标签处（标签后的命令尚未执行），途中遇到断点时先停下
+    ///
+    /// 执行路径没有经过该标签时一直执行到结束。
+    pub fn run_to(&mut self, label: &str) -> Result<StopReason> {
+        let target = self.script.label(label).ok_or_else(|| {
+            KeyboardSenderError::CommandParseError(format!("unknown label '{}'", label))
+        })?;
+        self.run_until(Some((target, label)))
+    }
+
+    /// 回到脚本开头（不会释放仍按下的按键）
+    pub fn reset(&mut self) {
+        self.interpreter = Interpreter::new(&ScriptOptions::default());
+        self.notify();
+    }
+
+    fn run_until(&mut self, label: Option<(usize, &str)>) -> Result<StopReason> {
+        let mut first = true;
+        loop {
+            let at_label =
+                |interpreter: &Interpreter| label.filter(|(target, _)| interpreter.pc() == *target);
+            if let Some((_, name)) = at_label(&self.interpreter) {
+                return Ok(StopReason::Label(name.to_string()));
+            }
+            let Some(index) = self.resolve()? else {
+                return Ok(StopReason::Finished);
+            };
+            if let Some((_, name)) = at_label(&self.interpreter) {
+                return Ok(StopReason::Label(name.to_string()));
+            }
+            if !first && self.breakpoints.contains(&index) {
+                return Ok(StopReason::Breakpoint(index));
+            }
+            first = false;
+            self.step()?;
+        }
+    }
+
+    /// 处理控制流，返回下一条命令的序号
+    fn resolve(&mut self) -> Result<Option<usize>> {
+        Ok(self
+            .interpreter
+            .resolve(&self.script)?
+            .map(|index| self.event_index[&index]))
+    }
+
+    /// 不改变状态地预览下一步，执行时会出错时返回 `None`
+    fn peek(&self) -> Option<Peek> {
+        self.interpreter.peek(&self.script).ok()
+    }
+
+    fn notify(&mut self) {
+        if let Some(mut observer) = self.observer.take() {
+            observer(&self.state());
+            self.observer = Some(observer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script_with_labels() {
+        let debugger = MacroDebugger::new(&[
+            "# login",
+            "goto:start",
+            "key:a",
+            "label:start",
+            "text:user",
+            "action:key_down,key:shift,hwnd:0x10",
+        ])
+        .unwrap();
+        assert_eq!(debugger.events().len(), 3);
+
+        let state = debugger.state();
+        assert_eq!(state.position, 1);
+        assert_eq!(state.next.as_ref().map(|e| e.line), Some(5));
+        assert_eq!(state.target, None);
+        assert_eq!(
+            state.to_string(),
+            "#1 line 5: text:user (type 4 chars -> foreground)"
+        );
+    }
+
+    #[test]
+    fn test_invalid_script() {
+        let err = MacroDebugger::new(&["label:a", "key:a", "label:a"]).unwrap_err();
+        assert!(err.to_string().contains("line 3"));
+        assert!(MacroDebugger::new(&["key:a,duration:soon"]).is_err());
+    }
+
+    #[test]
+    fn test_preview_does_not_prompt() {
+        // 未安装询问接口：预览若判断 confirm 条件会出错，`waiting_on` 将为 `None`
+        let debugger =
+            MacroDebugger::new(&["if confirm:\"Go?\" goto:end", "key:a", "label:end", "key:b"])
+                .unwrap();
+        let state = debugger.state();
+        assert_eq!(state.waiting_on, Some(1));
+        assert_eq!(state.next, None);
+        assert_eq!(state.position, 0);
+        assert!(!debugger.is_finished());
+        assert_eq!(state.to_string(), "#0 waiting on line 1");
+    }
+
+    #[test]
+    fn test_run_to_unknown_label() {
+        let mut debugger = MacroDebugger::new(&["key:a"]).unwrap();
+        assert!(debugger.run_to("missing").is_err());
+        assert_eq!(debugger.state().position, 0);
+    }
+}
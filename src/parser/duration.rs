@@ -0,0 +1,231 @@
+// src/parser/duration.rs
+//! 预编译的时长解析器
+//!
+//! 与 `sleep-utils` 的 `parse_sleep_duration` 格式兼容，但正则只编译一次，
+//! 适合需要解析大量命令的场景。
+use crate::core::DelaySpec;
+use crate::error::{KeyboardSenderError, Result};
+use regex::Regex;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 时长解析器
+///
+/// 支持的格式：纯数字（毫秒）、`100ms`、`2s`、`1.5s`、`3m`、`1h`，
+/// 以及组合格式 `1h2m3s`、`2s 500ms`。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::DurationParser;
+/// use std::time::Duration;
+///
+/// let parser = DurationParser::new();
+/// assert_eq!(parser.parse("1m30s").unwrap(), Duration::from_secs(90));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DurationParser {
+    single: Regex,
+    float: Regex,
+    multi: Regex,
+}
+
+impl DurationParser {
+    /// 创建解析器并预编译所有正则
+    pub fn new() -> Self {
+        Self {
+            single: Regex::new(
+                r"^(\d+)\s*(ms|millis?|milliseconds?|s|sec|seconds?|m|min|minutes?|h|hr|hours?)$",
+            )
+            .unwrap(),
+            float: Regex::new(r"^(\d*\.?\d+)\s*(s|sec|seconds?|m|min|minutes?)$").unwrap(),
+            multi: Regex::new(r"(\d+)\s*([a-z]+)").unwrap(),
+        }
+    }
+
+    /// 进程内共享的解析器实例
+    pub fn shared() -> &'static DurationParser {
+        static SHARED: OnceLock<DurationParser> = OnceLock::new();
+        SHARED.get_or_init(DurationParser::new)
+    }
+
+    /// 解析时长字符串，无效时返回 `InvalidDuration` 错误
+    pub fn parse(&self, input: &str) -> Result<Duration> {
+        let normalized = input.trim().to_lowercase();
+        if normalized.is_empty() {
+            return Ok(Duration::ZERO);
+        }
+        if let Ok(millis) = normalized.parse::<isize>() {
+            return Ok(Duration::from_millis(millis.max(0) as u64));
+        }
+
+        self.parse_with_unit(&normalized)
+            .ok_or_else(|| KeyboardSenderError::InvalidDuration(input.to_string()))
+    }
+
+    /// 解析命令中的 `duration:` 类参数
+    ///
+    /// - `"default"`：返回 `None`，使用默认时长
+    /// - `"none"`：返回 `Some(Duration::ZERO)`
+    /// - 其他值按 [`DurationParser::parse`] 解析
+    pub fn parse_param(&self, input: &str) -> Result<Option<Duration>> {
+        match input.trim().to_lowercase().as_str() {
+            "default" => Ok(None),
+            "none" => Ok(Some(Duration::ZERO)),
+            _ => self.parse(input).map(Some),
+        }
+    }
+
+    /// 解析可随机化的时长
+    ///
+    /// 支持固定时长、`20ms..80ms` 和 `rand(10ms,50ms)`。
+    ///
+    /// # 示例
+    /// ```
+    /// use sophia_keyboard_sender::{DelaySpec, DurationParser};
+    /// use std::time::Duration;
+    ///
+    /// let parser = DurationParser::shared();
+    /// let ms = Duration::from_millis;
+    /// assert_eq!(parser.parse_spec("20ms..80ms").unwrap(), DelaySpec::range(ms(20), ms(80)));
+    /// assert_eq!(parser.parse_spec("rand(10ms, 50ms)").unwrap(), DelaySpec::range(ms(10), ms(50)));
+    /// assert_eq!(parser.parse_spec("1s").unwrap(), DelaySpec::Fixed(ms(1000)));
+    /// ```
+    pub fn parse_spec(&self, input: &str) -> Result<DelaySpec> {
+        let trimmed = input.trim();
+        let invalid = || KeyboardSenderError::InvalidDuration(input.to_string());
+        let bounds = match trimmed.strip_prefix("rand(") {
+            Some(rest) => Some(
+                rest.strip_suffix(')')
+                    .and_then(|args| args.split_once(','))
+                    .ok_or_else(invalid)?,
+            ),
+            None => trimmed.split_once(".."),
+        };
+        match bounds {
+            Some((min, max)) => {
+                if min.trim().is_empty() || max.trim().is_empty() {
+                    return Err(KeyboardSenderError::InvalidDuration(input.to_string()));
+                }
+                Ok(DelaySpec::range(self.parse(min)?, self.parse(max)?))
+            }
+            None => self.parse(trimmed).map(DelaySpec::Fixed),
+        }
+    }
+
+    /// 解析命令中可随机化的时长参数，`"default"` 和 `"none"` 的含义同 [`parse_param`](Self::parse_param)
+    pub fn parse_spec_param(&self, input: &str) -> Result<Option<DelaySpec>> {
+        match input.trim().to_lowercase().as_str() {
+            "default" => Ok(None),
+            "none" => Ok(Some(DelaySpec::Fixed(Duration::ZERO))),
+            _ => self.parse_spec(input).map(Some),
+        }
+    }
+
+    fn parse_with_unit(&self, input: &str) -> Option<Duration> {
+        if let Some(caps) = self.single.captures(input) {
+            if let Ok(value) = caps[1].parse::<u64>() {
+                let millis = value as f64 * unit_millis(&caps[2])?;
+                return Some(Duration::from_millis(millis as u64));
+            }
+        }
+
+        if let Some(caps) = self.float.captures(input) {
+            if let Ok(value) = caps[1].parse::<f64>() {
+                let millis = value.max(0.0) * unit_millis(&caps[2])?;
+                return Some(Duration::from_millis(millis as u64));
+            }
+        }
+
+        let mut total: u64 = 0;
+        let mut found_any = false;
+        for caps in self.multi.captures_iter(input) {
+            let Ok(value) = caps[1].parse::<u64>() else {
+                continue;
+            };
+            let Some(multiplier) = unit_millis(&caps[2]) else {
+                continue;
+            };
+            total = total.saturating_add(value.saturating_mul(multiplier as u64));
+            found_any = true;
+        }
+        found_any.then(|| Duration::from_millis(total))
+    }
+}
+
+impl Default for DurationParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 单位对应的毫秒数
+fn unit_millis(unit: &str) -> Option<f64> {
+    match unit {
+        "ms" | "milli" | "millis" | "millisecond" | "milliseconds" => Some(1.0),
+        "s" | "sec" | "second" | "seconds" => Some(1_000.0),
+        "m" | "min" | "minute" | "minutes" => Some(60_000.0),
+        "h" | "hr" | "hour" | "hours" => Some(3_600_000.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_sleep_utils_format() {
+        let parser = DurationParser::new();
+        for input in [
+            "100", "0", "-5", "100ms", "1s", "1.5s", "2 sec", "3m", "1h", "1m30s", "1h 2m 3s",
+            "2s500ms", "0h0m0s", "  250MS ",
+        ] {
+            assert_eq!(
+                parser.parse(input).unwrap(),
+                sleep_utils::parse_sleep_duration(input).unwrap(),
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_and_params() {
+        let parser = DurationParser::shared();
+        assert!(matches!(
+            parser.parse("soon"),
+            Err(KeyboardSenderError::InvalidDuration(s)) if s == "soon"
+        ));
+        assert_eq!(parser.parse_param("default").unwrap(), None);
+        assert_eq!(parser.parse_param("none").unwrap(), Some(Duration::ZERO));
+        assert_eq!(
+            parser.parse_param("20ms").unwrap(),
+            Some(Duration::from_millis(20))
+        );
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let parser = DurationParser::shared();
+        let ms = Duration::from_millis;
+        assert_eq!(
+            parser.parse_spec(" 80ms..20ms ").unwrap(),
+            DelaySpec::range(ms(20), ms(80))
+        );
+        assert_eq!(
+            parser.parse_spec("rand(1s,1.5s)").unwrap(),
+            DelaySpec::range(ms(1000), ms(1500))
+        );
+        assert_eq!(parser.parse_spec_param("default").unwrap(), None);
+        for invalid in [
+            "10ms..",
+            "..10ms",
+            "rand(10ms)",
+            "rand(10ms,soon)",
+            "rand(1ms,2ms)5ms",
+            "soon",
+        ] {
+            assert!(parser.parse_spec(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,422 @@
+// src/parser/executor.rs
+//! 异步命令执行与并发限制
+//!
+//! [`CommandExecutor`] 用固定数量的工作线程执行文本命令：发往同一目标窗口（命令的 `hwnd`
+//! 参数，缺省为全局输入）的命令按提交顺序逐条执行，不同目标之间最多并行 `workers` 条。
+//! 多个远程客户端同时发送命令时，同一窗口里的输入不会交错。
+//!
+//! [`send_async`] 使用进程级的默认执行器，返回的 [`CommandHandle`] 既可以 `.await`
+//! （不依赖特定异步运行时），也可以调用 [`CommandHandle::wait`] 阻塞等待。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread::JoinHandle;
+
+/// 默认执行器的工作线程数
+pub const DEFAULT_EXECUTOR_WORKERS: usize = 4;
+
+/// 默认执行器的排队上限
+pub const DEFAULT_EXECUTOR_QUEUE: usize = 256;
+
+type Handler = dyn Fn(&str) -> Result<()> + Send + Sync;
+
+/// 排队中的命令
+struct Job {
+    command: String,
+    completion: Arc<Completion>,
+}
+
+#[derive(Default)]
+struct State {
+    /// 每个目标的待执行命令
+    queues: HashMap<WindowHandle, VecDeque<Job>>,
+    /// 正在执行命令的目标
+    busy: HashSet<WindowHandle>,
+    /// 有待执行命令且空闲的目标
+    ready: VecDeque<WindowHandle>,
+    queued: usize,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    available: Condvar,
+    handler: Arc<Handler>,
+    max_queued: usize,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 有并发限制的命令执行器
+///
+/// 丢弃时等待已排队的命令执行完毕。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "command_parser")]
+/// # {
+/// use sophia_keyboard_sender::CommandExecutor;
+///
+/// let executor = CommandExecutor::new(2);
+/// let a = executor.submit("hwnd:0x1A2B,text:hello");
+/// let b = executor.submit("hwnd:0x3C4D,text:world"); // 与 a 并行
+/// a.wait().unwrap();
+/// b.wait().unwrap();
+/// # }
+/// ```
+pub struct CommandExecutor {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for CommandExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandExecutor")
+            .field("workers", &self.workers.len())
+            .field("max_queued", &self.shared.max_queued)
+            .finish()
+    }
+}
+
+impl CommandExecutor {
+    /// 创建执行器，最多 `workers` 个目标并行执行（至少 1 个）
+    pub fn new(workers: usize) -> Self {
+        Self::with_queue_limit(workers, DEFAULT_EXECUTOR_QUEUE)
+    }
+
+    /// 创建执行器并指定排队上限，排队已满时提交的命令立即以 `ExecutionDenied` 失败
+    pub fn with_queue_limit(workers: usize, max_queued: usize) -> Self {
+        Self::with_handler(workers, max_queued, Arc::new(super::command::send))
+    }
+
+    /// 使用自定义执行函数创建执行器
+    pub(crate) fn with_handler(workers: usize, max_queued: usize, handler: Arc<Handler>) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            available: Condvar::new(),
+            handler,
+            max_queued,
+        });
+        let workers = (0..workers.max(1))
+            .map(|index| {
+                let shared = shared.clone();
+                std::thread::Builder::new()
+                    .name(format!("keyboard-sender-exec-{}", index))
+                    .spawn(move || run_worker(&shared))
+                    .expect("failed to spawn executor thread")
+            })
+            .collect();
+        Self { shared, workers }
+    }
+
+    /// 提交命令，返回完成句柄
+    pub fn submit(&self, command: &str) -> CommandHandle {
+        let completion = Arc::new(Completion::default());
+        let handle = CommandHandle {
+            completion: completion.clone(),
+        };
+        let target = command_target(command);
+
+        let mut state = self.shared.state();
+        if state.queued >= self.shared.max_queued {
+            drop(state);
+            completion.complete(Err(KeyboardSenderError::ExecutionDenied(format!(
+                "command queue is full ({} pending)",
+                self.shared.max_queued
+            ))));
+            return handle;
+        }
+        state.queued += 1;
+        state.queues.entry(target).or_default().push_back(Job {
+            command: command.to_string(),
+            completion,
+        });
+        if !state.busy.contains(&target) && !state.ready.contains(&target) {
+            state.ready.push_back(target);
+        }
+        drop(state);
+        self.shared.available.notify_one();
+        handle
+    }
+
+    /// 排队中（尚未开始执行）的命令数
+    pub fn pending(&self) -> usize {
+        self.shared.state().queued
+    }
+}
+
+impl Drop for CommandExecutor {
+    fn drop(&mut self) {
+        self.shared.state().shutdown = true;
+        self.shared.available.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run_worker(shared: &Shared) {
+    let mut state = shared.state();
+    loop {
+        let Some(target) = state.ready.pop_front() else {
+            if state.shutdown && state.queued == 0 {
+                return;
+            }
+            state = shared
+                .available
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+            continue;
+        };
+        let Some(job) = state.queues.get_mut(&target).and_then(VecDeque::pop_front) else {
+            continue;
+        };
+        state.queued -= 1;
+        state.busy.insert(target);
+        drop(state);
+
+        // 处理函数 panic 时工作线程继续运行，该命令以错误结束，目标不会一直处于忙碌状态
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            (shared.handler)(&job.command)
+        }))
+        .unwrap_or_else(|payload| {
+            Err(KeyboardSenderError::HandlerPanicked(panic_message(
+                &*payload,
+            )))
+        });
+        job.completion.complete(result);
+
+        state = shared.state();
+        state.busy.remove(&target);
+        if state
+            .queues
+            .get(&target)
+            .is_some_and(|queue| !queue.is_empty())
+        {
+            state.ready.push_back(target);
+        } else {
+            state.queues.remove(&target);
+        }
+        shared.available.notify_all();
+    }
+}
+
+/// panic 负载中的消息
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// 命令的目标窗口，无法解析时按全局输入处理
+fn command_target(command: &str) -> WindowHandle {
+    super::grammar::parse_params(command)
+        .ok()
+        .and_then(|params| params.get("hwnd").cloned())
+        .and_then(|hwnd| super::command::parse_hwnd(&hwnd).ok())
+        .unwrap_or(0)
+}
+
+#[derive(Default)]
+struct Completion {
+    result: Mutex<Option<Result<()>>>,
+    waker: Mutex<Option<Waker>>,
+    done: Condvar,
+}
+
+impl Completion {
+    fn complete(&self, result: Result<()>) {
+        *self.result.lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+        self.done.notify_all();
+        if let Some(waker) = self.waker.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            waker.wake();
+        }
+    }
+}
+
+/// 已提交命令的完成句柄，可 `.await` 或调用 [`wait`](Self::wait)
+#[must_use = "the command runs regardless, but its result is only available through the handle"]
+pub struct CommandHandle {
+    completion: Arc<Completion>,
+}
+
+impl std::fmt::Debug for CommandHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl CommandHandle {
+    /// 命令是否已执行完毕
+    pub fn is_finished(&self) -> bool {
+        self.completion
+            .result
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    /// 阻塞等待命令执行完毕并返回结果
+    pub fn wait(self) -> Result<()> {
+        let mut result = self
+            .completion
+            .result
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(result) = result.take() {
+                return result;
+            }
+            result = self
+                .completion
+                .done
+                .wait(result)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+impl Future for CommandHandle {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // 先登记唤醒器再检查结果，避免两者之间完成而丢失唤醒
+        *self
+            .completion
+            .waker
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(cx.waker().clone());
+        match self
+            .completion
+            .result
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        {
+            Some(result) => Poll::Ready(result),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// 异步执行文本命令（使用进程级默认执行器）
+///
+/// 默认执行器有 [`DEFAULT_EXECUTOR_WORKERS`] 个工作线程，同一目标窗口的命令按提交顺序执行。
+///
+/// # 示例
+/// ```no_run
+/// # #[cfg(feature = "command_parser")]
+/// # async fn run() -> sophia_keyboard_sender::Result<()> {
+/// use sophia_keyboard_sender::send_async;
+///
+/// send_async("text:hello").await?;
+/// send_async("key:enter").await
+/// # }
+/// ```
+pub fn send_async(command: &str) -> CommandHandle {
+    static EXECUTOR: OnceLock<CommandExecutor> = OnceLock::new();
+    EXECUTOR
+        .get_or_init(|| CommandExecutor::new(DEFAULT_EXECUTOR_WORKERS))
+        .submit(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_same_target_is_serialized() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let handler = {
+            let (log, running, max_running) = (log.clone(), running.clone(), max_running.clone());
+            Arc::new(move |command: &str| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(5));
+                log.lock().unwrap().push(command.to_string());
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            })
+        };
+        let executor = CommandExecutor::with_handler(4, 16, handler);
+        let handles: Vec<_> = (0..6)
+            .map(|i| executor.submit(&format!("hwnd:100,text:{}", i)))
+            .collect();
+        for handle in handles {
+            handle.wait().unwrap();
+        }
+
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+        let expected: Vec<String> = (0..6).map(|i| format!("hwnd:100,text:{}", i)).collect();
+        assert_eq!(*log.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_handler_panic() {
+        let handler = Arc::new(|command: &str| {
+            if command.contains("boom") {
+                panic!("boom");
+            }
+            Ok(())
+        });
+        let executor = CommandExecutor::with_handler(1, 16, handler);
+        let failed = executor.submit("hwnd:100,text:boom");
+        let next = executor.submit("hwnd:100,text:ok");
+        assert!(matches!(
+            failed.wait(),
+            Err(KeyboardSenderError::HandlerPanicked(message)) if message == "boom"
+        ));
+        // 唯一的工作线程仍然存活，同一目标的后续命令照常执行
+        next.wait().unwrap();
+        assert_eq!(executor.pending(), 0);
+    }
+
+    #[test]
+    fn test_queue_limit() {
+        let gate = Arc::new((Mutex::new(false), Condvar::new()));
+        let handler = {
+            let gate = gate.clone();
+            Arc::new(move |_: &str| {
+                let (open, cvar) = &*gate;
+                let mut open = open.lock().unwrap();
+                while !*open {
+                    open = cvar.wait(open).unwrap();
+                }
+                Ok(())
+            })
+        };
+        let executor = CommandExecutor::with_handler(1, 1, handler);
+        let first = executor.submit("text:a");
+        // 等第一条开始执行，第二条排队，第三条超出上限
+        while executor.pending() > 0 {
+            std::thread::yield_now();
+        }
+        let second = executor.submit("text:b");
+        let third = executor.submit("text:c");
+        assert!(matches!(
+            third.wait(),
+            Err(KeyboardSenderError::ExecutionDenied(_))
+        ));
+
+        *gate.0.lock().unwrap() = true;
+        gate.1.notify_all();
+        first.wait().unwrap();
+        second.wait().unwrap();
+    }
+}
@@ -0,0 +1,323 @@
+// src/parser/grammar.rs
+//! 命令语法版本与严格模式
+//!
+//! - V1（兼容模式，默认）：按 `name:value` 正则宽松匹配，无法识别的片段被忽略，
+//!   重复参数以后出现的为准，多个操作参数按固定优先级只执行一个。
+//! - V2（严格模式）：逐个按逗号切分参数，值中的逗号写作 `\,`（反斜杠写作 `\\`）；
+//!   格式错误、重复参数、互相冲突的参数以及未知参数都会返回错误。
+//!
+//! 通过 [`set_grammar_mode`] 切换全局模式，可逐步把脚本迁移到严格模式。
+use crate::error::{KeyboardSenderError, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::command::parse_command_params;
+
+/// 命令语法版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GrammarVersion {
+    V1,
+    V2,
+}
+
+/// 命令解析模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GrammarMode {
+    /// 宽松解析，与旧命令字符串保持兼容
+    Compat(GrammarVersion),
+    /// 严格解析，任何可疑写法都返回错误
+    Strict(GrammarVersion),
+}
+
+impl GrammarMode {
+    /// 使用的语法版本
+    pub fn version(self) -> GrammarVersion {
+        match self {
+            GrammarMode::Compat(version) | GrammarMode::Strict(version) => version,
+        }
+    }
+
+    /// 是否为严格模式
+    pub fn is_strict(self) -> bool {
+        matches!(self, GrammarMode::Strict(_))
+    }
+}
+
+impl Default for GrammarMode {
+    fn default() -> Self {
+        GrammarMode::Compat(GrammarVersion::V1)
+    }
+}
+
+/// 内置命令参数
+pub const KNOWN_PARAMS: &[&str] = &[
+    "action", "type", "key", "char", "text", "shortcut", "hwnd", "duration", "count", "gap",
+    "delay", "template", "numpad",
+];
+
+/// 表示要执行的操作的参数，严格模式下同一条命令中最多出现一个
+const PAYLOAD_PARAMS: &[&str] = &["key", "char", "text", "shortcut"];
+
+static GRAMMAR_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// 设置 `send()` 等函数使用的命令解析模式
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::*;
+///
+/// set_grammar_mode(GrammarMode::Strict(GrammarVersion::V2));
+/// assert!(validate_command("key:a,key:b").is_err()); // 重复参数
+/// assert!(validate_command(r"text:a\, b").is_ok()); // 转义逗号
+/// set_grammar_mode(GrammarMode::default());
+/// ```
+pub fn set_grammar_mode(mode: GrammarMode) {
+    let value = match mode {
+        GrammarMode::Compat(GrammarVersion::V1) => 0,
+        GrammarMode::Compat(GrammarVersion::V2) => 1,
+        GrammarMode::Strict(GrammarVersion::V1) => 2,
+        GrammarMode::Strict(GrammarVersion::V2) => 3,
+    };
+    GRAMMAR_MODE.store(value, Ordering::Relaxed);
+}
+
+/// 当前的命令解析模式
+pub fn grammar_mode() -> GrammarMode {
+    match GRAMMAR_MODE.load(Ordering::Relaxed) {
+        1 => GrammarMode::Compat(GrammarVersion::V2),
+        2 => GrammarMode::Strict(GrammarVersion::V1),
+        3 => GrammarMode::Strict(GrammarVersion::V2),
+        _ => GrammarMode::Compat(GrammarVersion::V1),
+    }
+}
+
+/// 按当前模式解析命令参数
+pub fn parse_params(command: &str) -> Result<HashMap<String, String>> {
+    parse_params_with(command, grammar_mode())
+}
+
+/// 按指定模式解析命令参数
+///
+/// 兼容模式下 V2 分词出错时回退到 V1 的宽松解析；严格模式下还会检查参数冲突和未知参数。
+pub fn parse_params_with(command: &str, mode: GrammarMode) -> Result<HashMap<String, String>> {
+    let params = match (mode.version(), mode.is_strict()) {
+        (GrammarVersion::V1, false) => return Ok(parse_command_params(command)),
+        (GrammarVersion::V1, true) => tokenize_v1_strict(command)?,
+        (GrammarVersion::V2, false) => {
+            tokenize_v2(command).unwrap_or_else(|_| parse_command_params(command))
+        }
+        (GrammarVersion::V2, true) => tokenize_v2(command)?,
+    };
+    if mode.is_strict() {
+        check_strict(&params)?;
+    }
+    Ok(params)
+}
+
+/// V2 分词：按未转义的逗号切分，每段必须为 `name:value`
+fn tokenize_v2(command: &str) -> Result<HashMap<String, String>> {
+    let mut tokens = vec![String::new()];
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next @ (',' | '\\')) => tokens.last_mut().unwrap().push(next),
+                Some(next) => {
+                    let token = tokens.last_mut().unwrap();
+                    token.push('\\');
+                    token.push(next);
+                }
+                None => tokens.last_mut().unwrap().push('\\'),
+            },
+            ',' if !in_rand(tokens.last().unwrap()) => tokens.push(String::new()),
+            c => tokens.last_mut().unwrap().push(c),
+        }
+    }
+
+    let mut params = HashMap::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let (name, value) = token.split_once(':').ok_or_else(|| {
+            grammar_error(format!(
+                "parameter {} is not name:value: {:?}",
+                index + 1,
+                token
+            ))
+        })?;
+        insert_param(&mut params, name.trim(), value)?;
+    }
+    Ok(params)
+}
+
+/// V1 语法的严格版本：仍按正则匹配，但不允许重复参数
+fn tokenize_v1_strict(command: &str) -> Result<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    for token in split_params(command) {
+        if let Some((name, value)) = token.split_once(':') {
+            insert_param(&mut params, name.trim(), value)?;
+        }
+    }
+    if params.is_empty() {
+        return Err(grammar_error("No valid command found".to_string()));
+    }
+    Ok(params)
+}
+
+/// 参数片段的值是否以 `rand(` 开头且尚未闭合（其中的逗号不分隔参数）
+fn in_rand(token: &str) -> bool {
+    token
+        .split_once(':')
+        .is_some_and(|(_, value)| value.trim_start().starts_with("rand(") && !value.contains(')'))
+}
+
+/// 按逗号切分参数，`rand(a,b)` 中的逗号除外
+fn split_params(command: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    for (index, c) in command.char_indices() {
+        if c == ',' && !in_rand(&command[start..index]) {
+            tokens.push(&command[start..index]);
+            start = index + 1;
+        }
+    }
+    tokens.push(&command[start..]);
+    tokens
+}
+
+fn insert_param(params: &mut HashMap<String, String>, name: &str, value: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(grammar_error(format!("invalid parameter name {:?}", name)));
+    }
+    if value.is_empty() {
+        return Err(grammar_error(format!("parameter {} has no value", name)));
+    }
+    if params.insert(name.to_string(), value.to_string()).is_some() {
+        return Err(grammar_error(format!("duplicate parameter {}", name)));
+    }
+    Ok(())
+}
+
+/// 严格模式的冲突与未知参数检查
+fn check_strict(params: &HashMap<String, String>) -> Result<()> {
+    if params.contains_key("action") && params.contains_key("type") {
+        return Err(grammar_error("both action and type given".to_string()));
+    }
+    let payloads: Vec<&str> = PAYLOAD_PARAMS
+        .iter()
+        .copied()
+        .filter(|name| params.contains_key(*name))
+        .collect();
+    if payloads.len() > 1 {
+        return Err(grammar_error(format!(
+            "conflicting parameters: {}",
+            payloads.join(", ")
+        )));
+    }
+    if let Some(c) = params.get("char") {
+        if c.chars().count() != 1 {
+            return Err(grammar_error(format!(
+                "char must be one character: {:?}",
+                c
+            )));
+        }
+    }
+
+    let action = params.get("action").or_else(|| params.get("type"));
+    if action.is_some() && params.contains_key("shortcut") {
+        return Err(grammar_error(
+            "shortcut cannot be combined with action".to_string(),
+        ));
+    }
+    let custom_action =
+        action.is_some_and(|a| !super::action::BUILTIN_ACTIONS.contains(&a.as_str()));
+    if custom_action {
+        // 自定义动作接收全部参数，由处理函数自行校验
+        return Ok(());
+    }
+
+    let text = params.get("text").map(String::as_str).unwrap_or("");
+    for name in params.keys() {
+        let is_variable = text.contains(&format!("{{{}}}", name));
+        if !KNOWN_PARAMS.contains(&name.as_str()) && !is_variable {
+            return Err(grammar_error(format!("unknown parameter {}", name)));
+        }
+    }
+    Ok(())
+}
+
+fn grammar_error(message: String) -> KeyboardSenderError {
+    KeyboardSenderError::CommandParseError(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRICT: GrammarMode = GrammarMode::Strict(GrammarVersion::V2);
+
+    #[test]
+    fn test_v2_tokenizer_escapes() {
+        let params = parse_params_with(r"text:a\, b\\c,count:2", STRICT).unwrap();
+        assert_eq!(params["text"], r"a, b\c");
+        assert_eq!(params["count"], "2");
+
+        let params = parse_params_with("key:a,delay:rand(10ms,50ms),count:2", STRICT).unwrap();
+        assert_eq!(params["delay"], "rand(10ms,50ms)");
+        let v1 = GrammarMode::Strict(GrammarVersion::V1);
+        let params = parse_params_with("key:a,delay:rand(10ms,50ms),count:2", v1).unwrap();
+        assert_eq!(params["delay"], "rand(10ms,50ms)");
+        assert_eq!(
+            parse_params_with("key:a,delay:rand(1,2)", GrammarMode::default()).unwrap()["delay"],
+            "rand(1,2)"
+        );
+    }
+
+    #[test]
+    fn test_rand_only_at_value_start() {
+        for mode in [STRICT, GrammarMode::Strict(GrammarVersion::V1)] {
+            let params = parse_params_with("text:operand(1,count:2", mode).unwrap();
+            assert_eq!(params["text"], "operand(1");
+            assert_eq!(params["count"], "2");
+        }
+    }
+
+    #[test]
+    fn test_strict_rejects() {
+        for command in [
+            "key:a,key:b",
+            "key:a,text:b",
+            "key:a,bogus:1",
+            "key:a,",
+            "key",
+            "char:ab",
+            "action:key_click,type:key_click,key:a",
+            "action:key_click,shortcut:ctrl+c",
+        ] {
+            assert!(parse_params_with(command, STRICT).is_err(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_strict_allows_variables_and_custom_actions() {
+        assert!(parse_params_with("text:Hi {name},name:Bob", STRICT).is_ok());
+        assert!(parse_params_with("action:my_plugin,x:1,y:2", STRICT).is_ok());
+        assert!(parse_params_with("text:1234,numpad:true", STRICT).is_ok());
+    }
+
+    #[test]
+    fn test_compat_keeps_v1_behaviour() {
+        let compat = GrammarMode::default();
+        let params = parse_params_with("key:a,key:b,???", compat).unwrap();
+        assert_eq!(params["key"], "b");
+
+        // V2 兼容模式：分词失败时回退到 V1
+        let params = parse_params_with("key:a,,", GrammarMode::Compat(GrammarVersion::V2)).unwrap();
+        assert_eq!(params["key"], "a");
+    }
+
+    #[test]
+    fn test_strict_v1_rejects_duplicates_only() {
+        let mode = GrammarMode::Strict(GrammarVersion::V1);
+        assert!(parse_params_with("key:a,key:b", mode).is_err());
+        assert!(parse_params_with("key:a,count:2", mode).is_ok());
+    }
+}
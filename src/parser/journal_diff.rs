@@ -0,0 +1,355 @@
+// src/parser/journal_diff.rs
+//! 对比两次回放记录
+//!
+//! 按命令内容对齐两份 [`ReplayReport`]（Myers 差分，即最短编辑脚本），标出只在一侧出现的命令、
+//! 执行结果不同的命令以及注入时间偏差超过阈值的命令，
+//! 用于排查“脚本昨天能用、今天不行”之类的问题。
+use std::fmt;
+use std::time::Duration;
+
+use super::replay::{EventTiming, ReplayReport};
+
+/// 默认的时间偏差阈值
+pub const DEFAULT_TIMING_TOLERANCE: Duration = Duration::from_millis(20);
+
+/// 对齐后单条事件的差异类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffKind {
+    /// 两侧一致
+    Same,
+    /// 只在第一份记录中出现
+    OnlyInA,
+    /// 只在第二份记录中出现
+    OnlyInB,
+    /// 执行结果不同（`None` 表示成功）
+    OutcomeChanged {
+        a_error: Option<String>,
+        b_error: Option<String>,
+    },
+    /// 注入时间偏差超过阈值
+    TimingDrift { a: Duration, b: Duration },
+}
+
+/// 对齐后的一条事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventDiff {
+    /// 在第一份记录 `events` 中的位置
+    pub a: Option<usize>,
+    /// 在第二份记录 `events` 中的位置
+    pub b: Option<usize>,
+    pub command: String,
+    pub kind: DiffKind,
+}
+
+impl EventDiff {
+    /// 是否存在差异
+    pub fn is_divergent(&self) -> bool {
+        self.kind != DiffKind::Same
+    }
+}
+
+impl fmt::Display for EventDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DiffKind::Same => write!(f, "  {}", self.command),
+            DiffKind::OnlyInA => write!(f, "- {}", self.command),
+            DiffKind::OnlyInB => write!(f, "+ {}", self.command),
+            DiffKind::OutcomeChanged { a_error, b_error } => write!(
+                f,
+                "! {}: {} -> {}",
+                self.command,
+                a_error.as_deref().unwrap_or("ok"),
+                b_error.as_deref().unwrap_or("ok")
+            ),
+            DiffKind::TimingDrift { a, b } => {
+                write!(f, "~ {}: {:?} -> {:?}", self.command, a, b)
+            }
+        }
+    }
+}
+
+/// 两次回放的对比结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    /// 按执行顺序对齐的事件
+    pub events: Vec<EventDiff>,
+    /// 两次回放的总耗时
+    pub total_duration: (Duration, Duration),
+}
+
+impl DiffReport {
+    /// 存在差异的事件
+    pub fn divergences(&self) -> impl Iterator<Item = &EventDiff> {
+        self.events.iter().filter(|e| e.is_divergent())
+    }
+
+    /// 第一处差异
+    pub fn first_divergence(&self) -> Option<&EventDiff> {
+        self.divergences().next()
+    }
+
+    /// 两次回放是否一致
+    pub fn is_identical(&self) -> bool {
+        self.first_divergence().is_none()
+    }
+}
+
+impl fmt::Display for DiffReport {
+    /// 类似 diff 的逐行输出：`-` 仅在 A，`+` 仅在 B，`!` 结果不同，`~` 时间偏差
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, event) in self.events.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// 使用默认时间阈值对比两次回放
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{compare_journals, replay, ReplayOptions, TimedCommand};
+/// use std::time::Duration;
+///
+/// let commands = [
+///     TimedCommand::new(Duration::ZERO, "key:tab"),
+///     TimedCommand::new(Duration::from_millis(100), "text:hello"),
+/// ];
+/// let yesterday = replay(&commands, &ReplayOptions::default());
+/// let today = replay(&commands, &ReplayOptions::default());
+///
+/// let diff = compare_journals(&yesterday, &today);
+/// if let Some(first) = diff.first_divergence() {
+///     println!("first divergence: {}", first);
+/// }
+/// ```
+pub fn compare_journals(a: &ReplayReport, b: &ReplayReport) -> DiffReport {
+    compare_journals_with(a, b, DEFAULT_TIMING_TOLERANCE)
+}
+
+/// 按指定时间阈值对比两次回放
+pub fn compare_journals_with(
+    a: &ReplayReport,
+    b: &ReplayReport,
+    tolerance: Duration,
+) -> DiffReport {
+    let pairs = align(&a.events, &b.events);
+    let events = pairs
+        .into_iter()
+        .map(|(ia, ib)| match (ia, ib) {
+            (Some(ia), Some(ib)) => EventDiff {
+                a: Some(ia),
+                b: Some(ib),
+                command: a.events[ia].command.clone(),
+                kind: compare_event(&a.events[ia], &b.events[ib], tolerance),
+            },
+            (Some(ia), None) => EventDiff {
+                a: Some(ia),
+                b: None,
+                command: a.events[ia].command.clone(),
+                kind: DiffKind::OnlyInA,
+            },
+            (None, Some(ib)) => EventDiff {
+                a: None,
+                b: Some(ib),
+                command: b.events[ib].command.clone(),
+                kind: DiffKind::OnlyInB,
+            },
+            (None, None) => unreachable!(),
+        })
+        .collect();
+
+    DiffReport {
+        events,
+        total_duration: (a.total_duration, b.total_duration),
+    }
+}
+
+/// 比较两条已对齐的事件：先比较执行结果，再比较注入时间
+fn compare_event(a: &EventTiming, b: &EventTiming, tolerance: Duration) -> DiffKind {
+    let a_error = a.error.as_ref().map(|e| e.to_string());
+    let b_error = b.error.as_ref().map(|e| e.to_string());
+    if a_error != b_error {
+        return DiffKind::OutcomeChanged { a_error, b_error };
+    }
+
+    if let (Some(ta), Some(tb)) = (a.injected, b.injected) {
+        let drift = ta.abs_diff(tb);
+        if drift > tolerance {
+            return DiffKind::TimingDrift { a: ta, b: tb };
+        }
+    }
+    DiffKind::Same
+}
+
+/// 按命令内容求最短编辑脚本（Myers 差分），返回对齐后的 (A 位置, B 位置)
+///
+/// 耗时和内存与 `(n + m) * D` 和 `D²` 成正比（`D` 为差异条数），两份记录相近时接近线性。
+fn align(a: &[EventTiming], b: &[EventTiming]) -> Vec<(Option<usize>, Option<usize>)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let same = |x: isize, y: isize| a[x as usize].command == b[y as usize].command;
+
+    // frontiers[d][k + d]：d 次编辑后在对角线 k = x - y 上能到达的最远 x
+    let mut frontiers: Vec<Vec<isize>> = Vec::new();
+    'search: for d in 0..=n + m {
+        let previous = frontiers.last();
+        let mut frontier = vec![0; 2 * d as usize + 1];
+        for k in (-d..=d).step_by(2) {
+            let mut x = match previous {
+                None => 0,
+                Some(previous) => {
+                    let reach = |k: isize| previous[(k + d - 1) as usize];
+                    if k == -d || (k != d && reach(k - 1) < reach(k + 1)) {
+                        reach(k + 1)
+                    } else {
+                        reach(k - 1) + 1
+                    }
+                }
+            };
+            let mut y = x - k;
+            while x < n && y < m && same(x, y) {
+                x += 1;
+                y += 1;
+            }
+            frontier[(k + d) as usize] = x;
+            if x >= n && y >= m {
+                frontiers.push(frontier);
+                break 'search;
+            }
+        }
+        frontiers.push(frontier);
+    }
+
+    // 从终点沿各步的选择回溯
+    let mut pairs = Vec::with_capacity(n.max(m) as usize);
+    let (mut x, mut y) = (n, m);
+    for d in (1..frontiers.len() as isize).rev() {
+        let k = x - y;
+        let reach = |k: isize| frontiers[d as usize - 1][(k + d - 1) as usize];
+        let down = k == -d || (k != d && reach(k - 1) < reach(k + 1));
+        let (start_x, start_y) = if down {
+            let prev_x = reach(k + 1);
+            (prev_x, prev_x - k)
+        } else {
+            let prev_x = reach(k - 1) + 1;
+            (prev_x, prev_x - k)
+        };
+        while x > start_x && y > start_y {
+            x -= 1;
+            y -= 1;
+            pairs.push((Some(x as usize), Some(y as usize)));
+        }
+        if down {
+            y -= 1;
+            pairs.push((None, Some(y as usize)));
+        } else {
+            x -= 1;
+            pairs.push((Some(x as usize), None));
+        }
+    }
+    while x > 0 {
+        x -= 1;
+        y -= 1;
+        pairs.push((Some(x as usize), Some(y as usize)));
+    }
+    pairs.reverse();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KeyboardSenderError;
+
+    fn event(command: &str, injected_ms: u64, failed: bool) -> EventTiming {
+        EventTiming {
+            index: 0,
+            command: command.to_string(),
+            scheduled: Duration::ZERO,
+            injected: Some(Duration::from_millis(injected_ms)),
+            injected_at: None,
+            finished: None,
+            attempts: 1,
+            error: failed.then_some(KeyboardSenderError::WindowsError),
+        }
+    }
+
+    fn report(events: Vec<EventTiming>) -> ReplayReport {
+        ReplayReport {
+            events,
+            total_duration: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_identical_runs() {
+        let a = report(vec![event("key:a", 0, false), event("key:b", 100, false)]);
+        let b = report(vec![event("key:a", 5, false), event("key:b", 110, false)]);
+        assert!(compare_journals(&a, &b).is_identical());
+    }
+
+    #[test]
+    fn test_aligns_and_reports_divergences() {
+        let a = report(vec![
+            event("key:a", 0, false),
+            event("key:b", 100, false),
+            event("key:c", 200, false),
+            event("key:d", 300, false),
+        ]);
+        let b = report(vec![
+            event("key:a", 0, false),
+            event("key:x", 50, false),
+            event("key:c", 400, false),
+            event("key:d", 500, true),
+        ]);
+
+        let diff = compare_journals(&a, &b);
+        let kinds: Vec<&DiffKind> = diff.events.iter().map(|e| &e.kind).collect();
+        assert_eq!(kinds[0], &DiffKind::Same);
+        assert!(kinds.contains(&&DiffKind::OnlyInA));
+        assert!(kinds.contains(&&DiffKind::OnlyInB));
+        assert!(matches!(kinds[3], DiffKind::TimingDrift { .. }));
+        assert!(matches!(
+            kinds[4],
+            DiffKind::OutcomeChanged {
+                a_error: None,
+                b_error: Some(_)
+            }
+        ));
+        assert_eq!(diff.first_divergence().unwrap().a, Some(1));
+        assert!(diff.to_string().contains("- key:b"));
+        assert!(diff.to_string().contains("+ key:x"));
+    }
+
+    #[test]
+    fn test_aligns_long_journals() {
+        let commands: Vec<String> = (0..20_000).map(|i| format!("key:{}", i)).collect();
+        let a = report(commands.iter().map(|c| event(c, 0, false)).collect());
+        let mut b_events: Vec<EventTiming> = commands.iter().map(|c| event(c, 0, false)).collect();
+        b_events[10_000] = event("key:x", 0, false);
+        let b = report(b_events);
+
+        let diff = compare_journals(&a, &b);
+        assert_eq!(diff.events.len(), 20_001);
+        let divergent: Vec<&EventDiff> = diff.divergences().collect();
+        assert_eq!(divergent.len(), 2);
+        assert_eq!(divergent[0].a, Some(10_000));
+        assert_eq!(divergent[1].b, Some(10_000));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_compare_with_saved_journal() {
+        let a = report(vec![event("key:a", 0, false), event("key:b", 100, true)]);
+        let saved = serde_json::to_string(&a).unwrap();
+        let restored: ReplayReport = serde_json::from_str(&saved).unwrap();
+        assert!(matches!(
+            restored.events[1].error,
+            Some(KeyboardSenderError::Recorded(_))
+        ));
+        assert!(compare_journals(&a, &restored).is_identical());
+    }
+}
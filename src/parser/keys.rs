@@ -0,0 +1,277 @@
+// src/parser/keys.rs
+//! 键名解析
+//!
+//! 在 `keyboard-codes` 的键名和别名之外，补充常见但容易出错的键名：
+//! - `menu` / `contextmenu` / `application` 指应用程序（上下文菜单）键 `Apps`，
+//!   而不是虚拟键码名 `VK_MENU` 对应的 Alt
+//! - `break` / `pausebreak` 指 `Pause`
+//! - `printscreen` / `prtsc` 等指 PrintScreen，它不在 [`Key`] 中，按虚拟键码 `0x2C` 发送，只能点击
+//!
+//! [`supported_keys`] 列出全部可解析的键名；键名无法解析时，`UnsupportedKey` 错误按编辑距离
+//! 给出相近的键名（[`suggest_key_names`]）。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Key, Modifier};
+use keyboard_codes::mapping::standard::{STANDARD_KEY_MAPPINGS, STANDARD_MODIFIER_MAPPINGS};
+
+/// 补充的按键别名（小写别名 -> `keyboard-codes` 键名）
+pub const KEY_NAME_ALIASES: &[(&str, &str)] = &[
+    ("menu", "Apps"),
+    ("contextmenu", "Apps"),
+    ("context_menu", "Apps"),
+    ("application", "Apps"),
+    ("break", "Pause"),
+    ("pausebreak", "Pause"),
+    ("scrlk", "ScrollLock"),
+];
+
+/// 没有对应 [`Key`] 的特殊按键（小写名称 -> 虚拟键码）
+pub const SPECIAL_KEY_NAMES: &[(&str, u16)] = &[
+    ("printscreen", 0x2C),
+    ("print_screen", 0x2C),
+    ("prtsc", 0x2C),
+    ("prtscr", 0x2C),
+    ("snapshot", 0x2C),
+];
+
+/// `keyboard-codes` 内置的按键和修饰键别名（小写）
+const LIBRARY_ALIASES: &[&str] = &[
+    "esc", "return", "back", "bs", "del", "ins", "pgup", "pgdn", "pagedown", "left", "right", "up",
+    "down", "ctrl", "ctl", "shft", "altgr", "opt", "option", "win", "windows", "cmd", "command",
+    "super", "lctrl", "lctl", "lshift", "lshft", "lalt", "lopt", "loption", "lmeta", "lwin",
+    "lcmd", "lcommand", "rctrl", "rctl", "rshift", "rshft", "ralt", "ropt", "roption", "rmeta",
+    "rwin", "rcmd", "rcommand", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+];
+
+/// 按键名称目录
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{Key, KeyCatalog};
+///
+/// assert!(Key::all_names().contains(&"PageDown"));
+/// ```
+pub trait KeyCatalog {
+    /// 全部标准键名（不含别名）
+    fn all_names() -> Vec<&'static str>;
+}
+
+impl KeyCatalog for Key {
+    fn all_names() -> Vec<&'static str> {
+        STANDARD_KEY_MAPPINGS
+            .iter()
+            .map(|&(name, ..)| name)
+            .collect()
+    }
+}
+
+/// 全部可解析的键名：标准键名、修饰键名、别名和特殊按键名（解析时不区分大小写）
+pub fn supported_keys() -> Vec<&'static str> {
+    let mut names = Key::all_names();
+    names.extend(STANDARD_MODIFIER_MAPPINGS.iter().map(|&(name, ..)| name));
+    names.extend(LIBRARY_ALIASES);
+    names.extend(KEY_NAME_ALIASES.iter().map(|&(alias, _)| alias));
+    names.extend(SPECIAL_KEY_NAMES.iter().map(|&(name, _)| name));
+    names
+}
+
+/// 与 `name` 相近的键名（按编辑距离排序，最多 3 个，忽略大小写）
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::suggest_key_names;
+///
+/// assert_eq!(suggest_key_names("entr"), vec!["Enter"]);
+/// ```
+pub fn suggest_key_names(name: &str) -> Vec<&'static str> {
+    let lower = name.trim().to_ascii_lowercase();
+    // 短键名只容许一处差异，避免 `x` 之类的输入匹配到大量单字符键名
+    let max_distance = (lower.chars().count() / 3).clamp(1, 3);
+    // 同一键名的大小写变体只保留第一个
+    let mut seen = std::collections::HashSet::new();
+    let mut candidates: Vec<(usize, &'static str)> = supported_keys()
+        .into_iter()
+        .filter(|candidate| seen.insert(candidate.to_ascii_lowercase()))
+        .map(|candidate| {
+            (
+                edit_distance(&lower, &candidate.to_ascii_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect();
+    candidates.sort_by_key(|&(distance, _)| distance);
+    candidates
+        .into_iter()
+        .take(3)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// 两个字符串之间的编辑距离（Levenshtein）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != cb))
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// 键名无法解析时的错误，附带相近的键名
+fn unsupported_key(name: &str) -> KeyboardSenderError {
+    let suggestions = suggest_key_names(name);
+    if suggestions.is_empty() {
+        return KeyboardSenderError::UnsupportedKey(format!("{:?}", name));
+    }
+    KeyboardSenderError::UnsupportedKey(format!(
+        "{:?} (did you mean {}?)",
+        name,
+        suggestions.join(", ")
+    ))
+}
+
+/// 键名解析结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyName {
+    Key(Key),
+    Modifier(Modifier),
+    /// 没有对应 [`Key`] 的特殊按键的虚拟键码
+    Vk(u16),
+}
+
+impl KeyName {
+    /// 虚拟键码
+    pub fn vk(&self) -> u16 {
+        match *self {
+            KeyName::Key(key) => crate::core::key_to_vk(key),
+            KeyName::Modifier(modifier) => crate::core::modifier_to_vk(modifier),
+            KeyName::Vk(vk) => vk,
+        }
+    }
+
+    /// 单独按下是否会触发系统快捷键（Win 键）
+    pub fn is_system_key(&self) -> bool {
+        crate::core::hotkeys::is_interlocked(0, self.vk())
+    }
+}
+
+/// 解析键名，支持 `keyboard-codes` 的键名和别名以及 [`KEY_NAME_ALIASES`]、[`SPECIAL_KEY_NAMES`]
+///
+/// 无法解析时返回 `UnsupportedKey` 错误，其中附带相近的键名。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{parse_key_name, Key, KeyName};
+///
+/// assert_eq!(parse_key_name("menu").unwrap(), KeyName::Key(Key::Apps));
+/// assert_eq!(parse_key_name("PrtSc").unwrap(), KeyName::Vk(0x2C));
+/// ```
+pub fn parse_key_name(name: &str) -> Result<KeyName> {
+    use keyboard_codes::{parse_keyboard_input, KeyboardInput};
+
+    let name = name.trim();
+    let lower = name.to_ascii_lowercase();
+    if let Some(&(_, vk)) = SPECIAL_KEY_NAMES.iter().find(|(alias, _)| *alias == lower) {
+        return Ok(KeyName::Vk(vk));
+    }
+    let resolved = KEY_NAME_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == lower)
+        .map_or(name, |&(_, key)| key);
+    match parse_keyboard_input(resolved).map_err(|_| unsupported_key(name))? {
+        KeyboardInput::Key(key) => Ok(KeyName::Key(key)),
+        KeyboardInput::Modifier(modifier) => Ok(KeyName::Modifier(modifier)),
+    }
+}
+
+/// 解析快捷键（如 `ctrl+shift+esc`），最后一段支持 [`KEY_NAME_ALIASES`]
+///
+/// 某一段不是有效键名时返回该段的 `UnsupportedKey` 错误。
+pub(crate) fn parse_shortcut(shortcut: &str) -> Result<(Vec<Modifier>, Key)> {
+    let resolved = match shortcut.rsplit_once('+') {
+        Some((modifiers, key)) => {
+            let lower = key.trim().to_ascii_lowercase();
+            match KEY_NAME_ALIASES.iter().find(|(alias, _)| *alias == lower) {
+                Some((_, key)) => format!("{}+{}", modifiers, key),
+                None => shortcut.to_string(),
+            }
+        }
+        None => shortcut.to_string(),
+    };
+    let parsed = keyboard_codes::parse_shortcut_with_aliases(&resolved).map_err(|e| {
+        shortcut
+            .split('+')
+            .find_map(|part| parse_key_name(part).err())
+            .unwrap_or_else(|| KeyboardSenderError::ParseError(e.to_string()))
+    })?;
+    Ok((parsed.modifiers, parsed.key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quirky_key_names() {
+        assert_eq!(parse_key_name("menu").unwrap(), KeyName::Key(Key::Apps));
+        assert_eq!(parse_key_name("Apps").unwrap(), KeyName::Key(Key::Apps));
+        assert_eq!(parse_key_name("Break").unwrap(), KeyName::Key(Key::Pause));
+        assert_eq!(parse_key_name("pause").unwrap(), KeyName::Key(Key::Pause));
+        assert_eq!(parse_key_name("printscreen").unwrap(), KeyName::Vk(0x2C));
+        assert_eq!(
+            parse_key_name("ctrl").unwrap(),
+            KeyName::Modifier(Modifier::Control)
+        );
+        assert!(parse_key_name("nosuchkey").is_err());
+        assert!(parse_key_name("lwin").unwrap().is_system_key());
+        assert!(!parse_key_name("menu").unwrap().is_system_key());
+    }
+
+    #[test]
+    fn test_shortcut_aliases() {
+        assert_eq!(
+            parse_shortcut("shift+menu").unwrap(),
+            (vec![Modifier::Shift], Key::Apps)
+        );
+        assert_eq!(
+            parse_shortcut("ctrl+break").unwrap(),
+            (vec![Modifier::Control], Key::Pause)
+        );
+        assert!(parse_shortcut("ctrl+nosuchkey").is_err());
+        assert!(matches!(
+            parse_shortcut("ctrl+shif+a"),
+            Err(KeyboardSenderError::UnsupportedKey(message)) if message.contains("Shift")
+        ));
+    }
+
+    #[test]
+    fn test_supported_keys_and_suggestions() {
+        for name in supported_keys() {
+            assert!(parse_key_name(name).is_ok(), "{}", name);
+        }
+        assert!(Key::all_names().contains(&"Escape"));
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(suggest_key_names("Entr"), vec!["Enter"]);
+        assert_eq!(suggest_key_names("pgdwn")[0], "pgdn");
+        assert!(suggest_key_names("nosuchkeyatall").is_empty());
+        assert!(supported_keys().contains(&"7"));
+        // 大小写不同的同名键名（`PageDown` 和别名 `pagedown`）只给出一次
+        let suggestions = suggest_key_names("pagedn");
+        assert!(suggestions.contains(&"PageDown"));
+        assert!(!suggestions.contains(&"pagedown"));
+        match parse_key_name("escpe") {
+            Err(KeyboardSenderError::UnsupportedKey(message)) => {
+                assert_eq!(message, "\"escpe\" (did you mean Escape?)")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
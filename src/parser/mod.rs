@@ -1,9 +1,65 @@
 // src/parser/mod.rs
 //! 命令和参数解析功能
 
+#[cfg(feature = "command_parser")]
+pub mod action;
+#[cfg(feature = "command_parser")]
+pub mod batch;
 #[cfg(feature = "command_parser")]
 pub mod command;
+#[cfg(feature = "command_parser")]
+pub mod confirm;
+#[cfg(feature = "command_parser")]
+pub mod debugger;
+#[cfg(feature = "command_parser")]
+pub mod duration;
+#[cfg(feature = "command_parser")]
+pub mod executor;
+#[cfg(feature = "command_parser")]
+pub mod grammar;
+#[cfg(feature = "command_parser")]
+pub mod journal_diff;
+#[cfg(feature = "command_parser")]
+pub mod keys;
+#[cfg(feature = "command_parser")]
+pub mod policy;
+#[cfg(feature = "command_parser")]
+pub mod prompt;
+#[cfg(feature = "command_parser")]
+pub mod replay;
+#[cfg(feature = "command_parser")]
+pub mod script;
+#[cfg(feature = "command_parser")]
+pub mod template;
 
 // 重新导出
 #[cfg(feature = "command_parser")]
-pub use command::*;
\ No newline at end of file
+pub use action::*;
+#[cfg(feature = "command_parser")]
+pub use batch::*;
+#[cfg(feature = "command_parser")]
+pub use command::*;
+#[cfg(feature = "command_parser")]
+pub use confirm::*;
+#[cfg(feature = "command_parser")]
+pub use debugger::*;
+#[cfg(feature = "command_parser")]
+pub use duration::*;
+#[cfg(feature = "command_parser")]
+pub use executor::*;
+#[cfg(feature = "command_parser")]
+pub use grammar::*;
+#[cfg(feature = "command_parser")]
+pub use journal_diff::*;
+#[cfg(feature = "command_parser")]
+pub use keys::*;
+#[cfg(feature = "command_parser")]
+pub use policy::*;
+#[cfg(feature = "command_parser")]
+pub use prompt::*;
+#[cfg(feature = "command_parser")]
+pub use replay::*;
+#[cfg(feature = "command_parser")]
+pub use script::*;
+#[cfg(feature = "command_parser")]
+pub use template::*;
\ No newline at end of file
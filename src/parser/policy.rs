@@ -0,0 +1,363 @@
+// src/parser/policy.rs
+//! 命令权限策略
+//!
+//! 通过 IPC/HTTP 等方式对外提供命令协议时，可安装 [`Policy`] 限制允许执行的操作：
+//! 禁止全局发送、只允许白名单窗口/进程、限制文本长度、禁止特定快捷键或动作。
+//! 策略在 `send()`、`send_batch()` 和 `replay()` 执行前统一检查（先于确认处理函数），
+//! 任一步骤违反策略时整个执行被拒绝，返回 `PolicyViolation` 错误。
+//!
+//! 模板中的运行时输入（`{clipboard}`、`{prompt:..}` 等）在执行时才取值，执行前的检查无法得知其长度，
+//! 因此每条命令输入文本前还会按渲染后的实际文本再检查一次文本长度。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::{Modifier, WindowHandle};
+use std::sync::RwLock;
+
+use super::confirm::{ExecutionPlan, PlannedAction, PlannedStep};
+
+/// 命令权限策略
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{clear_policy, send, set_policy, KeyboardSenderError, Policy};
+///
+/// set_policy(
+///     Policy::new()
+///         .deny_global()
+///         .allow_process("notepad.exe")
+///         .max_text_len(256)
+///         .deny_shortcut("win+*"),
+/// );
+/// assert!(matches!(
+///     send("text:hello"),
+///     Err(KeyboardSenderError::PolicyViolation(_))
+/// ));
+/// # clear_policy();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    /// 是否允许发送到前台窗口（全局发送），默认允许
+    pub allow_global: bool,
+    /// 允许的目标窗口句柄；与 `allowed_processes` 都为 `None` 时不限制窗口
+    pub allowed_windows: Option<Vec<WindowHandle>>,
+    /// 允许的目标进程名（不区分大小写，如 `notepad.exe`）
+    pub allowed_processes: Option<Vec<String>>,
+    /// 单次执行允许输入的最大字符数
+    pub max_text_len: Option<usize>,
+    /// 禁止的快捷键模式，如 `win+*`、`ctrl+alt+delete`
+    pub denied_shortcuts: Vec<String>,
+    /// 禁止的动作名（不区分大小写）
+    pub denied_actions: Vec<String>,
+    /// 是否允许系统快捷键（见 [`PlannedAction::is_system_shortcut`]），默认允许
+    pub allow_system_shortcuts: bool,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            allow_global: true,
+            allowed_windows: None,
+            allowed_processes: None,
+            max_text_len: None,
+            denied_shortcuts: Vec::new(),
+            denied_actions: Vec::new(),
+            allow_system_shortcuts: true,
+        }
+    }
+}
+
+impl Policy {
+    /// 不做任何限制的策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 禁止全局发送
+    pub fn deny_global(mut self) -> Self {
+        self.allow_global = false;
+        self
+    }
+
+    /// 允许发送到指定窗口
+    pub fn allow_window(mut self, hwnd: WindowHandle) -> Self {
+        self.allowed_windows.get_or_insert_with(Vec::new).push(hwnd);
+        self
+    }
+
+    /// 允许发送到指定进程的窗口
+    pub fn allow_process(mut self, name: &str) -> Self {
+        self.allowed_processes
+            .get_or_insert_with(Vec::new)
+            .push(name.to_string());
+        self
+    }
+
+    /// 限制单次执行输入的字符总数
+    pub fn max_text_len(mut self, len: usize) -> Self {
+        self.max_text_len = Some(len);
+        self
+    }
+
+    /// 禁止匹配模式的快捷键
+    ///
+    /// 模式为 `修饰键+...+按键`，按键可写 `*` 表示任意键；
+    /// 修饰键不区分左右（`win` 同时匹配左右 Win 键），快捷键含有额外修饰键时也算匹配。
+    pub fn deny_shortcut(mut self, pattern: &str) -> Self {
+        self.denied_shortcuts.push(pattern.to_string());
+        self
+    }
+
+    /// 禁止 Win 组合键、Ctrl+Alt+Del 等系统快捷键，以及单独按下 Win 键
+    ///
+    /// 与全局设置 [`allow_system_shortcuts`](crate::core::allow_system_shortcuts) 不同，
+    /// 策略在执行前检查整个计划，发往指定窗口的命令同样受限。
+    pub fn deny_system_shortcuts(mut self) -> Self {
+        self.allow_system_shortcuts = false;
+        self
+    }
+
+    /// 禁止指定动作（内置动作如 `key_down`，或自定义动作名）
+    pub fn deny_action(mut self, name: &str) -> Self {
+        self.denied_actions.push(name.trim().to_lowercase());
+        self
+    }
+
+    /// 检查执行计划是否符合策略
+    pub fn check(&self, plan: &ExecutionPlan) -> Result<()> {
+        for step in &plan.steps {
+            self.check_step(step)?;
+        }
+        if let Some(max) = self.max_text_len {
+            let len = plan.text_len();
+            if len > max {
+                return Err(violation(format!(
+                    "text length {} exceeds limit {}",
+                    len, max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_step(&self, step: &PlannedStep) -> Result<()> {
+        match step.target {
+            None if !self.allow_global => {
+                return Err(violation("global sends are not allowed".to_string()))
+            }
+            Some(hwnd) if !self.is_window_allowed(hwnd) => {
+                return Err(violation(format!("window 0x{:X} is not allowed", hwnd)))
+            }
+            _ => {}
+        }
+
+        let name = action_name(&step.action);
+        if self.denied_actions.contains(&name) {
+            return Err(violation(format!("action {} is not allowed", name)));
+        }
+        if !self.allow_system_shortcuts && step.action.is_system_shortcut() {
+            return Err(violation(format!("{} is a system shortcut", step.action)));
+        }
+        if let PlannedAction::Shortcut(shortcut) = &step.action {
+            for pattern in &self.denied_shortcuts {
+                if shortcut_matches(pattern, shortcut)? {
+                    return Err(violation(format!(
+                        "shortcut {} matches denied pattern {}",
+                        shortcut, pattern
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_window_allowed(&self, hwnd: WindowHandle) -> bool {
+        if self.allowed_windows.is_none() && self.allowed_processes.is_none() {
+            return true;
+        }
+        if self
+            .allowed_windows
+            .as_ref()
+            .is_some_and(|windows| windows.contains(&hwnd))
+        {
+            return true;
+        }
+        match &self.allowed_processes {
+            Some(processes) => target_process(hwnd)
+                .is_some_and(|name| processes.iter().any(|p| p.eq_ignore_ascii_case(&name))),
+            None => false,
+        }
+    }
+}
+
+static POLICY: RwLock<Option<Policy>> = RwLock::new(None);
+
+/// 安装命令权限策略（替换之前的策略）
+pub fn set_policy(policy: Policy) {
+    *POLICY.write().unwrap_or_else(|e| e.into_inner()) = Some(policy);
+}
+
+/// 移除命令权限策略
+pub fn clear_policy() {
+    *POLICY.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 当前的命令权限策略
+pub fn current_policy() -> Option<Policy> {
+    POLICY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 是否安装了策略
+pub(crate) fn has_policy() -> bool {
+    POLICY.read().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// 按当前策略检查执行计划（未安装策略时通过）
+pub(crate) fn enforce(plan: &ExecutionPlan) -> Result<()> {
+    match &*POLICY.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(policy) => policy.check(plan),
+        None => Ok(()),
+    }
+}
+
+/// 按当前策略检查单条命令渲染后的文本长度（未安装策略时通过）
+pub(crate) fn enforce_text_len(len: usize) -> Result<()> {
+    let max = POLICY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|policy| policy.max_text_len);
+    match max {
+        Some(max) if len > max => Err(violation(format!(
+            "text length {} exceeds limit {}",
+            len, max
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn violation(message: String) -> KeyboardSenderError {
+    KeyboardSenderError::PolicyViolation(message)
+}
+
+/// 步骤对应的动作名，与命令中 `action:` 的写法一致
+fn action_name(action: &PlannedAction) -> String {
+    match action {
+        PlannedAction::KeyClick { .. } => "key_click".to_string(),
+        PlannedAction::KeyDown(_) => "key_down".to_string(),
+        PlannedAction::KeyUp(_) => "key_up".to_string(),
+        PlannedAction::Char => "char".to_string(),
+        PlannedAction::Text { .. } => "text".to_string(),
+        PlannedAction::Shortcut(_) => "shortcut".to_string(),
+        PlannedAction::Custom(name) => name.trim().to_lowercase(),
+    }
+}
+
+/// 目标窗口所属进程名
+fn target_process(hwnd: WindowHandle) -> Option<String> {
+    #[cfg(not(feature = "window_target"))]
+    {
+        let _ = hwnd;
+        None
+    }
+
+    #[cfg(feature = "window_target")]
+    crate::core::process_name(crate::core::window_process_id(hwnd))
+}
+
+/// 快捷键是否匹配模式
+fn shortcut_matches(pattern: &str, shortcut: &str) -> Result<bool> {
+    use keyboard_codes::{parse_modifier_with_aliases, parse_shortcut_with_aliases};
+
+    let parse_error =
+        |e: keyboard_codes::KeyParseError| KeyboardSenderError::ParseError(e.to_string());
+    let shortcut = parse_shortcut_with_aliases(shortcut).map_err(parse_error)?;
+
+    let pattern = pattern.trim();
+    let (modifiers, key_matches) = match pattern.rsplit_once('+') {
+        Some((modifiers, "*")) => (Some(modifiers), true),
+        None if pattern == "*" => (None, true),
+        _ => {
+            let expected = parse_shortcut_with_aliases(pattern).map_err(parse_error)?;
+            let key_matches = expected.key == shortcut.key;
+            return Ok(key_matches
+                && expected
+                    .modifiers
+                    .iter()
+                    .all(|m| has_modifier(&shortcut.modifiers, *m)));
+        }
+    };
+
+    for name in modifiers.into_iter().flat_map(|m| m.split('+')) {
+        let modifier = parse_modifier_with_aliases(name.trim()).map_err(parse_error)?;
+        if !has_modifier(&shortcut.modifiers, modifier) {
+            return Ok(false);
+        }
+    }
+    Ok(key_matches)
+}
+
+/// 修饰键集合中是否包含指定修饰键（通用修饰键匹配左右两侧）
+fn has_modifier(modifiers: &[Modifier], wanted: Modifier) -> bool {
+    modifiers
+        .iter()
+        .any(|m| *m == wanted || generic_modifier(*m) == wanted)
+}
+
+fn generic_modifier(modifier: Modifier) -> Modifier {
+    match modifier {
+        Modifier::LeftAlt | Modifier::RightAlt => Modifier::Alt,
+        Modifier::LeftControl | Modifier::RightControl => Modifier::Control,
+        Modifier::LeftShift | Modifier::RightShift => Modifier::Shift,
+        Modifier::LeftMeta | Modifier::RightMeta => Modifier::Meta,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(commands: &[&str]) -> ExecutionPlan {
+        ExecutionPlan::from_commands(commands).unwrap()
+    }
+
+    #[test]
+    fn test_shortcut_patterns() {
+        assert!(shortcut_matches("win+*", "win+r").unwrap());
+        assert!(shortcut_matches("win+*", "ctrl+win+d").unwrap());
+        assert!(!shortcut_matches("win+*", "ctrl+r").unwrap());
+        assert!(shortcut_matches("ctrl+alt+delete", "alt+ctrl+delete").unwrap());
+        assert!(!shortcut_matches("ctrl+alt+delete", "ctrl+delete").unwrap());
+        assert!(shortcut_matches("*", "a").unwrap());
+        assert!(shortcut_matches("nope+*", "ctrl+a").is_err());
+    }
+
+    #[test]
+    fn test_policy_check() {
+        let policy = Policy::new()
+            .deny_global()
+            .allow_window(0x10)
+            .max_text_len(5)
+            .deny_shortcut("win+*")
+            .deny_action("key_down");
+
+        assert!(policy.check(&plan(&["text:hi,hwnd:0x10"])).is_ok());
+        assert!(policy.check(&plan(&["text:hi"])).is_err());
+        assert!(policy.check(&plan(&["text:hi,hwnd:0x20"])).is_err());
+        assert!(policy
+            .check(&plan(&["text:abc,hwnd:0x10", "text:abc,hwnd:0x10"]))
+            .is_err());
+        assert!(policy.check(&plan(&["shortcut:win+r,hwnd:0x10"])).is_err());
+        // 快捷键全局发送，窗口白名单不能放行
+        assert!(policy.check(&plan(&["shortcut:alt+f4,hwnd:0x10"])).is_err());
+        assert!(policy
+            .check(&plan(&["action:key_down,key:a,hwnd:0x10"]))
+            .is_err());
+        assert!(Policy::new().check(&plan(&["shortcut:win+r"])).is_ok());
+
+        let policy = Policy::new().deny_system_shortcuts();
+        assert!(policy.check(&plan(&["shortcut:win+r,hwnd:0x10"])).is_err());
+        assert!(policy.check(&plan(&["key:rwin"])).is_err());
+        assert!(policy.check(&plan(&["shortcut:ctrl+alt+delete"])).is_err());
+        assert!(policy.check(&plan(&["shortcut:ctrl+s"])).is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+// src/parser/prompt.rs
+//! 交互式询问接口
+//!
+//! 宿主程序实现 [`Prompter`]（控制台、GUI 对话框等）并通过 [`set_prompter`] 安装后，
+//! 以下场景会向它询问：
+//!
+//! - 模板中的 `{prompt:问题}` 占位符（见 [`template`](super::template)）
+//! - 脚本条件 `confirm:"问题"`（见 [`Script`](super::script::Script)）
+//! - 工作流的 `StepAction::Confirm` 步骤
+//! - [`confirm_with_prompter`] 安装的执行前确认
+//!
+//! 未安装时询问返回 `ExecutionDenied` 错误。
+use crate::error::{KeyboardSenderError, Result};
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::sync::{Arc, RwLock};
+
+/// 询问用户的接口
+pub trait Prompter: Send + Sync {
+    /// 询问输入，返回 `None` 表示取消
+    fn ask(&self, question: &str) -> Option<String>;
+
+    /// 请求确认，默认通过 [`ask`](Prompter::ask) 询问，回答 `y`/`yes`/`ok`/`是` 视为同意
+    fn confirm(&self, question: &str) -> bool {
+        self.ask(question)
+            .is_some_and(|answer| is_affirmative(&answer))
+    }
+}
+
+impl<F> Prompter for F
+where
+    F: Fn(&str) -> Option<String> + Send + Sync,
+{
+    fn ask(&self, question: &str) -> Option<String> {
+        self(question)
+    }
+}
+
+/// 在控制台询问：问题写到标准错误，从标准输入读取一行，输入结束时视为取消
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsolePrompter;
+
+impl Prompter for ConsolePrompter {
+    fn ask(&self, question: &str) -> Option<String> {
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "{}: ", question);
+        let _ = stderr.flush();
+
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end_matches(['\r', '\n']).to_string()),
+        }
+    }
+
+    fn confirm(&self, question: &str) -> bool {
+        self.ask(&format!("{} [y/N]", question))
+            .is_some_and(|answer| is_affirmative(&answer))
+    }
+}
+
+/// 回答是否表示同意
+fn is_affirmative(answer: &str) -> bool {
+    matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes" | "ok" | "true" | "是"
+    )
+}
+
+static PROMPTER: RwLock<Option<Arc<dyn Prompter>>> = RwLock::new(None);
+
+thread_local! {
+    /// 当前线程临时覆盖的询问接口
+    static PROMPTER_OVERRIDE: RefCell<Option<Arc<dyn Prompter>>> = const { RefCell::new(None) };
+}
+
+/// 安装询问接口（替换之前安装的）
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{clear_prompter, render_template, set_prompter};
+/// use std::collections::HashMap;
+///
+/// set_prompter(|question: &str| {
+///     println!("{}:", question);
+///     Some("T-1024".to_string())
+/// });
+/// let text = render_template("Fixes {prompt:Enter ticket id}", &HashMap::new()).unwrap();
+/// assert_eq!(text, "Fixes T-1024");
+/// # clear_prompter();
+/// ```
+pub fn set_prompter<P: Prompter + 'static>(prompter: P) {
+    *PROMPTER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(prompter));
+}
+
+/// 移除询问接口
+pub fn clear_prompter() {
+    *PROMPTER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 在当前线程内以指定询问接口执行操作，不影响全局安装的询问接口
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{render_template, with_prompter};
+/// use std::collections::HashMap;
+///
+/// let text = with_prompter(|_: &str| Some("T-1024".to_string()), || {
+///     render_template("Fixes {prompt:Enter ticket id}", &HashMap::new())
+/// })
+/// .unwrap();
+/// assert_eq!(text, "Fixes T-1024");
+/// ```
+pub fn with_prompter<P: Prompter + 'static, T, F: FnOnce() -> T>(prompter: P, f: F) -> T {
+    struct Restore(Option<Arc<dyn Prompter>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            PROMPTER_OVERRIDE.with(|o| *o.borrow_mut() = previous);
+        }
+    }
+
+    let prompter: Arc<dyn Prompter> = Arc::new(prompter);
+    let _restore = Restore(PROMPTER_OVERRIDE.with(|o| o.borrow_mut().replace(prompter)));
+    f()
+}
+
+/// 当前生效的询问接口（线程内覆盖优先）
+fn prompter(question: &str) -> Result<Arc<dyn Prompter>> {
+    PROMPTER_OVERRIDE
+        .with(|o| o.borrow().clone())
+        .or_else(|| PROMPTER.read().unwrap_or_else(|e| e.into_inner()).clone())
+        .ok_or_else(|| {
+            KeyboardSenderError::ExecutionDenied(format!("No prompter installed for: {}", question))
+        })
+}
+
+/// 询问输入，未安装询问接口或被取消时返回 `ExecutionDenied` 错误
+pub fn prompt_input(question: &str) -> Result<String> {
+    prompter(question)?.ask(question).ok_or_else(|| {
+        KeyboardSenderError::ExecutionDenied(format!("Prompt cancelled: {}", question))
+    })
+}
+
+/// 请求确认，未安装询问接口时返回 `ExecutionDenied` 错误
+pub fn prompt_confirm(question: &str) -> Result<bool> {
+    Ok(prompter(question)?.confirm(question))
+}
+
+/// 安装执行前确认处理函数，通过询问接口确认将执行的命令（见
+/// [`set_confirmation_handler`](super::confirm::set_confirmation_handler)）
+///
+/// 执行时未安装询问接口则拒绝执行。
+pub fn confirm_with_prompter() {
+    super::confirm::set_confirmation_handler(|plan| {
+        prompt_confirm(&format!("Run the following?\n{}", plan)).unwrap_or(false)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str);
+
+    impl Prompter for Fixed {
+        fn ask(&self, _question: &str) -> Option<String> {
+            Some(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn test_default_confirm() {
+        assert!(Fixed(" Yes ").confirm("Continue?"));
+        assert!(Fixed("是").confirm("Continue?"));
+        assert!(!Fixed("nope").confirm("Continue?"));
+        assert!(!(|_: &str| None).confirm("Continue?"));
+    }
+}
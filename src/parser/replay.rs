@@ -0,0 +1,312 @@
+// src/parser/replay.rs
+//! 按时间轴回放命令并生成时序报告
+//!
+//! 每条命令带有相对回放开始的计划时间。回放结束后 [`ReplayReport`] 对比每条命令的
+//! 计划时间与实际注入时间，并记录重试次数和被丢弃的命令，便于调整对时序敏感的自动化脚本。
+//!
+//! 启用 `serde` 特性时报告可以序列化保存，之后读回与新的回放对比（见 `compare_journals`）；
+//! 读回的错误为只含原描述的 [`KeyboardSenderError::Recorded`]。
+use crate::error::{KeyboardSenderError, Result};
+use crate::core::qpc::{now_qpc, QpcTimestamp};
+use crate::retry::RetryPolicy;
+use std::time::Duration;
+
+use super::command::send;
+use super::confirm::{confirmed, ExecutionPlan};
+
+/// 带计划时间的命令
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedCommand {
+    /// 相对回放开始的计划时间
+    pub at: Duration,
+    /// 文本命令（见 [`send`]）
+    pub command: String,
+}
+
+impl TimedCommand {
+    pub fn new(at: Duration, command: &str) -> Self {
+        Self {
+            at,
+            command: command.to_string(),
+        }
+    }
+}
+
+/// 回放选项
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// 单条命令失败时的重试策略（默认不重试）
+    pub retry: RetryPolicy,
+    /// 允许的最大延迟，命令开始时已晚于计划时间超过该值则丢弃；`None` 表示从不丢弃
+    pub max_lateness: Option<Duration>,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::no_retry(),
+            max_lateness: None,
+        }
+    }
+}
+
+/// 单条命令的回放时序
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventTiming {
+    /// 命令在输入中的序号
+    pub index: usize,
+    pub command: String,
+    /// 计划时间
+    pub scheduled: Duration,
+    /// 实际开始注入的时间，被丢弃时为 `None`
+    pub injected: Option<Duration>,
+    /// 开始注入时的 QPC 时间戳，可与钩子中记录的时间对比
+    pub injected_at: Option<QpcTimestamp>,
+    /// 注入完成的时间（含重试）
+    pub finished: Option<Duration>,
+    /// 尝试次数，被丢弃时为 0
+    pub attempts: u32,
+    /// 最终错误；因延迟过大被丢弃时为 `Timeout`，未通过确认时为 `ExecutionDenied`
+    #[cfg_attr(feature = "serde", serde(with = "error_text"))]
+    pub error: Option<KeyboardSenderError>,
+}
+
+impl EventTiming {
+    /// 实际注入时间相对计划时间的延迟
+    pub fn lateness(&self) -> Option<Duration> {
+        self.injected.map(|t| t.saturating_sub(self.scheduled))
+    }
+
+    /// 是否被丢弃（延迟过大或重试后仍失败）
+    pub fn is_dropped(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// 回放时序报告
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayReport {
+    pub events: Vec<EventTiming>,
+    /// 回放总耗时
+    pub total_duration: Duration,
+}
+
+impl ReplayReport {
+    /// 被丢弃的命令数
+    pub fn dropped(&self) -> usize {
+        self.events.iter().filter(|e| e.is_dropped()).count()
+    }
+
+    /// 经过重试的命令数
+    pub fn retried(&self) -> usize {
+        self.events.iter().filter(|e| e.attempts > 1).count()
+    }
+
+    /// 最大延迟
+    pub fn max_lateness(&self) -> Duration {
+        self.events
+            .iter()
+            .filter_map(EventTiming::lateness)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// 平均延迟
+    pub fn mean_lateness(&self) -> Duration {
+        let latenesses: Vec<Duration> = self
+            .events
+            .iter()
+            .filter_map(EventTiming::lateness)
+            .collect();
+        if latenesses.is_empty() {
+            return Duration::ZERO;
+        }
+        latenesses.iter().sum::<Duration>() / latenesses.len() as u32
+    }
+}
+
+/// 错误按描述文本序列化
+#[cfg(feature = "serde")]
+mod error_text {
+    use crate::error::KeyboardSenderError;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        error: &Option<KeyboardSenderError>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        error.as_ref().map(|e| e.to_string()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<KeyboardSenderError>, D::Error> {
+        Ok(Option::<String>::deserialize(deserializer)?.map(KeyboardSenderError::Recorded))
+    }
+}
+
+/// 按计划时间回放命令
+///
+/// 命令按计划时间排序后依次执行；执行慢于计划时，后续命令会立即开始而不是跳过。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::{replay, ReplayOptions, TimedCommand};
+/// use std::time::Duration;
+///
+/// let commands = [
+///     TimedCommand::new(Duration::ZERO, "key:w,duration:200ms"),
+///     TimedCommand::new(Duration::from_millis(250), "key:space"),
+/// ];
+/// let report = replay(&commands, &ReplayOptions::default());
+/// println!("max lateness {:?}, dropped {}", report.max_lateness(), report.dropped());
+/// ```
+///
+/// 安装了确认处理函数或权限策略时先检查整个回放；被拒绝时不执行任何命令，
+/// 每条命令都以 `ExecutionDenied` 错误记录在报告中。
+pub fn replay(commands: &[TimedCommand], options: &ReplayOptions) -> ReplayReport {
+    let plan = || {
+        let commands: Vec<&str> = commands.iter().map(|c| c.command.as_str()).collect();
+        ExecutionPlan::from_commands(&commands)
+    };
+    match confirmed(plan, || Ok(replay_with(commands, options, send))) {
+        Ok(report) => report,
+        Err(error) => {
+            let reason = match error {
+                KeyboardSenderError::ExecutionDenied(reason) => reason,
+                other => other.to_string(),
+            };
+            let events = commands
+                .iter()
+                .enumerate()
+                .map(|(index, command)| EventTiming {
+                    index,
+                    command: command.command.clone(),
+                    scheduled: command.at,
+                    injected: None,
+                    injected_at: None,
+                    finished: None,
+                    attempts: 0,
+                    error: Some(KeyboardSenderError::ExecutionDenied(reason.clone())),
+                })
+                .collect();
+            ReplayReport {
+                events,
+                total_duration: Duration::ZERO,
+            }
+        }
+    }
+}
+
+/// 使用指定执行函数回放
+fn replay_with<F>(commands: &[TimedCommand], options: &ReplayOptions, mut exec: F) -> ReplayReport
+where
+    F: FnMut(&str) -> Result<()>,
+{
+    let mut order: Vec<usize> = (0..commands.len()).collect();
+    order.sort_by_key(|&i| commands[i].at);
+
+    let clock = crate::core::clock();
+    let start = clock.now();
+    let elapsed = || clock.now().saturating_duration_since(start);
+    let mut report = ReplayReport::default();
+    for index in order {
+        let command = &commands[index];
+        clock.sleep_until(start + command.at);
+
+        let injected = elapsed();
+        let lateness = injected.saturating_sub(command.at);
+        let mut timing = EventTiming {
+            index,
+            command: command.command.clone(),
+            scheduled: command.at,
+            injected: None,
+            injected_at: None,
+            finished: None,
+            attempts: 0,
+            error: None,
+        };
+
+        if options.max_lateness.is_some_and(|max| lateness > max) {
+            timing.error = Some(KeyboardSenderError::Timeout(format!(
+                "dropped: started {:?} late",
+                lateness
+            )));
+        } else {
+            timing.injected = Some(injected);
+            timing.injected_at = Some(now_qpc());
+            let result = options.retry.run(|attempt| {
+                timing.attempts = attempt;
+                exec(&command.command)
+            });
+            timing.finished = Some(elapsed());
+            timing.error = result.err();
+        }
+        report.events.push(timing);
+    }
+    report.total_duration = elapsed();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_replay_orders_and_records_retries() {
+        let commands = [
+            TimedCommand::new(Duration::from_millis(20), "second"),
+            TimedCommand::new(Duration::ZERO, "first"),
+        ];
+        let options = ReplayOptions {
+            retry: RetryPolicy::polling(Duration::from_millis(10), Duration::from_millis(1)),
+            max_lateness: None,
+        };
+        let mut failures_left = 1;
+        let mut executed = Vec::new();
+        let report = replay_with(&commands, &options, |command| {
+            executed.push(command.to_string());
+            if command == "second" && failures_left > 0 {
+                failures_left -= 1;
+                return Err(KeyboardSenderError::WindowsError);
+            }
+            Ok(())
+        });
+
+        assert_eq!(executed, vec!["first", "second", "second"]);
+        assert_eq!(report.events[0].index, 1);
+        assert_eq!(report.retried(), 1);
+        assert_eq!(report.dropped(), 0);
+        assert!(report.events[1].injected.unwrap() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_replay_drops_late_events() {
+        let commands = [
+            TimedCommand::new(Duration::ZERO, "slow"),
+            TimedCommand::new(Duration::from_millis(1), "late"),
+        ];
+        let options = ReplayOptions {
+            max_lateness: Some(Duration::from_millis(5)),
+            ..ReplayOptions::default()
+        };
+        // 虚拟时钟下 "slow" 恰好耗时 30ms，结果与机器负载无关
+        let clock = Arc::new(crate::core::MockClock::new());
+        let report = crate::core::with_clock(clock.clone(), || {
+            replay_with(&commands, &options, |command| {
+                if command == "slow" {
+                    clock.advance(Duration::from_millis(30));
+                }
+                Ok(())
+            })
+        });
+
+        assert_eq!(report.dropped(), 1);
+        assert!(report.events[1].injected.is_none());
+        assert_eq!(report.events[1].attempts, 0);
+    }
+}
@@ -0,0 +1,748 @@
+// src/parser/script.rs
+//! 脚本控制流
+//!
+//! 在逐行命令的基础上支持标签、跳转和子程序，便于在同一脚本中复用登录、清理等公共片段：
+//!
+//! - `label:名称`：标记位置，本身不执行
+//! - `goto:名称`：跳转到标签
+//! - `call:名称`：调用标签处的子程序，执行到 `return` 后回到调用处的下一行
+//! - `return`：从子程序返回
+//! - `if [not] 条件 goto:名称`（或 `call:名称`、`return`）：条件成立时执行跳转
+//!
+//! 条件在执行时查询运行环境：
+//!
+//! - `keydown:shift`：按键当前处于按下状态
+//! - `toggled:capslock`：锁定键处于开启状态
+//! - `window:"Save As"`：存在标题包含该文本的窗口（需要 `window_target` 特性）
+//! - `clipboard~"^\d+$"`：剪贴板文本匹配正则表达式（需要 `clipboard` 特性）
+//! - `confirm:"继续吗？"`：通过询问接口确认（见 [`Prompter`](super::prompt::Prompter)）
+//!
+//! 调用深度超过 [`ScriptOptions::max_call_depth`] 时返回错误。两次条件判断之间，
+//! 控制流再次到达同一跳转且调用栈相同时必然无限循环，此时返回错误。
+//!
+//! 跳转回到已判断过的条件（如 `label:wait` + `if not window:"Done" goto:wait` 轮询窗口出现）时，
+//! 每次重新判断前等待 [`ScriptOptions::poll_interval`]，避免空转占满 CPU；设置了
+//! [`ScriptOptions::poll_timeout`] 时，期间没有执行任何命令的轮询超过该时长返回 `Timeout` 错误。
+use crate::error::{KeyboardSenderError, Result};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use super::batch::{BatchReport, CommandResult};
+use super::command::{send, validate_command};
+use super::confirm::{confirmed, ExecutionPlan};
+
+/// 默认最大调用深度
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 16;
+
+/// 默认的条件轮询间隔
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 脚本中的一条指令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptStep {
+    /// 文本命令
+    Command(String),
+    /// 跳转到标签
+    Goto(String),
+    /// 调用标签处的子程序
+    Call(String),
+    /// 从子程序返回
+    Return,
+    /// 条件成立时执行 `then`（`Goto`、`Call` 或 `Return`）
+    If {
+        condition: Condition,
+        then: Box<ScriptStep>,
+    },
+}
+
+/// 脚本条件中查询的运行环境
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// 按键（或修饰键）处于按下状态
+    KeyDown(String),
+    /// 锁定键处于开启状态
+    Toggled(String),
+    /// 存在标题包含该文本的窗口
+    Window(String),
+    /// 剪贴板文本匹配正则表达式
+    ClipboardMatches(String),
+    /// 用户确认了问题
+    Confirm(String),
+}
+
+impl Predicate {
+    /// 判断时是否需要与用户交互（预览时不判断这类条件）
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, Predicate::Confirm(_))
+    }
+}
+
+/// 脚本条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub predicate: Predicate,
+    /// 是否取反（`if not ...`）
+    pub negated: bool,
+}
+
+impl Condition {
+    /// 解析条件文本，如 `not keydown:shift`、`window:"Save As"`
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let (negated, text) = match text.strip_prefix("not ") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, text),
+        };
+        let predicate = if let Some(key) = text.strip_prefix("keydown:") {
+            parse_vk(key)?;
+            Predicate::KeyDown(key.trim().to_string())
+        } else if let Some(key) = text.strip_prefix("toggled:") {
+            parse_vk(key)?;
+            Predicate::Toggled(key.trim().to_string())
+        } else if let Some(title) = text.strip_prefix("window:") {
+            Predicate::Window(unquote(title).to_string())
+        } else if let Some(pattern) = text.strip_prefix("clipboard~") {
+            let pattern = unquote(pattern);
+            regex::Regex::new(pattern).map_err(|e| {
+                KeyboardSenderError::CommandParseError(format!("invalid pattern: {}", e))
+            })?;
+            Predicate::ClipboardMatches(pattern.to_string())
+        } else if let Some(question) = text.strip_prefix("confirm:") {
+            Predicate::Confirm(unquote(question).to_string())
+        } else {
+            return Err(KeyboardSenderError::CommandParseError(format!(
+                "unknown condition '{}'",
+                text
+            )));
+        };
+        Ok(Self { predicate, negated })
+    }
+
+    /// 查询运行环境，判断条件是否成立
+    pub fn evaluate(&self) -> Result<bool> {
+        let value = match &self.predicate {
+            Predicate::KeyDown(key) => crate::core::state::is_vk_down(parse_vk(key)?),
+            Predicate::Toggled(key) => crate::core::state::is_vk_toggled(parse_vk(key)?),
+            Predicate::Window(title) => window_exists(title)?,
+            Predicate::ClipboardMatches(pattern) => clipboard_matches(pattern)?,
+            Predicate::Confirm(question) => super::prompt::prompt_confirm(question)?,
+        };
+        Ok(value != self.negated)
+    }
+}
+
+/// 按键或修饰键名对应的虚拟键码
+fn parse_vk(name: &str) -> Result<u16> {
+    use keyboard_codes::{KeyCodeMapper, KeyboardInput};
+
+    let platform = keyboard_codes::current_platform();
+    match keyboard_codes::parse_keyboard_input(name.trim())
+        .map_err(|e| KeyboardSenderError::ParseError(e.to_string()))?
+    {
+        KeyboardInput::Key(key) => Ok(key.to_code(platform) as u16),
+        KeyboardInput::Modifier(modifier) => Ok(modifier.to_code(platform) as u16),
+    }
+}
+
+/// 去掉值两端的双引号
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn window_exists(title: &str) -> Result<bool> {
+    #[cfg(not(feature = "window_target"))]
+    {
+        let _ = title;
+        Err(KeyboardSenderError::FeatureNotEnabled(
+            "window_target".to_string(),
+        ))
+    }
+
+    #[cfg(feature = "window_target")]
+    Ok(crate::core::find_window(&title.into()).is_some())
+}
+
+fn clipboard_matches(pattern: &str) -> Result<bool> {
+    #[cfg(not(feature = "clipboard"))]
+    {
+        let _ = pattern;
+        Err(KeyboardSenderError::FeatureNotEnabled(
+            "clipboard".to_string(),
+        ))
+    }
+
+    #[cfg(feature = "clipboard")]
+    {
+        let regex = regex::Regex::new(pattern)
+            .map_err(|e| KeyboardSenderError::CommandParseError(e.to_string()))?;
+        Ok(crate::core::get_clipboard_text()?.is_some_and(|text| regex.is_match(&text)))
+    }
+}
+
+/// 带行号的指令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptLine {
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 指令
+    pub step: ScriptStep,
+}
+
+/// 已解析并校验的脚本
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    lines: Vec<ScriptLine>,
+    labels: HashMap<String, usize>,
+}
+
+impl Script {
+    /// 解析脚本文本
+    ///
+    /// 空行和以 `#` 开头的行会被跳过，但仍计入行号。任一命令无效、标签重复或
+    /// 跳转到不存在的标签时返回错误（带行号）。
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use sophia_keyboard_sender::Script;
+    ///
+    /// let script = Script::parse(
+    ///     "call:login\n\
+    ///      text:report\n\
+    ///      goto:end\n\
+    ///      label:login\n\
+    ///      text:user\n\
+    ///      key:tab\n\
+    ///      return\n\
+    ///      label:end",
+    /// )
+    /// .unwrap();
+    /// let report = script.run().unwrap();
+    /// println!("{} ok, {} failed", report.succeeded(), report.failed());
+    /// ```
+    pub fn parse(text: &str) -> Result<Self> {
+        Self::from_lines(&text.lines().collect::<Vec<_>>())
+    }
+
+    /// 从逐行命令解析脚本
+    pub fn from_lines(lines: &[&str]) -> Result<Self> {
+        let mut script = Script::default();
+        for (index, line) in lines.iter().enumerate() {
+            let line_no = index + 1;
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            let step = if let Some(label) = text.strip_prefix("label:") {
+                let label = label_name(line_no, label)?;
+                if script
+                    .labels
+                    .insert(label.clone(), script.lines.len())
+                    .is_some()
+                {
+                    return Err(line_error(line_no, format!("duplicate label '{}'", label)));
+                }
+                continue;
+            } else if let Some(rest) = text.strip_prefix("if ") {
+                let (condition, then) = split_condition(rest);
+                let condition =
+                    Condition::parse(condition).map_err(|e| line_error(line_no, e.to_string()))?;
+                let then = match parse_jump(line_no, then.trim())? {
+                    Some(then) => then,
+                    None => {
+                        return Err(line_error(
+                            line_no,
+                            "expected goto:, call: or return after condition".to_string(),
+                        ))
+                    }
+                };
+                ScriptStep::If {
+                    condition,
+                    then: Box::new(then),
+                }
+            } else if let Some(jump) = parse_jump(line_no, text)? {
+                jump
+            } else {
+                validate_command(text).map_err(|e| line_error(line_no, e.to_string()))?;
+                ScriptStep::Command(text.to_string())
+            };
+            script.lines.push(ScriptLine {
+                line: line_no,
+                step,
+            });
+        }
+
+        for line in &script.lines {
+            let step = match &line.step {
+                ScriptStep::If { then, .. } => then.as_ref(),
+                step => step,
+            };
+            if let ScriptStep::Goto(label) | ScriptStep::Call(label) = step {
+                if !script.labels.contains_key(label) {
+                    return Err(line_error(line.line, format!("unknown label '{}'", label)));
+                }
+            }
+        }
+        Ok(script)
+    }
+
+    /// 全部指令（不含标签和注释）
+    pub fn lines(&self) -> &[ScriptLine] {
+        &self.lines
+    }
+
+    /// 标签指向的指令序号（标签位于末尾时等于指令数）
+    pub fn label(&self, name: &str) -> Option<usize> {
+        self.labels.get(name).copied()
+    }
+
+    /// 脚本中出现的全部文本命令（按书写顺序）
+    pub fn commands(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match &line.step {
+                ScriptStep::Command(command) => Some(command.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 使用默认选项执行脚本
+    pub fn run(&self) -> Result<BatchReport> {
+        self.run_with(&ScriptOptions::default())
+    }
+
+    /// 按选项执行脚本
+    ///
+    /// 安装了确认处理函数时，按书写顺序列出脚本中的全部命令确认一次。
+    /// 运行时输入在执行到引用它的命令时才取值，未执行分支中的命令不会复制选中文本或询问用户；
+    /// 同一输入在整个脚本中只取值一次。
+    /// 命令失败不会返回错误，而是记录在报告中；运行时输入取值失败、调用过深或死循环时返回错误。
+    pub fn run_with(&self, options: &ScriptOptions) -> Result<BatchReport> {
+        confirmed(
+            || ExecutionPlan::from_commands(&self.commands()),
+            || self.execute(options),
+        )
+    }
+
+    fn execute(&self, options: &ScriptOptions) -> Result<BatchReport> {
+        let clock = crate::core::clock();
+        let start = clock.now();
+        let mut report = BatchReport::default();
+        let mut interpreter = Interpreter::new(options);
+        let mut inputs = super::template::bind_runtime_inputs::<&str>(&[])?;
+        while let Some(index) = interpreter.resolve(self)? {
+            if !report.results.is_empty() {
+                if let Some(delay) = options.delay {
+                    clock.sleep(delay);
+                }
+            }
+            let ScriptStep::Command(command) = &self.lines[index].step else {
+                unreachable!("resolve stops only at commands");
+            };
+            inputs.bind_more(&[command])?;
+
+            let command_start = clock.now();
+            let result = send(command);
+            let failed = result.is_err();
+            report.results.push(CommandResult {
+                line: self.lines[index].line,
+                command: command.clone(),
+                result,
+                duration: clock.now().saturating_duration_since(command_start),
+            });
+            interpreter.advance();
+            if failed && options.stop_on_error {
+                break;
+            }
+        }
+        report.total_duration = clock.now().saturating_duration_since(start);
+        Ok(report)
+    }
+}
+
+/// 脚本执行选项
+#[derive(Debug, Clone)]
+pub struct ScriptOptions {
+    /// 两条命令之间的等待时间
+    pub delay: Option<Duration>,
+    /// 某条命令执行失败后是否停止执行
+    pub stop_on_error: bool,
+    /// 最大调用深度
+    pub max_call_depth: usize,
+    /// 跳转回已判断过的条件时，重新判断前的等待时间（零按 1 毫秒处理）
+    pub poll_interval: Duration,
+    /// 没有执行任何命令的条件轮询最长持续时间，`None` 表示不限制
+    pub poll_timeout: Option<Duration>,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> Self {
+        Self {
+            delay: None,
+            stop_on_error: false,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            poll_timeout: None,
+        }
+    }
+}
+
+impl ScriptOptions {
+    /// 设置命令间隔
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// 设置失败后是否停止
+    pub fn stop_on_error(mut self, stop: bool) -> Self {
+        self.stop_on_error = stop;
+        self
+    }
+
+    /// 设置最大调用深度
+    pub fn max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = depth;
+        self
+    }
+
+    /// 设置条件轮询间隔
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 设置条件轮询的最长持续时间
+    pub fn poll_timeout(mut self, timeout: Duration) -> Self {
+        self.poll_timeout = Some(timeout);
+        self
+    }
+}
+
+/// 不执行命令地预览下一步的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Peek {
+    /// 下一条命令的指令序号
+    Command(usize),
+    /// 脚本已结束
+    Finished,
+    /// 需要等待后重新判断条件、或需要询问用户才能确定下一条命令（条件所在行号）
+    Waiting(usize),
+}
+
+/// 脚本解释器：处理控制流，定位下一条要执行的命令
+#[derive(Debug, Clone)]
+pub(crate) struct Interpreter {
+    pc: usize,
+    stack: Vec<usize>,
+    max_call_depth: usize,
+    poll_interval: Duration,
+    poll_timeout: Option<Duration>,
+    /// 到过的跳转状态（指令序号、调用栈），用于检测死循环
+    visited: HashSet<(usize, Vec<usize>)>,
+    /// 判断过的条件状态（指令序号、调用栈），再次到达时按轮询间隔等待
+    evaluated: HashSet<(usize, Vec<usize>)>,
+    /// 自上一条命令以来开始轮询的时刻
+    polling_since: Option<Instant>,
+}
+
+impl Interpreter {
+    pub(crate) fn new(options: &ScriptOptions) -> Self {
+        Self {
+            pc: 0,
+            stack: Vec::new(),
+            max_call_depth: options.max_call_depth,
+            poll_interval: options.poll_interval.max(Duration::from_millis(1)),
+            poll_timeout: options.poll_timeout,
+            visited: HashSet::new(),
+            evaluated: HashSet::new(),
+            polling_since: None,
+        }
+    }
+
+    /// 当前指令序号
+    pub(crate) fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// 执行控制流指令，停在下一条命令上并返回其序号；脚本结束时返回 `None`
+    pub(crate) fn resolve(&mut self, script: &Script) -> Result<Option<usize>> {
+        match self.run_control(script, false)? {
+            Peek::Command(index) => Ok(Some(index)),
+            Peek::Finished => Ok(None),
+            Peek::Waiting(_) => unreachable!("only previews stop before polling"),
+        }
+    }
+
+    /// 不改变状态地预览下一步，不会为轮询等待，也不判断需要询问用户的条件
+    pub(crate) fn peek(&self, script: &Script) -> Result<Peek> {
+        self.clone().run_control(script, true)
+    }
+
+    fn run_control(&mut self, script: &Script, preview: bool) -> Result<Peek> {
+        loop {
+            let Some(line) = script.lines.get(self.pc) else {
+                return Ok(Peek::Finished);
+            };
+            let step = match &line.step {
+                ScriptStep::Command(_) => return Ok(Peek::Command(self.pc)),
+                ScriptStep::If { condition, then } => {
+                    if preview && condition.predicate.is_interactive() {
+                        return Ok(Peek::Waiting(line.line));
+                    }
+                    if !self.evaluated.insert((self.pc, self.stack.clone())) {
+                        if preview {
+                            return Ok(Peek::Waiting(line.line));
+                        }
+                        self.wait_poll(line.line)?;
+                    }
+                    // 条件取决于运行环境，之前到过的跳转状态不再意味着死循环
+                    self.visited.clear();
+                    if !condition.evaluate()? {
+                        self.pc += 1;
+                        continue;
+                    }
+                    then.as_ref()
+                }
+                step => step,
+            };
+            let target = match step {
+                ScriptStep::Command(_) | ScriptStep::If { .. } => {
+                    unreachable!("conditions only wrap jumps")
+                }
+                ScriptStep::Return => {
+                    let Some(return_to) = self.stack.pop() else {
+                        return Err(line_error(line.line, "return without call".to_string()));
+                    };
+                    self.pc = return_to;
+                    continue;
+                }
+                ScriptStep::Goto(label) | ScriptStep::Call(label) => script.labels[label],
+            };
+
+            if !self.visited.insert((self.pc, self.stack.clone())) {
+                return Err(line_error(
+                    line.line,
+                    "infinite loop: jump reached again with the same call stack".to_string(),
+                ));
+            }
+            if let ScriptStep::Call(_) = step {
+                if self.stack.len() >= self.max_call_depth {
+                    return Err(line_error(
+                        line.line,
+                        format!("call depth exceeds {}", self.max_call_depth),
+                    ));
+                }
+                self.stack.push(self.pc + 1);
+            }
+            self.pc = target;
+        }
+    }
+
+    /// 越过 [`resolve`](Self::resolve) 返回的命令
+    pub(crate) fn advance(&mut self) {
+        self.pc += 1;
+        self.polling_since = None;
+    }
+
+    /// 重新判断条件前按轮询间隔等待，轮询超过时限时返回错误
+    fn wait_poll(&mut self, line: usize) -> Result<()> {
+        let clock = crate::core::clock();
+        let now = clock.now();
+        let since = *self.polling_since.get_or_insert(now);
+        if let Some(timeout) = self.poll_timeout {
+            if now.duration_since(since) >= timeout {
+                return Err(KeyboardSenderError::Timeout(format!(
+                    "line {}: condition polled for {:?} without running a command",
+                    line, timeout
+                )));
+            }
+        }
+        clock.sleep(self.poll_interval);
+        Ok(())
+    }
+}
+
+/// 解析跳转指令，不是跳转时返回 `None`
+fn parse_jump(line: usize, text: &str) -> Result<Option<ScriptStep>> {
+    Ok(Some(if let Some(label) = text.strip_prefix("goto:") {
+        ScriptStep::Goto(label_name(line, label)?)
+    } else if let Some(label) = text.strip_prefix("call:") {
+        ScriptStep::Call(label_name(line, label)?)
+    } else if text == "return" {
+        ScriptStep::Return
+    } else {
+        return Ok(None);
+    }))
+}
+
+/// 将 `if` 之后的文本拆分为条件和跳转（在引号外的最后一个空白处拆分）
+fn split_condition(text: &str) -> (&str, &str) {
+    let mut in_quotes = false;
+    let mut split = None;
+    for (index, c) in text.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => split = Some(index),
+            _ => {}
+        }
+    }
+    match split {
+        Some(index) => (&text[..index], &text[index..]),
+        None => (text, ""),
+    }
+}
+
+fn label_name(line: usize, label: &str) -> Result<String> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(line_error(line, "empty label".to_string()));
+    }
+    Ok(label.to_string())
+}
+
+fn line_error(line: usize, message: String) -> KeyboardSenderError {
+    KeyboardSenderError::CommandParseError(format!("line {}: {}", line, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 模拟执行，返回依次执行的命令
+    fn trace(script: &Script, max_call_depth: usize) -> Result<Vec<String>> {
+        let mut interpreter =
+            Interpreter::new(&ScriptOptions::default().max_call_depth(max_call_depth));
+        let mut executed = Vec::new();
+        while let Some(index) = interpreter.resolve(script)? {
+            if let ScriptStep::Command(command) = &script.lines[index].step {
+                executed.push(command.clone());
+            }
+            interpreter.advance();
+        }
+        Ok(executed)
+    }
+
+    #[test]
+    fn test_call_and_goto() {
+        let script = Script::parse(
+            "call:login\n\
+             text:a\n\
+             call:login\n\
+             goto:end\n\
+             label:login\n\
+             key:tab\n\
+             return\n\
+             label:end\n\
+             key:enter",
+        )
+        .unwrap();
+        assert_eq!(script.label("login"), Some(4));
+        assert_eq!(
+            trace(&script, DEFAULT_MAX_CALL_DEPTH).unwrap(),
+            vec!["key:tab", "text:a", "key:tab", "key:enter"]
+        );
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        let script = Script::parse(
+            "if not keydown:shift goto:end\n\
+             if window:\"Save As\" call:save\n\
+             if clipboard~\"^\\d+$\" return\n\
+             label:save\n\
+             label:end",
+        )
+        .unwrap();
+        let conditions: Vec<&Condition> = script
+            .lines()
+            .iter()
+            .filter_map(|line| match &line.step {
+                ScriptStep::If { condition, .. } => Some(condition),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            conditions[0].predicate,
+            Predicate::KeyDown("shift".to_string())
+        );
+        assert!(conditions[0].negated);
+        assert_eq!(
+            conditions[1].predicate,
+            Predicate::Window("Save As".to_string())
+        );
+        assert_eq!(
+            conditions[2].predicate,
+            Predicate::ClipboardMatches("^\\d+$".to_string())
+        );
+        assert_eq!(
+            Condition::parse("not confirm:\"Continue?\"").unwrap(),
+            Condition {
+                predicate: Predicate::Confirm("Continue?".to_string()),
+                negated: true,
+            }
+        );
+
+        for invalid in [
+            "if keydown:nope goto:x\nlabel:x",
+            "if weather:sunny goto:x\nlabel:x",
+            "if clipboard~\"(\" goto:x\nlabel:x",
+            "if keydown:shift\nlabel:x",
+            "if keydown:shift goto:missing",
+        ] {
+            assert!(Script::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Script::parse("key:a\ngoto:missing").unwrap_err();
+        assert!(err.to_string().contains("line 2: unknown label"));
+        let err = Script::parse("label:a\nlabel:a").unwrap_err();
+        assert!(err.to_string().contains("line 2: duplicate label"));
+        assert!(Script::parse("key:a,duration:soon").is_err());
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let recursive = Script::parse("label:again\nkey:a\ncall:again").unwrap();
+        let err = trace(&recursive, 3).unwrap_err();
+        assert!(err.to_string().contains("call depth exceeds 3"));
+
+        for looping in ["label:top\ngoto:top", "label:top\nkey:a\ngoto:top"] {
+            let script = Script::parse(looping).unwrap();
+            let err = trace(&script, DEFAULT_MAX_CALL_DEPTH).unwrap_err();
+            assert!(err.to_string().contains("infinite loop"));
+        }
+
+        let stray = Script::parse("key:a\nreturn").unwrap();
+        let err = trace(&stray, DEFAULT_MAX_CALL_DEPTH).unwrap_err();
+        assert!(err.to_string().contains("return without call"));
+    }
+
+    #[test]
+    fn test_polling_loop_waits() {
+        use std::sync::Arc;
+
+        let script = Script::parse("label:wait\nif not keydown:f24 goto:wait\nkey:a").unwrap();
+        let options = ScriptOptions::default()
+            .poll_interval(Duration::from_millis(100))
+            .poll_timeout(Duration::from_secs(1));
+
+        // 预览不等待，停在需要重新判断的条件上
+        let interpreter = Interpreter::new(&options);
+        assert_eq!(interpreter.peek(&script).unwrap(), Peek::Waiting(2));
+
+        let mock = Arc::new(crate::core::MockClock::new());
+        let err = crate::core::with_clock(mock.clone(), || {
+            Interpreter::new(&options).resolve(&script).unwrap_err()
+        });
+        assert!(matches!(err, KeyboardSenderError::Timeout(_)));
+        assert_eq!(mock.elapsed(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,638 @@
+// src/parser/template.rs
+//! 模板字符串插值：变量、日期时间、环境变量和随机数
+//!
+//! 支持的占位符：
+//! - `{name}`：变量
+//! - `{date}` / `{date:%Y-%m-%d}`：本地日期，可指定格式
+//! - `{time}` / `{time:%H:%M}`：本地时间，可指定格式
+//! - `{datetime}`：`%Y-%m-%d %H:%M:%S`
+//! - `{env:NAME}`：环境变量
+//! - `{random}` / `{random:1..100}`：随机整数（区间包含两端）
+//! - `{clipboard}`：剪贴板文本（需要启用 `clipboard` 特性）
+//! - `{selection}` / `{selection:0x1A2B}`：前台窗口或指定窗口中选中的文本，通过复制获取
+//!   （需要启用 `clipboard` 特性，指定窗口还需要 `window_target` 特性）
+//! - `{prompt:问题}`：向 [`set_prompter`](super::prompt::set_prompter) 安装的询问接口询问输入
+//! - `{{` / `}}`：字面量花括号
+//!
+//! 格式说明符：`%Y %y %m %d %H %M %S %3f %%`
+//!
+//! 文本命令中的 `text:` 参数只有在指定了 `template:true` 时才按模板渲染（见 [`command_text`]），
+//! 默认原样输入，字面量花括号和 `{clipboard}` 之类的文本不会触发任何副作用。
+//!
+//! `{clipboard}`、`{selection}` 和 `{prompt:..}` 是运行时输入（[`RuntimeInput`]），在命令执行时
+//! 才取值。批量命令在开始执行前通过 [`bind_runtime_inputs`] 一次性取值：同一输入只询问一次，
+//! 选中文本在任何输入发生之前复制；脚本在执行到某条命令时才为其取值（见 [`RuntimeBindings::bind_more`]）。
+//! 取值发生在获取逻辑操作锁之前，复制选中文本和询问用户时不会阻塞其他线程的输入。
+use crate::error::{KeyboardSenderError, Result};
+use crate::types::WindowHandle;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// 本地时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub year: u16,
+    pub month: u16,
+    pub day: u16,
+    pub hour: u16,
+    pub minute: u16,
+    pub second: u16,
+    pub millisecond: u16,
+}
+
+impl LocalTime {
+    /// 当前本地时间
+    pub fn now() -> Self {
+        let st = unsafe { windows::Win32::System::SystemInformation::GetLocalTime() };
+        Self {
+            year: st.wYear,
+            month: st.wMonth,
+            day: st.wDay,
+            hour: st.wHour,
+            minute: st.wMinute,
+            second: st.wSecond,
+            millisecond: st.wMilliseconds,
+        }
+    }
+
+    /// 按 strftime 风格的格式说明符格式化
+    pub fn format(&self, fmt: &str) -> String {
+        let mut out = String::new();
+        let mut chars = fmt.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => out.push_str(&format!("{:04}", self.year)),
+                Some('y') => out.push_str(&format!("{:02}", self.year % 100)),
+                Some('m') => out.push_str(&format!("{:02}", self.month)),
+                Some('d') => out.push_str(&format!("{:02}", self.day)),
+                Some('H') => out.push_str(&format!("{:02}", self.hour)),
+                Some('M') => out.push_str(&format!("{:02}", self.minute)),
+                Some('S') => out.push_str(&format!("{:02}", self.second)),
+                Some('3') if chars.clone().next() == Some('f') => {
+                    chars.next();
+                    out.push_str(&format!("{:03}", self.millisecond));
+                }
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+}
+
+/// 未知占位符的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownPlaceholder {
+    /// 返回错误
+    Error,
+    /// 原样保留（含花括号）
+    Keep,
+}
+
+/// 渲染模板，未知变量返回错误
+///
+/// # 示例
+/// ```
+/// # #[cfg(feature = "command_parser")]
+/// # {
+/// use sophia_keyboard_sender::render_template;
+/// use std::collections::HashMap;
+///
+/// let mut vars = HashMap::new();
+/// vars.insert("name".to_string(), "Alice".to_string());
+/// assert_eq!(render_template("Hello {name}!", &vars).unwrap(), "Hello Alice!");
+/// # }
+/// ```
+pub fn render_template(template: &str, vars: &HashMap<String, String>) -> Result<String> {
+    render_template_with(template, vars, UnknownPlaceholder::Error)
+}
+
+/// 渲染模板，并指定未知占位符的处理方式
+pub fn render_template_with(
+    template: &str,
+    vars: &HashMap<String, String>,
+    unknown: UnknownPlaceholder,
+) -> Result<String> {
+    render_with(template, unknown, |placeholder| {
+        resolve_placeholder(placeholder, vars)
+    })
+}
+
+/// 按 `resolve` 解析占位符渲染模板
+fn render_with(
+    template: &str,
+    unknown: UnknownPlaceholder,
+    mut resolve: impl FnMut(&str) -> Result<Option<String>>,
+) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let brace = rest.as_bytes()[pos];
+        rest = &rest[pos + 1..];
+
+        if brace == b'}' {
+            // 单独的 `}` 原样输出，`}}` 为转义
+            out.push('}');
+            rest = rest.strip_prefix('}').unwrap_or(rest);
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('{') {
+            out.push('{');
+            rest = after;
+            continue;
+        }
+
+        let Some(end) = rest.find('}') else {
+            return Err(KeyboardSenderError::ParseError(format!(
+                "Unclosed placeholder in template: {}",
+                template
+            )));
+        };
+        let placeholder = &rest[..end];
+        rest = &rest[end + 1..];
+
+        match resolve(placeholder)? {
+            Some(value) => out.push_str(&value),
+            None if unknown == UnknownPlaceholder::Keep => {
+                out.push('{');
+                out.push_str(placeholder);
+                out.push('}');
+            }
+            None => {
+                return Err(KeyboardSenderError::ParseError(format!(
+                    "Unknown template variable: {}",
+                    placeholder
+                )))
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// 解析单个占位符，未知时返回 `None`
+fn resolve_placeholder(
+    placeholder: &str,
+    vars: &HashMap<String, String>,
+) -> Result<Option<String>> {
+    let (name, arg) = match placeholder.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg)),
+        None => (placeholder.trim(), None),
+    };
+
+    if arg.is_none() {
+        if let Some(value) = vars.get(name) {
+            return Ok(Some(value.clone()));
+        }
+    }
+    if let Some(input) = RuntimeInput::parse(placeholder)? {
+        return bound_value(&input)
+            .map_or_else(|| input.resolve(), Ok)
+            .map(Some);
+    }
+
+    let value = match name {
+        "date" => LocalTime::now().format(arg.unwrap_or("%Y-%m-%d")),
+        "time" => LocalTime::now().format(arg.unwrap_or("%H:%M:%S")),
+        "datetime" => LocalTime::now().format(arg.unwrap_or("%Y-%m-%d %H:%M:%S")),
+        "env" => match arg {
+            Some(var) => std::env::var(var.trim()).unwrap_or_default(),
+            None => return Ok(None),
+        },
+        "random" => random_in_range(arg)?.to_string(),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+/// 生成随机整数，区间格式为 `min..max`（包含两端）
+fn random_in_range(range: Option<&str>) -> Result<i64> {
+    let (min, max) = match range {
+        None => (0, i64::from(u32::MAX)),
+        Some(range) => {
+            let invalid =
+                || KeyboardSenderError::ParseError(format!("Invalid random range: {}", range));
+            let (min, max) = range.split_once("..").ok_or_else(invalid)?;
+            let min: i64 = min.trim().parse().map_err(|_| invalid())?;
+            let max: i64 = max.trim().parse().map_err(|_| invalid())?;
+            if min > max {
+                return Err(invalid());
+            }
+            (min, max)
+        }
+    };
+    // 区间覆盖整个 i64 时跨度超出 u64，直接使用随机数
+    let offset = match max.abs_diff(min).checked_add(1) {
+        Some(span) => crate::random::next_u64() % span,
+        None => crate::random::next_u64(),
+    };
+    Ok(min.wrapping_add_unsigned(offset))
+}
+
+/// 命令是否指定了 `template:true`
+pub(crate) fn is_template(params: &HashMap<String, String>) -> Result<bool> {
+    params
+        .get("template")
+        .map_or(Ok(false), |flag| super::command::parse_flag(flag))
+}
+
+/// 命令的 `text:` 参数：指定了 `template:true` 时按模板渲染（其他参数可作为变量，
+/// 未知占位符原样保留），否则原样返回
+pub fn command_text(params: &HashMap<String, String>) -> Result<Option<String>> {
+    let Some(text) = params.get("text") else {
+        return Ok(None);
+    };
+    if !is_template(params)? {
+        return Ok(Some(text.clone()));
+    }
+    render_template_with(text, params, UnknownPlaceholder::Keep).map(Some)
+}
+
+/// 预览命令的 `text:` 参数：与 [`command_text`] 相同，但尚未绑定的运行时输入保留占位符，
+/// 不会复制选中文本或询问用户
+pub(crate) fn preview_command_text(params: &HashMap<String, String>) -> Result<Option<String>> {
+    let Some(text) = params.get("text") else {
+        return Ok(None);
+    };
+    if !is_template(params)? {
+        return Ok(Some(text.clone()));
+    }
+    render_with(
+        text,
+        UnknownPlaceholder::Keep,
+        |placeholder| match RuntimeInput::parse(placeholder)? {
+            Some(input) => Ok(bound_value(&input)),
+            None => resolve_placeholder(placeholder, params),
+        },
+    )
+    .map(Some)
+}
+
+/// 在命令执行时才取值的模板输入
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RuntimeInput {
+    /// `{clipboard}`：剪贴板文本
+    Clipboard,
+    /// `{selection}`：选中的文本，`None` 表示前台窗口
+    Selection(Option<WindowHandle>),
+    /// `{prompt:问题}`：询问用户输入
+    Prompt(String),
+}
+
+impl RuntimeInput {
+    /// 解析占位符（不含花括号），不是运行时输入时返回 `None`
+    pub fn parse(placeholder: &str) -> Result<Option<Self>> {
+        let (name, arg) = match placeholder.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (placeholder.trim(), None),
+        };
+        let input = match (name, arg) {
+            ("clipboard", None) => RuntimeInput::Clipboard,
+            ("selection", None) => RuntimeInput::Selection(None),
+            ("selection", Some(hwnd)) => match super::command::parse_hwnd(hwnd)? {
+                0 => RuntimeInput::Selection(None),
+                hwnd => RuntimeInput::Selection(Some(hwnd)),
+            },
+            ("prompt", Some(question)) => RuntimeInput::Prompt(question.to_string()),
+            _ => return Ok(None),
+        };
+        Ok(Some(input))
+    }
+
+    /// 立即取值
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            RuntimeInput::Clipboard => {
+                #[cfg(not(feature = "clipboard"))]
+                return Err(KeyboardSenderError::FeatureNotEnabled(
+                    "clipboard".to_string(),
+                ));
+
+                #[cfg(feature = "clipboard")]
+                Ok(crate::core::get_clipboard_text()?.unwrap_or_default())
+            }
+            RuntimeInput::Selection(hwnd) => resolve_selection(*hwnd),
+            RuntimeInput::Prompt(question) => super::prompt::prompt_input(question),
+        }
+    }
+
+    /// 规范化的占位符文本（不含花括号）
+    fn key(&self) -> String {
+        match self {
+            RuntimeInput::Clipboard => "clipboard".to_string(),
+            RuntimeInput::Selection(None) => "selection".to_string(),
+            RuntimeInput::Selection(Some(hwnd)) => format!("selection:{:#X}", hwnd),
+            RuntimeInput::Prompt(question) => format!("prompt:{}", question),
+        }
+    }
+
+    /// 取值顺序：先复制选中文本，再读剪贴板，最后询问（询问窗口可能抢走焦点）
+    fn order(&self) -> u8 {
+        match self {
+            RuntimeInput::Selection(_) => 0,
+            RuntimeInput::Clipboard => 1,
+            RuntimeInput::Prompt(_) => 2,
+        }
+    }
+}
+
+/// 复制前台窗口或指定窗口中选中的文本
+fn resolve_selection(hwnd: Option<WindowHandle>) -> Result<String> {
+    #[cfg(not(feature = "clipboard"))]
+    return Err(KeyboardSenderError::FeatureNotEnabled(format!(
+        "clipboard (selection of {:?})",
+        hwnd
+    )));
+
+    #[cfg(feature = "clipboard")]
+    match hwnd {
+        None => crate::core::copy_selection(crate::core::DEFAULT_COPY_TIMEOUT),
+        #[cfg(feature = "window_target")]
+        Some(hwnd) => crate::core::copy_from_window(hwnd),
+        #[cfg(not(feature = "window_target"))]
+        Some(_) => Err(KeyboardSenderError::FeatureNotEnabled(
+            "window_target".to_string(),
+        )),
+    }
+}
+
+/// 模板中的运行时输入，按出现顺序去重
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{runtime_inputs, RuntimeInput};
+///
+/// let inputs = runtime_inputs("#{prompt:Ticket id}: {selection} ({date})").unwrap();
+/// assert_eq!(
+///     inputs,
+///     vec![RuntimeInput::Prompt("Ticket id".to_string()), RuntimeInput::Selection(None)]
+/// );
+/// ```
+pub fn runtime_inputs(template: &str) -> Result<Vec<RuntimeInput>> {
+    let mut inputs = Vec::new();
+    render_with(template, UnknownPlaceholder::Keep, |placeholder| {
+        if let Some(input) = RuntimeInput::parse(placeholder)? {
+            if !inputs.contains(&input) {
+                inputs.push(input);
+            }
+        }
+        Ok(None)
+    })?;
+    Ok(inputs)
+}
+
+thread_local! {
+    static BOUND: RefCell<Option<HashMap<String, String>>> = const { RefCell::new(None) };
+}
+
+/// 已取值的运行时输入，丢弃时恢复之前的绑定
+#[derive(Debug)]
+pub struct RuntimeBindings {
+    previous: Option<HashMap<String, String>>,
+}
+
+impl RuntimeBindings {
+    /// 追加取出 `commands` 中尚未绑定的运行时输入，已绑定的输入沿用原值；丢弃时仍恢复最初的绑定
+    pub fn bind_more<S: AsRef<str>>(&mut self, commands: &[S]) -> Result<()> {
+        let values = resolve_unbound(commands)?;
+        BOUND.with(|bound| {
+            bound
+                .borrow_mut()
+                .get_or_insert_with(HashMap::new)
+                .extend(values)
+        });
+        Ok(())
+    }
+}
+
+impl Drop for RuntimeBindings {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        BOUND.with(|bound| *bound.borrow_mut() = previous);
+    }
+}
+
+/// 一次性取出命令中 `text:` 参数引用的全部运行时输入，并在当前线程上绑定到返回值的生命周期内
+///
+/// 只处理指定了 `template:true` 的命令。
+/// 绑定期间渲染模板时直接使用已取的值，同一输入不会重复询问或复制。
+/// 嵌套调用时外层已绑定的输入沿用外层的值。
+pub fn bind_runtime_inputs<S: AsRef<str>>(commands: &[S]) -> Result<RuntimeBindings> {
+    let resolved = resolve_unbound(commands)?;
+    let mut values = BOUND
+        .with(|bound| bound.borrow().clone())
+        .unwrap_or_default();
+    values.extend(resolved);
+    let previous = BOUND.with(|bound| bound.borrow_mut().replace(values));
+    Ok(RuntimeBindings { previous })
+}
+
+/// 取出命令中引用的、当前线程尚未绑定的运行时输入
+fn resolve_unbound<S: AsRef<str>>(commands: &[S]) -> Result<HashMap<String, String>> {
+    let mut inputs = Vec::new();
+    for command in commands {
+        let Ok(params) = super::grammar::parse_params(command.as_ref()) else {
+            continue;
+        };
+        if !is_template(&params)? {
+            continue;
+        }
+        if let Some(text) = params.get("text") {
+            for input in runtime_inputs(text)? {
+                if !inputs.contains(&input) && bound_value(&input).is_none() {
+                    inputs.push(input);
+                }
+            }
+        }
+    }
+    inputs.sort_by_key(RuntimeInput::order);
+
+    let mut values = HashMap::new();
+    for input in inputs {
+        let value = input.resolve()?;
+        values.insert(input.key(), value);
+    }
+    Ok(values)
+}
+
+/// 当前线程上已绑定的运行时输入值
+fn bound_value(input: &RuntimeInput) -> Option<String> {
+    BOUND.with(|bound| {
+        bound
+            .borrow()
+            .as_ref()
+            .and_then(|values| values.get(&input.key()).cloned())
+    })
+}
+
+/// 渲染模板并输入结果
+pub fn type_template(template: &str, vars: &HashMap<String, String>) -> Result<()> {
+    let text = render_template(template, vars)?;
+    crate::core::type_string(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), "Alice".to_string());
+        vars
+    }
+
+    #[test]
+    fn test_render_variables_and_escapes() {
+        assert_eq!(
+            render_template("Hi {name}, {{literal}}", &vars()).unwrap(),
+            "Hi Alice, {literal}"
+        );
+        assert!(render_template("Hi {missing}", &vars()).is_err());
+        assert!(render_template("Hi {name", &vars()).is_err());
+        assert_eq!(
+            render_template_with("Hi {missing}", &vars(), UnknownPlaceholder::Keep).unwrap(),
+            "Hi {missing}"
+        );
+    }
+
+    #[test]
+    fn test_runtime_inputs() {
+        assert_eq!(
+            RuntimeInput::parse("selection:0x1A2B").unwrap(),
+            Some(RuntimeInput::Selection(Some(0x1A2B)))
+        );
+        assert_eq!(
+            RuntimeInput::parse(" selection ").unwrap(),
+            Some(RuntimeInput::Selection(None))
+        );
+        assert_eq!(RuntimeInput::parse("prompt").unwrap(), None);
+        assert!(RuntimeInput::parse("selection:nope").is_err());
+        assert_eq!(
+            runtime_inputs("{clipboard} {{prompt:x}} {prompt:Id} {prompt:Id}").unwrap(),
+            vec![
+                RuntimeInput::Clipboard,
+                RuntimeInput::Prompt("Id".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prompt_bound_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static ASKED: AtomicUsize = AtomicUsize::new(0);
+
+        let prompter = |question: &str| {
+            ASKED.fetch_add(1, Ordering::SeqCst);
+            (question == "Id").then(|| "42".to_string())
+        };
+        // 只在当前线程内安装，断言失败时也会恢复
+        super::super::prompt::with_prompter(prompter, || {
+            {
+                let _inputs = bind_runtime_inputs(&[
+                    "text:{prompt:Id},template:true",
+                    "text:#{prompt:Id},template:true",
+                    "text:{prompt:Other}",
+                ])
+                .unwrap();
+                assert_eq!(
+                    render_template("{prompt:Id}/{prompt:Id}", &vars()).unwrap(),
+                    "42/42"
+                );
+            }
+            assert_eq!(ASKED.load(Ordering::SeqCst), 1);
+            assert!(matches!(
+                render_template("{prompt:Other}", &vars()),
+                Err(KeyboardSenderError::ExecutionDenied(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_bind_more() {
+        let mut inputs = bind_runtime_inputs::<&str>(&[]).unwrap();
+        assert!(matches!(
+            render_template("{prompt:Later}", &vars()),
+            Err(KeyboardSenderError::ExecutionDenied(_))
+        ));
+        BOUND.with(|bound| {
+            bound.borrow_mut().as_mut().unwrap().insert(
+                RuntimeInput::Prompt("Later".to_string()).key(),
+                "x".to_string(),
+            )
+        });
+        // 已绑定的输入不会再次询问
+        inputs
+            .bind_more(&["text:{prompt:Later},template:true"])
+            .unwrap();
+        assert_eq!(render_template("{prompt:Later}", &vars()).unwrap(), "x");
+        drop(inputs);
+        assert!(BOUND.with(|bound| bound.borrow().is_none()));
+    }
+
+    #[test]
+    fn test_random_range() {
+        for _ in 0..50 {
+            let n: i64 = render_template("{random:3..5}", &vars())
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!((3..=5).contains(&n));
+        }
+        assert!(render_template("{random:5..3}", &vars()).is_err());
+        let full = format!("{}..{}", i64::MIN, i64::MAX);
+        assert!(random_in_range(Some(&full)).is_ok());
+        let top = format!("{}..{}", i64::MAX - 1, i64::MAX);
+        assert!(random_in_range(Some(&top)).unwrap() >= i64::MAX - 1);
+    }
+
+    #[test]
+    fn test_command_text_is_opt_in() {
+        let params = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|&(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let literal = params(&[("text", "a { b {clipboard} {{x}}"), ("name", "Bob")]);
+        assert_eq!(
+            command_text(&literal).unwrap().as_deref(),
+            Some("a { b {clipboard} {{x}}")
+        );
+        let template = params(&[
+            ("text", "Hi {name} {{x}}"),
+            ("name", "Bob"),
+            ("template", "true"),
+        ]);
+        assert_eq!(
+            command_text(&template).unwrap().as_deref(),
+            Some("Hi Bob {x}")
+        );
+        assert_eq!(command_text(&params(&[("key", "a")])).unwrap(), None);
+        assert!(command_text(&params(&[("text", "x"), ("template", "maybe")])).is_err());
+    }
+
+    #[test]
+    fn test_local_time_format() {
+        let t = LocalTime {
+            year: 2024,
+            month: 3,
+            day: 7,
+            hour: 9,
+            minute: 5,
+            second: 2,
+            millisecond: 42,
+        };
+        assert_eq!(t.format("%Y-%m-%d %H:%M:%S.%3f"), "2024-03-07 09:05:02.042");
+        assert_eq!(t.format("%y%% %q"), "24% %q");
+    }
+}
@@ -0,0 +1,127 @@
+// src/random.rs
+//! 随机数来源（用于抖动、随机时长等非安全场景）
+//!
+//! 所有随机化的时序功能（按下抖动、[`DelaySpec`](crate::core::DelaySpec) 随机范围、
+//! 重试退避抖动、模板随机数）都从当前线程的随机数来源取值。测试或复现问题时用
+//! [`set_rng_seed`] 固定种子，或用 [`set_random_source`] 换成自定义来源，
+//! 即可得到完全相同的"随机"行为。
+use std::cell::RefCell;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 随机数来源
+pub trait RandomSource {
+    /// 生成下一个 u64
+    fn next_u64(&mut self) -> u64;
+}
+
+/// 默认的轻量伪随机数生成器（xorshift64*）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    /// 使用指定种子创建，相同种子产生相同序列
+    pub fn new(seed: u64) -> Self {
+        // 状态不能为 0，否则序列恒为 0
+        Self {
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        }
+    }
+
+    /// 使用时间和线程相关的种子创建
+    pub fn from_entropy() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15);
+        // 混入栈上变量地址，避免多个线程得到相同序列
+        let addr = &nanos as *const u64 as u64;
+        Self::new((nanos ^ addr.rotate_left(32)) | 1)
+    }
+}
+
+impl RandomSource for XorShift64 {
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+thread_local! {
+    static SOURCE: RefCell<Box<dyn RandomSource>> = RefCell::new(Box::new(XorShift64::from_entropy()));
+}
+
+/// 替换当前线程的随机数来源
+///
+/// 输入在调用线程上发送，因此只影响当前线程发起的操作。
+pub fn set_random_source<R: RandomSource + 'static>(source: R) {
+    SOURCE.with(|cell| *cell.borrow_mut() = Box::new(source));
+}
+
+/// 用固定种子重置当前线程的随机数来源（[`XorShift64`]）
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{set_rng_seed, DelaySpec};
+/// use std::time::Duration;
+///
+/// let spec = DelaySpec::range(Duration::from_millis(10), Duration::from_millis(50));
+/// set_rng_seed(42);
+/// let first: Vec<Duration> = (0..5).map(|_| spec.sample()).collect();
+/// set_rng_seed(42);
+/// let second: Vec<Duration> = (0..5).map(|_| spec.sample()).collect();
+/// assert_eq!(first, second);
+/// ```
+pub fn set_rng_seed(seed: u64) {
+    set_random_source(XorShift64::new(seed));
+}
+
+/// 从当前线程的随机数来源生成下一个 u64
+pub(crate) fn next_u64() -> u64 {
+    SOURCE.with(|cell| cell.borrow_mut().next_u64())
+}
+
+/// 生成 `[0, 1)` 区间内的 f64
+pub(crate) fn next_f64() -> f64 {
+    (next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Constant(u64);
+
+    impl RandomSource for Constant {
+        fn next_u64(&mut self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_seeded_sequence_repeats() {
+        set_rng_seed(7);
+        let first: Vec<u64> = (0..4).map(|_| next_u64()).collect();
+        set_rng_seed(7);
+        let second: Vec<u64> = (0..4).map(|_| next_u64()).collect();
+        assert_eq!(first, second);
+        assert_ne!(XorShift64::new(0).next_u64(), 0);
+    }
+
+    #[test]
+    fn test_custom_source() {
+        set_random_source(Constant(0));
+        assert_eq!(next_f64(), 0.0);
+        set_random_source(Constant(u64::MAX));
+        assert!(next_f64() < 1.0);
+    }
+}
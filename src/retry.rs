@@ -0,0 +1,239 @@
+// src/retry.rs
+//! 重试与退避策略，供等待类和发送类操作共用
+use crate::error::Result;
+use std::time::Duration;
+
+/// 退避方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    /// 每次等待固定时间
+    Fixed(Duration),
+    /// 第 n 次重试等待 n 倍时间
+    Linear(Duration),
+    /// 指数退避，不超过 `max`
+    Exponential {
+        initial: Duration,
+        factor: f64,
+        max: Duration,
+    },
+}
+
+/// 重试策略
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::{Backoff, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new()
+///     .attempts(5)
+///     .backoff(Backoff::Fixed(Duration::from_millis(100)))
+///     .jitter(0.2);
+/// assert_eq!(policy.attempts, 5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// 最多尝试次数（包含第一次），至少为 1
+    pub attempts: u32,
+    /// 两次尝试之间的退避方式
+    pub backoff: Backoff,
+    /// 抖动比例（0.0 ~ 1.0），实际等待时间在 `delay * (1 ± jitter)` 内随机
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Backoff::Exponential {
+                initial: Duration::from_millis(50),
+                factor: 2.0,
+                max: Duration::from_secs(1),
+            },
+            jitter: 0.1,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 使用默认值创建（3 次尝试，50ms 起的指数退避，10% 抖动）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只尝试一次，不重试
+    pub fn no_retry() -> Self {
+        Self {
+            attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// 在总时长内按固定间隔轮询
+    pub fn polling(timeout: Duration, interval: Duration) -> Self {
+        let interval = interval.max(Duration::from_millis(1));
+        let attempts = (timeout.as_millis() / interval.as_millis()).max(1) as u32 + 1;
+        Self {
+            attempts,
+            backoff: Backoff::Fixed(interval),
+            jitter: 0.0,
+        }
+    }
+
+    /// 设置尝试次数
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// 设置退避方式
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// 设置抖动比例
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 第 `retry` 次重试（从 1 开始）前的基础等待时间，不含抖动
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        let retry = retry.max(1);
+        match self.backoff {
+            Backoff::Fixed(d) => d,
+            Backoff::Linear(d) => d.saturating_mul(retry),
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => {
+                let secs = initial.as_secs_f64() * factor.max(1.0).powi(retry as i32 - 1);
+                Duration::try_from_secs_f64(secs).unwrap_or(max).min(max)
+            }
+        }
+    }
+
+    /// 第 `retry` 次重试前的实际等待时间（含抖动）
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let base = self.base_delay(retry);
+        if self.jitter <= 0.0 {
+            return base;
+        }
+        let scale = 1.0 + self.jitter * (crate::random::next_f64() * 2.0 - 1.0);
+        base.mul_f64(scale.max(0.0))
+    }
+
+    /// 执行操作，失败时按策略重试，返回最后一次的错误
+    pub fn run<T, F>(&self, mut op: F) -> Result<T>
+    where
+        F: FnMut(u32) -> Result<T>,
+    {
+        let attempts = self.attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match op(attempt) {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= attempts => return Err(e),
+                Err(_) => {
+                    sleep(self.delay_for(attempt));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// 轮询直到返回 `Some`，尝试次数用尽时返回 `None`
+    pub fn poll<T, F>(&self, mut check: F) -> Option<T>
+    where
+        F: FnMut() -> Option<T>,
+    {
+        let attempts = self.attempts.max(1);
+        for attempt in 1..=attempts {
+            if let Some(value) = check() {
+                return Some(value);
+            }
+            if attempt < attempts {
+                sleep(self.delay_for(attempt));
+            }
+        }
+        None
+    }
+
+    /// 所有重试的最长总等待时间（不含抖动和操作本身耗时）
+    pub fn max_total_delay(&self) -> Duration {
+        (1..self.attempts.max(1)).map(|r| self.base_delay(r)).sum()
+    }
+}
+
+/// 按 crate 时钟等待（未启用 `global` 特性时直接休眠）
+fn sleep(duration: Duration) {
+    #[cfg(feature = "global")]
+    crate::core::clock().sleep(duration);
+    #[cfg(not(feature = "global"))]
+    std::thread::sleep(duration);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KeyboardSenderError;
+
+    #[test]
+    fn test_backoff_delays() {
+        let policy = RetryPolicy::new()
+            .backoff(Backoff::Exponential {
+                initial: Duration::from_millis(10),
+                factor: 2.0,
+                max: Duration::from_millis(50),
+            })
+            .jitter(0.0);
+        assert_eq!(policy.delay_for(1), Duration::from_millis(10));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(20));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(40));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(50));
+
+        let linear = RetryPolicy::new().backoff(Backoff::Linear(Duration::from_millis(5)));
+        assert_eq!(linear.base_delay(3), Duration::from_millis(15));
+    }
+
+    #[test]
+    fn test_run_retries_until_success() {
+        let policy = RetryPolicy::new()
+            .attempts(4)
+            .backoff(Backoff::Fixed(Duration::from_secs(1)))
+            .jitter(0.0);
+        let mock = std::sync::Arc::new(crate::core::MockClock::new());
+        let mut calls = 0;
+        let result = crate::core::with_clock(mock.clone(), || {
+            policy.run(|attempt| {
+                calls += 1;
+                if attempt < 3 {
+                    Err(KeyboardSenderError::WindowsError)
+                } else {
+                    Ok(attempt)
+                }
+            })
+        });
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls, 3);
+        // 两次重试间隔走虚拟时钟
+        assert_eq!(mock.elapsed(), Duration::from_secs(2));
+
+        let exhausted: Result<()> =
+            RetryPolicy::no_retry().run(|_| Err(KeyboardSenderError::WindowsError));
+        assert!(exhausted.is_err());
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let policy = RetryPolicy::new()
+            .backoff(Backoff::Fixed(Duration::from_millis(100)))
+            .jitter(0.5);
+        for _ in 0..100 {
+            let d = policy.delay_for(1);
+            assert!(d >= Duration::from_millis(50) && d <= Duration::from_millis(150));
+        }
+    }
+}
@@ -1,8 +1,8 @@
 // src/smart/implementation.rs
+use crate::core::{DelaySpec, InputAction};
 use crate::error::Result;
 use keyboard_codes::{parse_keyboard_input, parse_shortcut_with_aliases, KeyboardInput};
 use std::time::Duration;
-use std::str::FromStr;
 
 /// 智能输入 - 自动检测输入类型并分派到合适的函数
 ///
@@ -28,31 +28,47 @@ pub fn type_auto(input: &str) -> Result<()> {
     if input.is_empty() {
         return Ok(());
     }
+    send_actions(&[auto_action(input)])
+}
+
+/// 发送动作序列，包含小键盘数字时临时开启 NumLock
+fn send_actions(actions: &[InputAction]) -> Result<()> {
+    let is_numpad = |key: &crate::types::Key| {
+        ('0'..='9').any(|c| crate::core::numpad_digit_key(c) == Some(*key))
+    };
+    let numpad = actions
+        .iter()
+        .any(|action| matches!(action, InputAction::Click(key) if is_numpad(key)));
+    if numpad {
+        crate::core::with_num_lock(|| crate::core::send_sequence(actions))
+    } else {
+        crate::core::send_sequence(actions)
+    }
+}
 
+/// 检测输入类型并转换为对应的动作
+fn auto_action(input: &str) -> InputAction {
     // 1. 检测并处理快捷键格式 (包含+号)
     if input.contains('+') {
-        return type_shortcut_auto(input);
+        return shortcut_action(input);
     }
 
     // 2. 检测单字符（字母、数字、空格等）
     if let Some(c) = detect_single_char(input) {
-        return type_char_auto(c);
+        return char_action(c);
     }
 
     // 3. 尝试解析为键盘输入
     if let Ok(keyboard_input) = parse_keyboard_input(input) {
         return match keyboard_input {
-            KeyboardInput::Key(key) => crate::core::key_click(key, None),
-            KeyboardInput::Modifier(modifier) => {
-                // 修饰符转为对应的键进行点击
-                let key = crate::core::modifier_to_key(modifier);
-                crate::core::key_click(key, None)
-            }
+            KeyboardInput::Key(key) => InputAction::Click(key),
+            // 修饰符单独点击
+            KeyboardInput::Modifier(modifier) => InputAction::ModifierClick(modifier),
         };
     }
 
     // 4. 默认作为文本处理
-    crate::core::type_string(input)
+    InputAction::Text(input.to_string())
 }
 
 /// 批量智能输入多个指令
@@ -68,22 +84,25 @@ pub fn type_auto(input: &str) -> Result<()> {
 /// # }
 /// ```
 pub fn type_multiple(inputs: &[&str]) -> Result<()> {
+    let mut actions = Vec::with_capacity(inputs.len() * 2);
     for (i, input) in inputs.iter().enumerate() {
-        type_auto(input)?;
+        if !input.is_empty() {
+            actions.push(auto_action(input));
+        }
 
         // 在指令之间添加小延迟（除了最后一个）
         if i < inputs.len() - 1 {
-            std::thread::sleep(Duration::from_millis(20));
+            actions.push(InputAction::Sleep(Duration::from_millis(20)));
         }
     }
-    Ok(())
+    send_actions(&actions)
 }
 
 /// 智能快捷键输入
-fn type_shortcut_auto(shortcut: &str) -> Result<()> {
+fn shortcut_action(shortcut: &str) -> InputAction {
     // 首先尝试完整的快捷键解析
     if let Ok(parsed) = parse_shortcut_with_aliases(shortcut) {
-        return crate::core::press_combination(&parsed.modifiers, parsed.key, None);
+        return InputAction::Combo(parsed.modifiers, parsed.key);
     }
 
     // 回退到简单的+分割解析
@@ -102,40 +121,33 @@ fn type_shortcut_auto(shortcut: &str) -> Result<()> {
         }
 
         if let Some(key) = main_key {
-            return crate::core::press_combination(&modifiers, key, None);
+            return InputAction::Combo(modifiers, key);
         }
     }
 
     // 如果都无法解析，作为文本处理（虽然不太可能）
-    crate::core::type_string(shortcut)
+    InputAction::Text(shortcut.to_string())
 }
 
 /// 智能字符输入
-fn type_char_auto(c: char) -> Result<()> {
+///
+/// 按前台窗口的键盘布局查找产生该字符的按键（见 [`char_to_key_combo`](crate::core::char_to_key_combo)），
+/// 需要修饰键时（如大写字母、`!`）发送组合键；布局中没有对应按键的字符直接发送。
+fn char_action(c: char) -> InputAction {
     match c {
-        // 字母 - 转为大写键名
-        'a'..='z' | 'A'..='Z' => {
-            let key_name = c.to_uppercase().to_string();
-            if let Ok(key) = keyboard_codes::Key::from_str(&key_name) {
-                crate::core::key_click(key, None)
-            } else {
-                crate::core::send_char(c)
-            }
-        }
-        // 数字 - 转为D0-D9格式
-        '0'..='9' => {
-            let key_name = format!("D{}", c);
-            if let Ok(key) = keyboard_codes::Key::from_str(&key_name) {
-                crate::core::key_click(key, None)
-            } else {
-                crate::core::send_char(c)
-            }
+        // 数字 - 开启小键盘数字时转为 Num0-Num9
+        '0'..='9' if crate::core::text_options::effective_text_options().numpad_digits => {
+            crate::core::numpad_digit_key(c).map_or(InputAction::Char(c), InputAction::Click)
         }
         // 特殊字符直接发送
-        ' ' => crate::core::key_click(crate::types::Key::Space, None),
-        '\n' => crate::core::key_click(crate::types::Key::Enter, None),
-        '\t' => crate::core::key_click(crate::types::Key::Tab, None),
-        _ => crate::core::send_char(c),
+        ' ' => InputAction::Click(crate::types::Key::Space),
+        '\n' => InputAction::Click(crate::types::Key::Enter),
+        '\t' => InputAction::Click(crate::types::Key::Tab),
+        _ => match crate::core::char_to_key_combo(c, crate::core::KeyboardLayout::foreground()) {
+            Some((modifiers, key)) if modifiers.is_empty() => InputAction::Click(key),
+            Some((modifiers, key)) => InputAction::Combo(modifiers, key),
+            None => InputAction::Char(c),
+        },
     }
 }
 
@@ -200,23 +212,31 @@ fn is_special_key_name(input: &str) -> bool {
 
 /// 带延迟控制的智能输入
 ///
+/// `delay` 可以是固定间隔、[`DelaySpec`] 随机范围（每个字符重新取值）或按 WPM 指定的
+/// [`TypingOptions`](crate::core::TypingOptions)。
+///
 /// # 示例
 /// ```
 /// # #[cfg(feature = "smart")]
 /// # {
 /// use sophia_keyboard_sender::smart::type_with_delay;
+/// use sophia_keyboard_sender::TypingOptions;
 /// use std::time::Duration;
 ///
 /// // 每个字符间隔50ms
 /// type_with_delay("Hello", Duration::from_millis(50)).unwrap();
+/// // 每分钟 80 个单词
+/// type_with_delay("Hello", TypingOptions { wpm: 80 }).unwrap();
 /// # }
 /// ```
-pub fn type_with_delay(text: &str, delay: Duration) -> Result<()> {
+pub fn type_with_delay(text: &str, delay: impl Into<DelaySpec>) -> Result<()> {
+    let delay = delay.into();
+    let mut actions = Vec::with_capacity(text.len() * 2);
     for c in text.chars() {
-        type_char_auto(c)?;
-        std::thread::sleep(delay);
+        actions.push(char_action(c));
+        actions.push(InputAction::Sleep(delay.sample()));
     }
-    Ok(())
+    send_actions(&actions)
 }
 
 #[cfg(test)]
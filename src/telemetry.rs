@@ -0,0 +1,290 @@
+// src/telemetry.rs
+//! 遥测钩子与脱敏策略
+//!
+//! 安装事件接收器后，核心发送函数会上报每个逻辑操作。输入的文本可能包含敏感信息，
+//! 上报前统一按 [`RedactionPolicy`] 处理；策略可全局配置，也可通过
+//! [`with_redaction`] 为某次调用单独指定。
+//!
+//! 通过 [`set_timed_event_sink`] 安装的接收器还会收到请求发送时的 QPC 时间戳，
+//! 可与钩子中观察到事件的时间对比以测量注入延迟。
+use crate::core::qpc::{now_qpc, QpcTimestamp};
+use crate::types::{Key, Modifier, WindowHandle};
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// 文本脱敏策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RedactionPolicy {
+    /// 上报完整文本
+    Full,
+    /// 仅上报字符数（默认）
+    #[default]
+    LengthOnly,
+    /// 上报字符数和文本的带密钥哈希，用于在同一进程的事件之间比对文本是否相同
+    ///
+    /// 密钥在进程内首次使用时随机生成，不同进程的哈希不可比对；没有密钥时无法通过枚举
+    /// 常见口令反查哈希。
+    Hash,
+    /// 不上报任何文本信息
+    Nothing,
+}
+
+impl RedactionPolicy {
+    fn to_u8(self) -> u8 {
+        match self {
+            RedactionPolicy::Full => 0,
+            RedactionPolicy::LengthOnly => 1,
+            RedactionPolicy::Hash => 2,
+            RedactionPolicy::Nothing => 3,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => RedactionPolicy::Full,
+            2 => RedactionPolicy::Hash,
+            3 => RedactionPolicy::Nothing,
+            _ => RedactionPolicy::LengthOnly,
+        }
+    }
+
+    /// 按策略脱敏文本
+    pub fn redact(self, text: &str) -> RedactedText {
+        match self {
+            RedactionPolicy::Full => RedactedText::Full(text.to_string()),
+            RedactionPolicy::LengthOnly => RedactedText::Length(text.chars().count()),
+            RedactionPolicy::Hash => RedactedText::Hash {
+                len: text.chars().count(),
+                hash: keyed_hash(text),
+            },
+            RedactionPolicy::Nothing => RedactedText::Hidden,
+        }
+    }
+}
+
+/// 脱敏后的文本
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RedactedText {
+    Full(String),
+    Length(usize),
+    Hash { len: usize, hash: u64 },
+    Hidden,
+}
+
+/// 遥测事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelemetryEvent {
+    /// 输入文本（字符或字符串）
+    Text {
+        target: Option<WindowHandle>,
+        text: RedactedText,
+    },
+    /// 按键点击
+    KeyClick {
+        target: Option<WindowHandle>,
+        key: Key,
+    },
+    /// 组合键
+    Combination {
+        target: Option<WindowHandle>,
+        modifiers: Vec<Modifier>,
+        key: Key,
+    },
+    /// 同时按下的按键数超过上限，已分批发送
+    KeyLimitExceeded { requested: usize, limit: usize },
+    /// 键盘状态检查发现卡住的按键（虚拟键码），`released` 表示已发送释放事件
+    StuckKeysDetected { vks: Vec<u16>, released: bool },
+    /// 保持的修饰键超时未释放，已被看门狗自动释放
+    ModifierAutoReleased { modifier: Modifier },
+    /// 目标窗口句柄失效，已按选择器重新找到窗口
+    TargetReacquired {
+        previous: WindowHandle,
+        current: WindowHandle,
+    },
+}
+
+/// 事件接收器
+pub type EventSink = dyn Fn(&TelemetryEvent) + Send + Sync;
+
+/// 带时间戳的事件接收器
+pub type TimedEventSink = dyn Fn(QpcTimestamp, &TelemetryEvent) + Send + Sync;
+
+static SINK_INSTALLED: AtomicBool = AtomicBool::new(false);
+static SINK: RwLock<Option<Arc<TimedEventSink>>> = RwLock::new(None);
+static GLOBAL_POLICY: AtomicU8 = AtomicU8::new(1);
+
+thread_local! {
+    static POLICY_OVERRIDE: Cell<Option<RedactionPolicy>> = const { Cell::new(None) };
+}
+
+/// 安装事件接收器（替换之前的接收器）
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::telemetry::*;
+///
+/// set_redaction_policy(RedactionPolicy::Hash);
+/// set_event_sink(|event| println!("{:?}", event));
+/// ```
+pub fn set_event_sink<F>(sink: F)
+where
+    F: Fn(&TelemetryEvent) + Send + Sync + 'static,
+{
+    set_timed_event_sink(move |_, event| sink(event));
+}
+
+/// 安装带时间戳的事件接收器（替换之前的接收器）
+///
+/// 时间戳在发送函数被调用、开始注入之前记录。
+///
+/// # 示例
+/// ```
+/// use sophia_keyboard_sender::telemetry::set_timed_event_sink;
+///
+/// set_timed_event_sink(|at, event| println!("{} {:?}", at.ticks(), event));
+/// ```
+pub fn set_timed_event_sink<F>(sink: F)
+where
+    F: Fn(QpcTimestamp, &TelemetryEvent) + Send + Sync + 'static,
+{
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(sink));
+    SINK_INSTALLED.store(true, Ordering::Release);
+}
+
+/// 以当前接收器为参数构造新接收器并安装，返回新旧接收器（用于串联接收器）
+#[cfg_attr(not(feature = "unstable"), allow(dead_code))]
+pub(crate) fn chain_timed_event_sink(
+    make: impl FnOnce(Option<Arc<TimedEventSink>>) -> Arc<TimedEventSink>,
+) -> (Arc<TimedEventSink>, Option<Arc<TimedEventSink>>) {
+    let mut slot = SINK.write().unwrap_or_else(|e| e.into_inner());
+    let previous = slot.clone();
+    let sink = make(previous.clone());
+    *slot = Some(Arc::clone(&sink));
+    SINK_INSTALLED.store(true, Ordering::Release);
+    (sink, previous)
+}
+
+/// 当前接收器仍是 `installed` 时换回 `previous`；已被替换时保持不变
+#[cfg_attr(not(feature = "unstable"), allow(dead_code))]
+pub(crate) fn restore_timed_event_sink(
+    installed: &Arc<TimedEventSink>,
+    previous: Option<Arc<TimedEventSink>>,
+) {
+    let mut slot = SINK.write().unwrap_or_else(|e| e.into_inner());
+    if slot
+        .as_ref()
+        .is_some_and(|current| Arc::ptr_eq(current, installed))
+    {
+        SINK_INSTALLED.store(previous.is_some(), Ordering::Release);
+        *slot = previous;
+    }
+}
+
+/// 移除事件接收器
+pub fn clear_event_sink() {
+    SINK_INSTALLED.store(false, Ordering::Release);
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 设置全局脱敏策略
+pub fn set_redaction_policy(policy: RedactionPolicy) {
+    GLOBAL_POLICY.store(policy.to_u8(), Ordering::Relaxed);
+}
+
+/// 当前生效的脱敏策略（调用级覆盖优先于全局策略）
+pub fn redaction_policy() -> RedactionPolicy {
+    POLICY_OVERRIDE
+        .with(|o| o.get())
+        .unwrap_or_else(|| RedactionPolicy::from_u8(GLOBAL_POLICY.load(Ordering::Relaxed)))
+}
+
+/// 在当前线程内以指定脱敏策略执行操作
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::telemetry::{with_redaction, RedactionPolicy};
+/// use sophia_keyboard_sender::type_string;
+///
+/// with_redaction(RedactionPolicy::Nothing, || type_string("p@ssw0rd")).unwrap();
+/// ```
+pub fn with_redaction<T, F: FnOnce() -> T>(policy: RedactionPolicy, f: F) -> T {
+    struct Restore(Option<RedactionPolicy>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            POLICY_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(POLICY_OVERRIDE.with(|o| o.replace(Some(policy))));
+    f()
+}
+
+/// 上报事件；未安装接收器时不构造事件
+pub(crate) fn emit<F: FnOnce() -> TelemetryEvent>(make_event: F) {
+    if !SINK_INSTALLED.load(Ordering::Acquire) {
+        return;
+    }
+    let at = now_qpc();
+    let sink = SINK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(sink) = sink {
+        sink(at, &make_event());
+    }
+}
+
+/// 上报文本输入事件
+pub(crate) fn emit_text(target: Option<WindowHandle>, text: &str) {
+    emit(|| TelemetryEvent::Text {
+        target,
+        text: redaction_policy().redact(text),
+    });
+}
+
+/// 以进程内随机密钥计算的 SipHash
+fn keyed_hash(text: &str) -> u64 {
+    static KEY: OnceLock<RandomState> = OnceLock::new();
+    KEY.get_or_init(RandomState::new).hash_one(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            RedactionPolicy::Full.redact("abc"),
+            RedactedText::Full("abc".to_string())
+        );
+        assert_eq!(
+            RedactionPolicy::LengthOnly.redact("密码"),
+            RedactedText::Length(2)
+        );
+        assert_eq!(RedactionPolicy::Nothing.redact("abc"), RedactedText::Hidden);
+
+        let a = RedactionPolicy::Hash.redact("secret");
+        let b = RedactionPolicy::Hash.redact("secret");
+        assert_eq!(a, b);
+        assert_ne!(a, RedactionPolicy::Hash.redact("Secret"));
+        // 密钥在进程内共享，其他线程得到相同的哈希
+        let other = std::thread::spawn(|| RedactionPolicy::Hash.redact("secret"))
+            .join()
+            .unwrap();
+        assert_eq!(a, other);
+    }
+
+    #[test]
+    fn test_with_redaction_overrides_and_restores() {
+        let global = redaction_policy();
+        with_redaction(RedactionPolicy::Nothing, || {
+            assert_eq!(redaction_policy(), RedactionPolicy::Nothing);
+            with_redaction(RedactionPolicy::Full, || {
+                assert_eq!(redaction_policy(), RedactionPolicy::Full);
+            });
+            assert_eq!(redaction_policy(), RedactionPolicy::Nothing);
+        });
+        assert_eq!(redaction_policy(), global);
+    }
+}
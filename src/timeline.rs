@@ -0,0 +1,685 @@
+// src/timeline.rs
+//! 键盘事件时间线导出
+//!
+//! 把注入的事件（来自遥测）和观察到的事件（来自钩子、消息监视等）记录到同一条时间线上，
+//! 时间戳为相对记录开始的单调时间（QPC），导出为 CSV 或 JSONL 供外部分析工具可视化，
+//! 排查"发出的事件何时、是否被目标收到"之类的时序问题。导出的文件可以再用
+//! [`Timeline::from_csv`] / [`Timeline::from_jsonl`] 读回。
+//!
+//! CSV 列：`t_us,origin,kind,target,detail`；JSONL 每行一个对象，字段相同。
+use crate::core::qpc::{now_qpc, QpcTimestamp};
+use crate::error::{KeyboardSenderError, Result};
+use crate::telemetry::{RedactedText, TelemetryEvent, TimedEventSink};
+use crate::types::WindowHandle;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 事件来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventOrigin {
+    /// 本库注入的事件
+    Injected,
+    /// 钩子或目标窗口中观察到的事件
+    Observed,
+}
+
+impl EventOrigin {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventOrigin::Injected => "injected",
+            EventOrigin::Observed => "observed",
+        }
+    }
+}
+
+impl fmt::Display for EventOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventOrigin {
+    type Err = KeyboardSenderError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "injected" => Ok(EventOrigin::Injected),
+            "observed" => Ok(EventOrigin::Observed),
+            _ => Err(KeyboardSenderError::ParseError(format!(
+                "unknown event origin: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// 时间线上的一个事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEvent {
+    /// 相对记录开始的时间（微秒精度）
+    pub at: Duration,
+    pub origin: EventOrigin,
+    /// 事件类型，如 `key_click`、`text`、`WM_KEYDOWN`
+    pub kind: String,
+    /// 目标窗口，全局发送时为 `None`
+    pub target: Option<WindowHandle>,
+    /// 事件内容（键名、脱敏后的文本等）
+    pub detail: String,
+}
+
+impl TimelineEvent {
+    pub fn new(at: Duration, origin: EventOrigin, kind: &str, detail: &str) -> Self {
+        Self {
+            at,
+            origin,
+            kind: kind.to_string(),
+            target: None,
+            detail: detail.to_string(),
+        }
+    }
+
+    /// 设置目标窗口
+    pub fn with_target(mut self, target: Option<WindowHandle>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// 由遥测事件生成注入事件
+    pub fn from_telemetry(at: Duration, event: &TelemetryEvent) -> Self {
+        let (kind, target, detail) = match event {
+            TelemetryEvent::Text { target, text } => {
+                let detail = match text {
+                    RedactedText::Full(text) => text.clone(),
+                    RedactedText::Length(len) => format!("len={}", len),
+                    RedactedText::Hash { len, hash } => format!("len={} hash={:016x}", len, hash),
+                    RedactedText::Hidden => String::new(),
+                };
+                ("text", *target, detail)
+            }
+            TelemetryEvent::KeyClick { target, key } => {
+                ("key_click", *target, format!("{:?}", key))
+            }
+            TelemetryEvent::Combination {
+                target,
+                modifiers,
+                key,
+            } => {
+                let mut parts: Vec<String> = modifiers.iter().map(|m| format!("{:?}", m)).collect();
+                parts.push(format!("{:?}", key));
+                ("combination", *target, parts.join("+"))
+            }
+            TelemetryEvent::KeyLimitExceeded { requested, limit } => (
+                "key_limit_exceeded",
+                None,
+                format!("requested={} limit={}", requested, limit),
+            ),
+            TelemetryEvent::StuckKeysDetected { vks, released } => {
+                let vks: Vec<String> = vks.iter().map(|vk| format!("{:#04x}", vk)).collect();
+                (
+                    "stuck_keys",
+                    None,
+                    format!("vks={} released={}", vks.join(";"), released),
+                )
+            }
+            TelemetryEvent::ModifierAutoReleased { modifier } => {
+                ("modifier_auto_released", None, format!("{:?}", modifier))
+            }
+            TelemetryEvent::TargetReacquired { previous, current } => (
+                "target_reacquired",
+                Some(*current),
+                format!("previous={:#x}", previous),
+            ),
+        };
+        Self {
+            at,
+            origin: EventOrigin::Injected,
+            kind: kind.to_string(),
+            target,
+            detail,
+        }
+    }
+}
+
+/// 事件时间线
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeline {
+    events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加事件（保持按时间排序，同一时间按添加顺序）
+    pub fn push(&mut self, event: TimelineEvent) {
+        let index = self.events.partition_point(|e| e.at <= event.at);
+        self.events.insert(index, event);
+    }
+
+    /// 按时间排序的事件
+    pub fn events(&self) -> &[TimelineEvent] {
+        &self.events
+    }
+
+    /// 指定来源的事件
+    pub fn by_origin(&self, origin: EventOrigin) -> impl Iterator<Item = &TimelineEvent> {
+        self.events.iter().filter(move |e| e.origin == origin)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// 写出 CSV（含表头）
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "t_us,origin,kind,target,detail")?;
+        for e in &self.events {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                e.at.as_micros(),
+                e.origin,
+                csv_field(&e.kind),
+                e.target.map(|t| t.to_string()).unwrap_or_default(),
+                csv_field(&e.detail)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 写出 JSONL（每行一个事件）
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for e in &self.events {
+            writeln!(
+                writer,
+                "{{\"t_us\":{},\"origin\":\"{}\",\"kind\":{},\"target\":{},\"detail\":{}}}",
+                e.at.as_micros(),
+                e.origin,
+                json_string(&e.kind),
+                e.target
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "null".to_string()),
+                json_string(&e.detail)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 导出为 CSV 字符串
+    pub fn to_csv(&self) -> String {
+        let mut out = Vec::new();
+        let _ = self.write_csv(&mut out);
+        String::from_utf8(out).unwrap_or_default()
+    }
+
+    /// 导出为 JSONL 字符串
+    pub fn to_jsonl(&self) -> String {
+        let mut out = Vec::new();
+        let _ = self.write_jsonl(&mut out);
+        String::from_utf8(out).unwrap_or_default()
+    }
+
+    /// 读取 [`Timeline::write_csv`] 导出的 CSV
+    pub fn from_csv(input: &str) -> Result<Self> {
+        let mut timeline = Timeline::new();
+        let mut records = csv_records(input).into_iter().enumerate();
+        match records.next() {
+            Some((_, header)) if header.first().map(String::as_str) == Some("t_us") => {}
+            _ => {
+                return Err(KeyboardSenderError::ParseError(
+                    "missing CSV header".to_string(),
+                ))
+            }
+        }
+        for (index, fields) in records {
+            let record = index;
+            let [t_us, origin, kind, target, detail] =
+                <[String; 5]>::try_from(fields).map_err(|f| {
+                    KeyboardSenderError::ParseError(format!(
+                        "record {}: expected 5 fields, got {}",
+                        record,
+                        f.len()
+                    ))
+                })?;
+            let target = if target.is_empty() {
+                None
+            } else {
+                Some(parse_number(&target, record)?)
+            };
+            timeline.push(
+                TimelineEvent::new(
+                    Duration::from_micros(parse_number(&t_us, record)?),
+                    origin.parse()?,
+                    &kind,
+                    &detail,
+                )
+                .with_target(target),
+            );
+        }
+        Ok(timeline)
+    }
+
+    /// 读取 [`Timeline::write_jsonl`] 导出的 JSONL，空行被忽略
+    pub fn from_jsonl(input: &str) -> Result<Self> {
+        let mut timeline = Timeline::new();
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let record = index + 1;
+            let fields = parse_flat_object(line)
+                .map_err(|e| KeyboardSenderError::ParseError(format!("line {}: {}", record, e)))?;
+            let get = |name: &str| {
+                fields
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value)
+            };
+            let missing = |name: &str| {
+                KeyboardSenderError::ParseError(format!("line {}: missing {}", record, name))
+            };
+            let text = |name: &str| match get(name) {
+                Some(JsonValue::Str(s)) => Ok(s.clone()),
+                _ => Err(missing(name)),
+            };
+            let at = match get("t_us") {
+                Some(JsonValue::Number(n)) => parse_number(n, record)?,
+                _ => return Err(missing("t_us")),
+            };
+            let target = match get("target") {
+                Some(JsonValue::Number(n)) => Some(parse_number(n, record)?),
+                _ => None,
+            };
+            timeline.push(
+                TimelineEvent::new(
+                    Duration::from_micros(at),
+                    text("origin")?.parse()?,
+                    &text("kind")?,
+                    &text("detail")?,
+                )
+                .with_target(target),
+            );
+        }
+        Ok(timeline)
+    }
+}
+
+/// 时间线记录器
+///
+/// 创建时安装带时间戳的遥测接收器，注入事件自动记入时间线，并继续转发给之前安装的接收器；
+/// [`finish`](TimelineRecorder::finish) 时恢复之前的接收器。
+/// 观察到的事件通过 [`TimelineRecorder::observe`] 记入，记录器可克隆后传入钩子回调。
+///
+/// # 示例
+/// ```no_run
+/// use sophia_keyboard_sender::timeline::TimelineRecorder;
+/// use sophia_keyboard_sender::{key_click, now_qpc, Key};
+///
+/// let recorder = TimelineRecorder::start();
+/// key_click(Key::A, None).unwrap();
+/// recorder.observe(now_qpc(), "WM_KEYDOWN", "A", None);
+/// let timeline = recorder.finish();
+/// std::fs::write("timeline.csv", timeline.to_csv()).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimelineRecorder {
+    start: QpcTimestamp,
+    timeline: Arc<Mutex<Timeline>>,
+    sinks: Arc<SinkChain>,
+}
+
+/// 记录器安装的接收器和被它串联的之前的接收器
+struct SinkChain {
+    installed: Arc<TimedEventSink>,
+    previous: Option<Arc<TimedEventSink>>,
+}
+
+impl fmt::Debug for SinkChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SinkChain")
+            .field("previous", &self.previous.is_some())
+            .finish()
+    }
+}
+
+impl TimelineRecorder {
+    /// 开始记录
+    pub fn start() -> Self {
+        let start = now_qpc();
+        let timeline = Arc::new(Mutex::new(Timeline::new()));
+        let events = Arc::clone(&timeline);
+        let (installed, previous) = crate::telemetry::chain_timed_event_sink(|previous| {
+            Arc::new(move |at, event: &TelemetryEvent| {
+                events
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(TimelineEvent::from_telemetry(offset(start, at), event));
+                if let Some(previous) = &previous {
+                    previous(at, event);
+                }
+            })
+        });
+        Self {
+            start,
+            timeline,
+            sinks: Arc::new(SinkChain {
+                installed,
+                previous,
+            }),
+        }
+    }
+
+    /// 记录开始时的时间戳
+    pub fn started_at(&self) -> QpcTimestamp {
+        self.start
+    }
+
+    /// 记录一个观察到的事件，`at` 为观察到事件时的 QPC 时间戳
+    pub fn observe(
+        &self,
+        at: QpcTimestamp,
+        kind: &str,
+        detail: &str,
+        target: Option<WindowHandle>,
+    ) {
+        self.record(
+            TimelineEvent::new(self.offset(at), EventOrigin::Observed, kind, detail)
+                .with_target(target),
+        );
+    }
+
+    /// 记录任意事件
+    pub fn record(&self, event: TimelineEvent) {
+        self.timeline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event);
+    }
+
+    /// 当前已记录内容的副本
+    pub fn snapshot(&self) -> Timeline {
+        self.timeline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 结束记录，恢复开始记录前的遥测接收器并返回时间线
+    ///
+    /// 之后又安装了其他接收器（包括另一个记录器）时保留那个接收器。
+    pub fn finish(self) -> Timeline {
+        crate::telemetry::restore_timed_event_sink(
+            &self.sinks.installed,
+            self.sinks.previous.clone(),
+        );
+        self.snapshot()
+    }
+
+    fn offset(&self, at: QpcTimestamp) -> Duration {
+        offset(self.start, at)
+    }
+}
+
+/// `at` 相对 `start` 的偏移，截断到微秒，导出后读回时保持一致
+fn offset(start: QpcTimestamp, at: QpcTimestamp) -> Duration {
+    Duration::from_micros(at.duration_since(start).as_micros() as u64)
+}
+
+fn parse_number<T: FromStr>(value: &str, record: usize) -> Result<T> {
+    value.trim().parse().map_err(|_| {
+        KeyboardSenderError::ParseError(format!("record {}: invalid number '{}'", record, value))
+    })
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 拆分 CSV 记录（支持带引号、含逗号和换行的字段），跳过空行
+fn csv_records(input: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                if fields.len() > 1 || !fields[0].is_empty() {
+                    records.push(std::mem::take(&mut fields));
+                } else {
+                    fields.clear();
+                }
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(fields);
+    }
+    records
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+enum JsonValue {
+    Str(String),
+    Number(String),
+    Null,
+}
+
+/// 解析只含字符串、数字和 null 字段的单层 JSON 对象
+fn parse_flat_object(line: &str) -> std::result::Result<Vec<(String, JsonValue)>, String> {
+    let mut chars = line.chars().peekable();
+    let mut fields = Vec::new();
+    let skip_ws = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    };
+    if chars.next() != Some('{') {
+        return Err("expected '{'".to_string());
+    }
+    skip_ws(&mut chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+        return Ok(fields);
+    }
+    loop {
+        skip_ws(&mut chars);
+        let key = parse_json_string(&mut chars)?;
+        skip_ws(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(format!("expected ':' after \"{}\"", key));
+        }
+        skip_ws(&mut chars);
+        let value = match chars.peek() {
+            Some('"') => JsonValue::Str(parse_json_string(&mut chars)?),
+            Some('n') => {
+                let word: String = chars.by_ref().take(4).collect();
+                if word != "null" {
+                    return Err(format!("invalid value for \"{}\"", key));
+                }
+                JsonValue::Null
+            }
+            Some(c) if c.is_ascii_digit() || *c == '-' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '-' {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                JsonValue::Number(number)
+            }
+            _ => return Err(format!("unsupported value for \"{}\"", key)),
+        };
+        fields.push((key, value));
+        skip_ws(&mut chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => return Ok(fields),
+            _ => return Err("expected ',' or '}'".to_string()),
+        }
+    }
+}
+
+fn parse_json_string(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> std::result::Result<String, String> {
+    if chars.next() != Some('"') {
+        return Err("expected string".to_string());
+    }
+    let mut out = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(out),
+            Some('\\') => match chars.next() {
+                Some('"') => out.push('"'),
+                Some('\\') => out.push('\\'),
+                Some('/') => out.push('/'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let c = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| format!("invalid escape \\u{}", hex))?;
+                    out.push(c);
+                }
+                _ => return Err("invalid escape".to_string()),
+            },
+            Some(c) => out.push(c),
+            None => return Err("unterminated string".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Key;
+
+    fn sample() -> Timeline {
+        let mut timeline = Timeline::new();
+        timeline.push(TimelineEvent::new(
+            Duration::from_micros(1500),
+            EventOrigin::Observed,
+            "WM_CHAR",
+            "a, \"quoted\"\nline",
+        ));
+        timeline.push(TimelineEvent::from_telemetry(
+            Duration::from_micros(1000),
+            &TelemetryEvent::KeyClick {
+                target: Some(0x1A2B),
+                key: Key::A,
+            },
+        ));
+        timeline
+    }
+
+    #[test]
+    fn test_sorted_push() {
+        let timeline = sample();
+        assert_eq!(timeline.events()[0].kind, "key_click");
+        assert_eq!(timeline.events()[0].detail, "A");
+        assert_eq!(timeline.by_origin(EventOrigin::Observed).count(), 1);
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let timeline = sample();
+        let csv = timeline.to_csv();
+        assert!(csv.starts_with("t_us,origin,kind,target,detail\n1000,injected,key_click,6699,A\n"));
+        assert_eq!(Timeline::from_csv(&csv).unwrap(), timeline);
+        assert!(Timeline::from_csv("1000,injected").is_err());
+    }
+
+    #[test]
+    fn test_jsonl_roundtrip() {
+        let timeline = sample();
+        let jsonl = timeline.to_jsonl();
+        assert!(jsonl.starts_with(
+            "{\"t_us\":1000,\"origin\":\"injected\",\"kind\":\"key_click\",\"target\":6699,\"detail\":\"A\"}\n"
+        ));
+        assert_eq!(Timeline::from_jsonl(&jsonl).unwrap(), timeline);
+        assert!(Timeline::from_jsonl("{\"t_us\":1}").is_err());
+    }
+
+    #[test]
+    fn test_recorder_chains_and_restores_sink() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // 并行测试的发送同样产生遥测事件，只统计带标记窗口句柄的事件
+        const MARKER: WindowHandle = 0x5EED_1262;
+        let forwarded = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&forwarded);
+        crate::telemetry::set_event_sink(move |event| {
+            if matches!(event, TelemetryEvent::KeyClick { target: Some(MARKER), .. }) {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        let click = || TelemetryEvent::KeyClick {
+            target: Some(MARKER),
+            key: Key::A,
+        };
+
+        let recorder = TimelineRecorder::start();
+        crate::telemetry::emit(click);
+        let timeline = recorder.finish();
+        let marked = timeline
+            .events()
+            .iter()
+            .filter(|event| event.target == Some(MARKER))
+            .count();
+        assert_eq!(marked, 1);
+        assert_eq!(forwarded.load(Ordering::Relaxed), 1);
+
+        // 结束后恢复用户的接收器
+        crate::telemetry::emit(click);
+        assert_eq!(forwarded.load(Ordering::Relaxed), 2);
+        crate::telemetry::clear_event_sink();
+    }
+}
@@ -0,0 +1,31 @@
+// src/workflow/mod.rs
+//! 多步骤 UI 工作流状态机（需要启用 `workflow` 特性）
+//!
+//! 将"等待对话框 → 输入 → 等待下一屏"这类流程形式化：每个步骤由等待条件、
+//! 动作、成功/失败转移和超时组成，由 [`run_workflow`] 逐步执行并记录日志。
+//!
+//! # 示例
+//! ```no_run
+//! # #[cfg(feature = "workflow")]
+//! # {
+//! use sophia_keyboard_sender::workflow::*;
+//! use std::time::Duration;
+//!
+//! let wf = Workflow::new("save-file")
+//!     .step(Step::new("open").action(StepAction::commands(&["shortcut:ctrl+s"])))
+//!     .step(
+//!         Step::new("type-name")
+//!             .wait(WaitCondition::Delay(Duration::from_millis(500)))
+//!             .action(StepAction::commands(&["text:report.txt", "key:enter"])),
+//!     );
+//!
+//! let report = run_workflow(&wf).unwrap();
+//! assert!(report.is_success());
+//! # }
+//! ```
+
+mod runner;
+mod step;
+
+pub use runner::*;
+pub use step::*;
@@ -0,0 +1,320 @@
+// src/workflow/runner.rs
+//! 工作流执行器
+use super::step::{Step, StepAction, Transition, WaitCondition};
+use crate::error::{KeyboardSenderError, Result};
+use std::time::Duration;
+
+/// 默认最大转移次数，防止 `Goto` 造成死循环
+pub const DEFAULT_MAX_TRANSITIONS: usize = 1000;
+
+/// 等待条件的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 日志回调
+pub type WorkflowLogger = dyn Fn(&StepLog) + Send + Sync;
+
+/// 工作流定义
+pub struct Workflow {
+    pub name: String,
+    pub steps: Vec<Step>,
+    pub max_transitions: usize,
+    logger: Option<Box<WorkflowLogger>>,
+}
+
+impl Workflow {
+    /// 创建空工作流
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            steps: Vec::new(),
+            max_transitions: DEFAULT_MAX_TRANSITIONS,
+            logger: None,
+        }
+    }
+
+    /// 追加步骤
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// 设置最大转移次数
+    pub fn max_transitions(mut self, max: usize) -> Self {
+        self.max_transitions = max;
+        self
+    }
+
+    /// 设置日志回调，每个步骤结束时调用
+    pub fn on_log<F>(mut self, logger: F) -> Self
+    where
+        F: Fn(&StepLog) + Send + Sync + 'static,
+    {
+        self.logger = Some(Box::new(logger));
+        self
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.steps.iter().position(|s| s.name == name)
+    }
+
+    /// 检查所有 `Goto` 目标都存在
+    fn validate(&self) -> Result<()> {
+        for step in &self.steps {
+            for transition in [&step.on_success, &step.on_failure] {
+                if let Transition::Goto(target) = transition {
+                    if self.index_of(target).is_none() {
+                        return Err(KeyboardSenderError::CommandParseError(format!(
+                            "Workflow '{}': step '{}' jumps to unknown step '{}'",
+                            self.name, step.name, target
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 步骤执行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// 成功
+    Success,
+    /// 等待条件超时
+    WaitTimeout,
+    /// 动作执行出错
+    ActionFailed(String),
+}
+
+/// 单个步骤的执行日志
+#[derive(Debug, Clone)]
+pub struct StepLog {
+    pub step: String,
+    pub outcome: StepOutcome,
+    pub elapsed: Duration,
+}
+
+/// 工作流最终结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowOutcome {
+    /// 成功完成
+    Completed,
+    /// 在指定步骤失败
+    Failed { step: String, reason: String },
+    /// 超过最大转移次数
+    TransitionLimitExceeded,
+}
+
+/// 工作流执行报告
+#[derive(Debug, Clone)]
+pub struct WorkflowReport {
+    pub workflow: String,
+    pub outcome: WorkflowOutcome,
+    pub log: Vec<StepLog>,
+    pub elapsed: Duration,
+}
+
+impl WorkflowReport {
+    /// 是否成功完成
+    pub fn is_success(&self) -> bool {
+        self.outcome == WorkflowOutcome::Completed
+    }
+}
+
+/// 执行工作流
+///
+/// 仅在工作流定义无效（如跳转到不存在的步骤）时返回错误；
+/// 步骤失败体现在返回报告的 `outcome` 中。
+pub fn run_workflow(wf: &Workflow) -> Result<WorkflowReport> {
+    wf.validate()?;
+
+    let clock = crate::core::clock();
+    let start = clock.now();
+    let mut log = Vec::new();
+    let mut current = 0usize;
+    let mut transitions = 0usize;
+
+    let outcome = loop {
+        let Some(step) = wf.steps.get(current) else {
+            break WorkflowOutcome::Completed;
+        };
+
+        if transitions >= wf.max_transitions {
+            break WorkflowOutcome::TransitionLimitExceeded;
+        }
+        transitions += 1;
+
+        let step_start = clock.now();
+        let step_outcome = run_step(step);
+        let entry = StepLog {
+            step: step.name.clone(),
+            outcome: step_outcome.clone(),
+            elapsed: clock.now().saturating_duration_since(step_start),
+        };
+        if let Some(logger) = &wf.logger {
+            logger(&entry);
+        }
+        log.push(entry);
+
+        let transition = if step_outcome == StepOutcome::Success {
+            &step.on_success
+        } else {
+            &step.on_failure
+        };
+
+        match transition {
+            Transition::Next => current += 1,
+            Transition::Goto(target) => {
+                // validate() 已保证目标存在
+                current = wf.index_of(target).unwrap_or(wf.steps.len());
+            }
+            Transition::Finish => break WorkflowOutcome::Completed,
+            Transition::Fail => {
+                let reason = match step_outcome {
+                    StepOutcome::Success => "failed by transition".to_string(),
+                    StepOutcome::WaitTimeout => format!("wait timed out after {:?}", step.timeout),
+                    StepOutcome::ActionFailed(e) => e,
+                };
+                break WorkflowOutcome::Failed {
+                    step: step.name.clone(),
+                    reason,
+                };
+            }
+        }
+    };
+
+    Ok(WorkflowReport {
+        workflow: wf.name.clone(),
+        outcome,
+        log,
+        elapsed: clock.now().saturating_duration_since(start),
+    })
+}
+
+fn run_step(step: &Step) -> StepOutcome {
+    if !wait_for(&step.wait, step.timeout) {
+        return StepOutcome::WaitTimeout;
+    }
+
+    match run_action(&step.action) {
+        Ok(()) => StepOutcome::Success,
+        Err(e) => StepOutcome::ActionFailed(e.to_string()),
+    }
+}
+
+/// 等待条件满足，超时返回 `false`
+fn wait_for(condition: &WaitCondition, timeout: Duration) -> bool {
+    let check: Box<dyn Fn() -> bool + '_> = match condition {
+        WaitCondition::None => return true,
+        WaitCondition::Delay(d) => {
+            crate::core::clock().sleep(*d);
+            return true;
+        }
+        #[cfg(feature = "window_target")]
+        WaitCondition::WindowExists(selector) => Box::new(move || selector.resolve().is_some()),
+        #[cfg(feature = "window_target")]
+        WaitCondition::WindowGone(selector) => Box::new(move || selector.resolve().is_none()),
+        WaitCondition::Custom(f) => Box::new(f),
+    };
+
+    let clock = crate::core::clock();
+    let deadline = clock.now() + timeout;
+    loop {
+        if check() {
+            return true;
+        }
+        if clock.now() >= deadline {
+            return false;
+        }
+        clock.sleep(POLL_INTERVAL);
+    }
+}
+
+fn run_action(action: &StepAction) -> Result<()> {
+    match action {
+        StepAction::None => Ok(()),
+        StepAction::Commands(commands) => {
+            for command in commands {
+                crate::parser::send(command)?;
+            }
+            Ok(())
+        }
+        StepAction::Confirm(question) => {
+            if crate::parser::prompt_confirm(question)? {
+                Ok(())
+            } else {
+                Err(KeyboardSenderError::ExecutionDenied(format!(
+                    "Not confirmed: {}",
+                    question
+                )))
+            }
+        }
+        StepAction::Custom(f) => f(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_goto_loop_until_condition() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let c = counter.clone();
+
+        let wf = Workflow::new("loop")
+            .step(Step::new("inc").action(StepAction::custom(move || {
+                c.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })))
+            .step({
+                let c = counter.clone();
+                Step::new("check")
+                    .action(StepAction::custom(move || {
+                        if c.load(Ordering::SeqCst) < 3 {
+                            Err(KeyboardSenderError::CommandParseError("again".to_string()))
+                        } else {
+                            Ok(())
+                        }
+                    }))
+                    .on_failure(Transition::Goto("inc".to_string()))
+            });
+
+        let report = run_workflow(&wf).unwrap();
+        assert!(report.is_success());
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+        assert_eq!(report.log.len(), 6);
+    }
+
+    #[test]
+    fn test_wait_timeout_fails() {
+        let wf = Workflow::new("timeout").step(
+            Step::new("never")
+                .wait(WaitCondition::custom(|| false))
+                .timeout(Duration::from_millis(10)),
+        );
+
+        let report = run_workflow(&wf).unwrap();
+        assert!(
+            matches!(report.outcome, WorkflowOutcome::Failed { ref step, .. } if step == "never")
+        );
+    }
+
+    #[test]
+    fn test_unknown_goto_is_error() {
+        let wf = Workflow::new("bad")
+            .step(Step::new("a").on_success(Transition::Goto("missing".to_string())));
+        assert!(run_workflow(&wf).is_err());
+    }
+
+    #[test]
+    fn test_transition_limit() {
+        let wf = Workflow::new("spin")
+            .max_transitions(5)
+            .step(Step::new("a").on_success(Transition::Goto("a".to_string())));
+        let report = run_workflow(&wf).unwrap();
+        assert_eq!(report.outcome, WorkflowOutcome::TransitionLimitExceeded);
+    }
+}
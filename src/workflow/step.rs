@@ -0,0 +1,159 @@
+// src/workflow/step.rs
+//! 工作流步骤定义
+use crate::error::Result;
+use std::fmt;
+use std::time::Duration;
+
+/// 步骤默认的等待超时时间
+pub const DEFAULT_STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 步骤执行前的等待条件
+pub enum WaitCondition {
+    /// 不等待
+    None,
+    /// 固定延迟
+    Delay(Duration),
+    /// 等待匹配的窗口出现
+    #[cfg(feature = "window_target")]
+    WindowExists(crate::core::WindowSelector),
+    /// 等待匹配的窗口消失
+    #[cfg(feature = "window_target")]
+    WindowGone(crate::core::WindowSelector),
+    /// 自定义条件，返回 `true` 表示满足
+    Custom(Box<dyn Fn() -> bool + Send + Sync>),
+}
+
+impl WaitCondition {
+    /// 使用闭包创建自定义条件
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        WaitCondition::Custom(Box::new(f))
+    }
+}
+
+impl fmt::Debug for WaitCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitCondition::None => write!(f, "None"),
+            WaitCondition::Delay(d) => write!(f, "Delay({:?})", d),
+            #[cfg(feature = "window_target")]
+            WaitCondition::WindowExists(s) => write!(f, "WindowExists({:?})", s),
+            #[cfg(feature = "window_target")]
+            WaitCondition::WindowGone(s) => write!(f, "WindowGone({:?})", s),
+            WaitCondition::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// 步骤动作
+pub enum StepAction {
+    /// 无动作（仅等待）
+    None,
+    /// 依次执行文本命令（见 [`crate::send`]）
+    Commands(Vec<String>),
+    /// 通过询问接口确认（见 [`crate::Prompter`]），未确认时步骤失败
+    Confirm(String),
+    /// 自定义动作
+    Custom(Box<dyn Fn() -> Result<()> + Send + Sync>),
+}
+
+impl StepAction {
+    /// 由命令字符串列表创建动作
+    pub fn commands(commands: &[&str]) -> Self {
+        StepAction::Commands(commands.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// 创建确认动作
+    pub fn confirm(question: &str) -> Self {
+        StepAction::Confirm(question.to_string())
+    }
+
+    /// 使用闭包创建自定义动作
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn() -> Result<()> + Send + Sync + 'static,
+    {
+        StepAction::Custom(Box::new(f))
+    }
+}
+
+impl fmt::Debug for StepAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepAction::None => write!(f, "None"),
+            StepAction::Commands(cmds) => write!(f, "Commands({:?})", cmds),
+            StepAction::Confirm(question) => write!(f, "Confirm({:?})", question),
+            StepAction::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// 步骤结束后的转移
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transition {
+    /// 进入下一个步骤（最后一步之后即完成）
+    Next,
+    /// 跳转到指定名称的步骤
+    Goto(String),
+    /// 工作流成功结束
+    Finish,
+    /// 工作流失败结束
+    Fail,
+}
+
+/// 工作流步骤
+#[derive(Debug)]
+pub struct Step {
+    pub name: String,
+    pub wait: WaitCondition,
+    pub timeout: Duration,
+    pub action: StepAction,
+    pub on_success: Transition,
+    pub on_failure: Transition,
+}
+
+impl Step {
+    /// 创建步骤：无等待、无动作，成功进入下一步，失败结束工作流
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            wait: WaitCondition::None,
+            timeout: DEFAULT_STEP_TIMEOUT,
+            action: StepAction::None,
+            on_success: Transition::Next,
+            on_failure: Transition::Fail,
+        }
+    }
+
+    /// 设置等待条件
+    pub fn wait(mut self, wait: WaitCondition) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// 设置等待超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置动作
+    pub fn action(mut self, action: StepAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// 设置成功后的转移
+    pub fn on_success(mut self, transition: Transition) -> Self {
+        self.on_success = transition;
+        self
+    }
+
+    /// 设置失败（超时或动作出错）后的转移
+    pub fn on_failure(mut self, transition: Transition) -> Self {
+        self.on_failure = transition;
+        self
+    }
+}
@@ -0,0 +1,186 @@
+//! 按键事件集成测试
+//!
+//! 打开一个测试窗口，对 `keyboard-codes` 标准映射表中的每个按键调用 `key_click`，
+//! 检查窗口恰好收到一次按下和一次释放，且虚拟键码、扩展键标志（以及扫描码模式下的扫描码）正确。
+//! 用于在升级 `keyboard-codes` 时发现映射回归。Pause、PrintScreen 等需要特殊事件序列的按键单独测试。
+//!
+//! 测试需要交互式桌面并会抢占焦点，默认忽略：
+//!
+//! ```bash
+//! cargo test --features window_target --test key_events -- --ignored --test-threads=1
+//! ```
+#![cfg(all(windows, feature = "window_target"))]
+
+use keyboard_codes::mapping::standard::STANDARD_KEY_MAPPINGS;
+use keyboard_codes::Key;
+use sophia_keyboard_sender::{
+    is_extended_key, key_click, set_injection_mode, set_text_injection, vk_click, CaptureWindow,
+    CapturedEvent, CapturedKind, InjectionMode, TextInjection,
+};
+use std::time::Duration;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{MapVirtualKeyW, MAPVK_VK_TO_VSC};
+
+/// 会触发系统行为（睡眠、媒体控制、浏览器、菜单）的按键，不发送
+const SKIPPED_KEYS: &[&str] = &[
+    "Sleep",
+    "Apps",
+    "MediaPlayPause",
+    "MediaStop",
+    "MediaNext",
+    "MediaPrevious",
+    "VolumeUp",
+    "VolumeDown",
+    "VolumeMute",
+    "BrowserBack",
+    "BrowserForward",
+    "BrowserRefresh",
+    "BrowserHome",
+];
+
+/// 扫描码模式下由多个事件组成的按键，由单独的测试检查
+const SCAN_SEQUENCE_KEYS: &[&str] = &["Pause"];
+
+/// 切换锁定状态的按键，测试后再点击一次恢复
+const LOCK_KEYS: &[&str] = &["CapsLock", "NumLock", "ScrollLock"];
+
+/// 接收按键的测试窗口
+struct TestWindow(CaptureWindow);
+
+impl TestWindow {
+    fn open() -> Self {
+        Self(CaptureWindow::open("sophia key events").expect("failed to create test window"))
+    }
+
+    /// 取出收到的按键消息（忽略转换出的字符消息）
+    fn drain(&self) -> Vec<CapturedEvent> {
+        std::thread::sleep(Duration::from_millis(30));
+        self.0
+            .take_events()
+            .into_iter()
+            .filter(CapturedEvent::is_key)
+            .collect()
+    }
+
+    fn click(&self, key: Key) -> Vec<CapturedEvent> {
+        key_click(key, Some(Duration::from_millis(5))).unwrap();
+        self.drain()
+    }
+
+    fn click_vk(&self, vk: u16) -> Vec<CapturedEvent> {
+        vk_click(vk, Some(Duration::from_millis(5))).unwrap();
+        self.drain()
+    }
+}
+
+fn check_all_keys(mode: InjectionMode) {
+    set_injection_mode(mode);
+    set_text_injection(TextInjection::Unicode);
+    let window = TestWindow::open();
+    let mut failures = Vec::new();
+
+    for &(name, key, vk, _, _) in STANDARD_KEY_MAPPINGS {
+        if SKIPPED_KEYS.contains(&name)
+            || (mode == InjectionMode::ScanCode && SCAN_SEQUENCE_KEYS.contains(&name))
+        {
+            continue;
+        }
+
+        let vk = vk as u16;
+        let scan = match name {
+            "Pause" => 0x45,
+            _ => (unsafe { MapVirtualKeyW(u32::from(vk), MAPVK_VK_TO_VSC) }) as u16,
+        };
+        let extended = is_extended_key(key);
+        let events = window.click(key);
+        if LOCK_KEYS.contains(&name) {
+            window.click(key);
+        }
+
+        let matches = match events.as_slice() {
+            [down, up] => {
+                down.kind == CapturedKind::KeyDown
+                    && up.kind == CapturedKind::KeyUp
+                    && down.code == vk
+                    && up.code == vk
+                    && down.extended == extended
+                    && up.extended == extended
+                    && (mode == InjectionMode::VirtualKey || (down.scan == scan && up.scan == scan))
+            }
+            _ => false,
+        };
+        if !matches {
+            failures.push(format!(
+                "{} (vk {:#04x}, scan {:#04x}): {:?}",
+                name, vk, scan, events
+            ));
+        }
+    }
+
+    set_injection_mode(InjectionMode::VirtualKey);
+    assert!(
+        failures.is_empty(),
+        "unexpected key events:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+#[ignore]
+fn every_key_produces_one_down_and_up_with_virtual_keys() {
+    check_all_keys(InjectionMode::VirtualKey);
+}
+
+#[test]
+#[ignore]
+fn every_key_produces_one_down_and_up_with_scan_codes() {
+    check_all_keys(InjectionMode::ScanCode);
+}
+
+#[test]
+#[ignore]
+fn pause_in_scan_code_mode_arrives_as_pause() {
+    const VK_PAUSE: u16 = 0x13;
+
+    set_injection_mode(InjectionMode::ScanCode);
+    let window = TestWindow::open();
+    let events = window.click(Key::Pause);
+    set_injection_mode(InjectionMode::VirtualKey);
+
+    // 发送的是 Ctrl+NumLock 序列，窗口还会看到左 Ctrl，这里只检查 Pause 本身
+    let pause: Vec<&CapturedEvent> = events.iter().filter(|e| e.code == VK_PAUSE).collect();
+    assert!(
+        matches!(
+            pause.as_slice(),
+            [down, up] if down.kind == CapturedKind::KeyDown
+                && up.kind == CapturedKind::KeyUp
+                && !down.extended
+        ),
+        "unexpected Pause events: {:?}",
+        events
+    );
+}
+
+#[test]
+#[ignore]
+fn print_screen_is_extended_0x37() {
+    const VK_SNAPSHOT: u16 = 0x2C;
+
+    for mode in [InjectionMode::VirtualKey, InjectionMode::ScanCode] {
+        set_injection_mode(mode);
+        let window = TestWindow::open();
+        let events = window.click_vk(VK_SNAPSHOT);
+        set_injection_mode(InjectionMode::VirtualKey);
+
+        // 系统会吞掉 PrintScreen 的按下消息，窗口通常只收到释放
+        let up = events
+            .iter()
+            .find(|e| e.kind == CapturedKind::KeyUp && e.code == VK_SNAPSHOT);
+        assert!(
+            up.is_some_and(|e| e.extended && (mode == InjectionMode::VirtualKey || e.scan == 0x37)),
+            "{:?}: unexpected PrintScreen events: {:?}",
+            mode,
+            events
+        );
+    }
+}
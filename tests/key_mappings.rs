@@ -0,0 +1,44 @@
+//! 键码映射快照测试
+//!
+//! 比较 `dump_key_mappings()` 与 `tests/snapshots/key_mappings.txt` 中记录的虚拟键码，
+//! 升级 `keyboard-codes` 导致键码变化时失败并列出差异。扫描码依赖键盘布局，快照中不记录。
+//!
+//! 确认变化符合预期后，设置 `UPDATE_SNAPSHOTS=1` 重新运行以更新快照。
+#![cfg(windows)]
+
+use sophia_keyboard_sender::{
+    diff_key_mappings, dump_key_mappings, format_key_mappings, parse_key_mappings, KeyMapping,
+};
+
+const SNAPSHOT_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/snapshots/key_mappings.txt"
+);
+const SNAPSHOT_HEADER: &str =
+    "# keyboard-codes standard key mappings (Windows virtual-key codes)\n# name\tvk\n";
+
+#[test]
+fn key_mappings_match_snapshot() {
+    let current: Vec<KeyMapping> = dump_key_mappings()
+        .into_iter()
+        .map(|m| KeyMapping { scan: None, ..m })
+        .collect();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        let text = format!("{}{}", SNAPSHOT_HEADER, format_key_mappings(&current));
+        std::fs::write(SNAPSHOT_PATH, text).unwrap();
+        return;
+    }
+
+    let snapshot = parse_key_mappings(&std::fs::read_to_string(SNAPSHOT_PATH).unwrap()).unwrap();
+    let changes = diff_key_mappings(&snapshot, &current);
+    assert!(
+        changes.is_empty(),
+        "key mappings changed (set UPDATE_SNAPSHOTS=1 to accept):\n{}",
+        changes
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
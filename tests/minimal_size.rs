@@ -0,0 +1,70 @@
+//! 检查 `minimal` 构建的体积，防止新增依赖悄悄进入最小构建
+//!
+//! 体积测试以发布配置单独编译示例（使用独立的目标目录），首次运行较慢：
+//!
+//! ```bash
+//! cargo test --test minimal_size
+//! ```
+
+/// 最小构建示例的体积上限（字节）
+#[cfg(windows)]
+const SIZE_BUDGET: u64 = 512 * 1024;
+
+#[cfg(windows)]
+#[test]
+fn minimal_example_stays_within_size_budget() {
+    use std::path::Path;
+    use std::process::Command;
+
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let target_dir = Path::new(manifest_dir).join("target").join("minimal-size");
+    let status = Command::new(env!("CARGO"))
+        .current_dir(manifest_dir)
+        .args([
+            "build",
+            "--release",
+            "--example",
+            "minimal_size",
+            "--no-default-features",
+            "--features",
+            "minimal",
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "minimal build failed");
+
+    let binary = target_dir
+        .join("release")
+        .join("examples")
+        .join("minimal_size.exe");
+    let size = std::fs::metadata(&binary)
+        .expect("minimal example binary not found")
+        .len();
+    assert!(
+        size <= SIZE_BUDGET,
+        "minimal build is {} bytes, budget is {} bytes",
+        size,
+        SIZE_BUDGET
+    );
+}
+
+#[test]
+fn minimal_feature_excludes_optional_dependencies() {
+    let manifest = include_str!("../Cargo.toml");
+    let minimal = manifest
+        .lines()
+        .find(|line| line.starts_with("minimal ="))
+        .expect("minimal feature missing");
+    for dep in [
+        "global",
+        "regex",
+        "lazy_static",
+        "serde",
+        "sleep",
+        "command_parser",
+    ] {
+        assert!(!minimal.contains(dep), "minimal feature pulls in {}", dep);
+    }
+}